@@ -0,0 +1,160 @@
+//! Per-component benchmarks
+//!
+//! ```text
+//! cargo bench                                   every group
+//! cargo bench -- spatial_insert                 one group
+//! cargo bench -- --save-baseline main           record a baseline
+//! cargo bench -- --baseline main                compare against it
+//! ```
+//!
+//! Inputs come from seeded generators, so runs on the same machine measure
+//! the same work and criterion's change reports track regressions.
+
+use std::time::{Duration, Instant};
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use genesis_env_awareness::anomaly::{AnomalyDetector, MahalanobisDetector};
+use genesis_env_awareness::config::SystemConfig;
+use genesis_env_awareness::neural::NeuralNetwork;
+use genesis_env_awareness::sensors::{SensorData, SensorProcessor};
+use genesis_env_awareness::spatial::SpatialGraph;
+use genesis_env_awareness::EnvironmentalAwarenessSystem;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const SEED: u64 = 42;
+
+/// Distinct inputs cycled through, so no single value gets special-cased
+const SAMPLES: usize = 1024;
+
+/// Graph sizes the spatial insert is measured at
+const GRAPH_SIZES: [usize; 3] = [100, 1_000, 10_000];
+
+fn frames() -> Vec<SensorData> {
+    let mut rng = StdRng::seed_from_u64(SEED);
+    (0..SAMPLES).map(|i| SensorData::generate_with(&mut rng, i as f64 * 0.01)).collect()
+}
+
+fn features() -> Vec<Vec<f32>> {
+    let processor = SensorProcessor::new();
+    frames().iter().map(|frame| processor.process(frame).features).collect()
+}
+
+fn sensor_fusion(c: &mut Criterion) {
+    let processor = SensorProcessor::new();
+    let frames = frames();
+    let mut next = frames.iter().cycle();
+    let mut buffer = Vec::with_capacity(processor.feature_count());
+    c.bench_function("sensor_fusion", |b| {
+        b.iter(|| {
+            let processed = processor.process_with_buffer(black_box(next.next().unwrap()), std::mem::take(&mut buffer));
+            buffer = black_box(processed).features;
+        })
+    });
+}
+
+fn neural_forward(c: &mut Criterion) {
+    let config = SystemConfig::default();
+    let network = NeuralNetwork::with_rng(
+        config.features.len(),
+        config.hidden_size,
+        config.output_size,
+        &mut StdRng::seed_from_u64(SEED),
+    );
+    let features = features();
+    let mut next = features.iter().cycle();
+    let mut output = Vec::new();
+    c.bench_function("neural_forward", |b| {
+        b.iter(|| {
+            network.forward_with_buffer(black_box(next.next().unwrap()), &mut output);
+            black_box(&output);
+        })
+    });
+}
+
+/// One insert into a graph holding `size` nodes; each node is removed again
+/// outside the timed section so the graph stays at its size
+fn spatial_insert(c: &mut Criterion) {
+    let features = features();
+    let mut group = c.benchmark_group("spatial_insert");
+    for size in GRAPH_SIZES {
+        let mut graph: SpatialGraph = SpatialGraph::with_capacity(size + 1);
+        for sample in features.iter().cycle().take(size) {
+            graph.add_node(sample);
+        }
+        let mut next = features.iter().cycle();
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, _| {
+            b.iter_custom(|iters| {
+                let mut elapsed = Duration::ZERO;
+                for _ in 0..iters {
+                    let sample = next.next().unwrap();
+                    let start = Instant::now();
+                    let id = graph.add_node(black_box(sample));
+                    elapsed += start.elapsed();
+                    graph.remove_node(id);
+                }
+                elapsed
+            })
+        });
+    }
+    group.finish();
+}
+
+fn anomaly_detect(c: &mut Criterion) {
+    let config = SystemConfig::default();
+    let processor = SensorProcessor::new();
+    let processed: Vec<_> = frames().iter().map(|frame| processor.process(frame)).collect();
+    let mut group = c.benchmark_group("anomaly_detect");
+
+    // Fill the windows first so every detection scores against full statistics
+    let mut zscore = AnomalyDetector::with_config(config.anomaly_window, config.anomaly);
+    let mut mahalanobis = MahalanobisDetector::new(config.features.len());
+    for (i, sample) in processed.iter().enumerate() {
+        zscore.detect_with_features(sample.fused_confidence, &sample.features, i as f64);
+        mahalanobis.detect(&sample.features, i as f64);
+    }
+
+    let mut next = processed.iter().cycle();
+    let mut timestamp = SAMPLES as f64;
+    group.bench_function("zscore", |b| {
+        b.iter(|| {
+            let sample = next.next().unwrap();
+            timestamp += 1.0;
+            black_box(zscore.detect_with_features(black_box(sample.fused_confidence), &sample.features, timestamp))
+        })
+    });
+    group.bench_function("mahalanobis", |b| {
+        b.iter(|| {
+            timestamp += 1.0;
+            black_box(mahalanobis.detect(black_box(&next.next().unwrap().features), timestamp))
+        })
+    });
+    group.finish();
+}
+
+/// Cycles on a map that grows to the configured graph capacity and then
+/// starts over, so the figure doesn't drift with the iteration count
+fn full_cycle(c: &mut Criterion) {
+    let config = SystemConfig { seed: Some(SEED), ..SystemConfig::default() };
+    let capacity = config.graph_capacity;
+    let mut system = EnvironmentalAwarenessSystem::with_config(config).expect("default configuration is valid");
+    system.warmup(100);
+    c.bench_function("full_cycle", |b| {
+        b.iter_custom(|iters| {
+            let mut elapsed = Duration::ZERO;
+            for _ in 0..iters {
+                if system.spatial_graph().node_count() >= capacity {
+                    system.reset();
+                }
+                let start = Instant::now();
+                let result = black_box(system.run_cycle());
+                elapsed += start.elapsed();
+                system.recycle(result);
+            }
+            elapsed
+        })
+    });
+}
+
+criterion_group!(benches, sensor_fusion, neural_forward, spatial_insert, anomaly_detect, full_cycle);
+criterion_main!(benches);
@@ -0,0 +1,21 @@
+//! Generates the gRPC service from `proto/genesis.proto` when the `grpc`
+//! feature is enabled. A vendored `protoc` is used unless `PROTOC` is set.
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "grpc")]
+    grpc();
+}
+
+#[cfg(feature = "grpc")]
+fn grpc() {
+    println!("cargo:rerun-if-changed=proto/genesis.proto");
+    if std::env::var_os("PROTOC").is_none() {
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("no vendored protoc for this host");
+        std::env::set_var("PROTOC", protoc);
+    }
+    tonic_prost_build::configure()
+        .build_client(true)
+        .compile_protos(&["proto/genesis.proto"], &["proto"])
+        .expect("failed to compile proto/genesis.proto");
+}
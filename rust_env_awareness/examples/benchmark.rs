@@ -4,7 +4,7 @@
 //! of the optimized Rust implementation.
 
 use std::time::Instant;
-use genesis_awareness::EnvironmentalAwarenessSystem;
+use genesis_env_awareness::EnvironmentalAwarenessSystem;
 
 fn main() {
     println!("🚀 Genesis Environmental Awareness System - Performance Benchmark");
@@ -71,6 +71,7 @@ fn main() {
     
     println!("Total cycles: {}", final_metrics.cycles);
     println!("Runtime: {:.2}s", final_metrics.runtime_seconds);
+    println!("Wall time: {:.2}s", final_duration.as_secs_f64());
     println!("Processing rate: {:.0} Hz", final_metrics.processing_rate_hz);
     println!("Theoretical max: {:.0} Hz", final_metrics.theoretical_max_hz);
     println!("\nLatency Distribution:");
@@ -2,56 +2,42 @@
 //! in a real-world robotics application scenario.
 
 use std::thread;
-use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use genesis_awareness::{EnvironmentalAwarenessSystem, CycleResult};
+use genesis_env_awareness::{EnvironmentalAwarenessSystem, CycleResult, PredictionResult, SystemMetrics};
+use genesis_env_awareness::anomaly::Anomaly;
+use genesis_env_awareness::mode::BehaviorMode;
+use genesis_env_awareness::observer::SystemObserver;
+use genesis_env_awareness::predictor::Trend;
+use genesis_env_awareness::spatial::Position;
+use genesis_env_awareness::swarm::SwarmCoordinator;
 
 /// Robot controller that uses environmental awareness for decision making
 struct RobotController {
-    awareness: Arc<Mutex<EnvironmentalAwarenessSystem>>,
     position: (f32, f32, f32),
     velocity: (f32, f32, f32),
-    mode: RobotMode,
-}
-
-#[derive(Debug, Clone)]
-enum RobotMode {
-    Exploring,
-    Navigating,
-    Avoiding,
-    Idle,
+    mode: BehaviorMode,
 }
 
 impl RobotController {
     fn new() -> Self {
         Self {
-            awareness: Arc::new(Mutex::new(EnvironmentalAwarenessSystem::new())),
             position: (0.0, 0.0, 0.0),
             velocity: (0.0, 0.0, 0.0),
-            mode: RobotMode::Idle,
+            mode: BehaviorMode::Idle,
         }
     }
     
-    /// Process environmental data and update robot state
-    fn process_environment(&mut self) -> CycleResult {
-        let mut system = self.awareness.lock().unwrap();
-        let result = system.run_cycle();
-        
-        // Update robot mode based on environmental awareness
-        if result.anomaly_detected {
-            self.mode = RobotMode::Avoiding;
+    /// Update robot state from the robot's latest awareness cycle
+    fn react(&mut self, result: &CycleResult) {
+        // The system decides the mode, with hysteresis and a hold after anomalies
+        self.mode = result.mode;
+        if result.mode_transition.is_some_and(|t| t.to == BehaviorMode::Avoiding) {
             println!("⚠️  Anomaly detected! Switching to avoidance mode");
-        } else if result.confidence > 0.8 {
-            self.mode = RobotMode::Navigating;
-        } else if result.confidence > 0.5 {
-            self.mode = RobotMode::Exploring;
-        } else {
-            self.mode = RobotMode::Idle;
         }
         
         // Update velocity based on predictions
         if let Some(prediction) = &result.prediction {
-            if prediction.trend == "increasing" {
+            if prediction.trend == Trend::Increasing {
                 self.velocity.0 *= 1.1;  // Speed up
             } else {
                 self.velocity.0 *= 0.9;  // Slow down
@@ -62,21 +48,6 @@ impl RobotController {
         self.position.0 += self.velocity.0;
         self.position.1 += self.velocity.1;
         self.position.2 += self.velocity.2;
-        
-        result
-    }
-    
-    /// Get current system metrics
-    fn get_metrics(&self) -> String {
-        let system = self.awareness.lock().unwrap();
-        let metrics = system.get_metrics();
-        format!(
-            "Cycles: {}, Rate: {:.0} Hz, P99: {}μs, Memory: {:.2}MB",
-            metrics.cycles,
-            metrics.processing_rate_hz,
-            metrics.p99_processing_us,
-            metrics.memory_usage_mb
-        )
     }
 }
 
@@ -86,22 +57,25 @@ fn swarm_coordination_demo() {
     println!("=====================================\n");
     
     const NUM_ROBOTS: usize = 5;
+    let mut swarm = SwarmCoordinator::new();
     let mut robots: Vec<RobotController> = Vec::new();
     
-    // Initialize robot swarm
+    // Initialize robot swarm; each robot's map sits at its start position
     for i in 0..NUM_ROBOTS {
         let mut robot = RobotController::new();
         robot.position = (i as f32 * 10.0, 0.0, 0.0);
         robot.velocity = (1.0, 0.0, 0.0);
+        swarm.add_at(EnvironmentalAwarenessSystem::new(), Position { x: robot.position.0, y: 0.0, z: 0.0 });
         robots.push(robot);
     }
     
-    // Run coordination cycles
+    // Run coordination cycles; every robot's awareness runs in parallel
     for cycle in 1..=20 {
         println!("Cycle {}", cycle);
         
-        for (i, robot) in robots.iter_mut().enumerate() {
-            let result = robot.process_environment();
+        let tick = swarm.run_cycle();
+        for (i, (robot, result)) in robots.iter_mut().zip(&tick.results).enumerate() {
+            robot.react(result);
             
             println!(
                 "  Robot {}: Mode={:?}, Pos=({:.1}, {:.1}, {:.1}), Conf={:.2}",
@@ -110,15 +84,28 @@ fn swarm_coordination_demo() {
                 result.confidence
             );
         }
+        if let Some(correlated) = &tick.correlated {
+            println!("  🔗 Shared anomaly seen by robots {:?}", correlated.members());
+        }
         
         thread::sleep(Duration::from_millis(100));
     }
     
     // Print final metrics
+    let metrics = swarm.metrics();
     println!("\n📊 Final Swarm Metrics:");
-    for (i, robot) in robots.iter().enumerate() {
-        println!("  Robot {}: {}", i, robot.get_metrics());
+    for (i, robot) in metrics.members.iter().enumerate() {
+        println!(
+            "  Robot {}: Cycles: {}, Rate: {:.0} Hz, P99: {}μs, Memory: {:.2}MB",
+            i, robot.cycles, robot.processing_rate_hz, robot.p99_processing_us, robot.memory_usage_mb
+        );
     }
+    println!(
+        "  Swarm: {} cycles, {:.0} Hz combined, {} shared anomalies, {:.2}MB",
+        metrics.cycles, metrics.processing_rate_hz, metrics.correlated_anomalies, metrics.memory_usage_mb
+    );
+    let map = swarm.merged_map(20.0);
+    println!("  Shared map: {} nodes, {} edges", map.node_count(), map.edge_count());
 }
 
 /// Real-time monitoring demo with concurrent processing
@@ -126,50 +113,46 @@ fn realtime_monitoring_demo() {
     println!("\n📡 Real-Time Environmental Monitoring");
     println!("=====================================\n");
     
-    let system = Arc::new(Mutex::new(EnvironmentalAwarenessSystem::new()));
+    let mut system = EnvironmentalAwarenessSystem::new();
+    // Readers use the handle, so the processing thread can own the system outright
+    let handle = system.metrics_handle();
     
     // Spawn monitoring thread
-    let monitor_system = system.clone();
     let monitor_thread = thread::spawn(move || {
         for _ in 0..100 {
-            let mut sys = monitor_system.lock().unwrap();
-            let result = sys.run_cycle();
+            let result = system.run_cycle();
             
             if result.anomaly_detected {
                 println!("🚨 ALERT: Anomaly detected at cycle {}", result.cycle);
             }
             
-            drop(sys);  // Release lock
             thread::sleep(Duration::from_millis(10));
         }
+        system
     });
     
     // Spawn analysis thread
-    let analysis_system = system.clone();
     let analysis_thread = thread::spawn(move || {
-        thread::sleep(Duration::from_millis(500));  // Let some data accumulate
+        thread::sleep(Duration::from_millis(100));  // Let some data accumulate
         
-        for i in 0..10 {
-            let sys = analysis_system.lock().unwrap();
-            let metrics = sys.get_metrics();
+        for i in 0..5 {
+            let metrics = handle.metrics();
             
-            println!("📊 Analysis Report #{}:", i + 1);
+            println!("📊 Analysis Report #{} (cycle {}):", i + 1, handle.cycles());
             println!("   Processing rate: {:.0} Hz", metrics.processing_rate_hz);
             println!("   Anomalies: {}", metrics.anomalies_detected);
             println!("   Predictions: {}", metrics.predictions_made);
             
-            drop(sys);
-            thread::sleep(Duration::from_millis(1000));
+            thread::sleep(Duration::from_millis(200));
         }
     });
     
     // Wait for threads to complete
-    monitor_thread.join().unwrap();
+    let system = monitor_thread.join().unwrap();
     analysis_thread.join().unwrap();
     
     // Final report
-    let sys = system.lock().unwrap();
-    let final_metrics = sys.get_metrics();
+    let final_metrics = system.get_metrics();
     
     println!("\n📈 Monitoring Session Complete:");
     println!("   Total cycles: {}", final_metrics.cycles);
@@ -177,45 +160,39 @@ fn realtime_monitoring_demo() {
     println!("   Memory used: {:.2}MB", final_metrics.memory_usage_mb);
 }
 
-/// Integration with external systems via callback
+/// Forwards system events to external services as they happen
+#[derive(Debug)]
+struct ExternalBridge;
+
+impl SystemObserver for ExternalBridge {
+    fn on_anomaly(&mut self, anomaly: &Anomaly) {
+        println!("📧 Sending alert email for {:?} anomaly (z={:.2})", anomaly.severity, anomaly.z_score);
+    }
+    
+    fn on_prediction(&mut self, cycle: u32, prediction: &PredictionResult) {
+        println!("📊 Logging prediction: cycle={}, trend={}, conf={:.2}", 
+                 cycle, prediction.trend, prediction.confidence);
+    }
+    
+    fn on_metrics(&mut self, metrics: &SystemMetrics) {
+        println!("📡 Telemetry: rate={:.0} Hz, memory={:.2}MB", metrics.processing_rate_hz, metrics.memory_usage_mb);
+    }
+    
+    fn metrics_interval(&self) -> Option<u32> {
+        Some(10)
+    }
+}
+
+/// Integration with external systems via an observer
 fn callback_integration_demo() {
     println!("\n🔗 External System Integration Demo");
     println!("===================================\n");
     
     let mut system = EnvironmentalAwarenessSystem::new();
     
-    // Define callbacks for different events
-    let anomaly_callback = |cycle: u32| {
-        println!("📧 Sending alert email for anomaly at cycle {}", cycle);
-    };
-    
-    let prediction_callback = |cycle: u32, trend: &str, confidence: f32| {
-        println!("📊 Logging prediction: cycle={}, trend={}, conf={:.2}", 
-                 cycle, trend, confidence);
-    };
-    
-    let metrics_callback = |rate: f64, memory: f64| {
-        println!("📡 Telemetry: rate={:.0} Hz, memory={:.2}MB", rate, memory);
-    };
-    
-    // Run system with callbacks
-    for _ in 0..50 {
-        let result = system.run_cycle();
-        
-        // Trigger callbacks based on results
-        if result.anomaly_detected {
-            anomaly_callback(result.cycle);
-        }
-        
-        if let Some(pred) = result.prediction {
-            prediction_callback(result.cycle, &pred.trend, pred.confidence);
-        }
-        
-        if result.cycle % 10 == 0 {
-            let metrics = system.get_metrics();
-            metrics_callback(metrics.processing_rate_hz, metrics.memory_usage_mb);
-        }
-    }
+    // Hooks fire from inside each cycle; no polling of results needed
+    system.add_observer(Box::new(ExternalBridge));
+    system.run_cycles(50);
 }
 
 fn main() {
@@ -230,7 +207,7 @@ fn main() {
     
     println!("\n✅ All integration examples completed successfully!");
     println!("\n💡 Key Integration Points:");
-    println!("  • Thread-safe access via Arc<Mutex<T>>");
+    println!("  • Lock-free metrics via MetricsHandle");
     println!("  • Real-time monitoring capabilities");
     println!("  • Swarm coordination support");
     println!("  • External system callbacks");
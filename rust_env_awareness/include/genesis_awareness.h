@@ -0,0 +1,160 @@
+#ifndef GENESIS_AWARENESS_H
+#define GENESIS_AWARENESS_H
+
+/* Generated with cbindgen:0.29.4 */
+
+/* Generated by cbindgen from src/ffi.rs; do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Outcome of an FFI call
+ */
+typedef enum GenesisStatus {
+  GENESIS_STATUS_OK = 0,
+  /**
+   * A required pointer argument was null
+   */
+  GENESIS_STATUS_NULL_POINTER = 1,
+  /**
+   * The sensor frame contained NaN or infinite readings
+   */
+  GENESIS_STATUS_INVALID_READING = 2,
+  /**
+   * Any other failure
+   */
+  GENESIS_STATUS_OTHER = 3,
+} GenesisStatus;
+
+/**
+ * Opaque handle to a system
+ */
+typedef struct GenesisSystem GenesisSystem;
+
+/**
+ * One sensor reading, mirroring `SensorData`
+ */
+typedef struct GenesisSensorFrame {
+  uint8_t visual_objects;
+  float visual_brightness;
+  float visual_motion;
+  uint16_t lidar_points;
+  float lidar_max_range;
+  uint8_t lidar_obstacles;
+  float audio_amplitude;
+  float audio_frequency;
+  uint8_t audio_event_type;
+  float imu_accel_x;
+  float imu_accel_y;
+  float imu_accel_z;
+  float imu_gyro;
+  double timestamp;
+} GenesisSensorFrame;
+
+/**
+ * Fixed-size summary of a `CycleResult`
+ */
+typedef struct GenesisCycle {
+  uint32_t cycle;
+  float confidence;
+  size_t node_id;
+  bool anomaly_detected;
+  float anomaly_score;
+  /**
+   * Next-step forecast, valid when `has_prediction` is set
+   */
+  bool has_prediction;
+  float predicted_value;
+  float prediction_confidence;
+  uint64_t processing_us;
+} GenesisCycle;
+
+/**
+ * Headline figures of `SystemMetrics`
+ */
+typedef struct GenesisMetrics {
+  uint32_t cycles;
+  double runtime_seconds;
+  double processing_rate_hz;
+  double avg_processing_us;
+  uint64_t p50_processing_us;
+  uint64_t p95_processing_us;
+  uint64_t p99_processing_us;
+  size_t spatial_nodes;
+  size_t spatial_edges;
+  size_t anomalies_detected;
+  size_t predictions_made;
+  size_t loop_closures;
+  double memory_usage_mb;
+} GenesisMetrics;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Create a system with default configuration; free it with `genesis_system_free`
+ */
+struct GenesisSystem *genesis_system_new(void);
+
+/**
+ * Destroy a system; null is ignored
+ *
+ * # Safety
+ * `system` must be null or a handle from `genesis_system_new` not freed before.
+ */
+void genesis_system_free(struct GenesisSystem *system);
+
+/**
+ * Process one sensor frame; the result is written to `out` when it is not null
+ *
+ * # Safety
+ * `system` must be a live handle, `frame` must point to a valid frame and
+ * `out` must be null or writable.
+ */
+enum GenesisStatus genesis_push_sensor_frame(struct GenesisSystem *system,
+                                             const struct GenesisSensorFrame *frame,
+                                             struct GenesisCycle *out);
+
+/**
+ * Run one cycle on simulated sensor data
+ *
+ * # Safety
+ * `system` must be a live handle and `out` must be null or writable.
+ */
+enum GenesisStatus genesis_run_cycle(struct GenesisSystem *system, struct GenesisCycle *out);
+
+/**
+ * Fill `out` with the current metrics
+ *
+ * # Safety
+ * `system` must be a live handle and `out` must be writable.
+ */
+enum GenesisStatus genesis_get_metrics(const struct GenesisSystem *system,
+                                       struct GenesisMetrics *out);
+
+/**
+ * Full metrics as a JSON string, or null on failure; free it with `genesis_string_free`
+ *
+ * # Safety
+ * `system` must be a live handle.
+ */
+char *genesis_metrics_json(const struct GenesisSystem *system);
+
+/**
+ * Release a string returned by this library; null is ignored
+ *
+ * # Safety
+ * `string` must be null or a pointer from `genesis_metrics_json` not freed before.
+ */
+void genesis_string_free(char *string);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* GENESIS_AWARENESS_H */
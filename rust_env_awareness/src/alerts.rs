@@ -0,0 +1,260 @@
+//! Severity-prioritized alert dispatch
+//!
+//! Anomalies are queued per severity and delivered High first. Low severity
+//! anomalies arriving close together are coalesced into one alert, and every
+//! queue is bounded, so a burst of minor anomalies can neither exhaust memory
+//! nor delay a critical one.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::mpsc::Sender;
+use serde::{Serialize, Deserialize};
+
+use crate::anomaly::{Anomaly, Severity};
+
+/// One deliverable alert, possibly standing for several coalesced anomalies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    pub severity: Severity,
+    /// Highest z-score anomaly among those coalesced
+    pub anomaly: Anomaly,
+    /// Anomalies this alert stands for
+    pub count: usize,
+    pub first_timestamp: f64,
+    pub last_timestamp: f64,
+}
+
+impl Alert {
+    fn new(anomaly: Anomaly) -> Self {
+        Self {
+            severity: anomaly.severity,
+            first_timestamp: anomaly.timestamp,
+            last_timestamp: anomaly.timestamp,
+            anomaly,
+            count: 1,
+        }
+    }
+
+    /// Fold another anomaly into this alert
+    fn absorb(&mut self, anomaly: Anomaly) {
+        self.count += 1;
+        self.first_timestamp = self.first_timestamp.min(anomaly.timestamp);
+        self.last_timestamp = self.last_timestamp.max(anomaly.timestamp);
+        if anomaly.z_score > self.anomaly.z_score {
+            self.anomaly = anomaly;
+        }
+    }
+}
+
+/// Destination for dispatched alerts
+pub trait AlertSink: fmt::Debug + Send {
+    /// Deliver one alert; return false once the sink is gone so it gets dropped
+    fn deliver(&mut self, alert: &Alert) -> bool;
+}
+
+impl AlertSink for Sender<Alert> {
+    fn deliver(&mut self, alert: &Alert) -> bool {
+        self.send(alert.clone()).is_ok()
+    }
+}
+
+/// Alert queue limits
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AlertQueueConfig {
+    /// Alerts held per severity; the oldest is dropped when full
+    pub capacity: usize,
+    /// Low alerts starting within this many seconds are merged
+    pub coalesce_window: f64,
+    /// Alerts delivered per `dispatch` call
+    pub dispatch_batch: usize,
+}
+
+impl Default for AlertQueueConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 64,
+            coalesce_window: 5.0,
+            dispatch_batch: 8,
+        }
+    }
+}
+
+/// Bounded per-severity alert queues with prioritized delivery
+#[derive(Debug, Default)]
+pub struct AlertQueue {
+    config: AlertQueueConfig,
+    /// Indexed by `Severity as usize`
+    queues: [VecDeque<Alert>; 3],
+    sinks: Vec<Box<dyn AlertSink>>,
+    dropped: usize,
+    delivered: usize,
+}
+
+impl AlertQueue {
+    /// Create an empty queue
+    pub fn new(config: AlertQueueConfig) -> Self {
+        Self {
+            config: AlertQueueConfig { capacity: config.capacity.max(1), ..config },
+            ..Self::default()
+        }
+    }
+
+    /// Register a destination for dispatched alerts
+    pub fn add_sink(&mut self, sink: Box<dyn AlertSink>) {
+        self.sinks.push(sink);
+    }
+
+    /// Number of registered sinks
+    pub fn sink_count(&self) -> usize {
+        self.sinks.len()
+    }
+
+    /// Queue an anomaly, coalescing Low ones and evicting the oldest alert of its severity when full
+    pub fn push(&mut self, anomaly: Anomaly) {
+        let queue = &mut self.queues[anomaly.severity as usize];
+        if anomaly.severity == Severity::Low {
+            let full = queue.len() >= self.config.capacity;
+            if let Some(last) = queue.back_mut() {
+                // A full Low queue absorbs instead of evicting
+                if full || anomaly.timestamp - last.first_timestamp <= self.config.coalesce_window {
+                    last.absorb(anomaly);
+                    return;
+                }
+            }
+        }
+        if queue.len() >= self.config.capacity {
+            queue.pop_front();
+            self.dropped += 1;
+        }
+        queue.push_back(Alert::new(anomaly));
+    }
+
+    /// Take the most severe, oldest pending alert
+    pub fn pop(&mut self) -> Option<Alert> {
+        self.queues.iter_mut().rev().find_map(VecDeque::pop_front)
+    }
+
+    /// Deliver up to `dispatch_batch` alerts to every sink in severity order; returns the number dispatched
+    pub fn dispatch(&mut self) -> usize {
+        if self.sinks.is_empty() {
+            return 0;
+        }
+        let mut sent = 0;
+        while sent < self.config.dispatch_batch {
+            let Some(alert) = self.pop() else { break };
+            self.sinks.retain_mut(|sink| sink.deliver(&alert));
+            sent += 1;
+        }
+        self.delivered += sent;
+        sent
+    }
+
+    /// Alerts waiting for dispatch
+    pub fn len(&self) -> usize {
+        self.queues.iter().map(VecDeque::len).sum()
+    }
+
+    /// Check whether nothing is pending
+    pub fn is_empty(&self) -> bool {
+        self.queues.iter().all(VecDeque::is_empty)
+    }
+
+    /// Pending alerts of one severity
+    pub fn pending(&self, severity: Severity) -> usize {
+        self.queues[severity as usize].len()
+    }
+
+    /// Alerts evicted because their queue was full
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Alerts handed to sinks so far
+    pub fn delivered(&self) -> usize {
+        self.delivered
+    }
+
+    /// Current limits
+    pub fn config(&self) -> AlertQueueConfig {
+        self.config
+    }
+
+    /// Replace limits; queues over the new capacity lose their oldest alerts
+    pub fn set_config(&mut self, config: AlertQueueConfig) {
+        self.config = AlertQueueConfig { capacity: config.capacity.max(1), ..config };
+        for queue in &mut self.queues {
+            while queue.len() > self.config.capacity {
+                queue.pop_front();
+                self.dropped += 1;
+            }
+        }
+    }
+
+    /// Drop pending alerts and counters, keeping sinks
+    pub fn clear(&mut self) {
+        self.queues.iter_mut().for_each(VecDeque::clear);
+        self.dropped = 0;
+        self.delivered = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use crate::anomaly::AnomalyExplanation;
+
+    fn anomaly(timestamp: f64, severity: Severity, z_score: f32) -> Anomaly {
+        Anomaly {
+            id: 0,
+            timestamp,
+            value: 1.0,
+            z_score,
+            severity,
+            mean: 0.0,
+            stdev: 1.0,
+            contributions: Vec::new(),
+            explanation: AnomalyExplanation::default(),
+            detectors: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_priority_and_coalescing() {
+        let mut queue = AlertQueue::new(AlertQueueConfig { capacity: 4, coalesce_window: 5.0, dispatch_batch: 2 });
+        let (tx, rx) = mpsc::channel();
+        queue.add_sink(Box::new(tx));
+
+        // A burst of Low anomalies ahead of one High one
+        for i in 0..100 {
+            queue.push(anomaly(i as f64 / 10.0, Severity::Low, 2.1 + i as f32 * 0.001));
+        }
+        queue.push(anomaly(10.0, Severity::High, 4.0));
+        assert_eq!(queue.pending(Severity::Low), 2);
+
+        assert_eq!(queue.dispatch(), 2);
+        let first = rx.try_recv().unwrap();
+        assert_eq!(first.severity, Severity::High);
+        let coalesced = rx.try_recv().unwrap();
+        assert_eq!(coalesced.count, 51);
+        assert_eq!((coalesced.first_timestamp, coalesced.last_timestamp), (0.0, 5.0));
+        assert!((coalesced.anomaly.z_score - 2.15).abs() < 1e-4);
+
+        drop(rx);
+        assert_eq!(queue.dispatch(), 1);
+        assert_eq!(queue.sink_count(), 0);
+    }
+
+    #[test]
+    fn test_bounded() {
+        let mut queue = AlertQueue::new(AlertQueueConfig { capacity: 3, coalesce_window: 0.0, dispatch_batch: 8 });
+        for i in 0..10 {
+            queue.push(anomaly(i as f64, Severity::Medium, 3.0));
+            queue.push(anomaly(i as f64 * 10.0, Severity::Low, 2.0));
+        }
+        assert_eq!(queue.pending(Severity::Medium), 3);
+        assert_eq!(queue.pending(Severity::Low), 3);
+        assert_eq!(queue.dropped(), 7);
+        assert_eq!(queue.pop().unwrap().anomaly.timestamp, 7.0);
+    }
+}
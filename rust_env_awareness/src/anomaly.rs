@@ -1,122 +1,2565 @@
 //! Fast anomaly detection module
 
-use std::collections::VecDeque;
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender};
+use serde::{Serialize, Deserialize};
+
+use crate::error::Result;
+use crate::float::Float;
+use crate::health::{Health, HealthCheck, HealthContext};
+use crate::sensors::{FeatureChannel, Reading, FEATURE_NAMES};
+use crate::spatial::Position;
 
 /// Anomaly information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Anomaly {
+    /// Identifier assigned when the system surfaces the anomaly (0 = unassigned)
+    #[serde(default)]
+    pub id: u64,
     pub timestamp: f64,
     pub value: f32,
     pub z_score: f32,
     pub severity: Severity,
     pub mean: f32,
     pub stdev: f32,
+    /// Per-feature attribution, largest contribution first (empty for scalar-only detection)
+    pub contributions: Vec<FeatureContribution>,
+    pub explanation: AnomalyExplanation,
+    /// Detectors that fired on this sample (filled in by the system)
+    #[serde(default)]
+    pub detectors: Vec<DetectorKind>,
+}
+
+impl Anomaly {
+    /// Feature that contributed most, if attribution is available
+    pub fn top_contributor(&self) -> Option<&FeatureContribution> {
+        self.contributions.first()
+    }
+    
+    /// Attach window statistics and name the features carrying most of the attribution
+    fn explained(mut self, window: Option<WindowStats>) -> Self {
+        self.explanation.window = window;
+        self.name_contributors();
+        self
+    }
+    
+    /// Tag attributed features with the readings of the `channels` they were processed with
+    pub(crate) fn label_features(&mut self, channels: &[FeatureChannel]) {
+        for contribution in &mut self.contributions {
+            contribution.reading = channels.get(contribution.feature).map(|c| c.reading);
+        }
+        if !self.contributions.is_empty() {
+            self.name_contributors();
+        }
+    }
+    
+    fn name_contributors(&mut self) {
+        self.explanation.contributing_features = self.contributions
+            .iter()
+            .filter(|c| c.share >= CONTRIBUTION_SHARE)
+            .map(|c| c.name().to_string())
+            .collect();
+    }
+}
+
+/// Minimum attribution share for a feature to be listed in an explanation
+const CONTRIBUTION_SHARE: f32 = 0.2;
+
+/// Summary of the samples an anomaly was scored against
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowStats {
+    pub len: usize,
+    pub mean: f32,
+    pub stdev: f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl WindowStats {
+    fn of<'a>(values: impl Iterator<Item = &'a f32>, mean: f32, stdev: f32) -> Option<Self> {
+        let (len, min, max) = values.fold((0, f32::INFINITY, f32::NEG_INFINITY), |(n, lo, hi), &v| {
+            (n + 1, lo.min(v), hi.max(v))
+        });
+        (len > 0).then_some(WindowStats { len, mean, stdev, min, max })
+    }
+}
+
+/// Context carried with an anomaly so an alert is actionable on its own
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnomalyExplanation {
+    /// Window statistics, for detectors that score against a window
+    pub window: Option<WindowStats>,
+    /// Names of features carrying a large share of the attribution
+    pub contributing_features: Vec<String>,
+    /// Spatial node created in the detecting cycle (filled in by the system)
+    pub node_id: Option<usize>,
+    /// Map position of that node
+    pub position: Option<Position>,
+    /// Value the predictor expected for this cycle (filled in by the system)
+    pub predicted: Option<f32>,
+    pub prediction_confidence: Option<f32>,
+}
+
+/// How much one feature channel contributed to an anomaly
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FeatureContribution {
+    /// Index into the processed feature vector
+    pub feature: usize,
+    /// Reading of the feature's channel, once the system has labeled it
+    #[serde(default)]
+    pub reading: Option<Reading>,
+    /// Signed z-score of this feature against its own recent history
+    pub z_score: f32,
+    /// Fraction of the total absolute z-score across features, in `[0, 1]`
+    pub share: f32,
+}
+
+impl FeatureContribution {
+    /// Sensor channel name of the feature; unlabeled features are named after the default channels
+    pub fn name(&self) -> &'static str {
+        match self.reading {
+            Some(reading) => reading.name(),
+            None => FEATURE_NAMES.get(self.feature).copied().unwrap_or("unknown"),
+        }
+    }
+}
+
+/// Rank features by absolute z-score and normalize into shares
+fn rank_contributions(z_scores: impl Iterator<Item = f32>) -> Vec<FeatureContribution> {
+    let mut contributions: Vec<FeatureContribution> = z_scores
+        .enumerate()
+        .map(|(feature, z_score)| FeatureContribution { feature, reading: None, z_score, share: 0.0 })
+        .collect();
+    let total: f32 = contributions.iter().map(|c| c.z_score.abs()).sum();
+    if total > 0.0 {
+        contributions.iter_mut().for_each(|c| c.share = c.z_score.abs() / total);
+    }
+    contributions.sort_unstable_by(|a, b| b.z_score.abs().total_cmp(&a.z_score.abs()));
+    contributions
+}
+
+/// Windowed per-feature mean and variance, used for attribution
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct FeatureWindow {
+    window: VecDeque<Vec<f32>>,
+    sums: Vec<f32>,
+    sums_sq: Vec<f32>,
+}
+
+impl FeatureWindow {
+    fn push(&mut self, features: &[f32], window_size: usize) {
+        if features.len() != self.sums.len() {
+            // Feature layout changed; start over
+            self.window.clear();
+            self.sums = vec![0.0; features.len()];
+            self.sums_sq = vec![0.0; features.len()];
+        }
+        if self.window.len() >= window_size {
+            if let Some(old) = self.window.pop_front() {
+                for (i, v) in old.iter().enumerate() {
+                    self.sums[i] -= v;
+                    self.sums_sq[i] -= v * v;
+                }
+            }
+        }
+        for (i, v) in features.iter().enumerate() {
+            self.sums[i] += v;
+            self.sums_sq[i] += v * v;
+        }
+        self.window.push_back(features.to_vec());
+    }
+    
+    fn contributions(&self, features: &[f32]) -> Vec<FeatureContribution> {
+        let n = self.window.len() as f32;
+        if n < 2.0 || features.len() != self.sums.len() {
+            return Vec::new();
+        }
+        rank_contributions(features.iter().enumerate().map(|(i, v)| {
+            let mean = self.sums[i] / n;
+            let stdev = (self.sums_sq[i] / n - mean * mean).max(0.0).sqrt();
+            if stdev > 0.0001 { (v - mean) / stdev } else { 0.0 }
+        }))
+    }
+    
+    fn clear(&mut self) {
+        self.window.clear();
+        self.sums.clear();
+        self.sums_sq.clear();
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+}
+
+impl Severity {
+    /// Classify a z-score above the detection threshold using the default bands
+    #[inline]
+    fn from_z(z_score: f32) -> Self {
+        AnomalyConfig::default().severity(z_score)
+    }
+}
+
+/// Thresholds and severity bands for the z-score detector
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AnomalyConfig {
+    /// Z-score above which a value is anomalous
+    pub threshold: f32,
+    /// Z-score above which an anomaly is at least `Medium`
+    pub medium: f32,
+    /// Z-score above which an anomaly is `High`
+    pub high: f32,
+    /// Samples required in the window before scoring
+    pub min_window: usize,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 2.0,
+            medium: 2.5,
+            high: 3.0,
+            min_window: 3,
+        }
+    }
+}
+
+impl AnomalyConfig {
+    /// Severity band of a z-score already above `threshold`
+    #[inline]
+    pub fn severity(&self, z_score: f32) -> Severity {
+        if z_score > self.high {
+            Severity::High
+        } else if z_score > self.medium {
+            Severity::Medium
+        } else {
+            Severity::Low
+        }
+    }
+    
+    /// Check that bands are ordered and the window can produce a variance
+    pub fn is_valid(&self) -> bool {
+        self.threshold > 0.0 && self.threshold <= self.medium && self.medium <= self.high && self.min_window >= 2
+    }
+}
+
+/// Which detectors the system runs each cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DetectionMode {
+    /// Sliding-window z-score over the fused confidence
+    #[default]
+    ZScore,
+    /// Mahalanobis distance over the full feature vector
+    Mahalanobis,
+    /// Both detectors; either one flags the cycle
+    Both,
+    /// EWMA control chart over the fused confidence
+    Ewma,
+    /// Median / MAD robust z-score over the fused confidence
+    Mad,
+    /// CUSUM change-point detection over the fused confidence
+    Cusum,
+    /// Percentile bounds from a t-digest over the fused confidence
+    Quantile,
+    /// One-class SVM boundary over the full feature vector
+    OneClassSvm,
+    /// Several detectors combined by an `EnsembleConfig`
+    Ensemble,
+    /// The user-supplied `AnomalyDetection` installed on the system
+    Custom,
+}
+
+impl DetectionMode {
+    /// Detectors run by a single-detector mode (empty for `Ensemble`)
+    pub fn members(&self) -> &'static [DetectorKind] {
+        match self {
+            DetectionMode::ZScore => &[DetectorKind::ZScore],
+            DetectionMode::Mahalanobis => &[DetectorKind::Mahalanobis],
+            DetectionMode::Both => &[DetectorKind::ZScore, DetectorKind::Mahalanobis],
+            DetectionMode::Ewma => &[DetectorKind::Ewma],
+            DetectionMode::Mad => &[DetectorKind::Mad],
+            DetectionMode::Cusum => &[DetectorKind::Cusum],
+            DetectionMode::Quantile => &[DetectorKind::Quantile],
+            DetectionMode::OneClassSvm => &[DetectorKind::OneClassSvm],
+            DetectionMode::Ensemble => &[],
+            DetectionMode::Custom => &[DetectorKind::Custom],
+        }
+    }
+}
+
+/// Identifies one of the built-in detectors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DetectorKind {
+    ZScore,
+    Mahalanobis,
+    Ewma,
+    Mad,
+    Cusum,
+    Quantile,
+    OneClassSvm,
+    /// The user-supplied `AnomalyDetection` installed on the system
+    Custom,
+}
+
+/// How ensemble members' verdicts are combined
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum CombinationRule {
+    /// Any member firing flags the cycle
+    Any,
+    /// More than half the members must fire
+    Majority,
+    /// Weights of firing members, as a fraction of the total, must reach `threshold`
+    Weighted { threshold: f32 },
+}
+
+/// Detectors run together on the same stream and how their votes combine
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnsembleConfig {
+    pub members: Vec<(DetectorKind, f32)>,
+    pub rule: CombinationRule,
+}
+
+impl Default for EnsembleConfig {
+    fn default() -> Self {
+        Self::new(CombinationRule::Majority)
+            .with(DetectorKind::ZScore, 1.0)
+            .with(DetectorKind::Ewma, 1.0)
+            .with(DetectorKind::Mad, 1.0)
+    }
+}
+
+impl EnsembleConfig {
+    /// Create an empty ensemble
+    pub fn new(rule: CombinationRule) -> Self {
+        Self { members: Vec::new(), rule }
+    }
+    
+    /// Add a member with a voting weight (re-adding replaces the weight)
+    pub fn with(mut self, kind: DetectorKind, weight: f32) -> Self {
+        self.members.retain(|&(k, _)| k != kind);
+        self.members.push((kind, weight.max(0.0)));
+        self
+    }
+    
+    /// Check whether the firing members carry the vote
+    pub fn combine(&self, fired: &[DetectorKind]) -> bool {
+        let voted = |kind: &DetectorKind| fired.contains(kind);
+        match self.rule {
+            CombinationRule::Any => self.members.iter().any(|(k, _)| voted(k)),
+            CombinationRule::Majority => {
+                2 * self.members.iter().filter(|(k, _)| voted(k)).count() > self.members.len()
+            }
+            CombinationRule::Weighted { threshold } => {
+                let total: f32 = self.members.iter().map(|(_, w)| w).sum();
+                let votes: f32 = self.members.iter().filter(|(k, _)| voted(k)).map(|(_, w)| w).sum();
+                total > 0.0 && votes / total >= threshold
+            }
+        }
+    }
+}
+
+/// A detector the system can run in place of (or alongside) the built-in ones
+///
+/// Implement this to plug a custom detector into the pipeline with
+/// `EnvironmentalAwarenessSystem::set_detector`. The built-in detectors
+/// implement it too, and the system runs all of them through it.
+pub trait AnomalyDetection: fmt::Debug + Send {
+    /// Score one cycle's feature vector, learn from it and report an anomaly if it is one
+    fn observe(&mut self, features: &[f32], timestamp: f64) -> Option<Anomaly>;
+    
+    /// Score one cycle given its fused confidence `value` as well; the system
+    /// calls this, and detectors watching that single series override it
+    fn observe_cycle(&mut self, value: f32, features: &[f32], timestamp: f64) -> Option<Anomaly> {
+        let _ = value;
+        self.observe(features, timestamp)
+    }
+    
+    /// Score of the most recent observation (z-score scale preferred)
+    fn last_score(&self) -> f32 {
+        0.0
+    }
+    
+    /// Total anomalies reported over the detector's lifetime
+    fn anomaly_count(&self) -> usize {
+        0
+    }
+    
+    /// Bound the number of retained anomalies
+    fn set_history_capacity(&mut self, _capacity: usize) {}
+    
+    /// Forget all learned state
+    fn clear(&mut self) {}
+}
+
+/// Value a single-series detector watches when given only a feature vector
+fn mean_feature(features: &[f32]) -> f32 {
+    if features.is_empty() {
+        0.0
+    } else {
+        features.iter().sum::<f32>() / features.len() as f32
+    }
+}
+
+/// `AnomalyDetection` for a detector of one scalar series, which watches the
+/// fused confidence in the system and the mean feature otherwise
+macro_rules! scalar_detection {
+    ($($detector:ty),*) => {$(
+        impl AnomalyDetection for $detector {
+            fn observe(&mut self, features: &[f32], timestamp: f64) -> Option<Anomaly> {
+                self.detect(mean_feature(features), timestamp)
+            }
+            
+            fn observe_cycle(&mut self, value: f32, _features: &[f32], timestamp: f64) -> Option<Anomaly> {
+                self.detect(value, timestamp)
+            }
+            
+            fn last_score(&self) -> f32 {
+                <$detector>::last_score(self)
+            }
+            
+            fn anomaly_count(&self) -> usize {
+                <$detector>::anomaly_count(self)
+            }
+            
+            fn set_history_capacity(&mut self, capacity: usize) {
+                <$detector>::set_history_capacity(self, capacity);
+            }
+            
+            fn clear(&mut self) {
+                <$detector>::clear(self);
+            }
+        }
+    )*};
+}
+
+/// `AnomalyDetection` for a detector of the full feature vector
+macro_rules! feature_detection {
+    ($($detector:ty),*) => {$(
+        impl AnomalyDetection for $detector {
+            fn observe(&mut self, features: &[f32], timestamp: f64) -> Option<Anomaly> {
+                self.detect(features, timestamp)
+            }
+            
+            fn last_score(&self) -> f32 {
+                <$detector>::last_score(self)
+            }
+            
+            fn anomaly_count(&self) -> usize {
+                <$detector>::anomaly_count(self)
+            }
+            
+            fn set_history_capacity(&mut self, capacity: usize) {
+                <$detector>::set_history_capacity(self, capacity);
+            }
+            
+            fn clear(&mut self) {
+                <$detector>::clear(self);
+            }
+        }
+    )*};
+}
+
+scalar_detection!(EwmaDetector, MadDetector, CusumDetector, QuantileDetector);
+feature_detection!(MahalanobisDetector, OneClassSvmDetector);
+
+/// The detectors a system runs, looked up by kind
+///
+/// The built-ins keep their concrete types so their state can be saved and
+/// restored; everything else treats them as `AnomalyDetection`s.
+#[derive(Debug)]
+pub(crate) struct Detectors {
+    pub(crate) z_score: AnomalyDetector,
+    pub(crate) mahalanobis: MahalanobisDetector,
+    pub(crate) ewma: EwmaDetector,
+    pub(crate) mad: MadDetector,
+    pub(crate) cusum: CusumDetector,
+    pub(crate) quantile: QuantileDetector,
+    pub(crate) one_class_svm: OneClassSvmDetector,
+    /// User-supplied detector run as `DetectorKind::Custom`
+    pub(crate) custom: Option<Box<dyn AnomalyDetection>>,
+}
+
+impl Detectors {
+    /// Default scalar and SVM detectors around the configured z-score and Mahalanobis ones
+    pub(crate) fn new(z_score: AnomalyDetector, mahalanobis: MahalanobisDetector) -> Self {
+        Self {
+            z_score,
+            mahalanobis,
+            ewma: EwmaDetector::default(),
+            mad: MadDetector::default(),
+            cusum: CusumDetector::default(),
+            quantile: QuantileDetector::default(),
+            one_class_svm: OneClassSvmDetector::default(),
+            custom: None,
+        }
+    }
+    
+    /// The detector run as `kind`, unless it is a custom one that isn't installed
+    pub(crate) fn get_mut(&mut self, kind: DetectorKind) -> Option<&mut dyn AnomalyDetection> {
+        match kind {
+            DetectorKind::ZScore => Some(&mut self.z_score),
+            DetectorKind::Mahalanobis => Some(&mut self.mahalanobis),
+            DetectorKind::Ewma => Some(&mut self.ewma),
+            DetectorKind::Mad => Some(&mut self.mad),
+            DetectorKind::Cusum => Some(&mut self.cusum),
+            DetectorKind::Quantile => Some(&mut self.quantile),
+            DetectorKind::OneClassSvm => Some(&mut self.one_class_svm),
+            DetectorKind::Custom => self.custom.as_deref_mut().map(|d| d as &mut dyn AnomalyDetection),
+        }
+    }
+    
+    /// Every installed detector
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &dyn AnomalyDetection> {
+        let builtin: [&dyn AnomalyDetection; 7] = [
+            &self.z_score,
+            &self.mahalanobis,
+            &self.ewma,
+            &self.mad,
+            &self.cusum,
+            &self.quantile,
+            &self.one_class_svm,
+        ];
+        builtin.into_iter().chain(self.custom.as_deref())
+    }
+    
+    /// Every installed detector, mutably
+    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = &mut dyn AnomalyDetection> {
+        let builtin: [&mut dyn AnomalyDetection; 7] = [
+            &mut self.z_score,
+            &mut self.mahalanobis,
+            &mut self.ewma,
+            &mut self.mad,
+            &mut self.cusum,
+            &mut self.quantile,
+            &mut self.one_class_svm,
+        ];
+        builtin.into_iter().chain(self.custom.as_deref_mut().map(|d| d as &mut dyn AnomalyDetection))
+    }
+}
+
+/// Send an anomaly to every live subscriber, dropping disconnected ones
+#[inline]
+pub(crate) fn notify(subscribers: &mut Vec<Sender<Anomaly>>, anomaly: &Anomaly) {
+    if !subscribers.is_empty() {
+        subscribers.retain(|tx| tx.send(anomaly.clone()).is_ok());
+    }
+}
+
+/// Selects anomalies for suppression; unset fields match anything
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnomalyMatcher {
+    /// Match only anomalies at or below this severity
+    pub max_severity: Option<Severity>,
+    /// Match only anomalies whose top contributing feature is this index
+    pub feature: Option<usize>,
+    /// Match only anomalies whose value lies in `[min, max]`
+    pub value_range: Option<(f32, f32)>,
+}
+
+impl AnomalyMatcher {
+    /// Match every anomaly
+    pub fn any() -> Self {
+        Self::default()
+    }
+    
+    /// Match anomalies driven mainly by one feature channel
+    pub fn feature(feature: usize) -> Self {
+        Self { feature: Some(feature), ..Self::default() }
+    }
+    
+    /// Restrict to anomalies at or below a severity
+    pub fn up_to(mut self, severity: Severity) -> Self {
+        self.max_severity = Some(severity);
+        self
+    }
+    
+    /// Check whether an anomaly matches
+    pub fn matches(&self, anomaly: &Anomaly) -> bool {
+        self.max_severity.is_none_or(|max| anomaly.severity <= max)
+            && self.feature.is_none_or(|f| anomaly.top_contributor().is_some_and(|c| c.feature == f))
+            && self.value_range.is_none_or(|(lo, hi)| anomaly.value >= lo && anomaly.value <= hi)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Suppression {
+    id: u64,
+    matcher: AnomalyMatcher,
+    until: f64,
+}
+
+/// Assigns anomaly ids and applies operator acknowledgements and suppressions
+///
+/// Suppressed anomalies are still detected and counted; they are only kept
+/// from alerting.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnomalyFilter {
+    next_anomaly_id: u64,
+    next_suppression_id: u64,
+    acknowledged: BTreeSet<u64>,
+    suppressions: Vec<Suppression>,
+}
+
+impl AnomalyFilter {
+    /// Give an anomaly the next id
+    pub fn assign_id(&mut self, anomaly: &mut Anomaly) -> u64 {
+        self.next_anomaly_id += 1;
+        anomaly.id = self.next_anomaly_id;
+        anomaly.id
+    }
+    
+    /// Mark an anomaly as handled; false if the id was never issued or is already acknowledged
+    pub fn acknowledge(&mut self, id: u64) -> bool {
+        id > 0 && id <= self.next_anomaly_id && self.acknowledged.insert(id)
+    }
+    
+    /// Check whether an anomaly was acknowledged
+    pub fn is_acknowledged(&self, id: u64) -> bool {
+        self.acknowledged.contains(&id)
+    }
+    
+    /// Mute matching anomalies for `duration` seconds from `now`, returning a suppression id
+    pub fn suppress(&mut self, matcher: AnomalyMatcher, duration: f64, now: f64) -> u64 {
+        self.next_suppression_id += 1;
+        self.suppressions.push(Suppression { id: self.next_suppression_id, matcher, until: now + duration });
+        self.next_suppression_id
+    }
+    
+    /// Remove a suppression early
+    pub fn lift(&mut self, suppression_id: u64) -> bool {
+        let before = self.suppressions.len();
+        self.suppressions.retain(|s| s.id != suppression_id);
+        self.suppressions.len() < before
+    }
+    
+    /// Number of suppressions still in force at `now`
+    pub fn active_suppressions(&self, now: f64) -> usize {
+        self.suppressions.iter().filter(|s| s.until > now).count()
+    }
+    
+    /// Check whether an anomaly is muted at `now`, dropping expired suppressions
+    pub fn is_suppressed(&mut self, anomaly: &Anomaly, now: f64) -> bool {
+        self.suppressions.retain(|s| s.until > now);
+        self.suppressions.iter().any(|s| s.matcher.matches(anomaly))
+    }
+}
+
+/// Anomaly counts over the trailing minute and hour
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnomalyRates {
+    pub last_minute: u32,
+    pub last_hour: u32,
+    /// Trailing-minute counts by severity (`Low`, `Medium`, `High`)
+    pub minute_by_severity: [u32; 3],
+    pub hour_by_severity: [u32; 3],
+}
+
+/// Limits on the anomaly rate itself; unset limits never alert
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RateAlertConfig {
+    pub max_per_minute: Option<u32>,
+    pub max_per_hour: Option<u32>,
+}
+
+/// Raised when the anomaly rate crosses a limit, suggesting a sensor fault or environment change
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RateAlert {
+    pub timestamp: f64,
+    pub rates: AnomalyRates,
+    /// Which limit was crossed
+    pub per_minute_exceeded: bool,
+    pub per_hour_exceeded: bool,
+}
+
+/// Rolling anomaly counts in one-second buckets over the last hour
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnomalyRateTracker {
+    buckets: VecDeque<(u64, [u32; 3])>,
+    config: RateAlertConfig,
+    /// Limits currently exceeded, so alerts fire on crossing rather than every cycle
+    exceeded: (bool, bool),
+}
+
+impl AnomalyRateTracker {
+    /// Create a tracker with meta-alert limits
+    pub fn new(config: RateAlertConfig) -> Self {
+        Self { config, ..Self::default() }
+    }
+    
+    /// Count an anomaly at `timestamp` (seconds)
+    pub fn record(&mut self, timestamp: f64, severity: Severity) {
+        let second = timestamp.max(0.0) as u64;
+        match self.buckets.back_mut() {
+            Some((s, counts)) if *s == second => counts[severity as usize] += 1,
+            _ => {
+                let mut counts = [0; 3];
+                counts[severity as usize] = 1;
+                self.buckets.push_back((second, counts));
+            }
+        }
+    }
+    
+    /// Counts over the trailing minute and hour as of `now`
+    pub fn rates(&self, now: f64) -> AnomalyRates {
+        let now = now.max(0.0) as u64;
+        let mut rates = AnomalyRates::default();
+        for &(second, counts) in self.buckets.iter().filter(|&&(s, _)| s + 3600 > now) {
+            let recent = second + 60 > now;
+            for (band, &count) in counts.iter().enumerate() {
+                rates.hour_by_severity[band] += count;
+                if recent {
+                    rates.minute_by_severity[band] += count;
+                }
+            }
+        }
+        rates.last_minute = rates.minute_by_severity.iter().sum();
+        rates.last_hour = rates.hour_by_severity.iter().sum();
+        rates
+    }
+    
+    /// Alert when a rate limit is newly crossed at `now`
+    pub fn check(&mut self, now: f64) -> Option<RateAlert> {
+        // Drop buckets older than an hour
+        let second = now.max(0.0) as u64;
+        while self.buckets.front().is_some_and(|&(s, _)| s + 3600 <= second) {
+            self.buckets.pop_front();
+        }
+        
+        let rates = self.rates(now);
+        let minute = self.config.max_per_minute.is_some_and(|max| rates.last_minute > max);
+        let hour = self.config.max_per_hour.is_some_and(|max| rates.last_hour > max);
+        
+        let crossed = (minute && !self.exceeded.0) || (hour && !self.exceeded.1);
+        self.exceeded = (minute, hour);
+        crossed.then_some(RateAlert {
+            timestamp: now,
+            rates,
+            per_minute_exceeded: minute,
+            per_hour_exceeded: hour,
+        })
+    }
+    
+    /// Meta-alert limits
+    pub fn config(&self) -> RateAlertConfig {
+        self.config
+    }
+    
+    /// Change meta-alert limits
+    pub fn set_config(&mut self, config: RateAlertConfig) {
+        self.config = config;
+    }
+    
+    /// Forget recorded anomalies, keeping the limits
+    pub fn clear(&mut self) {
+        *self = Self::new(self.config);
+    }
+}
+
+/// File format for anomaly and episode exports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Header row plus one row per record (pandas, Excel)
+    Csv,
+    /// Array of serde-serialized records
+    Json,
+}
+
+/// Bounded anomaly history with lifetime counters
+///
+/// Keeps the most recent `capacity` anomalies; counts and peak z-score cover
+/// everything ever recorded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyHistory {
+    retained: VecDeque<Anomaly>,
+    capacity: usize,
+    total: u64,
+    by_severity: [u64; 3],
+    max_z_score: f32,
+}
+
+impl Default for AnomalyHistory {
+    fn default() -> Self {
+        Self::new(1000)
+    }
+}
+
+impl AnomalyHistory {
+    /// Create a history retaining at most `capacity` anomalies
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            retained: VecDeque::with_capacity(capacity.min(1024)),
+            capacity,
+            total: 0,
+            by_severity: [0; 3],
+            max_z_score: 0.0,
+        }
+    }
+    
+    /// Record an anomaly, evicting the oldest retained one when full
+    pub fn push(&mut self, anomaly: Anomaly) {
+        self.total += 1;
+        self.by_severity[anomaly.severity as usize] += 1;
+        self.max_z_score = self.max_z_score.max(anomaly.z_score);
+        
+        if self.capacity == 0 {
+            return;
+        }
+        if self.retained.len() >= self.capacity {
+            self.retained.pop_front();
+        }
+        self.retained.push_back(anomaly);
+    }
+    
+    /// Change the retention limit, dropping the oldest entries if needed
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        let excess = self.retained.len().saturating_sub(capacity);
+        self.retained.drain(..excess);
+    }
+    
+    /// Retention limit
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    
+    /// Number of retained anomalies
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.retained.len()
+    }
+    
+    /// Check whether no anomalies are retained
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.retained.is_empty()
+    }
+    
+    /// Anomalies ever recorded
+    #[inline]
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+    
+    /// Anomalies ever recorded at a severity
+    #[inline]
+    pub fn count(&self, severity: Severity) -> u64 {
+        self.by_severity[severity as usize]
+    }
+    
+    /// Highest z-score ever recorded
+    #[inline]
+    pub fn max_z_score(&self) -> f32 {
+        self.max_z_score
+    }
+    
+    /// Retained anomalies, oldest first
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Anomaly> + ExactSizeIterator {
+        self.retained.iter()
+    }
+    
+    /// Most recent retained anomaly
+    pub fn latest(&self) -> Option<&Anomaly> {
+        self.retained.back()
+    }
+    
+    /// One page of retained anomalies, newest first
+    pub fn page(&self, page: usize, page_size: usize) -> impl Iterator<Item = &Anomaly> {
+        self.retained.iter().rev().skip(page.saturating_mul(page_size)).take(page_size)
+    }
+    
+    /// Retained anomalies with `from <= timestamp < to`
+    pub fn between(&self, from: f64, to: f64) -> impl Iterator<Item = &Anomaly> {
+        self.retained.iter().filter(move |a| a.timestamp >= from && a.timestamp < to)
+    }
+    
+    /// Write retained anomalies, oldest first
+    pub fn export<W: Write>(&self, mut out: W, format: ExportFormat) -> io::Result<()> {
+        match format {
+            ExportFormat::Json => {
+                let all: Vec<&Anomaly> = self.retained.iter().collect();
+                serde_json::to_writer(&mut out, &all)?;
+            }
+            ExportFormat::Csv => {
+                writeln!(out, "timestamp,value,z_score,severity,mean,stdev,top_feature")?;
+                for a in &self.retained {
+                    writeln!(
+                        out,
+                        "{},{},{},{:?},{},{},{}",
+                        a.timestamp,
+                        a.value,
+                        a.z_score,
+                        a.severity,
+                        a.mean,
+                        a.stdev,
+                        a.top_contributor().map_or("", |c| c.name()),
+                    )?;
+                }
+            }
+        }
+        out.flush()
+    }
+    
+    /// Drop retained anomalies and reset counters
+    pub fn clear(&mut self) {
+        self.retained.clear();
+        self.total = 0;
+        self.by_severity = [0; 3];
+        self.max_z_score = 0.0;
+    }
+}
+
+/// Grouping and alert rate limiting for anomaly episodes
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EpisodeConfig {
+    /// Quiet cycles tolerated before an ongoing episode is closed
+    pub max_gap: u32,
+    /// Minimum seconds between alerts of each severity (`Low`, `Medium`, `High`)
+    pub cooldown: [f64; 3],
+}
+
+impl Default for EpisodeConfig {
+    fn default() -> Self {
+        Self {
+            max_gap: 0,
+            cooldown: [60.0, 10.0, 0.0],
+        }
+    }
+}
+
+/// Run of consecutive anomalous cycles folded into one event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnomalyEpisode {
+    pub id: u64,
+    pub start: f64,
+    /// Timestamp of the last anomalous sample
+    pub end: f64,
+    pub samples: u32,
+    pub peak_severity: Severity,
+    pub peak_z_score: f32,
+}
+
+impl AnomalyEpisode {
+    /// Episode duration in timestamp units
+    #[inline]
+    pub fn duration(&self) -> f64 {
+        self.end - self.start
+    }
+}
+
+/// Folds per-cycle anomalies into episodes and rate-limits alerts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EpisodeTracker {
+    config: EpisodeConfig,
+    current: Option<AnomalyEpisode>,
+    quiet: u32,
+    closed: Vec<AnomalyEpisode>,
+    next_id: u64,
+    last_alert: [Option<f64>; 3],
+}
+
+impl EpisodeTracker {
+    /// Create a tracker with custom grouping and cooldowns
+    pub fn new(config: EpisodeConfig) -> Self {
+        Self { config, ..Self::default() }
+    }
+    
+    /// Feed one cycle's detection result.
+    ///
+    /// Returns the ongoing episode when an alert should be raised: when an
+    /// episode starts or escalates and that severity is out of cooldown.
+    pub fn observe(&mut self, anomaly: Option<&Anomaly>, timestamp: f64) -> Option<AnomalyEpisode> {
+        let Some(anomaly) = anomaly else {
+            if self.current.is_some() {
+                self.quiet += 1;
+                if self.quiet > self.config.max_gap {
+                    self.closed.extend(self.current.take());
+                }
+            }
+            return None;
+        };
+        self.quiet = 0;
+        
+        let escalated = match &mut self.current {
+            Some(episode) => {
+                episode.end = anomaly.timestamp;
+                episode.samples += 1;
+                episode.peak_z_score = episode.peak_z_score.max(anomaly.z_score);
+                let escalated = anomaly.severity > episode.peak_severity;
+                episode.peak_severity = episode.peak_severity.max(anomaly.severity);
+                escalated
+            }
+            None => {
+                self.next_id += 1;
+                self.current = Some(AnomalyEpisode {
+                    id: self.next_id,
+                    start: anomaly.timestamp,
+                    end: anomaly.timestamp,
+                    samples: 1,
+                    peak_severity: anomaly.severity,
+                    peak_z_score: anomaly.z_score,
+                });
+                true
+            }
+        };
+        
+        let band = anomaly.severity as usize;
+        let cooled = self.last_alert[band].is_none_or(|last| timestamp - last >= self.config.cooldown[band]);
+        if escalated && cooled {
+            self.last_alert[band] = Some(timestamp);
+            self.current.clone()
+        } else {
+            None
+        }
+    }
+    
+    /// Episode still in progress
+    pub fn current(&self) -> Option<&AnomalyEpisode> {
+        self.current.as_ref()
+    }
+    
+    /// End the episode in progress without waiting for quiet cycles
+    pub fn close(&mut self) {
+        self.closed.extend(self.current.take());
+        self.quiet = 0;
+    }
+    
+    /// Completed episodes, oldest first
+    pub fn episodes(&self) -> &[AnomalyEpisode] {
+        &self.closed
+    }
+    
+    /// Number of episodes seen, including one in progress
+    #[inline]
+    pub fn episode_count(&self) -> usize {
+        self.next_id as usize
+    }
+    
+    /// Write completed episodes and the one in progress, oldest first
+    pub fn export<W: Write>(&self, mut out: W, format: ExportFormat) -> io::Result<()> {
+        let all: Vec<&AnomalyEpisode> = self.closed.iter().chain(&self.current).collect();
+        match format {
+            ExportFormat::Json => serde_json::to_writer(&mut out, &all)?,
+            ExportFormat::Csv => {
+                writeln!(out, "id,start,end,duration,samples,peak_severity,peak_z_score")?;
+                for e in all {
+                    writeln!(
+                        out,
+                        "{},{},{},{},{},{:?},{}",
+                        e.id, e.start, e.end, e.duration(), e.samples, e.peak_severity, e.peak_z_score,
+                    )?;
+                }
+            }
+        }
+        out.flush()
+    }
+    
+    /// Grouping and cooldown settings
+    pub fn config(&self) -> EpisodeConfig {
+        self.config
+    }
+    
+    /// Change grouping and cooldowns at runtime
+    pub fn set_config(&mut self, config: EpisodeConfig) {
+        self.config = config;
+    }
+    
+    /// Clear episodes and cooldown state, keeping the config
+    pub fn clear(&mut self) {
+        *self = Self::new(self.config);
+    }
+}
+
+/// Periodic baseline learned per phase bucket (e.g. hour of day)
+///
+/// Each bucket tracks how far values in that phase of the period sit from
+/// the overall level; subtracting it leaves the non-seasonal residual.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeasonalBaseline {
+    period: f64,
+    level: f32,
+    level_count: usize,
+    offsets: Vec<f32>,
+    counts: Vec<u32>,
+    /// Floor on the adaptation rate so the baseline keeps tracking slow change
+    min_rate: f32,
+}
+
+impl SeasonalBaseline {
+    /// Create a baseline for a period (in timestamp units) split into `buckets` phases
+    pub fn new(period: f64, buckets: usize) -> Self {
+        let buckets = buckets.max(1);
+        Self {
+            period: period.max(f64::EPSILON),
+            level: 0.0,
+            level_count: 0,
+            offsets: vec![0.0; buckets],
+            counts: vec![0; buckets],
+            min_rate: 0.05,
+        }
+    }
+    
+    /// Daily cycle with hourly buckets, for timestamps in seconds
+    pub fn daily() -> Self {
+        Self::new(86_400.0, 24)
+    }
+    
+    /// Phase bucket of a timestamp
+    #[inline]
+    pub fn bucket(&self, timestamp: f64) -> usize {
+        let phase = timestamp.rem_euclid(self.period) / self.period;
+        ((phase * self.offsets.len() as f64) as usize).min(self.offsets.len() - 1)
+    }
+    
+    /// Expected seasonal offset at a timestamp (zero for unseen phases)
+    #[inline]
+    pub fn expected(&self, timestamp: f64) -> f32 {
+        self.offsets[self.bucket(timestamp)]
+    }
+    
+    /// Value with the seasonal offset removed
+    #[inline]
+    pub fn deseasonalize(&self, value: f32, timestamp: f64) -> f32 {
+        value - self.expected(timestamp)
+    }
+    
+    /// Learn from an observation
+    pub fn update(&mut self, value: f32, timestamp: f64) {
+        self.level_count += 1;
+        let level_rate = (1.0 / self.level_count as f32).max(self.min_rate * 0.1);
+        self.level += level_rate * (value - self.level);
+        
+        let b = self.bucket(timestamp);
+        self.counts[b] = self.counts[b].saturating_add(1);
+        let rate = (1.0 / self.counts[b] as f32).max(self.min_rate);
+        self.offsets[b] += rate * ((value - self.level) - self.offsets[b]);
+    }
+    
+    /// Number of phase buckets
+    #[inline]
+    pub fn buckets(&self) -> usize {
+        self.offsets.len()
+    }
+    
+    /// Forget everything learned
+    pub fn clear(&mut self) {
+        self.level = 0.0;
+        self.level_count = 0;
+        self.offsets.iter_mut().for_each(|o| *o = 0.0);
+        self.counts.iter_mut().for_each(|c| *c = 0);
+    }
+}
+
+/// High-performance anomaly detector using statistical methods
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyDetector {
+    window: VecDeque<f32>,
+    window_size: usize,
+    anomalies: AnomalyHistory,
+    last_score: f32,
+    seasonal: Option<SeasonalBaseline>,
+    config: AnomalyConfig,
+    #[serde(skip)]
+    subscribers: Vec<Sender<Anomaly>>,
+    feature_window: FeatureWindow,
+    /// Prior (mean, variance, samples) standing in for the unfilled part of the window
+    #[serde(default)]
+    prior: Option<(f32, f32, usize)>,
+    
+    // Running statistics for O(1) updates
+    running_sum: f32,
+    running_sum_sq: f32,
+}
+
+impl AnomalyDetector {
+    /// Create a new anomaly detector
+    pub fn new(window_size: usize) -> Self {
+        Self::with_config(window_size, AnomalyConfig::default())
+    }
+    
+    /// Create a detector with custom thresholds (invalid configs fall back to the default)
+    pub fn with_config(window_size: usize, config: AnomalyConfig) -> Self {
+        Self {
+            window: VecDeque::with_capacity(window_size),
+            window_size,
+            anomalies: AnomalyHistory::default(),
+            last_score: 0.0,
+            seasonal: None,
+            config: if config.is_valid() { config } else { AnomalyConfig::default() },
+            subscribers: Vec::new(),
+            feature_window: FeatureWindow::default(),
+            prior: None,
+            running_sum: 0.0,
+            running_sum_sq: 0.0,
+        }
+    }
+    
+    /// Current thresholds
+    pub fn config(&self) -> AnomalyConfig {
+        self.config
+    }
+    
+    /// Replace thresholds at runtime; returns false and keeps the old config if invalid
+    pub fn set_config(&mut self, config: AnomalyConfig) -> bool {
+        if !config.is_valid() {
+            return false;
+        }
+        self.config = config;
+        true
+    }
+    
+    /// Receive every anomaly as soon as it is detected.
+    ///
+    /// Dropping the receiver unsubscribes.
+    pub fn subscribe(&mut self) -> Receiver<Anomaly> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers.push(tx);
+        rx
+    }
+    
+    /// Score residuals after removing a learned periodic baseline
+    pub fn with_seasonal_baseline(mut self, baseline: SeasonalBaseline) -> Self {
+        self.seasonal = Some(baseline);
+        self
+    }
+    
+    /// Enable, replace or disable seasonal baseline removal
+    pub fn set_seasonal_baseline(&mut self, baseline: Option<SeasonalBaseline>) {
+        self.seasonal = baseline;
+    }
+    
+    /// Access the seasonal baseline, if enabled
+    pub fn seasonal_baseline(&self) -> Option<&SeasonalBaseline> {
+        self.seasonal.as_ref()
+    }
+    
+    /// Seed the statistics with a known mean and stdev worth `samples` observations.
+    ///
+    /// The prior fills the part of the window real samples have not reached
+    /// yet, so detection starts immediately and fades to purely observed
+    /// statistics once the window is full. With a seasonal baseline the
+    /// prior describes the residual.
+    pub fn warm_start(&mut self, mean: f32, stdev: f32, samples: usize) {
+        self.prior = (samples > 0 && mean.is_finite() && stdev.is_finite())
+            .then_some((mean, stdev * stdev, samples));
+    }
+    
+    /// Current window (mean, stdev, samples), e.g. to warm-start another detector
+    pub fn baseline(&self) -> Option<(f32, f32, usize)> {
+        let n = self.window.len();
+        (n > 0).then(|| {
+            let mean = self.running_sum / n as f32;
+            let variance = (self.running_sum_sq / n as f32) - mean * mean;
+            (mean, variance.max(0.0).sqrt(), n)
+        })
+    }
+    
+    /// Serialize the statistical state (window, running sums, history) as JSON
+    pub fn save_to<W: Write>(&self, out: W) -> Result<()> {
+        serde_json::to_writer(out, self)?;
+        Ok(())
+    }
+    
+    /// Restore a detector written by `save_to` (without subscribers)
+    pub fn load_from<R: Read>(input: R) -> Result<Self> {
+        Ok(serde_json::from_reader(input)?)
+    }
+    
+    /// Save state to a file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.save_to(&mut out)?;
+        Ok(out.flush()?)
+    }
+    
+    /// Load state from a file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::load_from(BufReader::new(File::open(path)?))
+    }
+    
+    /// Adopt saved state while keeping this detector's subscribers
+    pub fn restore(&mut self, saved: AnomalyDetector) {
+        let subscribers = std::mem::take(&mut self.subscribers);
+        *self = AnomalyDetector { subscribers, ..saved };
+    }
+    
+    /// Detect anomalies using optimized single-pass statistics
+    pub fn detect(&mut self, value: f32, timestamp: f64) -> Option<Anomaly> {
+        self.detect_with_features(value, &[], timestamp)
+    }
+    
+    /// Detect anomalies in a fused value, attributing them to the features it was built from
+    pub fn detect_with_features(&mut self, value: f32, features: &[f32], timestamp: f64) -> Option<Anomaly> {
+        self.last_score = 0.0;
+        if !features.is_empty() {
+            self.feature_window.push(features, self.window_size);
+        }
+        
+        // Score the residual; `Anomaly::mean` is shifted back to the expected level
+        let expected = match &mut self.seasonal {
+            Some(baseline) => {
+                let expected = baseline.expected(timestamp);
+                baseline.update(value, timestamp);
+                expected
+            }
+            None => 0.0,
+        };
+        let residual = value - expected;
+        
+        // Update running statistics
+        if self.window.len() >= self.window_size {
+            if let Some(old_val) = self.window.pop_front() {
+                self.running_sum -= old_val;
+                self.running_sum_sq -= old_val * old_val;
+            }
+        }
+        
+        self.window.push_back(residual);
+        self.running_sum += residual;
+        self.running_sum_sq += residual * residual;
+        
+        // Prior pseudo-samples fill whatever the window has not reached yet
+        let (prior_n, prior_mean, prior_var) = match self.prior {
+            Some((mean, var, samples)) => {
+                let k = samples.min(self.window_size).saturating_sub(self.window.len());
+                if k == 0 {
+                    self.prior = None;
+                }
+                (k as f32, mean, var)
+            }
+            None => (0.0, 0.0, 0.0),
+        };
+        
+        // Need enough values for meaningful statistics
+        if self.window.len() + (prior_n as usize) < self.config.min_window {
+            return None;
+        }
+        
+        let n = self.window.len() as f32 + prior_n;
+        let mean = (self.running_sum + prior_n * prior_mean) / n;
+        let variance = (self.running_sum_sq + prior_n * (prior_var + prior_mean * prior_mean)) / n - (mean * mean);
+        let stdev = variance.max(0.0).sqrt();
+        
+        // Calculate Z-score
+        let z_score = if stdev > 0.0001 {
+            ((residual - mean) / stdev).abs()
+        } else {
+            0.0
+        };
+        self.last_score = z_score;
+        
+        // Detect anomaly based on Z-score
+        if z_score > self.config.threshold {
+            let severity = self.config.severity(z_score);
+            
+            let anomaly = Anomaly {
+                id: 0,
+                timestamp,
+                value,
+                z_score,
+                severity,
+                mean: mean + expected,
+                stdev,
+                contributions: self.feature_window.contributions(features),
+                explanation: AnomalyExplanation::default(),
+                detectors: Vec::new(),
+            }
+            .explained(WindowStats::of(self.window.iter(), mean + expected, stdev).map(|w| WindowStats {
+                min: w.min + expected,
+                max: w.max + expected,
+                ..w
+            }));
+            
+            notify(&mut self.subscribers, &anomaly);
+            self.anomalies.push(anomaly.clone());
+            Some(anomaly)
+        } else {
+            None
+        }
+    }
+    
+    /// Score computed for the most recent sample (0 while warming up)
+    #[inline]
+    pub fn last_score(&self) -> f32 {
+        self.last_score
+    }
+    
+    /// Get the count of detected anomalies (including ones evicted from history)
+    #[inline]
+    pub fn anomaly_count(&self) -> usize {
+        self.anomalies.total() as usize
+    }
+    
+    /// Get retained anomalies and aggregate counters
+    pub fn get_anomalies(&self) -> &AnomalyHistory {
+        &self.anomalies
+    }
+    
+    /// Export retained anomalies as CSV or JSON
+    pub fn export<W: Write>(&self, out: W, format: ExportFormat) -> io::Result<()> {
+        self.anomalies.export(out, format)
+    }
+    
+    /// Bound the number of retained anomalies
+    pub fn set_history_capacity(&mut self, capacity: usize) {
+        self.anomalies.set_capacity(capacity);
+    }
+    
+    /// Clear the detector state
+    pub fn clear(&mut self) {
+        self.window.clear();
+        self.feature_window.clear();
+        self.anomalies.clear();
+        self.last_score = 0.0;
+        self.prior = None;
+        if let Some(baseline) = &mut self.seasonal {
+            baseline.clear();
+        }
+        self.running_sum = 0.0;
+        self.running_sum_sq = 0.0;
+    }
+}
+
+impl AnomalyDetection for AnomalyDetector {
+    fn observe(&mut self, features: &[f32], timestamp: f64) -> Option<Anomaly> {
+        self.detect_with_features(mean_feature(features), features, timestamp)
+    }
+    
+    fn observe_cycle(&mut self, value: f32, features: &[f32], timestamp: f64) -> Option<Anomaly> {
+        self.detect_with_features(value, features, timestamp)
+    }
+    
+    fn last_score(&self) -> f32 {
+        self.last_score
+    }
+    
+    fn anomaly_count(&self) -> usize {
+        AnomalyDetector::anomaly_count(self)
+    }
+    
+    fn set_history_capacity(&mut self, capacity: usize) {
+        AnomalyDetector::set_history_capacity(self, capacity);
+    }
+    
+    fn clear(&mut self) {
+        AnomalyDetector::clear(self);
+    }
+}
+
+impl HealthCheck for AnomalyDetector {
+    fn health(&self, _: &HealthContext<'_>) -> Health {
+        // A prior stands in for the samples the window has not reached yet
+        let prior = self.prior.map_or(0, |(_, _, samples)| samples.min(self.window_size));
+        let samples = self.window.len().max(prior);
+        if samples < self.config.min_window {
+            Health::degraded(format!("warming up: {samples} of {} samples", self.config.min_window))
+        } else {
+            Health::healthy()
+        }
+    }
+}
+
+/// Robust detector using the median and median absolute deviation
+///
+/// Scores each value with the modified z-score `0.6745 * (x - median) / MAD`
+/// against the preceding window. Unlike mean/stdev, the median and MAD are
+/// barely moved by the outliers already in the window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MadDetector {
+    window: VecDeque<f32>,
+    window_size: usize,
+    threshold: f32,
+    #[serde(skip)]
+    scratch: Vec<f32>,
+    anomalies: AnomalyHistory,
+    last_score: f32,
+}
+
+impl MadDetector {
+    /// Create a detector over `window_size` samples flagging modified z-scores above `threshold`
+    pub fn new(window_size: usize, threshold: f32) -> Self {
+        Self {
+            window: VecDeque::with_capacity(window_size),
+            window_size: window_size.max(3),
+            threshold: threshold.max(f32::EPSILON),
+            scratch: Vec::with_capacity(window_size),
+            anomalies: AnomalyHistory::default(),
+            last_score: 0.0,
+        }
+    }
+    
+    /// Detect outliers robustly
+    pub fn detect(&mut self, value: f32, timestamp: f64) -> Option<Anomaly> {
+        self.last_score = 0.0;
+        let anomaly = if self.window.len() >= 3 {
+            let median = self.median_of_window(|v| v);
+            let mad = self.median_of_window(|v| (v - median).abs());
+            
+            // 1.4826 * MAD estimates the standard deviation for normal data
+            let sigma = 1.4826 * mad;
+            let z_score = if mad > 0.0001 { (0.6745 * (value - median) / mad).abs() } else { 0.0 };
+            self.last_score = z_score;
+            
+            // Map the threshold onto the scalar detector's severity scale
+            (z_score > self.threshold).then(|| Anomaly {
+                id: 0,
+                timestamp,
+                value,
+                z_score,
+                severity: Severity::from_z(z_score * 2.0 / self.threshold),
+                mean: median,
+                stdev: sigma,
+                contributions: Vec::new(),
+                explanation: AnomalyExplanation::default(),
+                detectors: Vec::new(),
+            }
+            .explained(WindowStats::of(self.window.iter(), median, sigma)))
+        } else {
+            None
+        };
+        
+        if self.window.len() >= self.window_size {
+            self.window.pop_front();
+        }
+        self.window.push_back(value);
+        
+        if let Some(a) = &anomaly {
+            self.anomalies.push(a.clone());
+        }
+        anomaly
+    }
+    
+    /// Median of the window after mapping each value
+    fn median_of_window(&mut self, map: impl Fn(f32) -> f32) -> f32 {
+        self.scratch.clear();
+        self.scratch.extend(self.window.iter().map(|&v| map(v)));
+        let mid = self.scratch.len() / 2;
+        let (_, median, _) = self.scratch.select_nth_unstable_by(mid, f32::total_cmp);
+        *median
+    }
+    
+    /// Score computed for the most recent sample (0 while warming up)
+    #[inline]
+    pub fn last_score(&self) -> f32 {
+        self.last_score
+    }
+    
+    /// Get the count of detected anomalies (including ones evicted from history)
+    #[inline]
+    pub fn anomaly_count(&self) -> usize {
+        self.anomalies.total() as usize
+    }
+    
+    /// Get retained anomalies and aggregate counters
+    pub fn get_anomalies(&self) -> &AnomalyHistory {
+        &self.anomalies
+    }
+    
+    /// Export retained anomalies as CSV or JSON
+    pub fn export<W: Write>(&self, out: W, format: ExportFormat) -> io::Result<()> {
+        self.anomalies.export(out, format)
+    }
+    
+    /// Bound the number of retained anomalies
+    pub fn set_history_capacity(&mut self, capacity: usize) {
+        self.anomalies.set_capacity(capacity);
+    }
+    
+    /// Clear the detector state
+    pub fn clear(&mut self) {
+        self.window.clear();
+        self.anomalies.clear();
+        self.last_score = 0.0;
+    }
+}
+
+impl Default for MadDetector {
+    fn default() -> Self {
+        Self::new(20, 3.5)
+    }
+}
+
+/// Two-sided CUSUM detector for slow drifts and small sustained shifts
+///
+/// Learns a reference mean and sigma over the first `warmup` samples, then
+/// accumulates standardized deviations beyond a slack of `k` sigmas. An
+/// alarm fires when either sum exceeds `h`; the sums then restart so an
+/// ongoing shift keeps re-alarming at a rate set by its size.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CusumDetector {
+    k: f32,
+    h: f32,
+    warmup: usize,
+    count: usize,
+    reference_mean: f32,
+    reference_m2: f32,
+    upper: f32,
+    lower: f32,
+    anomalies: AnomalyHistory,
+    last_score: f32,
+}
+
+impl CusumDetector {
+    /// Create a detector with slack `k` and decision interval `h` (both in sigmas)
+    pub fn new(k: f32, h: f32, warmup: usize) -> Self {
+        Self {
+            k: k.max(0.0),
+            h: h.max(f32::EPSILON),
+            warmup: warmup.max(2),
+            count: 0,
+            reference_mean: 0.0,
+            reference_m2: 0.0,
+            upper: 0.0,
+            lower: 0.0,
+            anomalies: AnomalyHistory::default(),
+            last_score: 0.0,
+        }
+    }
+    
+    /// Accumulate a sample and alarm on a sustained shift
+    pub fn detect(&mut self, value: f32, timestamp: f64) -> Option<Anomaly> {
+        self.last_score = 0.0;
+        if self.count < self.warmup {
+            // Welford over the reference period
+            self.count += 1;
+            let delta = value - self.reference_mean;
+            self.reference_mean += delta / self.count as f32;
+            self.reference_m2 += delta * (value - self.reference_mean);
+            return None;
+        }
+        
+        let sigma = self.reference_sigma();
+        if sigma <= 0.0001 {
+            return None;
+        }
+        let deviation = (value - self.reference_mean) / sigma;
+        self.upper = (self.upper + deviation - self.k).max(0.0);
+        self.lower = (self.lower - deviation - self.k).max(0.0);
+        
+        let statistic = self.upper.max(self.lower);
+        self.last_score = statistic;
+        if statistic <= self.h {
+            return None;
+        }
+        
+        // Map the decision interval onto the scalar detector's severity scale
+        let anomaly = Anomaly {
+            id: 0,
+            timestamp,
+            value,
+            z_score: statistic,
+            severity: Severity::from_z(statistic * 2.0 / self.h),
+            mean: self.reference_mean,
+            stdev: sigma,
+            contributions: Vec::new(),
+            explanation: AnomalyExplanation::default(),
+            detectors: Vec::new(),
+        };
+        self.upper = 0.0;
+        self.lower = 0.0;
+        self.anomalies.push(anomaly.clone());
+        Some(anomaly)
+    }
+    
+    /// Reference standard deviation learned during warmup
+    #[inline]
+    fn reference_sigma(&self) -> f32 {
+        (self.reference_m2 / (self.count.max(2) - 1) as f32).max(0.0).sqrt()
+    }
+    
+    /// Current upper and lower cumulative sums
+    #[inline]
+    pub fn sums(&self) -> (f32, f32) {
+        (self.upper, self.lower)
+    }
+    
+    /// Forget the reference and learn a new one (e.g. after an accepted change)
+    pub fn rebaseline(&mut self) {
+        self.count = 0;
+        self.reference_mean = 0.0;
+        self.reference_m2 = 0.0;
+        self.upper = 0.0;
+        self.lower = 0.0;
+    }
+    
+    /// Score computed for the most recent sample (0 while warming up)
+    #[inline]
+    pub fn last_score(&self) -> f32 {
+        self.last_score
+    }
+    
+    /// Get the count of detected anomalies (including ones evicted from history)
+    #[inline]
+    pub fn anomaly_count(&self) -> usize {
+        self.anomalies.total() as usize
+    }
+    
+    /// Get retained anomalies and aggregate counters
+    pub fn get_anomalies(&self) -> &AnomalyHistory {
+        &self.anomalies
+    }
+    
+    /// Export retained anomalies as CSV or JSON
+    pub fn export<W: Write>(&self, out: W, format: ExportFormat) -> io::Result<()> {
+        self.anomalies.export(out, format)
+    }
+    
+    /// Bound the number of retained anomalies
+    pub fn set_history_capacity(&mut self, capacity: usize) {
+        self.anomalies.set_capacity(capacity);
+    }
+    
+    /// Clear the detector state
+    pub fn clear(&mut self) {
+        self.rebaseline();
+        self.anomalies.clear();
+        self.last_score = 0.0;
+    }
+}
+
+impl Default for CusumDetector {
+    fn default() -> Self {
+        Self::new(0.5, 5.0, 30)
+    }
+}
+
+/// EWMA control-chart detector with O(1) memory
+///
+/// Smooths the signal with weight `lambda` and flags when the smoothed value
+/// leaves `limit` standard errors of a slowly adapting baseline. Small
+/// sustained level shifts accumulate in the EWMA, so they are caught sooner
+/// than by a windowed z-score.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EwmaDetector {
+    lambda: f32,
+    limit: f32,
+    /// Baseline adaptation rate once warmed up
+    baseline_rate: f32,
+    warmup: usize,
+    count: usize,
+    ewma: f32,
+    baseline_mean: f32,
+    baseline_var: f32,
+    anomalies: AnomalyHistory,
+    last_score: f32,
+}
+
+impl EwmaDetector {
+    /// Create a detector with smoothing `lambda` in (0, 1] and control limit `limit` (in sigmas)
+    pub fn new(lambda: f32, limit: f32) -> Self {
+        Self {
+            lambda: lambda.clamp(f32::EPSILON, 1.0),
+            limit: limit.max(f32::EPSILON),
+            baseline_rate: 0.01,
+            warmup: 10,
+            count: 0,
+            ewma: 0.0,
+            baseline_mean: 0.0,
+            baseline_var: 0.0,
+            anomalies: AnomalyHistory::default(),
+            last_score: 0.0,
+        }
+    }
+    
+    /// Detect level shifts in a scalar signal
+    pub fn detect(&mut self, value: f32, timestamp: f64) -> Option<Anomaly> {
+        self.last_score = 0.0;
+        self.count += 1;
+        
+        // Cumulative average during warmup, exponential afterwards
+        let rate = (1.0 / self.count as f32).max(self.baseline_rate);
+        let delta = value - self.baseline_mean;
+        
+        if self.count == 1 {
+            self.ewma = value;
+            self.baseline_mean = value;
+            return None;
+        }
+        self.ewma += self.lambda * (value - self.ewma);
+        
+        let anomaly = if self.count > self.warmup {
+            let stdev = self.baseline_var.max(0.0).sqrt();
+            let standard_error = stdev * (self.lambda / (2.0 - self.lambda)).sqrt();
+            let z_score = if standard_error > 0.0001 {
+                ((self.ewma - self.baseline_mean) / standard_error).abs()
+            } else {
+                0.0
+            };
+            self.last_score = z_score;
+            
+            // Map the control limit onto the scalar detector's severity scale
+            (z_score > self.limit).then(|| Anomaly {
+                id: 0,
+                timestamp,
+                value,
+                z_score,
+                severity: Severity::from_z(z_score * 2.0 / self.limit),
+                mean: self.baseline_mean,
+                stdev,
+                contributions: Vec::new(),
+                explanation: AnomalyExplanation::default(),
+                detectors: Vec::new(),
+            })
+        } else {
+            None
+        };
+        
+        self.baseline_mean += rate * delta;
+        self.baseline_var = (1.0 - rate) * (self.baseline_var + rate * delta * delta);
+        
+        if let Some(a) = &anomaly {
+            self.anomalies.push(a.clone());
+        }
+        anomaly
+    }
+    
+    /// Current smoothed value
+    #[inline]
+    pub fn ewma(&self) -> f32 {
+        self.ewma
+    }
+    
+    /// Score computed for the most recent sample (0 while warming up)
+    #[inline]
+    pub fn last_score(&self) -> f32 {
+        self.last_score
+    }
+    
+    /// Get the count of detected anomalies (including ones evicted from history)
+    #[inline]
+    pub fn anomaly_count(&self) -> usize {
+        self.anomalies.total() as usize
+    }
+    
+    /// Get retained anomalies and aggregate counters
+    pub fn get_anomalies(&self) -> &AnomalyHistory {
+        &self.anomalies
+    }
+    
+    /// Export retained anomalies as CSV or JSON
+    pub fn export<W: Write>(&self, out: W, format: ExportFormat) -> io::Result<()> {
+        self.anomalies.export(out, format)
+    }
+    
+    /// Bound the number of retained anomalies
+    pub fn set_history_capacity(&mut self, capacity: usize) {
+        self.anomalies.set_capacity(capacity);
+    }
+    
+    /// Clear the detector state
+    pub fn clear(&mut self) {
+        self.count = 0;
+        self.ewma = 0.0;
+        self.baseline_mean = 0.0;
+        self.baseline_var = 0.0;
+        self.anomalies.clear();
+        self.last_score = 0.0;
+    }
+}
+
+impl Default for EwmaDetector {
+    fn default() -> Self {
+        Self::new(0.2, 3.0)
+    }
+}
+
+/// Streaming quantile sketch (merging t-digest)
+///
+/// Keeps a bounded set of weighted centroids whose size shrinks toward the
+/// tails, so extreme quantiles stay accurate with `O(compression)` memory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TDigest {
+    compression: f32,
+    /// (mean, weight), sorted by mean
+    centroids: Vec<(f32, f32)>,
+    buffer: Vec<f32>,
+    count: u64,
+    min: f32,
+    max: f32,
+}
+
+impl Default for TDigest {
+    fn default() -> Self {
+        Self::new(100.0)
+    }
+}
+
+impl TDigest {
+    /// Create an empty digest; higher `compression` keeps more centroids
+    pub fn new(compression: f32) -> Self {
+        let compression = compression.max(10.0);
+        Self {
+            compression,
+            centroids: Vec::new(),
+            buffer: Vec::with_capacity(compression as usize * 5),
+            count: 0,
+            min: f32::MAX,
+            max: f32::MIN,
+        }
+    }
+    
+    /// Add one sample
+    pub fn add(&mut self, value: f32) {
+        if !value.is_finite() {
+            return;
+        }
+        self.buffer.push(value);
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        if self.buffer.len() >= self.compression as usize * 5 {
+            self.flush();
+        }
+    }
+    
+    /// Number of samples added
+    #[inline]
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+    
+    /// Number of centroids after merging buffered samples
+    pub fn centroid_count(&mut self) -> usize {
+        self.flush();
+        self.centroids.len()
+    }
+    
+    /// Merge buffered samples into the centroids
+    pub fn flush(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let mut items: Vec<(f32, f32)> = self.buffer.drain(..).map(|v| (v, 1.0)).collect();
+        items.append(&mut self.centroids);
+        items.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+        
+        // Scale function k1: centroids may span one unit of k = d/(2pi) * asin(2q - 1)
+        let total = self.count as f32;
+        let scale = |q: f32| self.compression / (2.0 * std::f32::consts::PI) * (2.0 * q - 1.0).clamp(-1.0, 1.0).asin();
+        let mut merged: Vec<(f32, f32)> = Vec::with_capacity(self.compression as usize * 2);
+        let mut current = items[0];
+        let mut before = 0.0f32;
+        let mut limit = scale(0.0) + 1.0;
+        for &(mean, weight) in &items[1..] {
+            if scale((before + current.1 + weight) / total) <= limit {
+                current.0 += (mean - current.0) * weight / (current.1 + weight);
+                current.1 += weight;
+            } else {
+                before += current.1;
+                limit = scale(before / total) + 1.0;
+                merged.push(current);
+                current = (mean, weight);
+            }
+        }
+        merged.push(current);
+        self.centroids = merged;
+    }
+    
+    /// Estimated value at quantile `q` in `[0, 1]`
+    pub fn quantile(&mut self, q: f32) -> Option<f32> {
+        self.flush();
+        if self.centroids.is_empty() {
+            return None;
+        }
+        let target = q.clamp(0.0, 1.0) * self.count as f32;
+        
+        // Interpolate between centroid centers, anchored at min and max
+        let (mut prev_rank, mut prev_value) = (0.0, self.min);
+        let mut rank = 0.0;
+        for &(mean, weight) in &self.centroids {
+            let center = rank + weight / 2.0;
+            if target < center {
+                let t = if center > prev_rank { (target - prev_rank) / (center - prev_rank) } else { 0.0 };
+                return Some(prev_value + t * (mean - prev_value));
+            }
+            rank += weight;
+            (prev_rank, prev_value) = (center, mean);
+        }
+        let t = if rank > prev_rank { (target - prev_rank) / (rank - prev_rank) } else { 1.0 };
+        Some(prev_value + t.min(1.0) * (self.max - prev_value))
+    }
+    
+    /// Forget all samples
+    pub fn clear(&mut self) {
+        self.centroids.clear();
+        self.buffer.clear();
+        self.count = 0;
+        self.min = f32::MAX;
+        self.max = f32::MIN;
+    }
+}
+
+/// Detector flagging values beyond percentiles of the long-run distribution
+///
+/// Unlike mean/stdev, percentile bounds are not inflated by heavy tails. The
+/// score is the distance from the median relative to the distance of the
+/// crossed bound, scaled so the bound sits at 2 on the z-score scale.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuantileDetector {
+    digest: TDigest,
+    lower: f32,
+    upper: f32,
+    /// Samples learned before scoring starts
+    warmup: u64,
+    anomalies: AnomalyHistory,
+    last_score: f32,
+}
+
+impl QuantileDetector {
+    /// Create a detector flagging values below quantile `lower` or above quantile `upper`
+    pub fn new(lower: f32, upper: f32) -> Self {
+        let lower = lower.clamp(0.0, 0.5);
+        Self {
+            digest: TDigest::default(),
+            lower,
+            upper: upper.clamp(0.5, 1.0).max(lower),
+            warmup: 100,
+            anomalies: AnomalyHistory::default(),
+            last_score: 0.0,
+        }
+    }
+    
+    /// Require `samples` observations before scoring
+    pub fn with_warmup(mut self, samples: u64) -> Self {
+        self.warmup = samples.max(1);
+        self
+    }
+    
+    /// Score a value against the learned percentiles, then learn from it
+    pub fn detect(&mut self, value: f32, timestamp: f64) -> Option<Anomaly> {
+        self.last_score = 0.0;
+        let bounds = if self.digest.count() >= self.warmup {
+            self.digest.quantile(self.lower)
+                .zip(self.digest.quantile(0.5))
+                .zip(self.digest.quantile(self.upper))
+        } else {
+            None
+        };
+        self.digest.add(value);
+        
+        let ((low, median), high) = bounds?;
+        let bound = if value >= median { high } else { low };
+        let reach = (bound - median).abs();
+        let z_score = if reach > 0.0001 { 2.0 * (value - median).abs() / reach } else { 0.0 };
+        self.last_score = z_score;
+        
+        let anomaly = (z_score > 2.0).then(|| Anomaly {
+            id: 0,
+            timestamp,
+            value,
+            z_score,
+            severity: Severity::from_z(z_score),
+            mean: median,
+            stdev: reach / 2.0,
+            contributions: Vec::new(),
+            explanation: AnomalyExplanation::default(),
+            detectors: Vec::new(),
+        }
+        .explained(None))?;
+        
+        self.anomalies.push(anomaly.clone());
+        Some(anomaly)
+    }
+    
+    /// Estimated quantile of everything seen so far
+    pub fn quantile(&mut self, q: f32) -> Option<f32> {
+        self.digest.quantile(q)
+    }
+    
+    /// Score computed for the most recent sample (0 while warming up)
+    #[inline]
+    pub fn last_score(&self) -> f32 {
+        self.last_score
+    }
+    
+    /// Get the count of detected anomalies (including ones evicted from history)
+    #[inline]
+    pub fn anomaly_count(&self) -> usize {
+        self.anomalies.total() as usize
+    }
+    
+    /// Get retained anomalies and aggregate counters
+    pub fn get_anomalies(&self) -> &AnomalyHistory {
+        &self.anomalies
+    }
+    
+    /// Export retained anomalies as CSV or JSON
+    pub fn export<W: Write>(&self, out: W, format: ExportFormat) -> io::Result<()> {
+        self.anomalies.export(out, format)
+    }
+    
+    /// Bound the number of retained anomalies
+    pub fn set_history_capacity(&mut self, capacity: usize) {
+        self.anomalies.set_capacity(capacity);
+    }
+    
+    /// Clear the detector state
+    pub fn clear(&mut self) {
+        self.digest.clear();
+        self.anomalies.clear();
+        self.last_score = 0.0;
+    }
+}
+
+impl Default for QuantileDetector {
+    fn default() -> Self {
+        Self::new(0.005, 0.995)
+    }
+}
+
+/// Multivariate detector using Mahalanobis distance over feature vectors
+///
+/// Keeps a running mean and covariance (Welford), so correlated features
+/// drifting together in an unusual direction are caught even when each one
+/// alone looks normal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MahalanobisDetector<F: Float = f32> {
+    dims: usize,
+    count: usize,
+    mean: Vec<F>,
+    /// Running sum of outer products of deviations (row-major, dims x dims)
+    comoment: Vec<F>,
+    /// Diagonal loading keeping the covariance invertible
+    regularization: F,
+    anomalies: AnomalyHistory,
+    last_score: f32,
+}
+
+impl MahalanobisDetector {
+    /// Create a detector for feature vectors of length `dims`
+    pub fn new(dims: usize) -> Self {
+        Self::with_precision(dims)
+    }
+}
+
+impl<F: Float> MahalanobisDetector<F> {
+    /// Create a detector keeping its statistics at precision `F`,
+    /// e.g. `MahalanobisDetector::<f64>::with_precision(dims)`
+    pub fn with_precision(dims: usize) -> Self {
+        Self {
+            dims,
+            count: 0,
+            mean: vec![F::ZERO; dims],
+            comoment: vec![F::ZERO; dims * dims],
+            regularization: F::from_f32(1e-4),
+            anomalies: AnomalyHistory::default(),
+            last_score: 0.0,
+        }
+    }
+    
+    /// Score a feature vector against the statistics seen so far, then learn from it.
+    ///
+    /// The squared distance of a normal sample is roughly chi-squared with
+    /// `dims` degrees of freedom; it is mapped onto a z-score so severities
+    /// match the scalar detector. `Anomaly::value` holds the distance and
+    /// `mean`/`stdev` the expected squared distance and its spread.
+    pub fn detect(&mut self, features: &[F], timestamp: f64) -> Option<Anomaly> {
+        self.last_score = 0.0;
+        if features.len() != self.dims {
+            return None;
+        }
+        
+        // Need more samples than dimensions for a usable covariance
+        let expected = self.dims as f32;
+        let spread = (2.0 * expected).sqrt();
+        let d2 = if self.count > self.dims + 1 { self.distance_squared(features).map(F::to_f32) } else { None };
+        let z_score = d2.map_or(0.0, |d2| (d2 - expected) / spread);
+        self.last_score = z_score;
+        
+        let anomaly = d2.filter(|_| z_score > 2.0).map(|d2| {
+            Anomaly {
+                id: 0,
+                timestamp,
+                value: d2.sqrt(),
+                z_score,
+                severity: Severity::from_z(z_score),
+                mean: expected,
+                stdev: spread,
+                contributions: self.contributions(features),
+                explanation: AnomalyExplanation::default(),
+                detectors: Vec::new(),
+            }
+            .explained(None)
+        });
+        
+        self.update(features);
+        
+        if let Some(a) = &anomaly {
+            self.anomalies.push(a.clone());
+        }
+        anomaly
+    }
+    
+    /// Squared Mahalanobis distance from the running mean
+    pub fn distance_squared(&self, features: &[F]) -> Option<F> {
+        if self.count < 2 || features.len() != self.dims {
+            return None;
+        }
+        
+        let n = self.dims;
+        let scale = F::ONE / F::from_usize(self.count - 1);
+        let mut cov: Vec<F> = self.comoment.iter().map(|&c| c * scale).collect();
+        for i in 0..n {
+            cov[i * n + i] += self.regularization;
+        }
+        
+        // Cholesky factor L (lower triangle, in place)
+        for j in 0..n {
+            let mut diag = cov[j * n + j];
+            for k in 0..j {
+                diag -= cov[j * n + k] * cov[j * n + k];
+            }
+            if diag <= F::ZERO {
+                return None;
+            }
+            let diag = diag.sqrt();
+            cov[j * n + j] = diag;
+            for i in (j + 1)..n {
+                let mut v = cov[i * n + j];
+                for k in 0..j {
+                    v -= cov[i * n + k] * cov[j * n + k];
+                }
+                cov[i * n + j] = v / diag;
+            }
+        }
+        
+        // Forward-solve L y = (x - mean); distance is |y|^2
+        let mut y = vec![F::ZERO; n];
+        for i in 0..n {
+            let mut v = features[i] - self.mean[i];
+            for k in 0..i {
+                v -= cov[i * n + k] * y[k];
+            }
+            y[i] = v / cov[i * n + i];
+        }
+        
+        Some(y.iter().map(|&v| v * v).sum())
+    }
+    
+    /// Marginal z-score of each feature against the running mean and variance
+    fn contributions(&self, features: &[F]) -> Vec<FeatureContribution> {
+        let scale = F::ONE / F::from_usize(self.count - 1);
+        rank_contributions(features.iter().enumerate().map(|(i, &v)| {
+            let stdev = (self.comoment[i * self.dims + i] * scale).max(F::ZERO).sqrt();
+            if stdev > F::from_f32(0.0001) { ((v - self.mean[i]) / stdev).to_f32() } else { 0.0 }
+        }))
+    }
+    
+    /// Welford update of mean and co-moment matrix
+    fn update(&mut self, features: &[F]) {
+        self.count += 1;
+        let n = self.dims;
+        let inv = F::ONE / F::from_usize(self.count);
+        
+        let before: Vec<F> = features.iter().zip(&self.mean).map(|(&x, &m)| x - m).collect();
+        for (m, &d) in self.mean.iter_mut().zip(&before) {
+            *m += d * inv;
+        }
+        for (i, row) in self.comoment.chunks_exact_mut(n).enumerate() {
+            let after_i = features[i] - self.mean[i];
+            for (c, &b) in row.iter_mut().zip(&before) {
+                *c += after_i * b;
+            }
+        }
+    }
+    
+    /// Number of feature vectors seen
+    #[inline]
+    pub fn sample_count(&self) -> usize {
+        self.count
+    }
+    
+    /// Score computed for the most recent sample (0 while warming up)
+    #[inline]
+    pub fn last_score(&self) -> f32 {
+        self.last_score
+    }
+    
+    /// Get the count of detected anomalies (including ones evicted from history)
+    #[inline]
+    pub fn anomaly_count(&self) -> usize {
+        self.anomalies.total() as usize
+    }
+    
+    /// Get retained anomalies and aggregate counters
+    pub fn get_anomalies(&self) -> &AnomalyHistory {
+        &self.anomalies
+    }
+    
+    /// Export retained anomalies as CSV or JSON
+    pub fn export<W: Write>(&self, out: W, format: ExportFormat) -> io::Result<()> {
+        self.anomalies.export(out, format)
+    }
+    
+    /// Bound the number of retained anomalies
+    pub fn set_history_capacity(&mut self, capacity: usize) {
+        self.anomalies.set_capacity(capacity);
+    }
+    
+    /// Clear the detector state
+    pub fn clear(&mut self) {
+        self.count = 0;
+        self.mean.iter_mut().for_each(|m| *m = F::ZERO);
+        self.comoment.iter_mut().for_each(|c| *c = F::ZERO);
+        self.anomalies.clear();
+        self.last_score = 0.0;
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum Severity {
-    Low,
-    Medium,
-    High,
+impl<F: Float> HealthCheck for MahalanobisDetector<F> {
+    fn health(&self, _: &HealthContext<'_>) -> Health {
+        // Scoring starts once there are more samples than dimensions
+        let needed = self.dims + 2;
+        if self.count < needed {
+            Health::degraded(format!("warming up: {} of {needed} samples", self.count))
+        } else {
+            Health::healthy()
+        }
+    }
 }
 
-/// High-performance anomaly detector using statistical methods
-pub struct AnomalyDetector {
-    window: VecDeque<f32>,
-    window_size: usize,
-    anomalies: Vec<Anomaly>,
-    
-    // Running statistics for O(1) updates
-    running_sum: f32,
-    running_sum_sq: f32,
+/// One-class SVM settings
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OneClassSvmConfig {
+    /// Upper bound on the fraction of training samples left outside the boundary
+    pub nu: f32,
+    /// RBF kernel width; `None` uses `1 / (dims * variance)` of the training window
+    pub gamma: Option<f32>,
+    /// Recent feature vectors the boundary is fit on
+    pub window: usize,
+    /// Samples between refits
+    pub refit_interval: usize,
 }
 
-impl AnomalyDetector {
-    /// Create a new anomaly detector
-    pub fn new(window_size: usize) -> Self {
+impl Default for OneClassSvmConfig {
+    fn default() -> Self {
         Self {
-            window: VecDeque::with_capacity(window_size),
-            window_size,
-            anomalies: Vec::new(),
-            running_sum: 0.0,
-            running_sum_sq: 0.0,
+            nu: 0.05,
+            gamma: None,
+            window: 200,
+            refit_interval: 100,
+        }
+    }
+}
+
+/// Boundary-based detector: a one-class SVM with an RBF kernel
+///
+/// Periodically fits the smallest region enclosing all but a `nu` fraction
+/// of the recent feature vectors and flags vectors falling outside it. The
+/// score is `2 * rho / sum(alpha_i * K(sv_i, x))`, so the boundary sits at 2
+/// on the z-score scale.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OneClassSvmDetector {
+    config: OneClassSvmConfig,
+    window: VecDeque<Vec<f32>>,
+    since_fit: usize,
+    /// (support vector, alpha)
+    support: Vec<(Vec<f32>, f32)>,
+    gamma: f32,
+    rho: f32,
+    anomalies: AnomalyHistory,
+    last_score: f32,
+}
+
+impl OneClassSvmDetector {
+    /// Create a detector; it starts scoring after the first fit
+    pub fn new(config: OneClassSvmConfig) -> Self {
+        let config = OneClassSvmConfig {
+            nu: config.nu.clamp(0.001, 1.0),
+            window: config.window.max(2),
+            refit_interval: config.refit_interval.max(1),
+            ..config
+        };
+        Self {
+            window: VecDeque::with_capacity(config.window),
+            config,
+            since_fit: 0,
+            support: Vec::new(),
+            gamma: 1.0,
+            rho: 0.0,
+            anomalies: AnomalyHistory::default(),
+            last_score: 0.0,
         }
     }
     
-    /// Detect anomalies using optimized single-pass statistics
-    pub fn detect(&mut self, value: f32, timestamp: f64) -> Option<Anomaly> {
-        // Update running statistics
-        if self.window.len() >= self.window_size {
-            if let Some(old_val) = self.window.pop_front() {
-                self.running_sum -= old_val;
-                self.running_sum_sq -= old_val * old_val;
+    /// Score a feature vector against the current boundary, then learn from it
+    pub fn detect(&mut self, features: &[f32], timestamp: f64) -> Option<Anomaly> {
+        self.last_score = 0.0;
+        let anomaly = self.decision(features).and_then(|s| {
+            let z_score = 2.0 * self.rho / s.max(1e-12);
+            self.last_score = z_score;
+            (z_score > 2.0).then(|| Anomaly {
+                id: 0,
+                timestamp,
+                value: s,
+                z_score,
+                severity: Severity::from_z(z_score),
+                mean: self.rho,
+                stdev: 0.0,
+                contributions: Vec::new(),
+                explanation: AnomalyExplanation::default(),
+                detectors: Vec::new(),
             }
-        }
+            .explained(None))
+        });
         
-        self.window.push_back(value);
-        self.running_sum += value;
-        self.running_sum_sq += value * value;
+        if self.window.front().is_some_and(|w| w.len() != features.len()) {
+            // Feature layout changed: drop the stale window and boundary
+            self.window.clear();
+            self.support.clear();
+        }
+        if self.window.len() >= self.config.window {
+            self.window.pop_front();
+        }
+        self.window.push_back(features.to_vec());
+        self.since_fit += 1;
+        if self.since_fit >= self.config.refit_interval && self.window.len() >= 2 {
+            self.fit();
+        }
         
-        // Need at least 3 values for meaningful statistics
-        if self.window.len() < 3 {
+        if let Some(a) = &anomaly {
+            self.anomalies.push(a.clone());
+        }
+        anomaly
+    }
+    
+    /// Kernel expansion `sum(alpha_i * K(sv_i, x))`, if fitted
+    pub fn decision(&self, features: &[f32]) -> Option<f32> {
+        if self.support.is_empty() || self.support[0].0.len() != features.len() {
             return None;
         }
+        Some(self.support.iter().map(|(sv, alpha)| alpha * self.kernel(sv, features)).sum())
+    }
+    
+    /// Whether a boundary has been fit
+    pub fn is_fitted(&self) -> bool {
+        !self.support.is_empty()
+    }
+    
+    /// Number of support vectors in the current boundary
+    pub fn support_vector_count(&self) -> usize {
+        self.support.len()
+    }
+    
+    #[inline]
+    fn kernel(&self, a: &[f32], b: &[f32]) -> f32 {
+        let d2: f32 = a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum();
+        (-self.gamma * d2).exp()
+    }
+    
+    /// Fit the boundary on the current window (SMO on the dual problem)
+    pub fn fit(&mut self) {
+        self.since_fit = 0;
+        let data: Vec<&Vec<f32>> = self.window.iter().collect();
+        let n = data.len();
+        if n < 2 {
+            return;
+        }
         
-        let n = self.window.len() as f32;
-        let mean = self.running_sum / n;
-        let variance = (self.running_sum_sq / n) - (mean * mean);
-        let stdev = variance.max(0.0).sqrt();
+        self.gamma = self.config.gamma.unwrap_or_else(|| {
+            let dims = data[0].len().max(1);
+            let count = (n * dims) as f32;
+            let mean = data.iter().flat_map(|v| v.iter()).sum::<f32>() / count;
+            let variance = data.iter().flat_map(|v| v.iter()).map(|x| (x - mean) * (x - mean)).sum::<f32>() / count;
+            1.0 / (dims as f32 * variance.max(1e-6))
+        });
+        let k: Vec<f32> = (0..n * n).map(|idx| self.kernel(data[idx / n], data[idx % n])).collect();
         
-        // Calculate Z-score
-        let z_score = if stdev > 0.0001 {
-            ((value - mean) / stdev).abs()
-        } else {
-            0.0
-        };
+        // Minimize 1/2 a'Ka subject to 0 <= a_i <= c and sum(a) = 1
+        let c = 1.0 / (self.config.nu * n as f32);
+        let mut alpha = vec![0.0f32; n];
+        let mut remaining = 1.0f32;
+        for a in alpha.iter_mut() {
+            *a = c.min(remaining);
+            remaining -= *a;
+            if remaining <= 0.0 {
+                break;
+            }
+        }
+        let mut grad: Vec<f32> = (0..n).map(|i| (0..n).map(|j| k[i * n + j] * alpha[j]).sum()).collect();
         
-        // Detect anomaly based on Z-score
-        if z_score > 2.0 {
-            let severity = if z_score > 3.0 {
-                Severity::High
-            } else if z_score > 2.5 {
-                Severity::Medium
-            } else {
-                Severity::Low
-            };
-            
-            let anomaly = Anomaly {
-                timestamp,
-                value,
-                z_score,
-                severity,
-                mean,
-                stdev,
-            };
-            
-            self.anomalies.push(anomaly.clone());
-            Some(anomaly)
-        } else {
-            None
+        for _ in 0..(100 * n) {
+            // Most violating pair: raise the lowest gradient, lower the highest
+            let up = (0..n).filter(|&i| alpha[i] < c).min_by(|&a, &b| grad[a].total_cmp(&grad[b]));
+            let down = (0..n).filter(|&j| alpha[j] > 0.0).max_by(|&a, &b| grad[a].total_cmp(&grad[b]));
+            let (Some(i), Some(j)) = (up, down) else { break };
+            if grad[j] - grad[i] < 1e-5 {
+                break;
+            }
+            let curvature = (k[i * n + i] + k[j * n + j] - 2.0 * k[i * n + j]).max(1e-12);
+            let step = ((grad[j] - grad[i]) / curvature).min(c - alpha[i]).min(alpha[j]);
+            alpha[i] += step;
+            alpha[j] -= step;
+            for (m, g) in grad.iter_mut().enumerate() {
+                *g += step * (k[m * n + i] - k[m * n + j]);
+            }
         }
+        
+        // rho is the gradient shared by unbounded support vectors
+        let free: Vec<f32> = (0..n).filter(|&i| alpha[i] > 1e-6 && alpha[i] < c - 1e-6).map(|i| grad[i]).collect();
+        self.rho = if free.is_empty() {
+            let lo = (0..n).filter(|&i| alpha[i] > 1e-6).map(|i| grad[i]).fold(f32::MIN, f32::max);
+            let hi = (0..n).filter(|&i| alpha[i] < c - 1e-6).map(|i| grad[i]).fold(f32::MAX, f32::min);
+            if lo > f32::MIN && hi < f32::MAX { (lo + hi) / 2.0 } else { lo.max(0.0) }
+        } else {
+            free.iter().sum::<f32>() / free.len() as f32
+        };
+        self.support = (0..n).filter(|&i| alpha[i] > 1e-6).map(|i| (data[i].clone(), alpha[i])).collect();
     }
     
-    /// Get the count of detected anomalies
+    /// Current settings
+    pub fn config(&self) -> OneClassSvmConfig {
+        self.config
+    }
+    
+    /// Score computed for the most recent sample (0 until the first fit)
+    #[inline]
+    pub fn last_score(&self) -> f32 {
+        self.last_score
+    }
+    
+    /// Get the count of detected anomalies (including ones evicted from history)
     #[inline]
     pub fn anomaly_count(&self) -> usize {
-        self.anomalies.len()
+        self.anomalies.total() as usize
     }
     
-    /// Get all detected anomalies
-    pub fn get_anomalies(&self) -> &[Anomaly] {
+    /// Get retained anomalies and aggregate counters
+    pub fn get_anomalies(&self) -> &AnomalyHistory {
         &self.anomalies
     }
     
+    /// Export retained anomalies as CSV or JSON
+    pub fn export<W: Write>(&self, out: W, format: ExportFormat) -> io::Result<()> {
+        self.anomalies.export(out, format)
+    }
+    
+    /// Bound the number of retained anomalies
+    pub fn set_history_capacity(&mut self, capacity: usize) {
+        self.anomalies.set_capacity(capacity);
+    }
+    
     /// Clear the detector state
     pub fn clear(&mut self) {
         self.window.clear();
+        self.support.clear();
+        self.since_fit = 0;
+        self.rho = 0.0;
         self.anomalies.clear();
-        self.running_sum = 0.0;
-        self.running_sum_sq = 0.0;
+        self.last_score = 0.0;
+    }
+}
+
+impl Default for OneClassSvmDetector {
+    fn default() -> Self {
+        Self::new(OneClassSvmConfig::default())
     }
 }
 
@@ -124,6 +2567,80 @@ impl AnomalyDetector {
 mod tests {
     use super::*;
     
+    #[test]
+    fn test_warm_start() {
+        let mut cold = AnomalyDetector::new(20);
+        let mut warm = AnomalyDetector::new(20);
+        warm.warm_start(0.5, 0.01, 100);
+        
+        // A warm detector flags the very first outlier; a cold one is still blind
+        assert!(cold.detect(0.9, 0.0).is_none());
+        assert!(warm.detect(0.9, 0.0).is_some());
+        
+        // The prior fades out once real samples fill the window
+        for i in 1..=20 {
+            warm.detect(0.2 + (i % 2) as f32 * 0.01, i as f64);
+        }
+        let (mean, _, samples) = warm.baseline().unwrap();
+        assert_eq!(samples, 20);
+        assert!((mean - 0.205).abs() < 1e-3);
+        assert!(warm.detect(0.2, 21.0).is_none());
+    }
+    
+    #[test]
+    fn test_tdigest_quantiles() {
+        let mut digest = TDigest::new(100.0);
+        for i in 0..10_000 {
+            digest.add((i * 7919 % 10_000) as f32);
+        }
+        
+        assert!(digest.centroid_count() < 200);
+        for q in [0.001, 0.01, 0.5, 0.99, 0.999] {
+            let estimate = digest.quantile(q).unwrap();
+            assert!((estimate - q * 10_000.0).abs() < 10_000.0 * 0.005, "q={} estimate={}", q, estimate);
+        }
+    }
+    
+    #[test]
+    fn test_quantile_detector_heavy_tail() {
+        let mut quantile = QuantileDetector::new(0.01, 0.99);
+        
+        // Pareto-like tail: only about 2% of samples fall outside the bounds
+        let sample = |i: usize| 1.0 / (1.0 - (i * 7919 % 1000) as f32 / 1000.0);
+        let fired = (0..5000).filter(|&i| quantile.detect(sample(i), i as f64).is_some()).count();
+        assert!(fired > 0 && fired < 150, "fired={}", fired);
+        
+        let outlier = quantile.quantile(1.0).unwrap() * 2.0;
+        let anomaly = quantile.detect(outlier, 5000.0).unwrap();
+        assert!(anomaly.z_score > 2.0);
+        assert!(quantile.detect(2.0, 5001.0).is_none());
+    }
+    
+    #[test]
+    fn test_one_class_svm() {
+        let mut svm = OneClassSvmDetector::new(OneClassSvmConfig { refit_interval: 200, ..OneClassSvmConfig::default() });
+        
+        // Deterministic cloud of normal points in [0.4, 0.6]^2
+        let mut seed = 12345u32;
+        let mut point = || {
+            let mut next = || {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                0.4 + 0.2 * (seed >> 8) as f32 / (1u32 << 24) as f32
+            };
+            vec![next(), next()]
+        };
+        for i in 0..200 {
+            assert!(svm.detect(&point(), i as f64).is_none());
+        }
+        assert!(svm.is_fitted());
+        assert!(svm.support_vector_count() < 200);
+        
+        // Roughly `nu` of fresh normal points fall outside the boundary
+        let inliers = (200..400).filter(|&i| svm.detect(&point(), i as f64).is_some()).count();
+        assert!(inliers < 30, "inliers flagged: {}", inliers);
+        assert!(svm.detect(&[2.0, 2.0], 401.0).unwrap().severity >= Severity::Medium);
+    }
+    
     #[test]
     fn test_anomaly_detection() {
         let mut detector = AnomalyDetector::new(10);
@@ -154,4 +2671,395 @@ mod tests {
         assert_eq!(detector.window.len(), 5);
         assert_eq!(detector.running_sum, 10.0); // 0+1+2+3+4
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_mahalanobis_catches_broken_correlation() {
+        let mut detector = MahalanobisDetector::new(2);
+        
+        // Two strongly correlated features
+        for i in 0..200 {
+            let a = (i as f32 * 0.37).sin() * 0.3 + 0.5;
+            let noise = (i as f32 * 1.91).cos() * 0.01;
+            detector.detect(&[a, a + noise], i as f64);
+        }
+        
+        // Each value is in range on its own, but they disagree
+        let anomaly = detector.detect(&[0.3, 0.7], 200.0);
+        assert!(anomaly.is_some());
+        assert_eq!(anomaly.unwrap().severity, Severity::High);
+        
+        // In-distribution point passes
+        assert!(detector.detect(&[0.6, 0.6], 201.0).is_none());
+        assert_eq!(detector.sample_count(), 202);
+    }
+    
+    #[test]
+    fn test_mahalanobis_f64_matches_f32() {
+        let mut single = MahalanobisDetector::new(2);
+        let mut double = MahalanobisDetector::<f64>::with_precision(2);
+        for i in 0..200 {
+            let a = (i as f32 * 0.37).sin() * 0.3 + 0.5;
+            let b = a + (i as f32 * 1.91).cos() * 0.01;
+            single.detect(&[a, b], i as f64);
+            double.detect(&[a as f64, b as f64], i as f64);
+        }
+        
+        let (x, y) = (single.distance_squared(&[0.3, 0.7]).unwrap(), double.distance_squared(&[0.3, 0.7]).unwrap());
+        assert!((x as f64 - y).abs() / y < 1e-3);
+        assert_eq!(single.detect(&[0.3, 0.7], 200.0).map(|a| a.severity), double.detect(&[0.3, 0.7], 200.0).map(|a| a.severity));
+        assert!(double.detect(&[0.6, 0.6], 201.0).is_none());
+    }
+    
+    #[test]
+    fn test_ewma_detects_level_shift() {
+        let mut detector = EwmaDetector::default();
+        
+        for i in 0..100 {
+            let noise = (i as f32 * 2.3).sin() * 0.05;
+            assert!(detector.detect(0.5 + noise, i as f64).is_none());
+        }
+        
+        // A shift of about one noise amplitude is caught within a few samples
+        let first = (100..110).find(|&i| {
+            let noise = (i as f32 * 2.3).sin() * 0.05;
+            detector.detect(0.56 + noise, i as f64).is_some()
+        });
+        assert!(first.is_some());
+    }
+    
+    #[test]
+    fn test_seasonal_baseline_suppresses_periodic_changes() {
+        // Square wave with period 24: low for 12 samples, high for 12
+        let signal = |t: usize| if t % 24 < 12 { 0.2 } else { 0.8 } + (t as f32 * 1.7).sin() * 0.01;
+        
+        let mut plain = AnomalyDetector::new(10);
+        let mut seasonal = AnomalyDetector::new(10).with_seasonal_baseline(SeasonalBaseline::new(24.0, 24));
+        for t in 0..24 * 20 {
+            plain.detect(signal(t), t as f64);
+            seasonal.detect(signal(t), t as f64);
+        }
+        
+        let before = (plain.anomaly_count(), seasonal.anomaly_count());
+        for t in 24 * 20..24 * 22 {
+            plain.detect(signal(t), t as f64);
+            seasonal.detect(signal(t), t as f64);
+        }
+        let (plain_new, seasonal_new) = (plain.anomaly_count() - before.0, seasonal.anomaly_count() - before.1);
+        assert!(plain_new >= 4);
+        assert!(seasonal_new < plain_new / 2);
+        
+        // A real excursion still shows up
+        assert!(seasonal.detect(1.5, (24 * 22) as f64).is_some());
+    }
+    
+    #[test]
+    fn test_custom_config() {
+        let config = AnomalyConfig { threshold: 1.0, medium: 1.5, high: 5.0, min_window: 5 };
+        let mut detector = AnomalyDetector::with_config(10, config);
+        
+        for i in 0..10 {
+            detector.detect(if i % 2 == 0 { 0.4 } else { 0.6 }, i as f64);
+        }
+        
+        // z ~= 1.8: below the default threshold, inside the custom medium band
+        let anomaly = detector.detect(0.75, 10.0).unwrap();
+        assert_eq!(anomaly.severity, Severity::Medium);
+        
+        // Out-of-order bands are rejected
+        assert!(!detector.set_config(AnomalyConfig { medium: 4.0, high: 3.0, ..config }));
+        assert_eq!(detector.config(), config);
+    }
+    
+    fn anomaly_at(timestamp: f64, severity: Severity) -> Anomaly {
+        Anomaly { id: 0, timestamp, value: 1.0, z_score: 2.0 + severity as usize as f32, severity, mean: 0.0, stdev: 1.0, contributions: Vec::new(), explanation: AnomalyExplanation::default(), detectors: Vec::new() }
+    }
+    
+    #[test]
+    fn test_episodes_fold_consecutive_anomalies() {
+        let mut tracker = EpisodeTracker::new(EpisodeConfig { max_gap: 1, cooldown: [100.0, 100.0, 0.0] });
+        
+        // Start alerts, repeats inside the episode do not
+        assert!(tracker.observe(Some(&anomaly_at(0.0, Severity::Low)), 0.0).is_some());
+        assert!(tracker.observe(Some(&anomaly_at(1.0, Severity::Low)), 1.0).is_none());
+        // One quiet cycle is within the gap
+        assert!(tracker.observe(None, 2.0).is_none());
+        // Escalation alerts again
+        let alert = tracker.observe(Some(&anomaly_at(3.0, Severity::High)), 3.0).unwrap();
+        assert_eq!((alert.id, alert.samples, alert.peak_severity), (1, 3, Severity::High));
+        
+        tracker.observe(None, 4.0);
+        tracker.observe(None, 5.0);
+        assert_eq!(tracker.episodes().len(), 1);
+        assert_eq!(tracker.episodes()[0].duration(), 3.0);
+        
+        // New Low episode is inside the Low cooldown
+        assert!(tracker.observe(Some(&anomaly_at(6.0, Severity::Low)), 6.0).is_none());
+        assert_eq!(tracker.episode_count(), 2);
+    }
+    
+    #[test]
+    fn test_bounded_history() {
+        let mut history = AnomalyHistory::new(3);
+        for i in 0..5 {
+            let severity = if i == 4 { Severity::High } else { Severity::Low };
+            history.push(anomaly_at(i as f64, severity));
+        }
+        
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.total(), 5);
+        assert_eq!(history.count(Severity::Low), 4);
+        assert_eq!(history.max_z_score(), 4.0);
+        
+        let newest: Vec<f64> = history.page(0, 2).map(|a| a.timestamp).collect();
+        assert_eq!(newest, vec![4.0, 3.0]);
+        let older: Vec<f64> = history.page(1, 2).map(|a| a.timestamp).collect();
+        assert_eq!(older, vec![2.0]);
+        
+        history.set_capacity(1);
+        assert_eq!(history.latest().map(|a| a.timestamp), Some(4.0));
+        assert_eq!(history.len(), 1);
+    }
+    
+    #[test]
+    fn test_subscribers_receive_anomalies() {
+        let mut detector = AnomalyDetector::new(10);
+        let rx = detector.subscribe();
+        let dropped = detector.subscribe();
+        drop(dropped);
+        
+        for i in 0..10 {
+            detector.detect(0.5, i as f64);
+        }
+        assert!(rx.try_recv().is_err());
+        
+        detector.detect(2.0, 10.0);
+        let anomaly = rx.try_recv().unwrap();
+        assert_eq!(anomaly.value, 2.0);
+        assert_eq!(detector.subscribers.len(), 1);
+    }
+    
+    #[test]
+    fn test_feature_attribution() {
+        let mut detector = AnomalyDetector::new(20);
+        for i in 0..20 {
+            let wobble = if i % 2 == 0 { 0.01 } else { -0.01 };
+            let features = [0.5 + wobble, 0.5 - wobble, 0.5, 0.1];
+            detector.detect_with_features(features.iter().sum(), &features, i as f64);
+        }
+        
+        // Lidar channel jumps
+        let features = [0.5, 1.5, 0.5, 0.1];
+        let anomaly = detector.detect_with_features(features.iter().sum(), &features, 20.0).unwrap();
+        let top = anomaly.top_contributor().unwrap();
+        assert_eq!(top.feature, 1);
+        assert_eq!(top.name(), "lidar_points");
+        assert!(top.share > 0.9);
+        
+        let explanation = &anomaly.explanation;
+        assert_eq!(explanation.contributing_features, vec!["lidar_points".to_string()]);
+        let window = explanation.window.unwrap();
+        assert_eq!(window.len, 20);
+        assert_eq!(window.max, features.iter().sum::<f32>());
+        
+        let json = serde_json::to_string(&anomaly).unwrap();
+        assert!(json.contains("\"contributing_features\":[\"lidar_points\"]"));
+    }
+    
+    #[test]
+    fn test_mad_robust_to_outliers_in_window() {
+        let mut zscore = AnomalyDetector::new(20);
+        let mut mad = MadDetector::default();
+        
+        // A window already polluted with large outliers
+        for i in 0..20 {
+            let v = if i % 5 == 0 { 5.0 } else if i % 2 == 0 { 0.49 } else { 0.51 };
+            zscore.detect(v, i as f64);
+            mad.detect(v, i as f64);
+        }
+        
+        // Inflated stdev hides a moderate excursion from the z-score detector
+        assert!(zscore.detect(1.0, 20.0).is_none());
+        let anomaly = mad.detect(1.0, 20.0).unwrap();
+        assert_eq!(anomaly.severity, Severity::High);
+        assert_eq!(anomaly.mean, 0.51);
+    }
+    
+    #[test]
+    fn test_cusum_catches_slow_drift() {
+        let noise = |i: usize| (i as f32 * 2.3).sin() * 0.05;
+        let mut zscore = AnomalyDetector::new(20);
+        let mut cusum = CusumDetector::default();
+        
+        for i in 0..30 {
+            zscore.detect(0.5 + noise(i), i as f64);
+            cusum.detect(0.5 + noise(i), i as f64);
+        }
+        
+        // Degrade by 0.002 per sample: far below the point-wise noise
+        let mut cusum_alarm = None;
+        for i in 30..130 {
+            let v = 0.5 + noise(i) - (i - 30) as f32 * 0.002;
+            zscore.detect(v, i as f64);
+            if cusum.detect(v, i as f64).is_some() && cusum_alarm.is_none() {
+                cusum_alarm = Some(i);
+            }
+        }
+        
+        assert!(cusum_alarm.is_some_and(|i| i < 100));
+        assert!(cusum.anomaly_count() > zscore.anomaly_count());
+    }
+    
+    #[test]
+    fn test_export_formats() {
+        let mut detector = AnomalyDetector::new(20);
+        for i in 0..20 {
+            detector.detect(0.5, i as f64);
+        }
+        detector.detect(2.0, 20.0);
+        
+        let mut csv = Vec::new();
+        detector.export(&mut csv, ExportFormat::Csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "timestamp,value,z_score,severity,mean,stdev,top_feature");
+        assert!(lines[1].starts_with("20,2,"));
+        assert!(lines[1].contains(",High,"));
+        
+        let mut json = Vec::new();
+        detector.export(&mut json, ExportFormat::Json).unwrap();
+        let parsed: Vec<Anomaly> = serde_json::from_slice(&json).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].severity, Severity::High);
+        
+        let mut tracker = EpisodeTracker::default();
+        tracker.observe(Some(&parsed[0]), 20.0);
+        let mut episodes = Vec::new();
+        tracker.export(&mut episodes, ExportFormat::Csv).unwrap();
+        let episodes = String::from_utf8(episodes).unwrap();
+        assert!(episodes.lines().nth(1).unwrap().starts_with("1,20,20,0,1,High,"));
+    }
+    
+    #[test]
+    fn test_state_round_trip() {
+        let mut detector = AnomalyDetector::new(20);
+        for i in 0..20 {
+            detector.detect(if i % 2 == 0 { 0.4 } else { 0.6 }, i as f64);
+        }
+        detector.detect(5.0, 20.0);
+        
+        let mut buf = Vec::new();
+        detector.save_to(&mut buf).unwrap();
+        let saved = AnomalyDetector::load_from(buf.as_slice()).unwrap();
+        
+        let mut restarted = AnomalyDetector::new(20);
+        let rx = restarted.subscribe();
+        restarted.restore(saved);
+        
+        assert_eq!(restarted.window, detector.window);
+        assert_eq!(restarted.running_sum, detector.running_sum);
+        assert_eq!(restarted.anomaly_count(), 1);
+        
+        // Baseline survives: the next normal value is not flagged, and subscribers still work
+        assert!(restarted.detect(0.5, 21.0).is_none());
+        restarted.detect(9.0, 22.0);
+        assert!(rx.try_recv().is_ok());
+    }
+    
+    #[test]
+    fn test_acknowledge_and_suppress() {
+        let mut filter = AnomalyFilter::default();
+        let mut low = anomaly_at(0.0, Severity::Low);
+        let mut high = anomaly_at(0.0, Severity::High);
+        assert_eq!(filter.assign_id(&mut low), 1);
+        assert_eq!(filter.assign_id(&mut high), 2);
+        
+        assert!(filter.acknowledge(1));
+        assert!(!filter.acknowledge(1));
+        assert!(!filter.acknowledge(99));
+        assert!(filter.is_acknowledged(1));
+        
+        let id = filter.suppress(AnomalyMatcher::any().up_to(Severity::Medium), 60.0, 0.0);
+        assert!(filter.is_suppressed(&low, 30.0));
+        assert!(!filter.is_suppressed(&high, 30.0));
+        
+        // Expires on its own, or can be lifted early
+        assert!(!filter.is_suppressed(&low, 61.0));
+        let id2 = filter.suppress(AnomalyMatcher::any(), 60.0, 100.0);
+        assert_ne!(id, id2);
+        assert!(filter.lift(id2));
+        assert!(!filter.is_suppressed(&high, 110.0));
+    }
+    
+    #[test]
+    fn test_ensemble_rules() {
+        let members = |rule| {
+            EnsembleConfig::new(rule)
+                .with(DetectorKind::ZScore, 1.0)
+                .with(DetectorKind::Ewma, 1.0)
+                .with(DetectorKind::Mahalanobis, 2.0)
+        };
+        let one = [DetectorKind::ZScore];
+        let two = [DetectorKind::ZScore, DetectorKind::Ewma];
+        
+        assert!(members(CombinationRule::Any).combine(&one));
+        assert!(!members(CombinationRule::Majority).combine(&one));
+        assert!(members(CombinationRule::Majority).combine(&two));
+        
+        // Two light members carry half the weight
+        let weighted = members(CombinationRule::Weighted { threshold: 0.6 });
+        assert!(!weighted.combine(&two));
+        assert!(weighted.combine(&[DetectorKind::Mahalanobis, DetectorKind::ZScore]));
+        
+        // Non-members never vote
+        assert!(!members(CombinationRule::Any).combine(&[DetectorKind::Cusum]));
+    }
+    
+    #[test]
+    fn test_detectors_run_through_the_trait() {
+        let mut detectors = Detectors::new(AnomalyDetector::new(50), MahalanobisDetector::new(2));
+        assert!(detectors.get_mut(DetectorKind::Custom).is_none());
+        
+        // A steady series with one spike, seen by every built-in detector
+        for i in 0..300 {
+            let value = if i == 250 { 5.0 } else { 0.5 + (i % 7) as f32 * 0.01 };
+            for detector in detectors.iter_mut() {
+                detector.observe_cycle(value, &[value, 1.0 - value], i as f64);
+            }
+        }
+        assert!(detectors.z_score.anomaly_count() > 0);
+        assert!(detectors.ewma.anomaly_count() > 0);
+        let total: usize = detectors.iter().map(AnomalyDetection::anomaly_count).sum();
+        assert!(total >= detectors.z_score.anomaly_count() + detectors.ewma.anomaly_count());
+        
+        for detector in detectors.iter_mut() {
+            detector.set_history_capacity(1);
+        }
+        assert!(detectors.mad.get_anomalies().len() <= 1);
+        
+        for detector in detectors.iter_mut() {
+            detector.clear();
+        }
+        assert_eq!(detectors.iter().map(AnomalyDetection::anomaly_count).sum::<usize>(), 0);
+    }
+    
+    #[test]
+    fn test_anomaly_rates_and_meta_alert() {
+        let mut tracker = AnomalyRateTracker::new(RateAlertConfig { max_per_minute: Some(5), max_per_hour: None });
+        
+        for i in 0..6 {
+            tracker.record(i as f64 * 5.0, if i == 0 { Severity::High } else { Severity::Low });
+            let alert = tracker.check(i as f64 * 5.0);
+            assert_eq!(alert.is_some(), i == 5);
+        }
+        // Stays exceeded without re-alerting
+        tracker.record(30.0, Severity::Low);
+        assert!(tracker.check(30.0).is_none());
+        
+        let rates = tracker.rates(66.0);
+        assert_eq!(rates.last_hour, 7);
+        assert_eq!(rates.last_minute, 5);
+        assert_eq!(rates.hour_by_severity, [6, 0, 1]);
+        
+        assert_eq!(tracker.rates(4000.0), AnomalyRates::default());
+    }
+}
@@ -0,0 +1,198 @@
+//! Spatial correlation of anomalies: where on the map they occur
+
+use std::collections::VecDeque;
+use ahash::AHashMap;
+use serde::{Serialize, Deserialize};
+
+use crate::anomaly::{Anomaly, Severity};
+use crate::spatial::{Coordinates, Position};
+
+/// An anomaly pinned to the spatial node it was detected at
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpatialAnomaly {
+    pub anomaly_id: u64,
+    pub node_id: usize,
+    pub position: Position,
+    pub timestamp: f64,
+    pub severity: Severity,
+    pub z_score: f32,
+}
+
+/// Anomaly count of one grid cell
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RegionDensity {
+    /// Integer cell coordinates (`floor(axis / cell_size)`)
+    pub cell: [i32; 3],
+    /// Center of the cell in map units
+    pub center: Position,
+    pub count: usize,
+    pub max_severity: Severity,
+}
+
+/// Located anomalies with neighborhood and density queries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyMap {
+    entries: VecDeque<SpatialAnomaly>,
+    capacity: usize,
+}
+
+impl Default for AnomalyMap {
+    fn default() -> Self {
+        Self::new(10_000)
+    }
+}
+
+impl AnomalyMap {
+    /// Create a map retaining at most `capacity` located anomalies
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity.min(1024)),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Pin an anomaly to the node it was detected at, evicting the oldest when full
+    pub fn record(&mut self, anomaly: &Anomaly, node_id: usize, position: Position) {
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(SpatialAnomaly {
+            anomaly_id: anomaly.id,
+            node_id,
+            position,
+            timestamp: anomaly.timestamp,
+            severity: anomaly.severity,
+            z_score: anomaly.z_score,
+        });
+    }
+
+    /// Number of located anomalies retained
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check whether no anomalies are retained
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Located anomalies, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &SpatialAnomaly> {
+        self.entries.iter()
+    }
+
+    /// Anomalies within `radius` of a position, nearest first
+    pub fn anomalies_near(&self, position: &Position, radius: f32) -> Vec<&SpatialAnomaly> {
+        let radius_sq = radius * radius;
+        let mut found: Vec<(f32, &SpatialAnomaly)> = self.entries
+            .iter()
+            .map(|e| (e.position.distance_squared_to(position), e))
+            .filter(|&(d, _)| d <= radius_sq)
+            .collect();
+        found.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+        found.into_iter().map(|(_, e)| e).collect()
+    }
+
+    /// Anomalies recorded at one node
+    pub fn anomalies_at_node(&self, node_id: usize) -> impl Iterator<Item = &SpatialAnomaly> {
+        self.entries.iter().filter(move |e| e.node_id == node_id)
+    }
+
+    /// Anomaly counts per grid cell, densest first
+    pub fn density(&self, cell_size: f32) -> Vec<RegionDensity> {
+        let cell_size = cell_size.max(f32::EPSILON);
+        let mut cells: AHashMap<[i32; 3], (usize, Severity)> = AHashMap::new();
+
+        for e in &self.entries {
+            let cell = [0, 1, 2].map(|axis| (e.position.axis(axis) / cell_size).floor() as i32);
+            let slot = cells.entry(cell).or_insert((0, e.severity));
+            slot.0 += 1;
+            slot.1 = slot.1.max(e.severity);
+        }
+
+        let mut regions: Vec<RegionDensity> = cells
+            .into_iter()
+            .map(|(cell, (count, max_severity))| RegionDensity {
+                cell,
+                center: Position::from_axes(&cell.map(|c| (c as f32 + 0.5) * cell_size)),
+                count,
+                max_severity,
+            })
+            .collect();
+        regions.sort_unstable_by(|a, b| b.count.cmp(&a.count).then(a.cell.cmp(&b.cell)));
+        regions
+    }
+
+    /// Cells holding at least `min_count` anomalies
+    pub fn hotspots(&self, cell_size: f32, min_count: usize) -> Vec<RegionDensity> {
+        let mut regions = self.density(cell_size);
+        regions.retain(|r| r.count >= min_count);
+        regions
+    }
+
+    /// Planar anomaly locations, e.g. for `CostmapBuilder::anomalies`
+    pub fn xy(&self) -> impl Iterator<Item = (f32, f32)> + '_ {
+        self.entries.iter().map(|e| (e.position.x, e.position.y))
+    }
+
+    /// Forget all located anomalies
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anomaly::AnomalyExplanation;
+
+    fn anomaly(id: u64, severity: Severity) -> Anomaly {
+        Anomaly {
+            id,
+            timestamp: id as f64,
+            value: 1.0,
+            z_score: 3.0,
+            severity,
+            mean: 0.0,
+            stdev: 1.0,
+            contributions: Vec::new(),
+            explanation: AnomalyExplanation::default(),
+            detectors: Vec::new(),
+        }
+    }
+
+    fn at(x: f32, y: f32) -> Position {
+        Position { x, y, z: 0.0 }
+    }
+
+    #[test]
+    fn test_near_and_density() {
+        let mut map = AnomalyMap::default();
+        map.record(&anomaly(1, Severity::Low), 10, at(1.0, 1.0));
+        map.record(&anomaly(2, Severity::High), 11, at(2.0, 1.5));
+        map.record(&anomaly(3, Severity::Low), 12, at(3.0, 3.0));
+        map.record(&anomaly(4, Severity::Low), 13, at(50.0, 50.0));
+
+        let near: Vec<u64> = map.anomalies_near(&at(2.0, 1.0), 2.0).iter().map(|e| e.anomaly_id).collect();
+        assert_eq!(near, vec![2, 1]);
+
+        let hotspots = map.hotspots(5.0, 2);
+        assert_eq!(hotspots.len(), 1);
+        assert_eq!(hotspots[0].cell, [0, 0, 0]);
+        assert_eq!(hotspots[0].count, 3);
+        assert_eq!(hotspots[0].max_severity, Severity::High);
+        assert_eq!((hotspots[0].center.x, hotspots[0].center.y), (2.5, 2.5));
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut map = AnomalyMap::new(2);
+        for id in 0..3 {
+            map.record(&anomaly(id, Severity::Low), id as usize, at(0.0, 0.0));
+        }
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.anomalies_at_node(0).count(), 0);
+    }
+}
@@ -0,0 +1,165 @@
+//! Calibration phase before anomaly detection starts
+//!
+//! A fresh system has empty detector windows, no forecaster history and no
+//! idea what its sensors normally read, so its first verdicts are noise.
+//! While calibrating, cycles run as usual and every detector, forecaster and
+//! the drift reference learn from the frames, but anomalies are not
+//! reported. A `Calibrator` counts the cycles down and gathers per-channel
+//! statistics; once it is done the system switches to detection with what
+//! it learned and keeps a `CalibrationReport` describing the data.
+
+use serde::{Deserialize, Serialize};
+
+use crate::sensors::{FeatureChannel, Reading};
+
+/// Mean, spread and range of a value over the calibration cycles
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SampleStats {
+    pub mean: f64,
+    pub std_dev: f64,
+    pub min: f32,
+    pub max: f32,
+}
+
+/// `SampleStats` of one feature channel
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ChannelStats {
+    pub reading: Reading,
+    #[serde(flatten)]
+    pub stats: SampleStats,
+}
+
+/// What a completed calibration learned from
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationReport {
+    pub cycles: u32,
+    /// Normalized features, in `SystemConfig::features` order
+    pub features: Vec<ChannelStats>,
+    /// Fused confidence, the value the univariate detectors and forecaster see
+    pub confidence: SampleStats,
+    /// Anomalies the detectors flagged while calibrating, which were not reported
+    pub suppressed: usize,
+}
+
+/// Running mean and squared deviations (Welford) with the range seen
+#[derive(Debug, Clone, Copy)]
+struct Accumulator {
+    count: u64,
+    mean: f64,
+    m2: f64,
+    min: f32,
+    max: f32,
+}
+
+impl Default for Accumulator {
+    fn default() -> Self {
+        Self { count: 0, mean: 0.0, m2: 0.0, min: f32::INFINITY, max: f32::NEG_INFINITY }
+    }
+}
+
+impl Accumulator {
+    fn push(&mut self, value: f32) {
+        self.count += 1;
+        let delta = value as f64 - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value as f64 - self.mean);
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    fn stats(&self) -> SampleStats {
+        if self.count == 0 {
+            return SampleStats::default();
+        }
+        let variance = if self.count > 1 { self.m2 / (self.count - 1) as f64 } else { 0.0 };
+        SampleStats { mean: self.mean, std_dev: variance.max(0.0).sqrt(), min: self.min, max: self.max }
+    }
+}
+
+/// Counts down a calibration phase and gathers its statistics
+#[derive(Debug, Clone)]
+pub(crate) struct Calibrator {
+    remaining: usize,
+    cycles: u32,
+    features: Vec<Accumulator>,
+    confidence: Accumulator,
+    suppressed: usize,
+}
+
+impl Calibrator {
+    /// A phase of `cycles` cycles, or `None` for no calibration
+    pub(crate) fn start(cycles: usize) -> Option<Self> {
+        (cycles > 0).then(|| Self {
+            remaining: cycles,
+            cycles: 0,
+            features: Vec::new(),
+            confidence: Accumulator::default(),
+            suppressed: 0,
+        })
+    }
+
+    /// Learn from one cycle's features and confidence; `anomalous` when
+    /// the detectors flagged it
+    pub(crate) fn observe(&mut self, features: &[f32], confidence: f32, anomalous: bool) {
+        if self.features.len() != features.len() {
+            self.features = vec![Accumulator::default(); features.len()];
+        }
+        for (accumulator, &value) in self.features.iter_mut().zip(features) {
+            accumulator.push(value);
+        }
+        self.confidence.push(confidence);
+        self.suppressed += anomalous as usize;
+        self.cycles += 1;
+        self.remaining = self.remaining.saturating_sub(1);
+    }
+
+    /// Cycles still to run before detection starts
+    pub(crate) fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    pub(crate) fn is_done(&self) -> bool {
+        self.remaining == 0
+    }
+
+    /// Statistics gathered so far, labelled with the readings of `channels`
+    pub(crate) fn report(&self, channels: &[FeatureChannel]) -> CalibrationReport {
+        CalibrationReport {
+            cycles: self.cycles,
+            features: channels
+                .iter()
+                .zip(&self.features)
+                .map(|(channel, accumulator)| ChannelStats { reading: channel.reading, stats: accumulator.stats() })
+                .collect(),
+            confidence: self.confidence.stats(),
+            suppressed: self.suppressed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sensors::default_channels;
+
+    #[test]
+    fn test_calibrator_statistics() {
+        assert!(Calibrator::start(0).is_none());
+        let mut calibrator = Calibrator::start(4).unwrap();
+        for (i, value) in [1.0, 2.0, 3.0, 4.0].into_iter().enumerate() {
+            assert!(!calibrator.is_done());
+            calibrator.observe(&[value, 0.5, 0.0, -value], value / 10.0, i == 2);
+        }
+        assert!(calibrator.is_done());
+
+        let report = calibrator.report(&default_channels());
+        assert_eq!((report.cycles, report.suppressed), (4, 1));
+        assert_eq!(report.features[0].reading, Reading::VisualObjects);
+        let stats = report.features[0].stats;
+        assert_eq!((stats.mean, stats.min, stats.max), (2.5, 1.0, 4.0));
+        assert!((stats.std_dev - (5.0f64 / 3.0).sqrt()).abs() < 1e-12);
+        assert_eq!(report.features[1].stats.std_dev, 0.0);
+        assert_eq!(report.features[3].stats.mean, -2.5);
+        assert!((report.confidence.mean - 0.25).abs() < 1e-7);
+    }
+}
@@ -0,0 +1,82 @@
+//! Time sources for the processing cycle
+//!
+//! The system reads every timestamp and processing time through a `Clock`.
+//! `SystemClock` follows wall time; `ManualClock` only moves when told to,
+//! which together with a configured seed makes runs reproducible. Without
+//! `std`, boards implement `Clock` over their own timer.
+
+#[cfg(target_has_atomic = "64")]
+use alloc::sync::Arc;
+use core::fmt;
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+/// Monotonic time source
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Time elapsed since an arbitrary, fixed origin
+    fn now(&self) -> Duration;
+}
+
+/// Real monotonic time, measured from the clock's creation
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    origin: Instant,
+}
+
+#[cfg(feature = "std")]
+impl SystemClock {
+    /// Start measuring from now
+    pub fn new() -> Self {
+        Self { origin: Instant::now() }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+/// Clock advanced explicitly; clones share the same time
+#[cfg(target_has_atomic = "64")]
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    nanos: Arc<AtomicU64>,
+}
+
+#[cfg(target_has_atomic = "64")]
+impl ManualClock {
+    /// Start at time zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Move time forward
+    pub fn advance(&self, by: Duration) {
+        self.nanos.fetch_add(by.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Jump to an absolute time
+    pub fn set(&self, to: Duration) {
+        self.nanos.store(to.as_nanos() as u64, Ordering::Relaxed);
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::Relaxed))
+    }
+}
@@ -0,0 +1,425 @@
+//! Columnar telemetry in Parquet files
+//!
+//! `ParquetSink` buffers rows and writes them as Arrow record batches, one
+//! row group per `batch_rows` rows, so weeks of telemetry load straight into
+//! DataFusion, polars or pandas without parsing JSON. It records
+//! `CycleResult`s as a `ResultSink`, and `MetricsRecorder` feeds it
+//! `SystemMetrics` every few cycles. A Parquet file is readable once `finish`
+//! writes its footer; that happens on rotation, at
+//! `EnvironmentalAwarenessSystem::shutdown` and when the sink is dropped.
+//! An existing file at the sink's path is moved aside like a rotated one.
+
+use std::fmt;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+use arrow_array::builder::{Float32Builder, ListBuilder, UInt64Builder};
+use arrow_array::{
+    ArrayRef, BooleanArray, Float32Array, Float64Array, RecordBatch, StringArray, UInt32Array, UInt64Array,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+
+use crate::error::{GenesisError, Result};
+use crate::observer::SystemObserver;
+use crate::sink::{next_free, report_failure, ResultSink, Rotation};
+use crate::{CycleResult, SystemMetrics};
+
+/// Rows per record batch and row group unless set otherwise
+pub const DEFAULT_BATCH_ROWS: usize = 4096;
+
+/// A row type with a fixed Arrow schema
+pub trait Columnar: Clone + fmt::Debug + Send {
+    fn schema() -> SchemaRef;
+
+    /// One array per `schema` field, holding `rows` in order
+    fn columns(rows: &[Self]) -> Vec<ArrayRef>;
+}
+
+fn field(name: &str, data_type: DataType, nullable: bool) -> Field {
+    Field::new(name, data_type, nullable)
+}
+
+/// Type of the list columns, matching what `ListBuilder` produces
+fn list_of(item: DataType) -> DataType {
+    DataType::List(Arc::new(Field::new("item", item, true)))
+}
+
+fn u64s<R>(rows: &[R], value: impl Fn(&R) -> u64) -> ArrayRef {
+    Arc::new(rows.iter().map(value).collect::<UInt64Array>())
+}
+
+fn f32s<R>(rows: &[R], value: impl Fn(&R) -> f32) -> ArrayRef {
+    Arc::new(rows.iter().map(value).collect::<Float32Array>())
+}
+
+fn f64s<R>(rows: &[R], value: impl Fn(&R) -> f64) -> ArrayRef {
+    Arc::new(rows.iter().map(value).collect::<Float64Array>())
+}
+
+/// One-step forecast and its confidence
+fn next_step(result: &CycleResult) -> Option<(f32, f32)> {
+    let prediction = result.prediction.as_ref()?;
+    prediction.values.first().map(|&value| (value, prediction.confidence))
+}
+
+/// Column names match `sink::CSV_COLUMNS`; lists are native list columns
+impl Columnar for CycleResult {
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            field("cycle", DataType::UInt32, false),
+            field("confidence", DataType::Float32, false),
+            field("node_id", DataType::UInt64, false),
+            field("anomaly_detected", DataType::Boolean, false),
+            field("anomaly_id", DataType::UInt64, true),
+            field("anomaly_score", DataType::Float32, false),
+            field("severity", DataType::Utf8, true),
+            field("predicted", DataType::Float32, true),
+            field("prediction_confidence", DataType::Float32, true),
+            field("trend", DataType::Utf8, true),
+            field("processing_us", DataType::UInt64, false),
+            field("sensor_ns", DataType::UInt64, false),
+            field("neural_ns", DataType::UInt64, false),
+            field("spatial_ns", DataType::UInt64, false),
+            field("anomaly_ns", DataType::UInt64, false),
+            field("prediction_ns", DataType::UInt64, false),
+            field("visual_confidence", DataType::Float32, false),
+            field("lidar_confidence", DataType::Float32, false),
+            field("audio_confidence", DataType::Float32, false),
+            field("imu_confidence", DataType::Float32, false),
+            field("model_version", DataType::UInt64, false),
+            field("neighbors", list_of(DataType::UInt64), false),
+            field("neural_output", list_of(DataType::Float32), false),
+        ]))
+    }
+
+    fn columns(rows: &[Self]) -> Vec<ArrayRef> {
+        let mut neighbors = ListBuilder::new(UInt64Builder::new());
+        let mut outputs = ListBuilder::new(Float32Builder::new());
+        for row in rows {
+            neighbors.values().extend(row.neighbors.iter().map(|&id| Some(id as u64)));
+            neighbors.append(true);
+            outputs.values().append_slice(&row.neural_output);
+            outputs.append(true);
+        }
+        vec![
+            Arc::new(rows.iter().map(|r| r.cycle).collect::<UInt32Array>()),
+            f32s(rows, |r| r.confidence),
+            u64s(rows, |r| r.node_id as u64),
+            Arc::new(rows.iter().map(|r| Some(r.anomaly_detected)).collect::<BooleanArray>()),
+            Arc::new(rows.iter().map(|r| r.anomaly_id).collect::<UInt64Array>()),
+            f32s(rows, |r| r.anomaly_score),
+            Arc::new(rows.iter().map(|r| r.anomaly.as_ref().map(|a| format!("{:?}", a.severity))).collect::<StringArray>()),
+            Arc::new(rows.iter().map(|r| next_step(r).map(|(value, _)| value)).collect::<Float32Array>()),
+            Arc::new(rows.iter().map(|r| next_step(r).map(|(_, confidence)| confidence)).collect::<Float32Array>()),
+            Arc::new(rows.iter().map(|r| r.prediction.as_ref().map(|p| p.trend.as_str())).collect::<StringArray>()),
+            u64s(rows, |r| r.processing_us),
+            u64s(rows, |r| r.stages.sensor_ns),
+            u64s(rows, |r| r.stages.neural_ns),
+            u64s(rows, |r| r.stages.spatial_ns),
+            u64s(rows, |r| r.stages.anomaly_ns),
+            u64s(rows, |r| r.stages.prediction_ns),
+            f32s(rows, |r| r.sensor_confidences.visual),
+            f32s(rows, |r| r.sensor_confidences.lidar),
+            f32s(rows, |r| r.sensor_confidences.audio),
+            f32s(rows, |r| r.sensor_confidences.imu),
+            u64s(rows, |r| r.model_version),
+            Arc::new(neighbors.finish()),
+            Arc::new(outputs.finish()),
+        ]
+    }
+}
+
+/// Headline figures, as in the gRPC `Metrics` message
+impl Columnar for SystemMetrics {
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            field("cycles", DataType::UInt32, false),
+            field("runtime_seconds", DataType::Float64, false),
+            field("processing_rate_hz", DataType::Float64, false),
+            field("avg_processing_us", DataType::Float64, false),
+            field("p50_processing_us", DataType::UInt64, false),
+            field("p95_processing_us", DataType::UInt64, false),
+            field("p99_processing_us", DataType::UInt64, false),
+            field("theoretical_max_hz", DataType::Float64, false),
+            field("spatial_nodes", DataType::UInt64, false),
+            field("spatial_edges", DataType::UInt64, false),
+            field("anomalies_detected", DataType::UInt64, false),
+            field("anomaly_episodes", DataType::UInt64, false),
+            field("drift_events", DataType::UInt64, false),
+            field("predictions_made", DataType::UInt64, false),
+            field("loop_closures", DataType::UInt64, false),
+            field("memory_usage_mb", DataType::Float64, false),
+        ]))
+    }
+
+    fn columns(rows: &[Self]) -> Vec<ArrayRef> {
+        vec![
+            Arc::new(rows.iter().map(|m| m.cycles).collect::<UInt32Array>()),
+            f64s(rows, |m| m.runtime_seconds),
+            f64s(rows, |m| m.processing_rate_hz),
+            f64s(rows, |m| m.avg_processing_us),
+            u64s(rows, |m| m.p50_processing_us),
+            u64s(rows, |m| m.p95_processing_us),
+            u64s(rows, |m| m.p99_processing_us),
+            f64s(rows, |m| m.theoretical_max_hz),
+            u64s(rows, |m| m.spatial_nodes as u64),
+            u64s(rows, |m| m.spatial_edges as u64),
+            u64s(rows, |m| m.anomalies_detected as u64),
+            u64s(rows, |m| m.anomaly_episodes as u64),
+            u64s(rows, |m| m.drift_events as u64),
+            u64s(rows, |m| m.predictions_made as u64),
+            u64s(rows, |m| m.loop_closures as u64),
+            f64s(rows, |m| m.memory_usage_mb),
+        ]
+    }
+}
+
+/// Parquet file of `R` rows, snappy-compressed, rotated like the other sinks
+pub struct ParquetSink<R: Columnar = CycleResult> {
+    path: PathBuf,
+    rotation: Rotation,
+    batch_rows: usize,
+    rows: Vec<R>,
+    /// `None` between `finish` and the next batch
+    writer: Option<ArrowWriter<File>>,
+    opened: Instant,
+    next_index: u32,
+    rotations: usize,
+}
+
+impl<R: Columnar> fmt::Debug for ParquetSink<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParquetSink")
+            .field("path", &self.path)
+            .field("rotation", &self.rotation)
+            .field("batch_rows", &self.batch_rows)
+            .field("pending", &self.rows.len())
+            .field("rotations", &self.rotations)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R: Columnar> ParquetSink<R> {
+    /// Start a file at `path`, moving any existing one aside
+    pub fn create(path: impl AsRef<Path>, rotation: Rotation) -> Result<Self> {
+        let mut sink = Self {
+            path: path.as_ref().to_path_buf(),
+            rotation,
+            batch_rows: DEFAULT_BATCH_ROWS,
+            rows: Vec::new(),
+            writer: None,
+            opened: Instant::now(),
+            next_index: 1,
+            rotations: 0,
+        };
+        sink.open()?;
+        Ok(sink)
+    }
+
+    /// Rows buffered before they are written as one row group
+    pub fn set_batch_rows(&mut self, rows: usize) {
+        self.batch_rows = rows.max(1);
+    }
+
+    /// Files rotated out so far
+    pub fn rotations(&self) -> usize {
+        self.rotations
+    }
+
+    /// Rows waiting for the next batch
+    pub fn pending(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Buffer one row, writing a batch once `batch_rows` are pending
+    pub fn push(&mut self, row: R) -> Result<()> {
+        self.rows.push(row);
+        if self.rows.len() >= self.batch_rows {
+            self.write_batch()?;
+        }
+        Ok(())
+    }
+
+    /// Write pending rows as a row group; the file still lacks its footer
+    pub fn write_batch(&mut self) -> Result<()> {
+        if self.rows.is_empty() {
+            return Ok(());
+        }
+        let batch = RecordBatch::try_new(R::schema(), R::columns(&self.rows)).map_err(ParquetError::from)?;
+        let writer = match self.writer.as_mut() {
+            Some(writer) => writer,
+            None => self.open()?,
+        };
+        writer.write(&batch)?;
+        writer.flush()?;
+        let bytes = writer.bytes_written() as u64;
+        self.rows.clear();
+
+        if self.rotation.max_bytes.is_some_and(|max| bytes >= max)
+            || self.rotation.max_age.is_some_and(|max| self.opened.elapsed() >= max)
+        {
+            self.close()?;
+            fs::rename(&self.path, next_free(&self.path, &mut self.next_index))?;
+            self.rotations += 1;
+        }
+        Ok(())
+    }
+
+    /// Write pending rows and the footer, leaving a complete file
+    pub fn finish(&mut self) -> Result<()> {
+        self.write_batch()?;
+        self.close()
+    }
+
+    fn close(&mut self) -> Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.finish()?;
+        }
+        Ok(())
+    }
+
+    fn open(&mut self) -> Result<&mut ArrowWriter<File>> {
+        if self.path.exists() {
+            fs::rename(&self.path, next_free(&self.path, &mut self.next_index))?;
+        }
+        let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+        let writer = ArrowWriter::try_new(File::create(&self.path)?, R::schema(), Some(properties))?;
+        self.opened = Instant::now();
+        Ok(self.writer.insert(writer))
+    }
+}
+
+impl<R: Columnar> Drop for ParquetSink<R> {
+    fn drop(&mut self) {
+        if let Err(error) = self.finish() {
+            report_failure(self, error);
+        }
+    }
+}
+
+impl ResultSink for ParquetSink<CycleResult> {
+    fn write(&mut self, result: &CycleResult) -> Result<()> {
+        self.push(result.clone())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.write_batch()
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        ParquetSink::finish(self)
+    }
+}
+
+/// Observer recording `SystemMetrics` every `interval` cycles and at shutdown
+#[derive(Debug)]
+pub struct MetricsRecorder {
+    sink: ParquetSink<SystemMetrics>,
+    interval: u32,
+    failed: bool,
+}
+
+impl MetricsRecorder {
+    pub fn new(sink: ParquetSink<SystemMetrics>, interval: u32) -> Self {
+        Self { sink, interval: interval.max(1), failed: false }
+    }
+
+    fn record(&mut self, metrics: &SystemMetrics, last: bool) {
+        if self.failed {
+            return;
+        }
+        let outcome = self.sink.push(metrics.clone()).and_then(|()| if last { self.sink.finish() } else { Ok(()) });
+        if let Err(error) = outcome {
+            self.fail(error);
+        }
+    }
+
+    fn fail(&mut self, error: GenesisError) {
+        report_failure(&self.sink, error);
+        self.failed = true;
+    }
+}
+
+impl SystemObserver for MetricsRecorder {
+    fn on_metrics(&mut self, metrics: &SystemMetrics) {
+        self.record(metrics, false);
+    }
+
+    fn on_shutdown(&mut self, metrics: &SystemMetrics) {
+        self.record(metrics, true);
+    }
+
+    fn metrics_interval(&self) -> Option<u32> {
+        Some(self.interval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::UInt32Type;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use crate::EnvironmentalAwarenessSystem;
+
+    /// Every batch in the file at `path`
+    fn read(path: &Path) -> Vec<RecordBatch> {
+        ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap()
+            .map(|batch| batch.unwrap())
+            .collect()
+    }
+
+    fn cycles(batches: &[RecordBatch], column: &str) -> Vec<u32> {
+        batches
+            .iter()
+            .flat_map(|batch| batch.column_by_name(column).unwrap().as_primitive::<UInt32Type>().values().to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn test_parquet_recording() {
+        let dir = std::env::temp_dir().join(format!("genesis_parquet_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let (cycles_path, metrics_path) = (dir.join("cycles.parquet"), dir.join("metrics.parquet"));
+
+        let mut system = EnvironmentalAwarenessSystem::new();
+        let mut sink = ParquetSink::create(&cycles_path, Rotation::default()).unwrap();
+        sink.set_batch_rows(10);
+        system.add_result_sink(Box::new(sink));
+        system.add_observer(Box::new(MetricsRecorder::new(
+            ParquetSink::create(&metrics_path, Rotation::default()).unwrap(),
+            10,
+        )));
+        system.run_cycles(25);
+        system.shutdown();
+
+        let batches = read(&cycles_path);
+        assert_eq!(batches[0].schema(), CycleResult::schema());
+        assert_eq!(cycles(&batches, "cycle"), (1..=25).collect::<Vec<_>>());
+        assert_eq!(cycles(&read(&metrics_path), "cycles"), [10, 20, 25]);
+
+        // Rotation by size leaves complete files; reopening moves the old one aside
+        let mut sink = ParquetSink::<CycleResult>::create(&cycles_path, Rotation::by_size(1)).unwrap();
+        sink.set_batch_rows(5);
+        for result in EnvironmentalAwarenessSystem::new().run_cycles(10) {
+            sink.write(&result).unwrap();
+        }
+        drop(sink);
+        assert_eq!(cycles(&read(&dir.join("cycles.1.parquet")), "cycle"), (1..=25).collect::<Vec<_>>());
+        assert_eq!(cycles(&read(&dir.join("cycles.2.parquet")), "cycle"), (1..=5).collect::<Vec<_>>());
+        assert_eq!(cycles(&read(&dir.join("cycles.3.parquet")), "cycle"), (6..=10).collect::<Vec<_>>());
+        assert!(!cycles_path.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
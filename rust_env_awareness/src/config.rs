@@ -0,0 +1,650 @@
+//! System-wide configuration
+//!
+//! Every component parameter the system is built from lives in
+//! `SystemConfig`; `SystemBuilder` sets them fluently and validates the
+//! combination before any component is constructed. `SystemConfig::from_file`
+//! loads one from JSON, TOML or YAML, and `ConfigWatcher` re-applies the file
+//! to a running system whenever it changes.
+
+use std::ffi::OsStr;
+use std::fmt::{self, Debug};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+use serde::{Serialize, Deserialize};
+use thiserror::Error;
+
+use crate::alerts::AlertQueueConfig;
+use crate::anomaly::{AnomalyConfig, DetectionMode, DetectorKind, EnsembleConfig, EpisodeConfig, RateAlertConfig};
+use crate::drift::DriftConfig;
+use crate::error::{GenesisError, Result};
+use crate::health::HealthConfig;
+use crate::latency::LatencyRetention;
+use crate::loop_closure::LoopClosureConfig;
+use crate::mode::ModeConfig;
+use crate::predictor::{AdaptiveWindow, ForecastModel};
+use crate::sensors::{self, FeatureChannel};
+use crate::EnvironmentalAwarenessSystem;
+
+/// A configuration value that is out of range or contradicts another
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("invalid {field}: {reason}")]
+pub struct ConfigError {
+    pub field: &'static str,
+    pub reason: String,
+}
+
+impl ConfigError {
+    fn new(field: &'static str, reason: impl Into<String>) -> Self {
+        Self { field, reason: reason.into() }
+    }
+}
+
+/// Parameters of every system component
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SystemConfig {
+    /// Processed cycles retained in the sensor buffer
+    pub buffer_capacity: usize,
+    /// No longer used: processing times are kept in fixed-size histograms.
+    /// Still accepted so existing config files load.
+    pub processing_capacity: usize,
+    /// Cycles the lifetime latency figures are computed from
+    pub latency_retention: LatencyRetention,
+    /// Feature channels extracted from every sensor frame, in feature order;
+    /// their count is the input size of the network and feature detectors
+    pub features: Vec<FeatureChannel>,
+    /// Hidden units of the inference network (inputs are the sensor features)
+    pub hidden_size: usize,
+    /// Outputs of the inference network
+    pub output_size: usize,
+    /// Nodes pre-allocated in the spatial graph
+    pub graph_capacity: usize,
+    /// Distance within which spatial nodes are connected
+    pub connection_radius: f32,
+    /// Sliding window of the z-score detector
+    pub anomaly_window: usize,
+    pub anomaly: AnomalyConfig,
+    pub detection_mode: DetectionMode,
+    /// Detectors combined in `DetectionMode::Ensemble`
+    pub ensemble: EnsembleConfig,
+    pub episodes: EpisodeConfig,
+    pub rate_alerts: RateAlertConfig,
+    pub alert_queue: AlertQueueConfig,
+    /// Located anomalies retained for spatial queries
+    pub anomaly_map_capacity: usize,
+    pub drift: DriftConfig,
+    /// Samples in the forecasting window
+    pub forecast_window: usize,
+    /// Steps forecast every cycle
+    pub forecast_horizon: usize,
+    pub forecast_model: ForecastModel,
+    pub adaptive_window: Option<AdaptiveWindow>,
+    /// Also forecast every feature channel with this model
+    pub multivariate_model: Option<ForecastModel>,
+    pub loop_closure: LoopClosureConfig,
+    /// Thresholds of `EnvironmentalAwarenessSystem::health`
+    pub health: HealthConfig,
+    /// Thresholds deciding the `BehaviorMode` reported every cycle
+    pub behavior: ModeConfig,
+    /// Cycles the system learns from before it reports anomalies; see
+    /// `EnvironmentalAwarenessSystem::calibrate`
+    pub calibration_cycles: usize,
+    /// Recent cycle results kept for `EnvironmentalAwarenessSystem::debug_dump`;
+    /// each retained cycle costs a copy of its result
+    pub debug_history: usize,
+    /// Seed for sensor simulation and network initialization; random when unset
+    pub seed: Option<u64>,
+}
+
+impl Default for SystemConfig {
+    fn default() -> Self {
+        Self {
+            buffer_capacity: 100,
+            processing_capacity: 1000,
+            latency_retention: LatencyRetention::default(),
+            features: sensors::default_channels(),
+            hidden_size: 8,
+            output_size: 2,
+            graph_capacity: 1000,
+            connection_radius: 50.0,
+            anomaly_window: 20,
+            anomaly: AnomalyConfig::default(),
+            detection_mode: DetectionMode::default(),
+            ensemble: EnsembleConfig::default(),
+            episodes: EpisodeConfig::default(),
+            rate_alerts: RateAlertConfig::default(),
+            alert_queue: AlertQueueConfig::default(),
+            anomaly_map_capacity: 10_000,
+            drift: DriftConfig::default(),
+            forecast_window: 10,
+            forecast_horizon: 5,
+            forecast_model: ForecastModel::default(),
+            adaptive_window: None,
+            multivariate_model: None,
+            loop_closure: LoopClosureConfig::default(),
+            health: HealthConfig::default(),
+            behavior: ModeConfig::default(),
+            calibration_cycles: 0,
+            debug_history: 0,
+            seed: None,
+        }
+    }
+}
+
+/// Fail with `reason` unless `ok`
+fn check(ok: bool, field: &'static str, reason: &str) -> Result<(), ConfigError> {
+    if ok {
+        Ok(())
+    } else {
+        Err(ConfigError::new(field, reason))
+    }
+}
+
+/// Check model parameters, and that `window` samples are enough to fit the model
+fn check_model(model: ForecastModel, window: usize, field: &'static str) -> Result<(), ConfigError> {
+    let factor = |v: f32| v > 0.0 && v <= 1.0;
+    match model {
+        ForecastModel::LinearRegression | ForecastModel::Arima { .. } => Ok(()),
+        ForecastModel::Exponential { alpha } => check(factor(alpha), field, "alpha must be in (0, 1]"),
+        ForecastModel::Holt { alpha, beta } => check(factor(alpha) && factor(beta), field, "alpha and beta must be in (0, 1]"),
+        ForecastModel::HoltWinters { alpha, beta, gamma, period } => {
+            check(factor(alpha) && factor(beta) && factor(gamma), field, "alpha, beta and gamma must be in (0, 1]")?;
+            check(period >= 2, field, "period must be at least 2")
+        }
+        ForecastModel::Polynomial { degree, .. } => {
+            check((1..=3).contains(&degree), field, "degree must be 1 to 3")?;
+            check(window > degree + 2, field, "forecast window too short for the polynomial degree")
+        }
+        ForecastModel::Stl { period, periods } => check(period >= 2 && periods >= 2, field, "period and periods must be at least 2"),
+    }
+}
+
+impl SystemConfig {
+    /// Start a builder from the defaults
+    pub fn builder() -> SystemBuilder {
+        SystemBuilder::default()
+    }
+
+    /// Load and validate a configuration, choosing the format by extension
+    ///
+    /// `.json` is always supported, `.toml` with the `toml` feature and
+    /// `.yaml`/`.yml` with the `yaml` feature. Missing fields take their
+    /// defaults.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        let error = |reason: String| GenesisError::ConfigFile { path: path.to_path_buf(), reason };
+        let config: Self = match path.extension().and_then(OsStr::to_str) {
+            Some("json") => serde_json::from_str(&text).map_err(|e| error(e.to_string()))?,
+            #[cfg(feature = "toml")]
+            Some("toml") => toml::from_str(&text).map_err(|e| error(e.to_string()))?,
+            #[cfg(feature = "yaml")]
+            Some("yaml" | "yml") => serde_yaml::from_str(&text).map_err(|e| error(e.to_string()))?,
+            _ => return Err(error("unsupported format; expected .json, .toml (`toml` feature) or .yaml (`yaml` feature)".into())),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check every parameter and their combinations
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        check(self.buffer_capacity > 0, "buffer_capacity", "must be positive")?;
+        check(self.latency_retention.size() != Some(0), "latency_retention", "sample size must be positive")?;
+        if let Some(reason) = sensors::check_channels(&self.features) {
+            return Err(ConfigError::new("features", reason));
+        }
+        check(self.hidden_size > 0, "hidden_size", "must be positive")?;
+        check(self.output_size > 0, "output_size", "must be positive")?;
+        check(self.connection_radius.is_finite() && self.connection_radius > 0.0, "connection_radius", "must be positive and finite")?;
+
+        check(self.anomaly.is_valid(), "anomaly", "bands must be ordered with a positive threshold and min_window >= 2")?;
+        check(self.anomaly_window >= self.anomaly.min_window, "anomaly_window", "must hold at least anomaly.min_window samples")?;
+        check(self.detection_mode != DetectionMode::Custom, "detection_mode", "install a custom detector with set_detector")?;
+        if self.detection_mode == DetectionMode::Ensemble {
+            check(!self.ensemble.members.is_empty(), "ensemble", "needs at least one member")?;
+            check(
+                self.ensemble.members.iter().all(|&(kind, _)| kind != DetectorKind::Custom),
+                "ensemble",
+                "custom members are added with set_detector",
+            )?;
+        }
+        check(self.alert_queue.capacity > 0 && self.alert_queue.dispatch_batch > 0, "alert_queue", "capacity and dispatch_batch must be positive")?;
+        check(self.anomaly_map_capacity > 0, "anomaly_map_capacity", "must be positive")?;
+        check(
+            self.drift.reference_size >= 2 && self.drift.test_size >= 2 && self.drift.check_interval > 0,
+            "drift",
+            "windows need at least 2 samples and check_interval must be positive",
+        )?;
+
+        check(self.forecast_window >= 2, "forecast_window", "must be at least 2")?;
+        check(self.forecast_horizon > 0, "forecast_horizon", "must be positive")?;
+        check_model(self.forecast_model, self.forecast_window, "forecast_model")?;
+        if let Some(model) = self.multivariate_model {
+            check_model(model, self.forecast_window, "multivariate_model")?;
+        }
+        if let Some(adaptive) = self.adaptive_window {
+            check(
+                adaptive.min_size >= 2 && adaptive.min_size <= adaptive.max_size,
+                "adaptive_window",
+                "needs 2 <= min_size <= max_size",
+            )?;
+            check(adaptive.sensitivity > 1.0, "adaptive_window", "sensitivity must exceed 1")?;
+        }
+
+        check(self.loop_closure.candidates > 0, "loop_closure", "candidates must be positive")?;
+        check(
+            self.health.stale_after > 0.0 && self.health.stale_after <= self.health.dead_after,
+            "health",
+            "needs 0 < stale_after <= dead_after",
+        )?;
+        check(
+            self.health.graph_degraded > 0.0 && self.health.graph_degraded <= self.health.graph_unhealthy,
+            "health",
+            "needs 0 < graph_degraded <= graph_unhealthy",
+        )?;
+        check(
+            0.0 <= self.behavior.explore_confidence
+                && self.behavior.explore_confidence <= self.behavior.navigate_confidence
+                && self.behavior.navigate_confidence <= 1.0,
+            "behavior",
+            "needs 0 <= explore_confidence <= navigate_confidence <= 1",
+        )?;
+        check((0.0..1.0).contains(&self.behavior.hysteresis), "behavior", "hysteresis must be in [0, 1)")
+    }
+}
+
+/// A field that differs between the running and a reloaded configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigChange {
+    pub field: &'static str,
+    pub old: String,
+    pub new: String,
+    /// False when the field only takes effect once the system is rebuilt
+    pub applied: bool,
+}
+
+impl ConfigChange {
+    pub(crate) fn new(field: &'static str, old: &impl Debug, new: &impl Debug, applied: bool) -> Self {
+        Self { field, old: format!("{old:?}"), new: format!("{new:?}"), applied }
+    }
+}
+
+impl fmt::Display for ConfigChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} -> {}", self.field, self.old, self.new)?;
+        if !self.applied {
+            f.write_str(" (takes effect after restart)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Re-applies a configuration file to a running system when it changes
+///
+/// Changes are detected by modification time and size, so `poll` is cheap
+/// enough to call every cycle. Only parameters that are safe to change live
+/// are applied (see `EnvironmentalAwarenessSystem::apply_config`).
+#[derive(Debug)]
+pub struct ConfigWatcher {
+    path: PathBuf,
+    version: Option<(SystemTime, u64)>,
+}
+
+impl ConfigWatcher {
+    /// Watch `path`, treating its current contents as already applied
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let version = file_version(&path);
+        Self { path, version }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reload and apply the file if it changed since the last call
+    ///
+    /// Returns `Ok(None)` when the file is unchanged. An unreadable or
+    /// invalid file is reported once and leaves the system untouched.
+    pub fn poll(&mut self, system: &mut EnvironmentalAwarenessSystem) -> Result<Option<Vec<ConfigChange>>> {
+        self.reload()?.map(|config| system.apply_config(&config)).transpose()
+    }
+
+    /// The file's configuration if it changed since the last call
+    fn reload(&mut self) -> Result<Option<SystemConfig>> {
+        let version = file_version(&self.path);
+        if version == self.version {
+            return Ok(None);
+        }
+        self.version = version;
+        SystemConfig::from_file(&self.path).map(Some)
+    }
+
+    /// Poll every `interval` on a background thread until the handle is dropped
+    ///
+    /// `on_reload` receives the outcome of every reload.
+    pub fn spawn(
+        mut self,
+        system: Arc<Mutex<EnvironmentalAwarenessSystem>>,
+        interval: Duration,
+        mut on_reload: impl FnMut(Result<Vec<ConfigChange>>) + Send + 'static,
+    ) -> ConfigWatchHandle {
+        let (stop, stopped) = mpsc::channel();
+        let thread = thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                // Parse outside the lock so cycles are only held up to apply the result
+                let outcome = self.reload().and_then(|config| match config {
+                    Some(config) => system.lock().unwrap_or_else(PoisonError::into_inner).apply_config(&config).map(Some),
+                    None => Ok(None),
+                });
+                if let Some(outcome) = outcome.transpose() {
+                    on_reload(outcome);
+                }
+            }
+        });
+        ConfigWatchHandle { stop: Some(stop), thread: Some(thread) }
+    }
+}
+
+/// Modification time and size, or `None` if the file cannot be read
+fn file_version(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Stops a `ConfigWatcher::spawn` thread when dropped
+#[derive(Debug)]
+pub struct ConfigWatchHandle {
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for ConfigWatchHandle {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Fluent construction of a validated `SystemConfig` or system
+#[derive(Debug, Clone, Default)]
+pub struct SystemBuilder {
+    config: SystemConfig,
+}
+
+impl SystemBuilder {
+    /// Start from the defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Processed cycles retained; `processing_capacity` is no longer used
+    pub fn buffers(mut self, buffer_capacity: usize, processing_capacity: usize) -> Self {
+        self.config.buffer_capacity = buffer_capacity;
+        self.config.processing_capacity = processing_capacity;
+        self
+    }
+
+    pub fn latency_retention(mut self, retention: LatencyRetention) -> Self {
+        self.config.latency_retention = retention;
+        self
+    }
+
+    /// Feature channels extracted from every sensor frame
+    pub fn features(mut self, channels: Vec<FeatureChannel>) -> Self {
+        self.config.features = channels;
+        self
+    }
+
+    /// Hidden and output sizes of the inference network
+    pub fn network(mut self, hidden_size: usize, output_size: usize) -> Self {
+        self.config.hidden_size = hidden_size;
+        self.config.output_size = output_size;
+        self
+    }
+
+    /// Pre-allocated nodes and connection radius of the spatial graph
+    pub fn graph(mut self, capacity: usize, connection_radius: f32) -> Self {
+        self.config.graph_capacity = capacity;
+        self.config.connection_radius = connection_radius;
+        self
+    }
+
+    /// Z-score window and thresholds
+    pub fn anomaly(mut self, window: usize, config: AnomalyConfig) -> Self {
+        self.config.anomaly_window = window;
+        self.config.anomaly = config;
+        self
+    }
+
+    pub fn detection_mode(mut self, mode: DetectionMode) -> Self {
+        self.config.detection_mode = mode;
+        self
+    }
+
+    /// Combine detectors (selects `DetectionMode::Ensemble`)
+    pub fn ensemble(mut self, config: EnsembleConfig) -> Self {
+        self.config.ensemble = config;
+        self.config.detection_mode = DetectionMode::Ensemble;
+        self
+    }
+
+    pub fn episodes(mut self, config: EpisodeConfig) -> Self {
+        self.config.episodes = config;
+        self
+    }
+
+    pub fn rate_alerts(mut self, config: RateAlertConfig) -> Self {
+        self.config.rate_alerts = config;
+        self
+    }
+
+    pub fn alert_queue(mut self, config: AlertQueueConfig) -> Self {
+        self.config.alert_queue = config;
+        self
+    }
+
+    pub fn anomaly_map_capacity(mut self, capacity: usize) -> Self {
+        self.config.anomaly_map_capacity = capacity;
+        self
+    }
+
+    pub fn drift(mut self, config: DriftConfig) -> Self {
+        self.config.drift = config;
+        self
+    }
+
+    /// Forecasting model, window and horizon
+    pub fn forecast(mut self, model: ForecastModel, window: usize, horizon: usize) -> Self {
+        self.config.forecast_model = model;
+        self.config.forecast_window = window;
+        self.config.forecast_horizon = horizon;
+        self
+    }
+
+    pub fn adaptive_window(mut self, config: Option<AdaptiveWindow>) -> Self {
+        self.config.adaptive_window = config;
+        self
+    }
+
+    pub fn multivariate_forecasting(mut self, model: Option<ForecastModel>) -> Self {
+        self.config.multivariate_model = model;
+        self
+    }
+
+    pub fn loop_closure(mut self, config: LoopClosureConfig) -> Self {
+        self.config.loop_closure = config;
+        self
+    }
+
+    pub fn health(mut self, config: HealthConfig) -> Self {
+        self.config.health = config;
+        self
+    }
+
+    pub fn behavior(mut self, config: ModeConfig) -> Self {
+        self.config.behavior = config;
+        self
+    }
+
+    /// Keep the last `cycles` results for `debug_dump`
+    pub fn debug_history(mut self, cycles: usize) -> Self {
+        self.config.debug_history = cycles;
+        self
+    }
+
+    /// Learn from the first `cycles` cycles before reporting anomalies
+    pub fn calibration(mut self, cycles: usize) -> Self {
+        self.config.calibration_cycles = cycles;
+        self
+    }
+
+    /// Make simulated readings and network weights reproducible
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = Some(seed);
+        self
+    }
+
+    /// Validate and return the configuration
+    pub fn build_config(self) -> Result<SystemConfig, ConfigError> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+
+    /// Validate and construct the system
+    pub fn build(self) -> Result<EnvironmentalAwarenessSystem> {
+        EnvironmentalAwarenessSystem::with_config(self.config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validation() {
+        assert!(SystemConfig::default().validate().is_ok());
+
+        let error = SystemConfig::builder().graph(1000, 0.0).build_config().unwrap_err();
+        assert_eq!(error.field, "connection_radius");
+
+        let error = SystemConfig::builder().latency_retention(LatencyRetention::Recent { size: 0 }).build_config().unwrap_err();
+        assert_eq!(error.field, "latency_retention");
+
+        let error = SystemConfig::builder()
+            .forecast(ForecastModel::Polynomial { degree: 3, criterion: None }, 5, 5)
+            .build_config()
+            .unwrap_err();
+        assert_eq!(error.field, "forecast_model");
+
+        let error = SystemConfig::builder()
+            .anomaly(2, AnomalyConfig { min_window: 5, ..AnomalyConfig::default() })
+            .build_config()
+            .unwrap_err();
+        assert_eq!(error.field, "anomaly_window");
+
+        let mut channels = sensors::default_channels();
+        channels.push(channels[0]);
+        let error = SystemConfig::builder().features(channels).build_config().unwrap_err();
+        assert_eq!(error.to_string(), "invalid features: each reading can be used once");
+
+        let health = HealthConfig { stale_after: 10.0, dead_after: 5.0, ..HealthConfig::default() };
+        let error = SystemConfig::builder().health(health).build_config().unwrap_err();
+        assert_eq!(error.to_string(), "invalid health: needs 0 < stale_after <= dead_after");
+
+        let behavior = ModeConfig { explore_confidence: 0.9, ..ModeConfig::default() };
+        let error = SystemConfig::builder().behavior(behavior).build_config().unwrap_err();
+        assert_eq!(error.field, "behavior");
+
+        let error = SystemConfig::builder().detection_mode(DetectionMode::Custom).build().unwrap_err();
+        assert_eq!(error.to_string(), "invalid detection_mode: install a custom detector with set_detector");
+    }
+
+    #[test]
+    fn test_build_system() {
+        let mut system = SystemBuilder::new()
+            .network(16, 3)
+            .graph(10, 0.5)
+            .forecast(ForecastModel::Holt { alpha: 0.5, beta: 0.2 }, 12, 8)
+            .build()
+            .unwrap();
+        assert_eq!(system.config().hidden_size, 16);
+
+        let results = system.run_cycles(5);
+        assert_eq!(results[4].neural_output.len(), 3);
+        assert_eq!(results[4].prediction.as_ref().unwrap().values.len(), 8);
+        assert_eq!(system.spatial_graph().connection_radius(), 0.5);
+    }
+
+    #[test]
+    fn test_from_file() {
+        let dir = std::env::temp_dir();
+        let json = dir.join(format!("genesis_config_{}.json", std::process::id()));
+        fs::write(&json, r#"{"hidden_size": 12, "forecast_horizon": 3}"#).unwrap();
+        let config = SystemConfig::from_file(&json).unwrap();
+        assert_eq!((config.hidden_size, config.forecast_horizon, config.output_size), (12, 3, 2));
+
+        fs::write(&json, r#"{"hidden_size": 0}"#).unwrap();
+        assert!(matches!(SystemConfig::from_file(&json), Err(GenesisError::Config(e)) if e.field == "hidden_size"));
+        fs::write(&json, "{").unwrap();
+        assert!(matches!(SystemConfig::from_file(&json), Err(GenesisError::ConfigFile { .. })));
+        fs::remove_file(&json).unwrap();
+
+        #[cfg(feature = "toml")]
+        {
+            let path = dir.join(format!("genesis_config_{}.toml", std::process::id()));
+            fs::write(&path, "connection_radius = 20.0\n\n[anomaly]\nthreshold = 2.5\nmedium = 3.0\nhigh = 4.0\nmin_window = 3\n").unwrap();
+            let config = SystemConfig::from_file(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+            assert_eq!((config.connection_radius, config.anomaly.threshold), (20.0, 2.5));
+        }
+        #[cfg(feature = "yaml")]
+        {
+            let path = dir.join(format!("genesis_config_{}.yaml", std::process::id()));
+            fs::write(&path, "episodes:\n  max_gap: 2\n  cooldown: [30.0, 5.0, 0.0]\nforecast_model: !Exponential\n  alpha: 0.5\n").unwrap();
+            let config = SystemConfig::from_file(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+            assert_eq!(config.episodes.cooldown, [30.0, 5.0, 0.0]);
+            assert_eq!(config.forecast_model, ForecastModel::Exponential { alpha: 0.5 });
+        }
+    }
+
+    #[test]
+    fn test_watcher_applies_safe_changes() {
+        let path = std::env::temp_dir().join(format!("genesis_watch_{}.json", std::process::id()));
+        let write = |config: &SystemConfig| fs::write(&path, serde_json::to_string(config).unwrap()).unwrap();
+        let mut config = SystemConfig::default();
+        write(&config);
+
+        let mut system = EnvironmentalAwarenessSystem::with_config(config.clone()).unwrap();
+        let mut watcher = ConfigWatcher::new(&path);
+        assert_eq!(watcher.poll(&mut system).unwrap(), None);
+
+        config.anomaly.threshold = 2.2;
+        config.forecast_horizon = 8;
+        config.hidden_size = 32;
+        write(&config);
+        let changes = watcher.poll(&mut system).unwrap().unwrap();
+        let fields: Vec<_> = changes.iter().map(|c| (c.field, c.applied)).collect();
+        assert_eq!(fields, [("anomaly", true), ("forecast_horizon", true), ("hidden_size", false)]);
+        assert_eq!(changes[2].to_string(), "hidden_size: 8 -> 32 (takes effect after restart)");
+        assert_eq!(system.anomaly_config().threshold, 2.2);
+        assert_eq!(system.config().hidden_size, 8);
+        let results = system.run_cycles(10);
+        assert_eq!(results[9].prediction.as_ref().unwrap().values.len(), 8);
+
+        // An invalid edit is reported once and changes nothing
+        fs::write(&path, r#"{"forecast_horizon": 0}"#).unwrap();
+        assert!(watcher.poll(&mut system).is_err());
+        assert_eq!(watcher.poll(&mut system).unwrap(), None);
+        assert_eq!(system.config().forecast_horizon, 8);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,201 @@
+//! Connectivity and centrality analysis for the spatial graph
+
+use std::collections::VecDeque;
+use ahash::{AHashMap, AHashSet};
+use serde::{Serialize, Deserialize};
+
+use crate::spatial::{Coordinates, SpatialGraph};
+
+/// Summary of map coverage and connectivity
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphStats {
+    pub min_degree: usize,
+    pub max_degree: usize,
+    pub mean_degree: f32,
+    pub median_degree: usize,
+    pub isolated_nodes: usize,
+    pub components: usize,
+    pub largest_component: usize,
+    /// Highest sampled betweenness (shortest paths through a node)
+    pub max_betweenness: f32,
+    pub mean_betweenness: f32,
+}
+
+impl<P: Coordinates> SpatialGraph<P> {
+    /// Degree histogram: entry `d` counts nodes with exactly `d` edges
+    pub fn degree_distribution(&self) -> Vec<usize> {
+        let mut histogram = Vec::new();
+        for node in self.nodes() {
+            let degree = self.neighbors(node.id).len();
+            if histogram.len() <= degree {
+                histogram.resize(degree + 1, 0);
+            }
+            histogram[degree] += 1;
+        }
+        histogram
+    }
+
+    /// Node ids grouped by connected component, largest first
+    pub fn connected_components(&self) -> Vec<Vec<usize>> {
+        let mut seen = AHashSet::with_capacity(self.node_count());
+        let mut components = Vec::new();
+        let mut queue = VecDeque::new();
+
+        for node in self.nodes() {
+            if !seen.insert(node.id) {
+                continue;
+            }
+
+            let mut component = vec![node.id];
+            queue.push_back(node.id);
+            while let Some(current) = queue.pop_front() {
+                for &(neighbor, _) in self.neighbors(current) {
+                    if seen.insert(neighbor) {
+                        component.push(neighbor);
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+            components.push(component);
+        }
+
+        components.sort_unstable_by_key(|c| std::cmp::Reverse(c.len()));
+        components
+    }
+
+    /// Approximate betweenness centrality by hop count (Brandes, sampled sources).
+    ///
+    /// Uses `samples` evenly spaced source nodes and scales the result up to
+    /// the full graph; all nodes are used when `samples >= node_count()`.
+    pub fn approximate_betweenness(&self, samples: usize) -> AHashMap<usize, f32> {
+        let n = self.node_count();
+        let mut centrality: AHashMap<usize, f32> = self.nodes().iter().map(|node| (node.id, 0.0)).collect();
+        if n < 3 || samples == 0 {
+            return centrality;
+        }
+
+        let samples = samples.min(n);
+        let stride = n / samples;
+        let scale = n as f32 / samples as f32;
+
+        let mut order = Vec::with_capacity(n);
+        let mut queue = VecDeque::new();
+        let mut sigma: AHashMap<usize, f32> = AHashMap::with_capacity(n);
+        let mut dist: AHashMap<usize, usize> = AHashMap::with_capacity(n);
+        let mut delta: AHashMap<usize, f32> = AHashMap::with_capacity(n);
+
+        for source in self.nodes().iter().step_by(stride).take(samples).map(|node| node.id) {
+            order.clear();
+            sigma.clear();
+            dist.clear();
+            delta.clear();
+
+            sigma.insert(source, 1.0);
+            dist.insert(source, 0);
+            queue.push_back(source);
+
+            while let Some(v) = queue.pop_front() {
+                order.push(v);
+                let (dv, sv) = (dist[&v], sigma[&v]);
+                for &(w, _) in self.neighbors(v) {
+                    let dw = *dist.entry(w).or_insert_with(|| {
+                        queue.push_back(w);
+                        dv + 1
+                    });
+                    if dw == dv + 1 {
+                        *sigma.entry(w).or_insert(0.0) += sv;
+                    }
+                }
+            }
+
+            // Accumulate dependencies in reverse BFS order
+            for &w in order.iter().rev() {
+                let (dw, sw) = (dist[&w], sigma[&w]);
+                let dep = 1.0 + delta.get(&w).copied().unwrap_or(0.0);
+                for &(v, _) in self.neighbors(w) {
+                    if dw > 0 && dist.get(&v) == Some(&(dw - 1)) {
+                        *delta.entry(v).or_insert(0.0) += sigma[&v] / sw * dep;
+                    }
+                }
+                if w != source {
+                    if let Some(c) = centrality.get_mut(&w) {
+                        *c += delta.get(&w).copied().unwrap_or(0.0) * scale;
+                    }
+                }
+            }
+        }
+
+        // Undirected graph: every path was counted from both ends
+        centrality.values_mut().for_each(|c| *c /= 2.0);
+        centrality
+    }
+
+    /// Degree, component and sampled betweenness summary
+    pub fn stats(&self, betweenness_samples: usize) -> GraphStats {
+        let mut degrees: Vec<usize> = self.nodes().iter().map(|node| self.neighbors(node.id).len()).collect();
+        if degrees.is_empty() {
+            return GraphStats::default();
+        }
+        degrees.sort_unstable();
+
+        let components = self.connected_components();
+        let betweenness = self.approximate_betweenness(betweenness_samples);
+
+        GraphStats {
+            min_degree: degrees[0],
+            max_degree: degrees[degrees.len() - 1],
+            mean_degree: degrees.iter().sum::<usize>() as f32 / degrees.len() as f32,
+            median_degree: degrees[degrees.len() / 2],
+            isolated_nodes: degrees.iter().take_while(|&&d| d == 0).count(),
+            components: components.len(),
+            largest_component: components.first().map_or(0, Vec::len),
+            max_betweenness: betweenness.values().copied().fold(0.0, f32::max),
+            mean_betweenness: betweenness.values().sum::<f32>() / betweenness.len() as f32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two chains of five nodes, 40 apart within a chain, 500 apart between chains
+    fn two_chains() -> SpatialGraph {
+        let mut graph = SpatialGraph::new();
+        for chain in 0..2 {
+            for i in 0..5 {
+                graph.add_node(&[i as f32 * 0.4 + chain as f32 * 5.0, 0.0, 0.0]);
+            }
+        }
+        graph
+    }
+
+    #[test]
+    fn test_components_and_degrees() {
+        let graph = two_chains();
+
+        let components = graph.connected_components();
+        assert_eq!(components.len(), 2);
+        assert_eq!(components[0].len(), 5);
+
+        // Chain ends have one neighbor, interior nodes two
+        assert_eq!(graph.degree_distribution(), vec![0, 4, 6]);
+    }
+
+    #[test]
+    fn test_exact_betweenness_on_chain() {
+        let graph = two_chains();
+        let betweenness = graph.approximate_betweenness(usize::MAX);
+
+        // In a 5-chain, the middle node sits on 2*2 = 4 shortest paths
+        assert_eq!(betweenness[&0], 0.0);
+        assert_eq!(betweenness[&1], 3.0);
+        assert_eq!(betweenness[&2], 4.0);
+
+        let stats = graph.stats(usize::MAX);
+        assert_eq!(stats.components, 2);
+        assert_eq!(stats.largest_component, 5);
+        assert_eq!(stats.max_betweenness, 4.0);
+        assert_eq!(stats.isolated_nodes, 0);
+    }
+}
@@ -0,0 +1,250 @@
+//! 2D navigation costmap rasterized from the spatial graph and anomalies
+
+use serde::{Serialize, Deserialize};
+
+use crate::spatial::{Coordinates, SpatialGraph};
+
+/// Cell never observed
+pub const COST_UNKNOWN: u8 = 255;
+/// Cell occupied by an obstacle
+pub const COST_LETHAL: u8 = 254;
+/// Observed free cell
+pub const COST_FREE: u8 = 0;
+
+/// Occupancy-style cost grid (row-major, `x` fastest)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Costmap {
+    pub width: usize,
+    pub height: usize,
+    /// Cell edge length in map units
+    pub resolution: f32,
+    /// World coordinates of the lower-left corner of cell (0, 0)
+    pub origin: (f32, f32),
+    pub cells: Vec<u8>,
+}
+
+impl Costmap {
+    /// Cell containing a world point, if inside the grid
+    pub fn cell_of(&self, x: f32, y: f32) -> Option<(usize, usize)> {
+        let cx = ((x - self.origin.0) / self.resolution).floor();
+        let cy = ((y - self.origin.1) / self.resolution).floor();
+        (cx >= 0.0 && cy >= 0.0 && (cx as usize) < self.width && (cy as usize) < self.height)
+            .then_some((cx as usize, cy as usize))
+    }
+
+    /// Cost at a cell
+    #[inline]
+    pub fn get(&self, cx: usize, cy: usize) -> u8 {
+        self.cells[cy * self.width + cx]
+    }
+
+    /// Cost at a world point (`COST_UNKNOWN` outside the grid)
+    pub fn cost_at(&self, x: f32, y: f32) -> u8 {
+        self.cell_of(x, y).map_or(COST_UNKNOWN, |(cx, cy)| self.get(cx, cy))
+    }
+
+    /// Raise a cell to `cost`, never lowering it and never overwriting lethal
+    #[inline]
+    fn raise(&mut self, cx: usize, cy: usize, cost: u8) {
+        if cost == COST_FREE {
+            return;
+        }
+        let cell = &mut self.cells[cy * self.width + cx];
+        if *cell == COST_UNKNOWN || (*cell < cost && *cell != COST_LETHAL) {
+            *cell = cost;
+        }
+    }
+}
+
+/// Builder rasterizing graph density, obstacles and anomalies
+#[derive(Debug, Clone)]
+pub struct CostmapBuilder {
+    resolution: f32,
+    inflation_radius: f32,
+    /// Nodes per cell at which density adds no further cost
+    density_saturation: usize,
+    /// Cost added to sparsely observed cells, scaled down as density rises
+    sparse_cost: u8,
+    anomaly_cost: u8,
+    obstacles: Vec<(f32, f32)>,
+    anomalies: Vec<(f32, f32)>,
+}
+
+impl Default for CostmapBuilder {
+    fn default() -> Self {
+        Self {
+            resolution: 1.0,
+            inflation_radius: 5.0,
+            density_saturation: 4,
+            sparse_cost: 50,
+            anomaly_cost: 200,
+            obstacles: Vec::new(),
+            anomalies: Vec::new(),
+        }
+    }
+}
+
+impl CostmapBuilder {
+    /// Create a builder with 1-unit cells and a 5-unit inflation radius
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cell edge length in map units
+    pub fn resolution(mut self, resolution: f32) -> Self {
+        self.resolution = resolution.max(f32::EPSILON);
+        self
+    }
+
+    /// Distance over which obstacle and anomaly costs decay to zero
+    pub fn inflation_radius(mut self, radius: f32) -> Self {
+        self.inflation_radius = radius.max(0.0);
+        self
+    }
+
+    /// Node count per cell treated as fully observed, and the cost of a cell with one node
+    pub fn density(mut self, saturation: usize, sparse_cost: u8) -> Self {
+        self.density_saturation = saturation.max(1);
+        self.sparse_cost = sparse_cost;
+        self
+    }
+
+    /// Peak cost of an anomaly location (below lethal, so planners may still pass)
+    pub fn anomaly_cost(mut self, cost: u8) -> Self {
+        self.anomaly_cost = cost.min(COST_LETHAL - 1);
+        self
+    }
+
+    /// Add obstacle observations (x, y)
+    pub fn obstacles<I: IntoIterator<Item = (f32, f32)>>(mut self, points: I) -> Self {
+        self.obstacles.extend(points);
+        self
+    }
+
+    /// Add anomaly locations (x, y)
+    pub fn anomalies<I: IntoIterator<Item = (f32, f32)>>(mut self, points: I) -> Self {
+        self.anomalies.extend(points);
+        self
+    }
+
+    /// Rasterize over the bounding box of all inputs (x/y axes of the graph)
+    pub fn build<P: Coordinates>(&self, graph: &SpatialGraph<P>) -> Costmap {
+        let nodes: Vec<(f32, f32)> = graph.nodes().iter().map(|n| (n.position.axis(0), n.position.axis(1))).collect();
+
+        let all = nodes.iter().chain(&self.obstacles).chain(&self.anomalies);
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (f32::INFINITY, f32::INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for &(x, y) in all {
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+        if !min_x.is_finite() {
+            return Costmap { width: 0, height: 0, resolution: self.resolution, origin: (0.0, 0.0), cells: Vec::new() };
+        }
+
+        // Pad by the inflation radius so inflated costs are not clipped, and
+        // snap the origin to the resolution so cells line up across rebuilds
+        let pad = self.inflation_radius;
+        let snap = |v: f32| ((v - pad) / self.resolution).floor() * self.resolution;
+        let origin = (snap(min_x), snap(min_y));
+        let width = ((max_x + pad - origin.0) / self.resolution).floor() as usize + 1;
+        let height = ((max_y + pad - origin.1) / self.resolution).floor() as usize + 1;
+
+        let mut map = Costmap {
+            width,
+            height,
+            resolution: self.resolution,
+            origin,
+            cells: vec![COST_UNKNOWN; width * height],
+        };
+
+        // Observed cells: sparse coverage costs more than dense coverage
+        let mut density = vec![0usize; width * height];
+        for &(x, y) in &nodes {
+            if let Some((cx, cy)) = map.cell_of(x, y) {
+                density[cy * width + cx] += 1;
+            }
+        }
+        for (i, &count) in density.iter().enumerate().filter(|(_, &c)| c > 0) {
+            let coverage = (count as f32 / self.density_saturation as f32).min(1.0);
+            map.cells[i] = (self.sparse_cost as f32 * (1.0 - coverage)).round() as u8 + COST_FREE;
+        }
+
+        for &(x, y) in &self.anomalies {
+            self.inflate(&mut map, x, y, self.anomaly_cost);
+        }
+        for &(x, y) in &self.obstacles {
+            if let Some((cx, cy)) = map.cell_of(x, y) {
+                map.cells[cy * width + cx] = COST_LETHAL;
+            }
+            self.inflate(&mut map, x, y, COST_LETHAL - 1);
+        }
+
+        map
+    }
+
+    /// Spread a linearly decaying cost around a point
+    fn inflate(&self, map: &mut Costmap, x: f32, y: f32, peak: u8) {
+        let Some((cx, cy)) = map.cell_of(x, y) else {
+            return;
+        };
+        let reach = (self.inflation_radius / map.resolution).ceil() as isize;
+
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                let (nx, ny) = (cx as isize + dx, cy as isize + dy);
+                if nx < 0 || ny < 0 || nx as usize >= map.width || ny as usize >= map.height {
+                    continue;
+                }
+                let distance = ((dx * dx + dy * dy) as f32).sqrt() * map.resolution;
+                if distance > self.inflation_radius {
+                    continue;
+                }
+                let falloff = if self.inflation_radius > 0.0 { 1.0 - distance / self.inflation_radius } else { 1.0 };
+                map.raise(nx as usize, ny as usize, (peak as f32 * falloff.max(0.0)).round() as u8);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn corridor() -> SpatialGraph {
+        let mut graph = SpatialGraph::new();
+        for i in 0..20 {
+            // Node centers sit mid-cell so float scaling cannot push them across a boundary
+            graph.add_node(&[i as f32 * 0.01 + 0.005, 0.005, 0.0]);
+        }
+        graph
+    }
+
+    #[test]
+    fn test_observed_free_and_unknown() {
+        let map = CostmapBuilder::new().inflation_radius(0.0).density(1, 50).build(&corridor());
+
+        assert_eq!(map.width, 20);
+        assert_eq!(map.height, 1);
+        assert_eq!(map.cost_at(5.5, 0.0), COST_FREE);
+        assert_eq!(map.cost_at(100.0, 0.0), COST_UNKNOWN);
+    }
+
+    #[test]
+    fn test_obstacle_inflation() {
+        let map = CostmapBuilder::new()
+            .inflation_radius(4.0)
+            .obstacles([(10.0, 0.0)])
+            .anomalies([(2.0, 0.0)])
+            .anomaly_cost(100)
+            .build(&corridor());
+
+        assert_eq!(map.cost_at(10.5, 0.5), COST_LETHAL);
+        let near = map.cost_at(12.5, 0.5);
+        let far = map.cost_at(13.5, 0.5);
+        assert!(near > far && far > 0, "near={} far={}", near, far);
+        assert_eq!(map.cost_at(2.5, 0.5), 100);
+        assert!(map.cost_at(4.5, 0.5) < 100);
+    }
+}
@@ -0,0 +1,108 @@
+//! Structured snapshot of a running system for bug reports
+//!
+//! `EnvironmentalAwarenessSystem::debug_dump` gathers what is needed to
+//! understand a misbehaving system without attaching a debugger: how full
+//! every buffer is, the built-in detectors and forecasters exactly as they
+//! persist (windows, covariances, smoothing and ARIMA state), the current
+//! forecast fit, a graph summary and the last `SystemConfig::debug_history`
+//! cycle results. Unlike a `SystemSnapshot` it is meant to be read, not
+//! restored: custom detectors, forecasters and stages appear only as their
+//! `Debug` output.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::SystemConfig;
+use crate::connectivity::GraphStats;
+use crate::error::Result;
+use crate::ingest::IngestStats;
+use crate::predictor::ForecastModel;
+use crate::spatial::GraphMemory;
+use crate::{CycleResult, PoolStats};
+
+/// Size of the spatial graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphSummary {
+    pub nodes: usize,
+    pub edges: usize,
+    pub memory: GraphMemory,
+    pub stats: GraphStats,
+}
+
+/// Entries held against the most a buffer holds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Occupancy {
+    pub len: usize,
+    pub capacity: usize,
+}
+
+impl Occupancy {
+    pub fn new(len: usize, capacity: usize) -> Self {
+        Self { len, capacity }
+    }
+}
+
+/// How full each of the system's buffers is
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BufferOccupancy {
+    pub sensor_buffer: Occupancy,
+    /// Nodes against `SystemConfig::graph_capacity`
+    pub spatial_graph: Occupancy,
+    pub anomaly_map: Occupancy,
+    /// Alerts waiting for dispatch against the per-severity capacity
+    pub alert_queue: Occupancy,
+    pub alerts_dropped: usize,
+    pub buffer_pool: PoolStats,
+    pub ingest: Option<IngestStats>,
+    pub recent_results: Occupancy,
+}
+
+/// Fit behind the built-in forecaster's next prediction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForecastFit {
+    pub model: ForecastModel,
+    pub window_size: usize,
+    /// Least-squares line through the window as `(slope, intercept, r_squared)`
+    pub line: Option<(f32, f32, f32)>,
+    /// `[constant, AR..., MA...]` with `ForecastModel::Arima`
+    pub arima: Option<Vec<f32>>,
+}
+
+/// Everything `debug_dump` captures
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DebugDump {
+    pub cycle: u32,
+    pub runtime_seconds: f64,
+    pub calibrating: bool,
+    pub config: SystemConfig,
+    pub buffers: BufferOccupancy,
+    pub graph: GraphSummary,
+    /// Built-in detector state as saved by `save_anomaly_state`
+    pub detectors: serde_json::Value,
+    /// Built-in forecaster state as saved by `save_forecast_state`
+    pub forecasters: serde_json::Value,
+    pub forecast_fit: ForecastFit,
+    /// `Debug` output of user-supplied detector, forecaster and stages
+    pub custom_detector: Option<String>,
+    pub custom_forecaster: Option<String>,
+    pub custom_stages: Vec<String>,
+    /// Oldest first
+    pub recent_results: Vec<CycleResult>,
+}
+
+impl DebugDump {
+    /// Indented JSON, for attaching to an issue
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Write `to_json` to a file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut out, self)?;
+        Ok(out.flush()?)
+    }
+}
@@ -0,0 +1,305 @@
+//! Distribution drift detection over feature streams
+//!
+//! Point anomalies are single samples far from recent behavior; drift is the
+//! whole distribution moving to a new regime. The monitor compares a frozen
+//! reference window against a sliding window of recent samples per feature
+//! and raises a `DriftEvent` when they differ significantly.
+
+use std::collections::VecDeque;
+use serde::{Serialize, Deserialize};
+
+use crate::sensors::{FeatureChannel, Reading, FEATURE_NAMES};
+
+/// Two-sample test used to compare reference and recent distributions
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DriftTest {
+    /// Kolmogorov-Smirnov statistic against its critical value at `alpha`
+    KolmogorovSmirnov { alpha: f32 },
+    /// Population stability index over reference-quantile bins
+    Psi { bins: usize, threshold: f32 },
+}
+
+impl Default for DriftTest {
+    fn default() -> Self {
+        DriftTest::KolmogorovSmirnov { alpha: 0.01 }
+    }
+}
+
+/// Drift monitor settings
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DriftConfig {
+    /// Samples frozen as the reference distribution
+    pub reference_size: usize,
+    /// Samples in the sliding recent window
+    pub test_size: usize,
+    /// Samples between tests once both windows are full
+    pub check_interval: usize,
+    pub test: DriftTest,
+}
+
+impl Default for DriftConfig {
+    fn default() -> Self {
+        Self {
+            reference_size: 200,
+            test_size: 100,
+            check_interval: 10,
+            test: DriftTest::default(),
+        }
+    }
+}
+
+/// Test result of one drifted feature
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FeatureDrift {
+    /// Index into the processed feature vector
+    pub feature: usize,
+    /// Reading of the feature's channel, once the system has labeled it
+    #[serde(default)]
+    pub reading: Option<Reading>,
+    pub statistic: f32,
+    /// Value `statistic` had to exceed
+    pub threshold: f32,
+}
+
+impl FeatureDrift {
+    /// Sensor channel name of the feature; unlabeled features are named after the default channels
+    pub fn name(&self) -> &'static str {
+        match self.reading {
+            Some(reading) => reading.name(),
+            None => FEATURE_NAMES.get(self.feature).copied().unwrap_or("unknown"),
+        }
+    }
+}
+
+/// The environment changed regime; the monitor re-learns its reference from the samples that follow
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DriftEvent {
+    pub timestamp: f64,
+    pub test: DriftTest,
+    /// Drifted features, largest statistic relative to its threshold first
+    pub features: Vec<FeatureDrift>,
+}
+
+impl DriftEvent {
+    /// Tag drifted features with the readings of the `channels` they were processed with
+    pub(crate) fn label_features(&mut self, channels: &[FeatureChannel]) {
+        for drift in &mut self.features {
+            drift.reading = channels.get(drift.feature).map(|c| c.reading);
+        }
+    }
+}
+
+/// Sliding two-window drift monitor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftMonitor {
+    config: DriftConfig,
+    reference: Vec<Vec<f32>>,
+    recent: Vec<VecDeque<f32>>,
+    since_check: usize,
+    events: usize,
+}
+
+impl Default for DriftMonitor {
+    fn default() -> Self {
+        Self::new(DriftConfig::default())
+    }
+}
+
+impl DriftMonitor {
+    /// Create a monitor; window sizes below 2 are raised to 2
+    pub fn new(config: DriftConfig) -> Self {
+        Self {
+            config: DriftConfig {
+                reference_size: config.reference_size.max(2),
+                test_size: config.test_size.max(2),
+                check_interval: config.check_interval.max(1),
+                ..config
+            },
+            reference: Vec::new(),
+            recent: Vec::new(),
+            since_check: 0,
+            events: 0,
+        }
+    }
+
+    /// Feed one feature vector; returns an event when drift is detected
+    pub fn observe(&mut self, features: &[f32], timestamp: f64) -> Option<DriftEvent> {
+        if self.reference.len() != features.len() {
+            // Feature layout changed: start learning a new reference
+            self.reference = vec![Vec::with_capacity(self.config.reference_size); features.len()];
+            self.recent = vec![VecDeque::with_capacity(self.config.test_size); features.len()];
+            self.since_check = 0;
+        }
+
+        if self.reference.first().is_some_and(|r| r.len() < self.config.reference_size) {
+            for (reference, &value) in self.reference.iter_mut().zip(features) {
+                reference.push(value);
+            }
+            return None;
+        }
+
+        for (recent, &value) in self.recent.iter_mut().zip(features) {
+            if recent.len() >= self.config.test_size {
+                recent.pop_front();
+            }
+            recent.push_back(value);
+        }
+        if self.recent.first().is_some_and(|r| r.len() < self.config.test_size) {
+            return None;
+        }
+        self.since_check += 1;
+        if self.since_check < self.config.check_interval {
+            return None;
+        }
+        self.since_check = 0;
+
+        let mut drifted: Vec<FeatureDrift> = (0..self.reference.len())
+            .filter_map(|feature| {
+                let recent: Vec<f32> = self.recent[feature].iter().copied().collect();
+                let (statistic, threshold) = self.test(&self.reference[feature], &recent);
+                (statistic > threshold).then_some(FeatureDrift { feature, reading: None, statistic, threshold })
+            })
+            .collect();
+        if drifted.is_empty() {
+            return None;
+        }
+        drifted.sort_unstable_by(|a, b| (b.statistic / b.threshold).total_cmp(&(a.statistic / a.threshold)));
+
+        // Re-learn the reference from the new regime so the next event marks the next change
+        self.reference.iter_mut().for_each(Vec::clear);
+        self.recent.iter_mut().for_each(VecDeque::clear);
+        self.events += 1;
+
+        Some(DriftEvent { timestamp, test: self.config.test, features: drifted })
+    }
+
+    /// Test statistic and the threshold it must exceed
+    fn test(&self, reference: &[f32], recent: &[f32]) -> (f32, f32) {
+        match self.config.test {
+            DriftTest::KolmogorovSmirnov { alpha } => {
+                let (n, m) = (reference.len() as f32, recent.len() as f32);
+                let c = (-(alpha.clamp(1e-6, 1.0) / 2.0).ln() / 2.0).sqrt();
+                (ks_statistic(reference, recent), c * ((n + m) / (n * m)).sqrt())
+            }
+            DriftTest::Psi { bins, threshold } => (psi(reference, recent, bins.max(2)), threshold),
+        }
+    }
+
+    /// Whether the reference distribution has been learned
+    pub fn is_ready(&self) -> bool {
+        self.reference.first().is_some_and(|r| r.len() >= self.config.reference_size)
+    }
+
+    /// Number of drift events raised
+    pub fn event_count(&self) -> usize {
+        self.events
+    }
+
+    /// Current settings
+    pub fn config(&self) -> DriftConfig {
+        self.config
+    }
+
+    /// Replace settings and start learning a new reference
+    pub fn set_config(&mut self, config: DriftConfig) {
+        let events = self.events;
+        *self = Self::new(config);
+        self.events = events;
+    }
+
+    /// Forget both windows and the event count
+    pub fn clear(&mut self) {
+        self.reference.clear();
+        self.recent.clear();
+        self.since_check = 0;
+        self.events = 0;
+    }
+}
+
+/// Largest gap between the two empirical CDFs
+pub fn ks_statistic(a: &[f32], b: &[f32]) -> f32 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    a.sort_unstable_by(f32::total_cmp);
+    b.sort_unstable_by(f32::total_cmp);
+
+    let (mut i, mut j, mut d) = (0, 0, 0.0f32);
+    while i < a.len() && j < b.len() {
+        let x = a[i].min(b[j]);
+        while i < a.len() && a[i] <= x {
+            i += 1;
+        }
+        while j < b.len() && b[j] <= x {
+            j += 1;
+        }
+        d = d.max((i as f32 / a.len() as f32 - j as f32 / b.len() as f32).abs());
+    }
+    d
+}
+
+/// Population stability index of `actual` against `expected`, binned at quantiles of `expected`
+pub fn psi(expected: &[f32], actual: &[f32], bins: usize) -> f32 {
+    if expected.is_empty() || actual.is_empty() || bins == 0 {
+        return 0.0;
+    }
+    let mut sorted = expected.to_vec();
+    sorted.sort_unstable_by(f32::total_cmp);
+    let mut edges: Vec<f32> = (1..bins).map(|b| sorted[b * sorted.len() / bins]).collect();
+    edges.dedup();
+
+    let shares = |values: &[f32]| {
+        let mut counts = vec![0usize; edges.len() + 1];
+        for v in values {
+            counts[edges.partition_point(|e| e <= v)] += 1;
+        }
+        counts.into_iter().map(|c| (c as f32 / values.len() as f32).max(1e-4)).collect::<Vec<f32>>()
+    };
+    let (e, a) = (shares(expected), shares(actual));
+    e.iter().zip(&a).map(|(e, a)| (a - e) * (a / e).ln()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic spread of values in `[offset, offset + 1)`
+    fn sample(i: usize, offset: f32) -> f32 {
+        offset + (i * 37 % 100) as f32 / 100.0
+    }
+
+    fn run(monitor: &mut DriftMonitor, range: std::ops::Range<usize>, offset: f32) -> Vec<DriftEvent> {
+        range.filter_map(|i| monitor.observe(&[sample(i, offset), 0.5], i as f64)).collect()
+    }
+
+    #[test]
+    fn test_ks_and_psi() {
+        let a: Vec<f32> = (0..100).map(|i| sample(i, 0.0)).collect();
+        let b: Vec<f32> = (0..100).map(|i| sample(i, 0.5)).collect();
+        assert_eq!(ks_statistic(&a, &a), 0.0);
+        assert!((ks_statistic(&a, &b) - 0.5).abs() < 0.02);
+        assert!(psi(&a, &a, 10) < 1e-3);
+        assert!(psi(&a, &b, 10) > 0.25);
+    }
+
+    #[test]
+    fn test_regime_change() {
+        for test in [DriftTest::default(), DriftTest::Psi { bins: 10, threshold: 0.25 }] {
+            let mut monitor = DriftMonitor::new(DriftConfig { test, ..DriftConfig::default() });
+
+            assert!(run(&mut monitor, 0..600, 0.0).is_empty());
+            assert!(monitor.is_ready());
+
+            let events = run(&mut monitor, 600..900, 0.5);
+            assert_eq!(events.len(), 1, "{:?}", test);
+            assert_eq!(events[0].features.len(), 1);
+            assert_eq!(events[0].features[0].name(), "visual_objects");
+
+            // The new regime became the reference
+            assert!(run(&mut monitor, 900..1200, 0.5).is_empty());
+            assert_eq!(monitor.event_count(), 1);
+        }
+    }
+}
@@ -0,0 +1,324 @@
+//! Fixed-capacity pipeline for `no_std` targets
+//!
+//! Everything here builds with only `core` and `alloc`, so the crate can run
+//! on Cortex-M class flight controllers with `default-features = false`.
+//! Sensor fusion and inference reuse `SensorProcessor` and `NeuralNetwork`;
+//! the spatial map, anomaly window and timing history live in heapless
+//! buffers sized by const generics, so they never grow after construction.
+//! Time comes from an injected `Clock` and randomness from a seeded `StdRng`
+//! instead of `thread_rng`.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::time::Duration;
+use heapless::HistoryBuffer;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::clock::Clock;
+use crate::error::Result;
+use crate::neural::NeuralNetwork;
+use crate::sensors::{self, FeatureChannel, ProcessedSensorData, SensorData, SensorProcessor};
+
+/// Edges kept per node of a `FixedGraph`
+pub const MAX_DEGREE: usize = 8;
+
+/// Settings of an `EmbeddedSystem`
+#[derive(Debug, Clone)]
+pub struct EmbeddedConfig {
+    /// Feature channels extracted from every sensor frame
+    pub features: Vec<FeatureChannel>,
+    /// Hidden units of the inference network
+    pub hidden_size: usize,
+    /// Outputs of the inference network
+    pub output_size: usize,
+    /// Distance within which spatial nodes are connected
+    pub connection_radius: f32,
+    /// Z-score above which a cycle is anomalous
+    pub anomaly_threshold: f32,
+    /// Samples required in the window before scoring
+    pub min_window: usize,
+    /// Seed for sensor simulation and network initialization
+    pub seed: u64,
+}
+
+impl Default for EmbeddedConfig {
+    fn default() -> Self {
+        Self {
+            features: sensors::default_channels(),
+            hidden_size: 8,
+            output_size: 2,
+            connection_radius: 50.0,
+            anomaly_threshold: 2.0,
+            min_window: 3,
+            seed: 0,
+        }
+    }
+}
+
+/// Node of a `FixedGraph`
+#[derive(Debug, Clone)]
+pub struct FixedNode {
+    pub id: usize,
+    pub position: [f32; 3],
+    /// Ids of connected nodes, at most `MAX_DEGREE`
+    pub neighbors: heapless::Vec<usize, MAX_DEGREE>,
+}
+
+/// Spatial graph holding the `N` most recent nodes
+///
+/// Positions are derived from features as in `SpatialGraph::add_node`. Once
+/// full, each new node replaces the oldest one and its edges.
+#[derive(Debug, Clone)]
+pub struct FixedGraph<const N: usize> {
+    nodes: heapless::Vec<FixedNode, N>,
+    next_id: usize,
+    connection_radius_sq: f32,
+}
+
+impl<const N: usize> FixedGraph<N> {
+    /// Empty graph connecting nodes closer than `connection_radius`
+    pub fn new(connection_radius: f32) -> Self {
+        Self {
+            nodes: heapless::Vec::new(),
+            next_id: 0,
+            connection_radius_sq: connection_radius.max(0.0) * connection_radius.max(0.0),
+        }
+    }
+
+    /// Add a node for an observation, evicting the oldest when full; returns its id
+    pub fn add_node(&mut self, features: &[f32]) -> usize {
+        let feature = |i: usize| features.get(i).copied().unwrap_or(0.0);
+        let position = [feature(0) * 100.0, feature(1) * 100.0, feature(2) * 10.0];
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let slot = id % N.max(1);
+        if let Some(evicted) = self.nodes.get(slot).map(|node| node.id) {
+            for node in self.nodes.iter_mut() {
+                node.neighbors.retain(|&n| n != evicted);
+            }
+        }
+
+        let mut node = FixedNode { id, position, neighbors: heapless::Vec::new() };
+        for other in self.nodes.iter_mut().filter(|other| other.id + N != id) {
+            let distance_sq: f32 = other.position.iter().zip(&position).map(|(a, b)| (a - b) * (a - b)).sum();
+            if distance_sq <= self.connection_radius_sq && !node.neighbors.is_full() && !other.neighbors.is_full() {
+                let _ = node.neighbors.push(other.id);
+                let _ = other.neighbors.push(id);
+            }
+        }
+
+        if slot < self.nodes.len() {
+            self.nodes[slot] = node;
+        } else {
+            let _ = self.nodes.push(node);
+        }
+        id
+    }
+
+    /// Node with `id`, if it has not been evicted
+    pub fn node(&self, id: usize) -> Option<&FixedNode> {
+        self.nodes.get(id % N.max(1)).filter(|node| node.id == id)
+    }
+
+    /// Nodes currently held, in slot order
+    pub fn nodes(&self) -> &[FixedNode] {
+        &self.nodes
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.nodes.iter().map(|node| node.neighbors.len()).sum::<usize>() / 2
+    }
+
+    /// Maximum number of nodes held
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+}
+
+/// Outcome of one `EmbeddedSystem` cycle
+#[derive(Debug, Clone)]
+pub struct EmbeddedCycle {
+    pub cycle: u32,
+    pub confidence: f32,
+    pub neural_output: Vec<f32>,
+    pub node_id: usize,
+    pub anomaly_detected: bool,
+    pub anomaly_score: f32,
+    pub processing_us: u64,
+}
+
+/// Awareness pipeline with bounded memory for microcontrollers
+///
+/// `NODES` bounds the spatial map and `WINDOW` the anomaly and timing
+/// history.
+#[derive(Debug)]
+pub struct EmbeddedSystem<const NODES: usize = 64, const WINDOW: usize = 32> {
+    config: EmbeddedConfig,
+    processor: SensorProcessor,
+    neural_net: NeuralNetwork,
+    graph: FixedGraph<NODES>,
+    confidence_window: HistoryBuffer<f32, WINDOW>,
+    processing_times: HistoryBuffer<u64, WINDOW>,
+    clock: Box<dyn Clock>,
+    start_time: Duration,
+    rng: StdRng,
+    cycle_count: u32,
+    anomalies_detected: u32,
+}
+
+impl<const NODES: usize, const WINDOW: usize> EmbeddedSystem<NODES, WINDOW> {
+    /// Build a system reading time from `clock`; fails if `config.features` are invalid
+    pub fn new(config: EmbeddedConfig, clock: impl Clock + 'static) -> Result<Self> {
+        let processor = SensorProcessor::with_channels(config.features.clone())?;
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let neural_net = NeuralNetwork::with_rng(processor.feature_count(), config.hidden_size, config.output_size, &mut rng);
+        Ok(Self {
+            processor,
+            neural_net,
+            graph: FixedGraph::new(config.connection_radius),
+            confidence_window: HistoryBuffer::new(),
+            processing_times: HistoryBuffer::new(),
+            start_time: clock.now(),
+            clock: Box::new(clock),
+            rng,
+            cycle_count: 0,
+            anomalies_detected: 0,
+            config,
+        })
+    }
+
+    /// Run one cycle on simulated sensor data
+    pub fn run_cycle(&mut self) -> EmbeddedCycle {
+        let timestamp = self.clock.now().saturating_sub(self.start_time).as_secs_f64();
+        let data = SensorData::generate_with(&mut self.rng, timestamp);
+        self.process_validated(&data)
+    }
+
+    /// Run one cycle on a real reading, rejecting NaN or infinite values
+    pub fn process_sensor_data(&mut self, data: &SensorData) -> Result<EmbeddedCycle> {
+        data.validate()?;
+        Ok(self.process_validated(data))
+    }
+
+    fn process_validated(&mut self, data: &SensorData) -> EmbeddedCycle {
+        let cycle_start = self.clock.now();
+        self.cycle_count += 1;
+
+        let ProcessedSensorData { features, fused_confidence } = self.processor.process(data);
+        let neural_output = self.neural_net.forward(&features);
+        let node_id = self.graph.add_node(&features);
+
+        let anomaly_score = self.score(fused_confidence);
+        let anomaly_detected = anomaly_score > self.config.anomaly_threshold;
+        self.anomalies_detected += anomaly_detected as u32;
+
+        let processing_us = self.clock.now().saturating_sub(cycle_start).as_micros() as u64;
+        self.processing_times.write(processing_us);
+
+        EmbeddedCycle {
+            cycle: self.cycle_count,
+            confidence: fused_confidence,
+            neural_output,
+            node_id,
+            anomaly_detected,
+            anomaly_score,
+            processing_us,
+        }
+    }
+
+    /// Add `value` to the window and return its z-score against it
+    fn score(&mut self, value: f32) -> f32 {
+        self.confidence_window.write(value);
+        let n = self.confidence_window.len();
+        if n < self.config.min_window.max(2) {
+            return 0.0;
+        }
+        let mean = self.confidence_window.iter().sum::<f32>() / n as f32;
+        let variance = self.confidence_window.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>() / n as f32;
+        let std_dev = libm::sqrtf(variance);
+        if std_dev > f32::EPSILON {
+            (value - mean).abs() / std_dev
+        } else {
+            0.0
+        }
+    }
+
+    /// Cycles processed
+    pub fn cycle_count(&self) -> u32 {
+        self.cycle_count
+    }
+
+    /// Cycles that detected an anomaly
+    pub fn anomalies_detected(&self) -> u32 {
+        self.anomalies_detected
+    }
+
+    /// Mean processing time over the last `WINDOW` cycles
+    pub fn avg_processing_us(&self) -> f64 {
+        match self.processing_times.len() {
+            0 => 0.0,
+            n => self.processing_times.iter().sum::<u64>() as f64 / n as f64,
+        }
+    }
+
+    pub fn spatial_graph(&self) -> &FixedGraph<NODES> {
+        &self.graph
+    }
+
+    pub fn config(&self) -> &EmbeddedConfig {
+        &self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    #[test]
+    fn test_fixed_graph_evicts_oldest() {
+        let mut graph = FixedGraph::<4>::new(1000.0);
+        for i in 0..6 {
+            assert_eq!(graph.add_node(&[i as f32 * 0.01, 0.0, 0.0, 0.0]), i);
+        }
+        assert_eq!(graph.node_count(), 4);
+        assert!(graph.node(1).is_none());
+        assert!(graph.node(5).is_some());
+        // Only the surviving nodes remain connected, each to the other three
+        assert_eq!(graph.edge_count(), 6);
+        assert!(graph.nodes().iter().all(|node| node.neighbors.iter().all(|&n| n >= 2)));
+    }
+
+    #[test]
+    fn test_embedded_runs_are_bounded_and_reproducible() {
+        let run = || {
+            let clock = ManualClock::new();
+            let mut system = EmbeddedSystem::<16, 8>::new(EmbeddedConfig { seed: 7, ..EmbeddedConfig::default() }, clock.clone()).unwrap();
+            (0..100)
+                .map(|_| {
+                    clock.advance(Duration::from_millis(10));
+                    system.run_cycle()
+                })
+                .map(|result| (result.node_id, result.confidence, result.anomaly_detected))
+                .collect::<Vec<_>>()
+        };
+        let first = run();
+        assert_eq!(first, run());
+        assert_eq!(first.last().map(|r| r.0), Some(99));
+
+        let mut system = EmbeddedSystem::<16, 8>::new(EmbeddedConfig::default(), ManualClock::new()).unwrap();
+        system.run_cycle();
+        let mut data = SensorData::generate_with(&mut StdRng::seed_from_u64(1), 0.0);
+        assert!(system.process_sensor_data(&data).is_ok());
+        data.lidar.max_range = f32::INFINITY;
+        assert!(system.process_sensor_data(&data).is_err());
+        assert_eq!(system.cycle_count(), 2);
+        assert!(system.spatial_graph().node_count() <= 16);
+    }
+}
@@ -0,0 +1,63 @@
+//! Crate-wide error type
+//!
+//! Constructors, ingestion and persistence report failures as `GenesisError`
+//! so embedders can recover instead of the system panicking.
+
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[cfg(feature = "std")]
+use crate::config::ConfigError;
+
+/// Any failure surfaced by the public API
+#[derive(Debug, Error)]
+pub enum GenesisError {
+    #[cfg(feature = "std")]
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    /// A configuration file that could not be parsed
+    #[cfg(feature = "std")]
+    #[error("cannot load {}: {reason}", path.display())]
+    ConfigFile { path: PathBuf, reason: String },
+    #[cfg(feature = "std")]
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    /// Saved state that could not be encoded or decoded
+    #[cfg(feature = "std")]
+    #[error("malformed state: {0}")]
+    Serialization(#[from] serde_json::Error),
+    /// A Parquet telemetry file that could not be written or read
+    #[cfg(feature = "parquet")]
+    #[error("parquet error: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+    /// An OTLP exporter that could not be built, flushed or shut down
+    #[cfg(feature = "otel")]
+    #[error("OpenTelemetry error: {0}")]
+    Otel(String),
+    /// A vector whose length does not match the configured dimension
+    #[error("{what} has {actual} dimensions, expected {expected}")]
+    DimensionMismatch {
+        what: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    /// Feature channels a `SensorProcessor` cannot be built from
+    #[error("invalid feature channels: {0}")]
+    InvalidChannels(&'static str),
+    /// A cycle rate that is not a positive, finite number of Hz
+    #[error("invalid cycle rate {0} Hz: must be positive and finite")]
+    InvalidRate(f64),
+    /// A sensor reading that is NaN or infinite
+    #[error("invalid sensor reading {field}: {value}")]
+    InvalidReading { field: &'static str, value: f32 },
+    /// A simulated sensor rig or scene that cannot produce frames
+    #[cfg(feature = "std")]
+    #[error("invalid simulation: {0}")]
+    InvalidSimulation(String),
+}
+
+/// `Result` defaulting to `GenesisError`
+pub type Result<T, E = GenesisError> = core::result::Result<T, E>;
@@ -0,0 +1,294 @@
+//! C ABI for embedding the system in C and C++ stacks
+//!
+//! Build with `--features ffi` as a `cdylib` or `staticlib` (see BUILD.md);
+//! `include/genesis_awareness.h` declares everything here and is
+//! regenerated with `cbindgen --config cbindgen.toml --output include/genesis_awareness.h`.
+//!
+//! Every function takes the handle returned by `genesis_system_new` and
+//! reports failure through `GenesisStatus` instead of unwinding into C.
+
+use std::ffi::{c_char, CString};
+use std::ptr;
+
+use crate::error::GenesisError;
+use crate::sensors::{AudioData, ImuData, LidarData, SensorData, VisualData};
+use crate::{CycleResult, EnvironmentalAwarenessSystem, SystemMetrics};
+
+/// Opaque handle to a system
+pub struct GenesisSystem(EnvironmentalAwarenessSystem);
+
+/// Outcome of an FFI call
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenesisStatus {
+    Ok = 0,
+    /// A required pointer argument was null
+    NullPointer = 1,
+    /// The sensor frame contained NaN or infinite readings
+    InvalidReading = 2,
+    /// Any other failure
+    Other = 3,
+}
+
+/// One sensor reading, mirroring `SensorData`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GenesisSensorFrame {
+    pub visual_objects: u8,
+    pub visual_brightness: f32,
+    pub visual_motion: f32,
+    pub lidar_points: u16,
+    pub lidar_max_range: f32,
+    pub lidar_obstacles: u8,
+    pub audio_amplitude: f32,
+    pub audio_frequency: f32,
+    pub audio_event_type: u8,
+    pub imu_accel_x: f32,
+    pub imu_accel_y: f32,
+    pub imu_accel_z: f32,
+    pub imu_gyro: f32,
+    pub timestamp: f64,
+}
+
+impl From<&GenesisSensorFrame> for SensorData {
+    fn from(frame: &GenesisSensorFrame) -> Self {
+        Self {
+            visual: VisualData {
+                objects: frame.visual_objects,
+                brightness: frame.visual_brightness,
+                motion: frame.visual_motion,
+            },
+            lidar: LidarData {
+                points: frame.lidar_points,
+                max_range: frame.lidar_max_range,
+                obstacles: frame.lidar_obstacles,
+            },
+            audio: AudioData {
+                amplitude: frame.audio_amplitude,
+                frequency: frame.audio_frequency,
+                event_type: frame.audio_event_type,
+            },
+            imu: ImuData {
+                accel_x: frame.imu_accel_x,
+                accel_y: frame.imu_accel_y,
+                accel_z: frame.imu_accel_z,
+                gyro: frame.imu_gyro,
+            },
+            timestamp: frame.timestamp,
+        }
+    }
+}
+
+/// Fixed-size summary of a `CycleResult`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GenesisCycle {
+    pub cycle: u32,
+    pub confidence: f32,
+    pub node_id: usize,
+    pub anomaly_detected: bool,
+    pub anomaly_score: f32,
+    /// Next-step forecast, valid when `has_prediction` is set
+    pub has_prediction: bool,
+    pub predicted_value: f32,
+    pub prediction_confidence: f32,
+    pub processing_us: u64,
+}
+
+impl From<&CycleResult> for GenesisCycle {
+    fn from(result: &CycleResult) -> Self {
+        let next = result.prediction.as_ref().and_then(|p| p.values.first().map(|&v| (v, p.confidence)));
+        Self {
+            cycle: result.cycle,
+            confidence: result.confidence,
+            node_id: result.node_id,
+            anomaly_detected: result.anomaly_detected,
+            anomaly_score: result.anomaly_score,
+            has_prediction: next.is_some(),
+            predicted_value: next.map_or(0.0, |(value, _)| value),
+            prediction_confidence: next.map_or(0.0, |(_, confidence)| confidence),
+            processing_us: result.processing_us,
+        }
+    }
+}
+
+/// Headline figures of `SystemMetrics`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GenesisMetrics {
+    pub cycles: u32,
+    pub runtime_seconds: f64,
+    pub processing_rate_hz: f64,
+    pub avg_processing_us: f64,
+    pub p50_processing_us: u64,
+    pub p95_processing_us: u64,
+    pub p99_processing_us: u64,
+    pub spatial_nodes: usize,
+    pub spatial_edges: usize,
+    pub anomalies_detected: usize,
+    pub predictions_made: usize,
+    pub loop_closures: usize,
+    pub memory_usage_mb: f64,
+}
+
+impl From<&SystemMetrics> for GenesisMetrics {
+    fn from(metrics: &SystemMetrics) -> Self {
+        Self {
+            cycles: metrics.cycles,
+            runtime_seconds: metrics.runtime_seconds,
+            processing_rate_hz: metrics.processing_rate_hz,
+            avg_processing_us: metrics.avg_processing_us,
+            p50_processing_us: metrics.p50_processing_us,
+            p95_processing_us: metrics.p95_processing_us,
+            p99_processing_us: metrics.p99_processing_us,
+            spatial_nodes: metrics.spatial_nodes,
+            spatial_edges: metrics.spatial_edges,
+            anomalies_detected: metrics.anomalies_detected,
+            predictions_made: metrics.predictions_made,
+            loop_closures: metrics.loop_closures,
+            memory_usage_mb: metrics.memory_usage_mb,
+        }
+    }
+}
+
+/// Write `value` through `out` when it is not null
+unsafe fn write_out<T>(out: *mut T, value: T) {
+    if let Some(out) = out.as_mut() {
+        *out = value;
+    }
+}
+
+/// Create a system with default configuration; free it with `genesis_system_free`
+#[no_mangle]
+pub extern "C" fn genesis_system_new() -> *mut GenesisSystem {
+    Box::into_raw(Box::new(GenesisSystem(EnvironmentalAwarenessSystem::new())))
+}
+
+/// Destroy a system; null is ignored
+///
+/// # Safety
+/// `system` must be null or a handle from `genesis_system_new` not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn genesis_system_free(system: *mut GenesisSystem) {
+    if !system.is_null() {
+        drop(Box::from_raw(system));
+    }
+}
+
+/// Process one sensor frame; the result is written to `out` when it is not null
+///
+/// # Safety
+/// `system` must be a live handle, `frame` must point to a valid frame and
+/// `out` must be null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn genesis_push_sensor_frame(
+    system: *mut GenesisSystem,
+    frame: *const GenesisSensorFrame,
+    out: *mut GenesisCycle,
+) -> GenesisStatus {
+    let (Some(system), Some(frame)) = (system.as_mut(), frame.as_ref()) else {
+        return GenesisStatus::NullPointer;
+    };
+    match system.0.process_sensor_data(&SensorData::from(frame)) {
+        Ok(result) => {
+            write_out(out, GenesisCycle::from(&result));
+            GenesisStatus::Ok
+        }
+        Err(GenesisError::InvalidReading { .. }) => GenesisStatus::InvalidReading,
+        Err(_) => GenesisStatus::Other,
+    }
+}
+
+/// Run one cycle on simulated sensor data
+///
+/// # Safety
+/// `system` must be a live handle and `out` must be null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn genesis_run_cycle(system: *mut GenesisSystem, out: *mut GenesisCycle) -> GenesisStatus {
+    let Some(system) = system.as_mut() else {
+        return GenesisStatus::NullPointer;
+    };
+    let result = system.0.run_cycle();
+    write_out(out, GenesisCycle::from(&result));
+    GenesisStatus::Ok
+}
+
+/// Fill `out` with the current metrics
+///
+/// # Safety
+/// `system` must be a live handle and `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn genesis_get_metrics(system: *const GenesisSystem, out: *mut GenesisMetrics) -> GenesisStatus {
+    let (Some(system), false) = (system.as_ref(), out.is_null()) else {
+        return GenesisStatus::NullPointer;
+    };
+    write_out(out, GenesisMetrics::from(&system.0.get_metrics()));
+    GenesisStatus::Ok
+}
+
+/// Full metrics as a JSON string, or null on failure; free it with `genesis_string_free`
+///
+/// # Safety
+/// `system` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn genesis_metrics_json(system: *const GenesisSystem) -> *mut c_char {
+    let Some(system) = system.as_ref() else {
+        return ptr::null_mut();
+    };
+    serde_json::to_string(&system.0.get_metrics())
+        .ok()
+        .and_then(|json| CString::new(json).ok())
+        .map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Release a string returned by this library; null is ignored
+///
+/// # Safety
+/// `string` must be null or a pointer from `genesis_metrics_json` not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn genesis_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn test_c_api_round_trip() {
+        unsafe {
+            let system = genesis_system_new();
+            let mut cycle = GenesisCycle::default();
+            assert_eq!(genesis_run_cycle(system, &mut cycle), GenesisStatus::Ok);
+            assert_eq!(cycle.cycle, 1);
+
+            let mut frame = GenesisSensorFrame {
+                visual_objects: 5,
+                lidar_points: 1000,
+                lidar_max_range: 40.0,
+                audio_amplitude: 0.5,
+                audio_frequency: 440.0,
+                imu_accel_z: 9.8,
+                ..GenesisSensorFrame::default()
+            };
+            assert_eq!(genesis_push_sensor_frame(system, &frame, &mut cycle), GenesisStatus::Ok);
+            assert_eq!(cycle.cycle, 2);
+            frame.imu_gyro = f32::NAN;
+            assert_eq!(genesis_push_sensor_frame(system, &frame, ptr::null_mut()), GenesisStatus::InvalidReading);
+            assert_eq!(genesis_push_sensor_frame(system, ptr::null(), &mut cycle), GenesisStatus::NullPointer);
+
+            let mut metrics = GenesisMetrics::default();
+            assert_eq!(genesis_get_metrics(system, &mut metrics), GenesisStatus::Ok);
+            assert_eq!((metrics.cycles, metrics.spatial_nodes), (2, 2));
+
+            let json = genesis_metrics_json(system);
+            let parsed: serde_json::Value = serde_json::from_str(CStr::from_ptr(json).to_str().unwrap()).unwrap();
+            assert_eq!(parsed["cycles"], 2);
+            genesis_string_free(json);
+            genesis_system_free(system);
+        }
+    }
+}
@@ -0,0 +1,646 @@
+//! Fleet-wide aggregation of many deployed systems
+//!
+//! Each device reports its cycles and periodic metrics; a `FleetAggregator`
+//! keeps per-device statistics, sums them over the fleet and raises a
+//! `FleetAlert` when a large share of the online devices turn anomalous at
+//! about the same time. One device's anomaly is its own business; many at
+//! once point at something shared, such as weather, a network outage or a
+//! bad software rollout.
+//!
+//! The aggregator is transport-agnostic and works on the server's clock, so
+//! device clocks need not agree. With the `grpc` feature, `FleetService`
+//! serves it as the `Fleet` service in `proto/genesis.proto` and a
+//! `FleetReporter` observer streams a system's telemetry to it; other
+//! transports (an MQTT bridge, say) call `record_cycle` and `record_metrics`
+//! themselves.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::SystemMetrics;
+
+/// When the fleet counts as anomalous
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FleetConfig {
+    /// Devices anomalous within this many seconds of each other are anomalous together
+    pub window_secs: f64,
+    /// Share of online devices that must be anomalous together to raise an alert
+    pub min_fraction: f32,
+    /// Devices that must be anomalous together, however small the fleet
+    pub min_devices: usize,
+    /// A device silent for this long is offline
+    pub offline_after_secs: f64,
+    /// Seconds after an alert before the next may be raised
+    pub cooldown_secs: f64,
+}
+
+impl Default for FleetConfig {
+    fn default() -> Self {
+        Self {
+            window_secs: 5.0,
+            min_fraction: 0.3,
+            min_devices: 3,
+            offline_after_secs: 30.0,
+            cooldown_secs: 30.0,
+        }
+    }
+}
+
+/// Headline `SystemMetrics` a device reports
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeviceMetrics {
+    pub cycles: u32,
+    pub runtime_seconds: f64,
+    pub processing_rate_hz: f64,
+    pub avg_processing_us: f64,
+    pub p50_processing_us: u64,
+    pub p95_processing_us: u64,
+    pub p99_processing_us: u64,
+    pub theoretical_max_hz: f64,
+    pub spatial_nodes: u64,
+    pub spatial_edges: u64,
+    pub anomalies_detected: u64,
+    pub anomaly_episodes: u64,
+    pub drift_events: u64,
+    pub predictions_made: u64,
+    pub loop_closures: u64,
+    pub memory_usage_mb: f64,
+}
+
+impl From<&SystemMetrics> for DeviceMetrics {
+    fn from(metrics: &SystemMetrics) -> Self {
+        Self {
+            cycles: metrics.cycles,
+            runtime_seconds: metrics.runtime_seconds,
+            processing_rate_hz: metrics.processing_rate_hz,
+            avg_processing_us: metrics.avg_processing_us,
+            p50_processing_us: metrics.p50_processing_us,
+            p95_processing_us: metrics.p95_processing_us,
+            p99_processing_us: metrics.p99_processing_us,
+            theoretical_max_hz: metrics.theoretical_max_hz,
+            spatial_nodes: metrics.spatial_nodes as u64,
+            spatial_edges: metrics.spatial_edges as u64,
+            anomalies_detected: metrics.anomalies_detected as u64,
+            anomaly_episodes: metrics.anomaly_episodes as u64,
+            drift_events: metrics.drift_events as u64,
+            predictions_made: metrics.predictions_made as u64,
+            loop_closures: metrics.loop_closures as u64,
+            memory_usage_mb: metrics.memory_usage_mb,
+        }
+    }
+}
+
+/// What the fleet server knows about one device
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeviceStats {
+    pub device_id: String,
+    /// Server time of the first and latest report, in seconds since the Unix epoch
+    pub first_seen: f64,
+    pub last_seen: f64,
+    /// Its report stream is still open
+    pub connected: bool,
+    /// Connected and heard from within `FleetConfig::offline_after_secs`, as of
+    /// the time these statistics were taken
+    pub online: bool,
+    pub cycles: u64,
+    pub anomalies: u64,
+    pub last_anomaly: Option<f64>,
+    /// Sum of reported cycle processing times
+    pub processing_us: u64,
+    /// Latest metrics the device reported
+    pub metrics: Option<DeviceMetrics>,
+}
+
+impl DeviceStats {
+    /// Anomalous share of the device's cycles
+    pub fn anomaly_rate(&self) -> f32 {
+        if self.cycles == 0 { 0.0 } else { self.anomalies as f32 / self.cycles as f32 }
+    }
+
+    pub fn avg_processing_us(&self) -> u64 {
+        self.processing_us.checked_div(self.cycles).unwrap_or(0)
+    }
+
+    fn is_online(&self, now: f64, config: &FleetConfig) -> bool {
+        self.connected && now - self.last_seen <= config.offline_after_secs
+    }
+
+    fn is_anomalous(&self, now: f64, config: &FleetConfig) -> bool {
+        self.last_anomaly.is_some_and(|at| now - at <= config.window_secs)
+    }
+}
+
+/// Many devices anomalous at once
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FleetAlert {
+    pub timestamp: f64,
+    /// Online devices anomalous within `FleetConfig::window_secs`, by id
+    pub devices: Vec<String>,
+    pub online: usize,
+    /// `devices` as a share of `online`
+    pub fraction: f32,
+}
+
+/// Fleet-wide totals with each device's statistics
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FleetStats {
+    pub online: usize,
+    /// Online devices anomalous within `FleetConfig::window_secs`
+    pub anomalous: usize,
+    pub cycles: u64,
+    pub anomalies: u64,
+    pub alerts: usize,
+    /// By device id, online or not
+    pub devices: Vec<DeviceStats>,
+}
+
+impl FleetStats {
+    /// Anomalous share of every cycle reported
+    pub fn anomaly_rate(&self) -> f32 {
+        if self.cycles == 0 { 0.0 } else { self.anomalies as f32 / self.cycles as f32 }
+    }
+}
+
+/// Per-device and fleet-wide statistics with fleet-level anomaly detection
+///
+/// Every method takes the server's current time, in seconds, so callers
+/// choose the clock.
+#[derive(Debug, Clone, Default)]
+pub struct FleetAggregator {
+    config: FleetConfig,
+    devices: BTreeMap<String, DeviceStats>,
+    last_alert: Option<f64>,
+    alerts: usize,
+}
+
+impl FleetAggregator {
+    pub fn new(config: FleetConfig) -> Self {
+        Self { config, ..Self::default() }
+    }
+
+    pub fn config(&self) -> &FleetConfig {
+        &self.config
+    }
+
+    fn device(&mut self, device_id: &str, now: f64) -> &mut DeviceStats {
+        let device = self.devices.entry(device_id.to_string()).or_insert_with(|| DeviceStats {
+            device_id: device_id.to_string(),
+            first_seen: now,
+            ..DeviceStats::default()
+        });
+        device.last_seen = now;
+        device.connected = true;
+        device
+    }
+
+    /// Count one cycle of `device_id`, returning an alert if it tipped the fleet into one
+    pub fn record_cycle(&mut self, device_id: &str, now: f64, anomalous: bool, processing_us: u64) -> Option<FleetAlert> {
+        let device = self.device(device_id, now);
+        device.cycles += 1;
+        device.processing_us += processing_us;
+        if !anomalous {
+            return None;
+        }
+        device.anomalies += 1;
+        device.last_anomaly = Some(now);
+        self.check(now)
+    }
+
+    /// Keep the latest metrics of `device_id`
+    pub fn record_metrics(&mut self, device_id: &str, now: f64, metrics: DeviceMetrics) {
+        self.device(device_id, now).metrics = Some(metrics);
+    }
+
+    /// Note that `device_id` closed its stream; its statistics are kept
+    pub fn disconnect(&mut self, device_id: &str) {
+        if let Some(device) = self.devices.get_mut(device_id) {
+            device.connected = false;
+        }
+    }
+
+    /// Drop a device's statistics entirely
+    pub fn forget(&mut self, device_id: &str) -> Option<DeviceStats> {
+        self.devices.remove(device_id)
+    }
+
+    /// Statistics of one device as of `now`
+    pub fn device_stats(&self, device_id: &str, now: f64) -> Option<DeviceStats> {
+        self.devices.get(device_id).map(|device| self.snapshot(device, now))
+    }
+
+    fn snapshot(&self, device: &DeviceStats, now: f64) -> DeviceStats {
+        DeviceStats { online: device.is_online(now, &self.config), ..device.clone() }
+    }
+
+    /// Raise an alert if enough online devices are anomalous together and the cooldown has passed
+    fn check(&mut self, now: f64) -> Option<FleetAlert> {
+        if self.last_alert.is_some_and(|at| now - at < self.config.cooldown_secs) {
+            return None;
+        }
+        let config = &self.config;
+        let online = self.devices.values().filter(|device| device.is_online(now, config)).count();
+        let devices: Vec<String> = self
+            .devices
+            .values()
+            .filter(|device| device.is_online(now, config) && device.is_anomalous(now, config))
+            .map(|device| device.device_id.clone())
+            .collect();
+        let fraction = devices.len() as f32 / online.max(1) as f32;
+        if devices.len() < config.min_devices.max(1) || fraction < config.min_fraction {
+            return None;
+        }
+        self.last_alert = Some(now);
+        self.alerts += 1;
+        #[cfg(feature = "tracing")]
+        tracing::warn!(anomalous = devices.len(), online, "fleet-wide anomaly");
+        Some(FleetAlert { timestamp: now, devices, online, fraction })
+    }
+
+    /// Fleet-wide statistics as of `now`
+    pub fn stats(&self, now: f64) -> FleetStats {
+        let config = &self.config;
+        FleetStats {
+            online: self.devices.values().filter(|device| device.is_online(now, config)).count(),
+            anomalous: self
+                .devices
+                .values()
+                .filter(|device| device.is_online(now, config) && device.is_anomalous(now, config))
+                .count(),
+            cycles: self.devices.values().map(|device| device.cycles).sum(),
+            anomalies: self.devices.values().map(|device| device.anomalies).sum(),
+            alerts: self.alerts,
+            devices: self.devices.values().map(|device| self.snapshot(device, now)).collect(),
+        }
+    }
+}
+
+#[cfg(feature = "grpc")]
+pub use service::{FleetReporter, FleetService, REPORT_BUFFER};
+
+#[cfg(feature = "grpc")]
+mod service {
+    use std::net::SocketAddr;
+    use std::pin::Pin;
+    use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use tokio::sync::{broadcast, mpsc};
+    use tokio_stream::wrappers::{BroadcastStream, ReceiverStream};
+    use tokio_stream::{Stream, StreamExt};
+    use tonic::{Request, Response, Status, Streaming};
+
+    use super::{DeviceMetrics, DeviceStats, FleetAggregator, FleetAlert, FleetConfig, FleetStats};
+    use crate::error::Result;
+    use crate::grpc::proto::{self, device_report, fleet_client::FleetClient, fleet_server::{Fleet, FleetServer}};
+    use crate::observer::SystemObserver;
+    use crate::{CycleResult, SystemMetrics};
+
+    /// Reports a `FleetReporter` holds while the connection catches up; later ones are dropped
+    pub const REPORT_BUFFER: usize = 1024;
+
+    /// Alerts a `StreamFleetAlerts` client may fall behind by before skipping ahead
+    const ALERT_BUFFER: usize = 64;
+
+    impl From<&DeviceStats> for proto::DeviceStatus {
+        fn from(device: &DeviceStats) -> Self {
+            Self {
+                device_id: device.device_id.clone(),
+                first_seen: device.first_seen,
+                last_seen: device.last_seen,
+                online: device.online,
+                cycles: device.cycles,
+                anomalies: device.anomalies,
+                last_anomaly: device.last_anomaly,
+                anomaly_rate: device.anomaly_rate(),
+                avg_processing_us: device.avg_processing_us(),
+                metrics: device.metrics.as_ref().map(Into::into),
+            }
+        }
+    }
+
+    impl From<&FleetStats> for proto::FleetStatus {
+        fn from(stats: &FleetStats) -> Self {
+            Self {
+                devices: stats.devices.len() as u32,
+                online: stats.online as u32,
+                anomalous: stats.anomalous as u32,
+                cycles: stats.cycles,
+                anomalies: stats.anomalies,
+                anomaly_rate: stats.anomaly_rate(),
+                alerts: stats.alerts as u64,
+                device_status: stats.devices.iter().map(Into::into).collect(),
+            }
+        }
+    }
+
+    impl From<&FleetAlert> for proto::FleetAlert {
+        fn from(alert: &FleetAlert) -> Self {
+            Self {
+                timestamp: alert.timestamp,
+                devices: alert.devices.clone(),
+                online: alert.online as u32,
+                fraction: alert.fraction,
+            }
+        }
+    }
+
+    impl From<&DeviceMetrics> for proto::Metrics {
+        fn from(metrics: &DeviceMetrics) -> Self {
+            Self {
+                cycles: metrics.cycles,
+                runtime_seconds: metrics.runtime_seconds,
+                processing_rate_hz: metrics.processing_rate_hz,
+                avg_processing_us: metrics.avg_processing_us,
+                p50_processing_us: metrics.p50_processing_us,
+                p95_processing_us: metrics.p95_processing_us,
+                p99_processing_us: metrics.p99_processing_us,
+                theoretical_max_hz: metrics.theoretical_max_hz,
+                spatial_nodes: metrics.spatial_nodes,
+                spatial_edges: metrics.spatial_edges,
+                anomalies_detected: metrics.anomalies_detected,
+                anomaly_episodes: metrics.anomaly_episodes,
+                drift_events: metrics.drift_events,
+                predictions_made: metrics.predictions_made,
+                loop_closures: metrics.loop_closures,
+                memory_usage_mb: metrics.memory_usage_mb,
+            }
+        }
+    }
+
+    impl From<&proto::Metrics> for DeviceMetrics {
+        fn from(metrics: &proto::Metrics) -> Self {
+            Self {
+                cycles: metrics.cycles,
+                runtime_seconds: metrics.runtime_seconds,
+                processing_rate_hz: metrics.processing_rate_hz,
+                avg_processing_us: metrics.avg_processing_us,
+                p50_processing_us: metrics.p50_processing_us,
+                p95_processing_us: metrics.p95_processing_us,
+                p99_processing_us: metrics.p99_processing_us,
+                theoretical_max_hz: metrics.theoretical_max_hz,
+                spatial_nodes: metrics.spatial_nodes,
+                spatial_edges: metrics.spatial_edges,
+                anomalies_detected: metrics.anomalies_detected,
+                anomaly_episodes: metrics.anomaly_episodes,
+                drift_events: metrics.drift_events,
+                predictions_made: metrics.predictions_made,
+                loop_closures: metrics.loop_closures,
+                memory_usage_mb: metrics.memory_usage_mb,
+            }
+        }
+    }
+
+    /// Lock ignoring poisoning; a panicked handler leaves the data usable
+    fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+        mutex.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Server wall-clock time in seconds since the Unix epoch
+    fn now() -> f64 {
+        match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_secs_f64(),
+            Err(before) => -before.duration().as_secs_f64(),
+        }
+    }
+
+    /// A `FleetAggregator` shared between gRPC calls
+    #[derive(Debug, Clone)]
+    pub struct FleetService {
+        aggregator: Arc<Mutex<FleetAggregator>>,
+        alerts: broadcast::Sender<proto::FleetAlert>,
+    }
+
+    impl FleetService {
+        pub fn new(config: FleetConfig) -> Self {
+            let (alerts, _) = broadcast::channel(ALERT_BUFFER);
+            Self { aggregator: Arc::new(Mutex::new(FleetAggregator::new(config))), alerts }
+        }
+
+        /// The aggregator, e.g. for feeding it from another transport
+        pub fn aggregator(&self) -> Arc<Mutex<FleetAggregator>> {
+            Arc::clone(&self.aggregator)
+        }
+
+        /// Fleet statistics now
+        pub fn stats(&self) -> FleetStats {
+            lock(&self.aggregator).stats(now())
+        }
+
+        /// The service for adding to an existing `tonic` server
+        pub fn into_service(self) -> FleetServer<Self> {
+            FleetServer::new(self)
+        }
+
+        /// Listen on `addr` until the task is cancelled
+        pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+            tonic::transport::Server::builder()
+                .add_service(self.into_service())
+                .serve(addr)
+                .await
+                .map_err(std::io::Error::other)?;
+            Ok(())
+        }
+
+        fn record(&self, report: proto::DeviceReport) {
+            let now = now();
+            let mut aggregator = lock(&self.aggregator);
+            let alert = match report.report {
+                Some(device_report::Report::Cycle(cycle)) => {
+                    aggregator.record_cycle(&report.device_id, now, cycle.anomaly_detected, cycle.processing_us)
+                }
+                Some(device_report::Report::Metrics(metrics)) => {
+                    aggregator.record_metrics(&report.device_id, now, (&metrics).into());
+                    None
+                }
+                None => None,
+            };
+            drop(aggregator);
+            if let Some(alert) = alert {
+                let _ = self.alerts.send((&alert).into());
+            }
+        }
+    }
+
+    type AlertStream = Pin<Box<dyn Stream<Item = Result<proto::FleetAlert, Status>> + Send>>;
+
+    #[tonic::async_trait]
+    impl Fleet for FleetService {
+        type StreamFleetAlertsStream = AlertStream;
+
+        async fn report(&self, request: Request<Streaming<proto::DeviceReport>>) -> Result<Response<proto::ReportSummary>, Status> {
+            let mut reports = request.into_inner();
+            let (mut count, mut device) = (0, None);
+            let outcome = loop {
+                match reports.message().await {
+                    Ok(Some(report)) => {
+                        if report.device_id.is_empty() {
+                            break Err(Status::invalid_argument("report without a device_id"));
+                        }
+                        device.get_or_insert_with(|| report.device_id.clone());
+                        self.record(report);
+                        count += 1;
+                    }
+                    Ok(None) => break Ok(Response::new(proto::ReportSummary { reports: count })),
+                    Err(status) => break Err(status),
+                }
+            };
+            if let Some(device) = device {
+                lock(&self.aggregator).disconnect(&device);
+            }
+            outcome
+        }
+
+        async fn get_fleet_status(&self, _: Request<proto::GetFleetStatusRequest>) -> Result<Response<proto::FleetStatus>, Status> {
+            Ok(Response::new((&self.stats()).into()))
+        }
+
+        async fn stream_fleet_alerts(&self, _: Request<proto::StreamFleetAlertsRequest>) -> Result<Response<AlertStream>, Status> {
+            // Lagged errors only mean alerts were dropped for this client
+            let alerts = BroadcastStream::new(self.alerts.subscribe()).filter_map(|alert| alert.ok().map(Ok));
+            Ok(Response::new(Box::pin(alerts)))
+        }
+    }
+
+    /// Observer streaming a system's cycles and metrics to a fleet server
+    ///
+    /// Reports are queued without blocking the cycle; if the connection
+    /// falls `REPORT_BUFFER` reports behind, further ones are dropped.
+    #[derive(Debug)]
+    pub struct FleetReporter {
+        device_id: String,
+        reports: mpsc::Sender<proto::DeviceReport>,
+        metrics_interval: Option<u32>,
+    }
+
+    impl FleetReporter {
+        /// An observer and the report stream to hand to `FleetClient::report`;
+        /// metrics are sent every `metrics_interval` cycles
+        pub fn channel(device_id: impl Into<String>, metrics_interval: Option<u32>) -> (Self, ReceiverStream<proto::DeviceReport>) {
+            let (reports, receiver) = mpsc::channel(REPORT_BUFFER);
+            (Self { device_id: device_id.into(), reports, metrics_interval }, ReceiverStream::new(receiver))
+        }
+
+        /// Connect to the fleet server at `endpoint` and report from a spawned
+        /// task until the observer is dropped
+        pub async fn connect(endpoint: String, device_id: impl Into<String>, metrics_interval: Option<u32>) -> Result<Self> {
+            let mut client = FleetClient::connect(endpoint).await.map_err(std::io::Error::other)?;
+            let (reporter, reports) = Self::channel(device_id, metrics_interval);
+            tokio::spawn(async move {
+                if let Err(status) = client.report(reports).await {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!(%status, "fleet reporting stopped");
+                    #[cfg(not(feature = "tracing"))]
+                    let _ = status;
+                }
+            });
+            Ok(reporter)
+        }
+
+        fn send(&self, report: device_report::Report) {
+            let _ = self.reports.try_send(proto::DeviceReport { device_id: self.device_id.clone(), report: Some(report) });
+        }
+    }
+
+    impl SystemObserver for FleetReporter {
+        fn on_cycle(&mut self, result: &CycleResult) {
+            self.send(device_report::Report::Cycle(result.into()));
+        }
+
+        fn on_metrics(&mut self, metrics: &SystemMetrics) {
+            self.send(device_report::Report::Metrics(metrics.into()));
+        }
+
+        fn metrics_interval(&self) -> Option<u32> {
+            self.metrics_interval
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fleet_alert_needs_many_devices_at_once() {
+        let config = FleetConfig { min_devices: 2, min_fraction: 0.5, ..FleetConfig::default() };
+        let mut fleet = FleetAggregator::new(config);
+        for device in ["a", "b", "c", "d"] {
+            assert!(fleet.record_cycle(device, 0.0, false, 100).is_none());
+        }
+
+        // One anomalous device is its own problem; a second outside the window is too
+        assert!(fleet.record_cycle("a", 1.0, true, 100).is_none());
+        assert!(fleet.record_cycle("b", 10.0, true, 300).is_none());
+        let stats = fleet.stats(10.0);
+        assert_eq!((stats.online, stats.anomalous, stats.cycles, stats.anomalies), (4, 1, 6, 2));
+        assert_eq!(fleet.device_stats("b", 10.0).unwrap().avg_processing_us(), 200);
+
+        // A second within the window makes half the fleet
+        let alert = fleet.record_cycle("c", 12.0, true, 100).unwrap();
+        assert_eq!((alert.devices.clone(), alert.online, alert.fraction), (vec!["b".to_string(), "c".to_string()], 4, 0.5));
+        // Cooldown holds back the next one
+        assert!(fleet.record_cycle("d", 13.0, true, 100).is_none());
+
+        // Devices that disconnect or fall silent no longer count as online
+        fleet.disconnect("d");
+        let stats = fleet.stats(13.0);
+        assert_eq!((stats.online, stats.anomalous, stats.alerts), (3, 2, 1));
+        assert_eq!(fleet.stats(100.0).online, 0);
+        assert_eq!(stats.devices.iter().filter(|device| device.online).count(), 3);
+        assert!((stats.anomaly_rate() - 4.0 / 8.0).abs() < 1e-6);
+    }
+
+    #[cfg(feature = "grpc")]
+    #[tokio::test]
+    async fn test_fleet_service_round_trip() {
+        use std::time::Duration;
+        use tokio_stream::wrappers::TcpListenerStream;
+        use crate::grpc::proto::{self, fleet_client::FleetClient};
+        use crate::EnvironmentalAwarenessSystem;
+
+        let config = FleetConfig { min_devices: 1, min_fraction: 0.0, cooldown_secs: 0.0, ..FleetConfig::default() };
+        let service = FleetService::new(config);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let server = tonic::transport::Server::builder().add_service(service.clone().into_service());
+        tokio::spawn(server.serve_with_incoming(TcpListenerStream::new(listener)));
+        let mut client = FleetClient::connect(endpoint.clone()).await.unwrap();
+        let mut alerts = client.stream_fleet_alerts(proto::StreamFleetAlertsRequest {}).await.unwrap().into_inner();
+
+        // A system reporting through the observer until it is dropped
+        let mut system = EnvironmentalAwarenessSystem::builder().seed(5).build().unwrap();
+        system.add_observer(Box::new(FleetReporter::connect(endpoint, "rover-1", Some(10)).await.unwrap()));
+        let results = system.run_cycles(30);
+        drop(system);
+        let anomalies = results.iter().filter(|result| result.anomaly_detected).count() as u64;
+        let mut status = client.get_fleet_status(proto::GetFleetStatusRequest {}).await.unwrap().into_inner();
+        for _ in 0..100 {
+            if status.online == 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            status = client.get_fleet_status(proto::GetFleetStatusRequest {}).await.unwrap().into_inner();
+        }
+        assert_eq!((status.devices, status.online, status.cycles, status.anomalies), (1, 0, 30, anomalies));
+        let device = &status.device_status[0];
+        assert_eq!(device.device_id, "rover-1");
+        assert_eq!(device.metrics.as_ref().unwrap().cycles, 30);
+
+        // With these thresholds any anomalous cycle alerts the fleet
+        let cycle = proto::Cycle { anomaly_detected: true, ..Default::default() };
+        let report = proto::DeviceReport { device_id: "rover-2".into(), report: Some(proto::device_report::Report::Cycle(cycle)) };
+        assert_eq!(client.report(tokio_stream::iter([report])).await.unwrap().into_inner().reports, 1);
+        loop {
+            let alert = alerts.message().await.unwrap().unwrap();
+            if alert.devices.contains(&"rover-2".to_string()) {
+                break;
+            }
+        }
+        assert_eq!(service.stats().anomalies, anomalies + 1);
+
+        let status = client.report(tokio_stream::iter([proto::DeviceReport::default()])).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+}
@@ -0,0 +1,213 @@
+//! Lightweight coordinate frame registry (sensor → body → world)
+
+use std::collections::VecDeque;
+use ahash::AHashMap;
+
+use crate::spatial::Position;
+
+/// Name of the implicit root frame
+pub const WORLD_FRAME: &str = "world";
+
+/// Number of timestamped transforms retained per frame
+const HISTORY_CAPACITY: usize = 64;
+
+/// Rigid transform: unit quaternion rotation followed by a translation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    /// Rotation as a unit quaternion `[w, x, y, z]`
+    pub rotation: [f32; 4],
+    pub translation: Position,
+}
+
+impl Transform {
+    /// Identity transform
+    pub fn identity() -> Self {
+        Self {
+            rotation: [1.0, 0.0, 0.0, 0.0],
+            translation: Position { x: 0.0, y: 0.0, z: 0.0 },
+        }
+    }
+
+    /// Pure translation
+    pub fn from_translation(translation: Position) -> Self {
+        Self { translation, ..Self::identity() }
+    }
+
+    /// Rotation about the z axis followed by a translation (planar rigs)
+    pub fn from_yaw(yaw: f32, translation: Position) -> Self {
+        let half = yaw * 0.5;
+        Self {
+            rotation: [half.cos(), 0.0, 0.0, half.sin()],
+            translation,
+        }
+    }
+
+    /// Rotate a vector by this transform's quaternion
+    #[inline]
+    fn rotate(&self, p: &Position) -> Position {
+        let [w, qx, qy, qz] = self.rotation;
+        // v' = v + 2w(q × v) + 2q × (q × v)
+        let tx = 2.0 * (qy * p.z - qz * p.y);
+        let ty = 2.0 * (qz * p.x - qx * p.z);
+        let tz = 2.0 * (qx * p.y - qy * p.x);
+        Position {
+            x: p.x + w * tx + (qy * tz - qz * ty),
+            y: p.y + w * ty + (qz * tx - qx * tz),
+            z: p.z + w * tz + (qx * ty - qy * tx),
+        }
+    }
+
+    /// Map a point from the child frame into the parent frame
+    #[inline]
+    pub fn apply(&self, p: &Position) -> Position {
+        let r = self.rotate(p);
+        Position {
+            x: r.x + self.translation.x,
+            y: r.y + self.translation.y,
+            z: r.z + self.translation.z,
+        }
+    }
+
+    /// Compose `self ∘ other` (apply `other` first, then `self`)
+    pub fn then(&self, other: &Transform) -> Transform {
+        let [w1, x1, y1, z1] = self.rotation;
+        let [w2, x2, y2, z2] = other.rotation;
+        Transform {
+            rotation: [
+                w1 * w2 - x1 * x2 - y1 * y2 - z1 * z2,
+                w1 * x2 + x1 * w2 + y1 * z2 - z1 * y2,
+                w1 * y2 - x1 * z2 + y1 * w2 + z1 * x2,
+                w1 * z2 + x1 * y2 - y1 * x2 + z1 * w2,
+            ],
+            translation: self.apply(&other.translation),
+        }
+    }
+}
+
+/// A registered frame and its transform history relative to the parent
+#[derive(Debug, Clone)]
+struct Frame {
+    parent: String,
+    history: VecDeque<(f64, Transform)>,
+}
+
+impl Frame {
+    /// Latest transform at or before `timestamp`, falling back to the oldest
+    fn at(&self, timestamp: f64) -> Option<Transform> {
+        self.history
+            .iter()
+            .rev()
+            .find(|(t, _)| *t <= timestamp)
+            .or_else(|| self.history.front())
+            .map(|(_, transform)| *transform)
+    }
+}
+
+/// TF-like registry of frames with timestamped rigid transforms
+#[derive(Debug, Clone, Default)]
+pub struct FrameRegistry {
+    frames: AHashMap<String, Frame>,
+}
+
+impl FrameRegistry {
+    /// Create a registry containing only the world frame
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a frame under `parent` with a static transform.
+    /// Returns `false` if the parent is unknown or the frame already exists.
+    pub fn register(&mut self, name: &str, parent: &str, transform: Transform) -> bool {
+        if name == WORLD_FRAME || self.frames.contains_key(name) || !self.contains(parent) {
+            return false;
+        }
+
+        let mut history = VecDeque::with_capacity(HISTORY_CAPACITY);
+        history.push_back((f64::NEG_INFINITY, transform));
+        self.frames.insert(name.to_string(), Frame { parent: parent.to_string(), history });
+        true
+    }
+
+    /// Record the frame's transform relative to its parent at `timestamp`.
+    /// Returns `false` if the frame is unknown.
+    pub fn set_transform(&mut self, name: &str, timestamp: f64, transform: Transform) -> bool {
+        let Some(frame) = self.frames.get_mut(name) else {
+            return false;
+        };
+
+        if frame.history.len() >= HISTORY_CAPACITY {
+            frame.history.pop_front();
+        }
+
+        // Keep history ordered by timestamp
+        let at = frame.history.partition_point(|(t, _)| *t <= timestamp);
+        frame.history.insert(at, (timestamp, transform));
+        true
+    }
+
+    /// Check whether a frame is registered
+    pub fn contains(&self, name: &str) -> bool {
+        name == WORLD_FRAME || self.frames.contains_key(name)
+    }
+
+    /// Resolve the `frame → world` transform at `timestamp`
+    pub fn lookup(&self, name: &str, timestamp: f64) -> Option<Transform> {
+        let mut transform = Transform::identity();
+        let mut current = name;
+
+        // Walk up the tree; depth is bounded by the number of frames
+        for _ in 0..=self.frames.len() {
+            if current == WORLD_FRAME {
+                return Some(transform);
+            }
+            let frame = self.frames.get(current)?;
+            transform = frame.at(timestamp)?.then(&transform);
+            current = &frame.parent;
+        }
+
+        None
+    }
+
+    /// Express a point given in `frame` in world coordinates
+    pub fn to_world(&self, name: &str, timestamp: f64, point: &Position) -> Option<Position> {
+        self.lookup(name, timestamp).map(|t| t.apply(point))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(a: &Position, b: &Position) -> bool {
+        a.distance_to(b) < 1e-4
+    }
+
+    #[test]
+    fn test_chained_transforms() {
+        let mut frames = FrameRegistry::new();
+
+        // Body is 10m along x and yawed 90°; lidar sits 1m ahead of the body
+        let body = Transform::from_yaw(std::f32::consts::FRAC_PI_2, Position { x: 10.0, y: 0.0, z: 0.0 });
+        assert!(frames.register("body", WORLD_FRAME, body));
+        assert!(frames.register("lidar", "body", Transform::from_translation(Position { x: 1.0, y: 0.0, z: 0.5 })));
+        assert!(!frames.register("camera", "missing", Transform::identity()));
+
+        let p = frames.to_world("lidar", 0.0, &Position { x: 2.0, y: 0.0, z: 0.0 }).unwrap();
+        assert!(approx(&p, &Position { x: 10.0, y: 3.0, z: 0.5 }), "{:?}", p);
+
+        assert!(frames.to_world("camera", 0.0, &p).is_none());
+    }
+
+    #[test]
+    fn test_timestamped_lookup() {
+        let mut frames = FrameRegistry::new();
+        frames.register("body", WORLD_FRAME, Transform::identity());
+        frames.set_transform("body", 2.0, Transform::from_translation(Position { x: 2.0, y: 0.0, z: 0.0 }));
+        frames.set_transform("body", 1.0, Transform::from_translation(Position { x: 1.0, y: 0.0, z: 0.0 }));
+
+        let origin = Position { x: 0.0, y: 0.0, z: 0.0 };
+        assert_eq!(frames.to_world("body", 0.5, &origin).unwrap().x, 0.0);
+        assert_eq!(frames.to_world("body", 1.5, &origin).unwrap().x, 1.0);
+        assert_eq!(frames.to_world("body", 9.0, &origin).unwrap().x, 2.0);
+    }
+}
@@ -0,0 +1,188 @@
+//! Geodetic (lat/lon/alt) coordinates mapped onto a local tangent plane
+
+use serde::{Serialize, Deserialize};
+
+use crate::spatial::{Position, SpatialGraph};
+
+/// WGS84 semi-major axis in meters
+const WGS84_A: f64 = 6_378_137.0;
+/// WGS84 flattening
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+/// WGS84 first eccentricity squared
+const WGS84_E2: f64 = WGS84_F * (2.0 - WGS84_F);
+
+/// Global position on the WGS84 ellipsoid
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GeodeticPosition {
+    /// Latitude in degrees
+    pub latitude: f64,
+    /// Longitude in degrees
+    pub longitude: f64,
+    /// Height above the ellipsoid in meters
+    pub altitude: f64,
+}
+
+impl GeodeticPosition {
+    /// Earth-centered, earth-fixed coordinates in meters
+    fn to_ecef(self) -> [f64; 3] {
+        let (lat, lon) = (self.latitude.to_radians(), self.longitude.to_radians());
+        let n = WGS84_A / (1.0 - WGS84_E2 * lat.sin().powi(2)).sqrt();
+        [
+            (n + self.altitude) * lat.cos() * lon.cos(),
+            (n + self.altitude) * lat.cos() * lon.sin(),
+            (n * (1.0 - WGS84_E2) + self.altitude) * lat.sin(),
+        ]
+    }
+
+    /// Convert ECEF back to geodetic (iterative, converges in a few steps)
+    fn from_ecef([x, y, z]: [f64; 3]) -> Self {
+        let lon = y.atan2(x);
+        let p = (x * x + y * y).sqrt();
+        let mut lat = z.atan2(p * (1.0 - WGS84_E2));
+        let mut alt = 0.0;
+
+        for _ in 0..5 {
+            let n = WGS84_A / (1.0 - WGS84_E2 * lat.sin().powi(2)).sqrt();
+            alt = p / lat.cos() - n;
+            lat = z.atan2(p * (1.0 - WGS84_E2 * n / (n + alt)));
+        }
+
+        Self {
+            latitude: lat.to_degrees(),
+            longitude: lon.to_degrees(),
+            altitude: alt,
+        }
+    }
+}
+
+/// East-north-up tangent plane anchored at a geodetic origin
+///
+/// Local `Position`s are `x = east`, `y = north`, `z = up` in meters, so
+/// graph distances stay metric. Accuracy degrades with distance from the
+/// origin; re-anchor for maps spanning more than a few tens of kilometers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LocalTangentPlane {
+    origin: GeodeticPosition,
+    origin_ecef: [f64; 3],
+    // Rows of the ECEF → ENU rotation
+    east: [f64; 3],
+    north: [f64; 3],
+    up: [f64; 3],
+}
+
+impl LocalTangentPlane {
+    /// Create a tangent plane anchored at `origin`
+    pub fn new(origin: GeodeticPosition) -> Self {
+        let (lat, lon) = (origin.latitude.to_radians(), origin.longitude.to_radians());
+        let (sin_lat, cos_lat) = lat.sin_cos();
+        let (sin_lon, cos_lon) = lon.sin_cos();
+
+        Self {
+            origin,
+            origin_ecef: origin.to_ecef(),
+            east: [-sin_lon, cos_lon, 0.0],
+            north: [-sin_lat * cos_lon, -sin_lat * sin_lon, cos_lat],
+            up: [cos_lat * cos_lon, cos_lat * sin_lon, sin_lat],
+        }
+    }
+
+    /// Geodetic anchor of the plane
+    pub fn origin(&self) -> &GeodeticPosition {
+        &self.origin
+    }
+
+    /// Project a geodetic position into local ENU meters
+    pub fn to_local(&self, geo: &GeodeticPosition) -> Position {
+        let ecef = geo.to_ecef();
+        let d = [
+            ecef[0] - self.origin_ecef[0],
+            ecef[1] - self.origin_ecef[1],
+            ecef[2] - self.origin_ecef[2],
+        ];
+        let dot = |r: &[f64; 3]| r[0] * d[0] + r[1] * d[1] + r[2] * d[2];
+
+        Position {
+            x: dot(&self.east) as f32,
+            y: dot(&self.north) as f32,
+            z: dot(&self.up) as f32,
+        }
+    }
+
+    /// Lift a local ENU position back to geodetic coordinates
+    pub fn to_geodetic(&self, local: &Position) -> GeodeticPosition {
+        let (e, n, u) = (local.x as f64, local.y as f64, local.z as f64);
+        let ecef = [
+            self.origin_ecef[0] + self.east[0] * e + self.north[0] * n + self.up[0] * u,
+            self.origin_ecef[1] + self.east[1] * e + self.north[1] * n + self.up[1] * u,
+            self.origin_ecef[2] + self.east[2] * e + self.north[2] * n + self.up[2] * u,
+        ];
+        GeodeticPosition::from_ecef(ecef)
+    }
+}
+
+impl SpatialGraph {
+    /// Add a node observed at a global position
+    pub fn add_node_geodetic(
+        &mut self,
+        plane: &LocalTangentPlane,
+        geo: &GeodeticPosition,
+        features: &[f32],
+    ) -> usize {
+        self.add_node_at(plane.to_local(geo), features)
+    }
+
+    /// Find k nearest neighbors of a global position (distances in meters)
+    pub fn k_nearest_geodetic(
+        &self,
+        plane: &LocalTangentPlane,
+        geo: &GeodeticPosition,
+        k: usize,
+    ) -> Vec<(usize, f32)> {
+        self.k_nearest_neighbors(&plane.to_local(geo), k)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGIN: GeodeticPosition = GeodeticPosition { latitude: 47.3977, longitude: 8.5456, altitude: 488.0 };
+
+    #[test]
+    fn test_local_projection() {
+        let plane = LocalTangentPlane::new(ORIGIN);
+
+        let north = GeodeticPosition { latitude: ORIGIN.latitude + 0.001, ..ORIGIN };
+        let p = plane.to_local(&north);
+        assert!((p.y - 111.2).abs() < 0.5, "{:?}", p);
+        assert!(p.x.abs() < 0.01);
+
+        let east = GeodeticPosition { longitude: ORIGIN.longitude + 0.001, ..ORIGIN };
+        let p = plane.to_local(&east);
+        assert!((p.x - 75.4).abs() < 0.5, "{:?}", p);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let plane = LocalTangentPlane::new(ORIGIN);
+        let geo = GeodeticPosition { latitude: 47.4012, longitude: 8.5391, altitude: 520.0 };
+
+        let back = plane.to_geodetic(&plane.to_local(&geo));
+        assert!((back.latitude - geo.latitude).abs() < 1e-6);
+        assert!((back.longitude - geo.longitude).abs() < 1e-6);
+        assert!((back.altitude - geo.altitude).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_metric_neighbor_queries() {
+        let plane = LocalTangentPlane::new(ORIGIN);
+        let mut graph = SpatialGraph::new();
+
+        let near = graph.add_node_geodetic(&plane, &GeodeticPosition { latitude: 47.39775, ..ORIGIN }, &[0.5]);
+        graph.add_node_geodetic(&plane, &GeodeticPosition { latitude: 47.4, ..ORIGIN }, &[0.5]);
+
+        let nearest = graph.k_nearest_geodetic(&plane, &ORIGIN, 1);
+        assert_eq!(nearest[0].0, near);
+        assert!((nearest[0].1 - 5.56).abs() < 0.1);
+    }
+}
@@ -0,0 +1,359 @@
+//! gRPC telemetry and control service
+//!
+//! A typed alternative to the REST server for fleet controllers, defined in
+//! `proto/genesis.proto`:
+//!
+//! ```text
+//! StreamCycles   server stream of every Cycle (optionally anomalies only)
+//! GetMetrics     headline SystemMetrics
+//! Reset          clear learned state, then optionally calibrate on warmup cycles
+//! UpdateConfig   anomaly thresholds, detection mode, metrics interval
+//! ```
+//!
+//! `proto::genesis_client::GenesisClient` is generated alongside the server.
+//! Like `server::Server`, the system is locked only for one call and never
+//! across an `.await`; a stream client that falls more than `CYCLE_BUFFER`
+//! cycles behind skips ahead instead of holding back the system.
+
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status};
+
+use crate::anomaly::{Anomaly, AnomalyConfig, DetectionMode};
+use crate::error::Result;
+use crate::observer::SystemObserver;
+use crate::sensors::SensorConfidences;
+use crate::{CycleResult, EnvironmentalAwarenessSystem, PredictionResult, SystemMetrics};
+
+/// Messages and stubs generated from `proto/genesis.proto`
+#[allow(clippy::all, missing_docs)]
+pub mod proto {
+    tonic::include_proto!("genesis.v1");
+}
+
+use proto::genesis_server::{Genesis, GenesisServer};
+
+/// Cycles a `StreamCycles` client may fall behind by before skipping ahead
+pub const CYCLE_BUFFER: usize = 256;
+
+impl From<&PredictionResult> for proto::Prediction {
+    fn from(prediction: &PredictionResult) -> Self {
+        Self {
+            values: prediction.values.clone(),
+            lower: prediction.lower.clone(),
+            upper: prediction.upper.clone(),
+            confidence: prediction.confidence,
+            trend: prediction.trend.to_string(),
+        }
+    }
+}
+
+impl From<&Anomaly> for proto::Anomaly {
+    fn from(anomaly: &Anomaly) -> Self {
+        Self {
+            id: anomaly.id,
+            timestamp: anomaly.timestamp,
+            value: anomaly.value,
+            z_score: anomaly.z_score,
+            severity: format!("{:?}", anomaly.severity),
+            contributions: anomaly
+                .contributions
+                .iter()
+                .map(|c| proto::FeatureContribution {
+                    feature: c.name().to_string(),
+                    z_score: c.z_score,
+                    share: c.share,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<SensorConfidences> for proto::SensorConfidences {
+    fn from(confidences: SensorConfidences) -> Self {
+        Self {
+            visual: confidences.visual,
+            lidar: confidences.lidar,
+            audio: confidences.audio,
+            imu: confidences.imu,
+        }
+    }
+}
+
+impl From<&CycleResult> for proto::Cycle {
+    fn from(result: &CycleResult) -> Self {
+        Self {
+            cycle: result.cycle,
+            confidence: result.confidence,
+            neural_output: result.neural_output.clone(),
+            node_id: result.node_id as u64,
+            anomaly_detected: result.anomaly_detected,
+            anomaly_id: result.anomaly_id,
+            anomaly_score: result.anomaly_score,
+            prediction: result.prediction.as_ref().map(Into::into),
+            processing_us: result.processing_us,
+            anomaly: result.anomaly.as_ref().map(Into::into),
+            neighbors: result.neighbors.iter().map(|&id| id as u64).collect(),
+            sensor_confidences: Some(result.sensor_confidences.into()),
+            model_version: result.model_version,
+            mode: result.mode.to_string(),
+        }
+    }
+}
+
+impl From<&SystemMetrics> for proto::Metrics {
+    fn from(metrics: &SystemMetrics) -> Self {
+        Self {
+            cycles: metrics.cycles,
+            runtime_seconds: metrics.runtime_seconds,
+            processing_rate_hz: metrics.processing_rate_hz,
+            avg_processing_us: metrics.avg_processing_us,
+            p50_processing_us: metrics.p50_processing_us,
+            p95_processing_us: metrics.p95_processing_us,
+            p99_processing_us: metrics.p99_processing_us,
+            theoretical_max_hz: metrics.theoretical_max_hz,
+            spatial_nodes: metrics.spatial_nodes as u64,
+            spatial_edges: metrics.spatial_edges as u64,
+            anomalies_detected: metrics.anomalies_detected as u64,
+            anomaly_episodes: metrics.anomaly_episodes as u64,
+            drift_events: metrics.drift_events as u64,
+            predictions_made: metrics.predictions_made as u64,
+            loop_closures: metrics.loop_closures as u64,
+            memory_usage_mb: metrics.memory_usage_mb,
+        }
+    }
+}
+
+impl From<AnomalyConfig> for proto::AnomalyThresholds {
+    fn from(config: AnomalyConfig) -> Self {
+        Self {
+            threshold: config.threshold,
+            medium: config.medium,
+            high: config.high,
+            min_window: config.min_window as u32,
+        }
+    }
+}
+
+impl From<proto::AnomalyThresholds> for AnomalyConfig {
+    fn from(thresholds: proto::AnomalyThresholds) -> Self {
+        Self {
+            threshold: thresholds.threshold,
+            medium: thresholds.medium,
+            high: thresholds.high,
+            min_window: thresholds.min_window as usize,
+        }
+    }
+}
+
+impl From<DetectionMode> for proto::DetectionMode {
+    fn from(mode: DetectionMode) -> Self {
+        match mode {
+            DetectionMode::ZScore => Self::ZScore,
+            DetectionMode::Mahalanobis => Self::Mahalanobis,
+            DetectionMode::Both => Self::Both,
+            DetectionMode::Ewma => Self::Ewma,
+            DetectionMode::Mad => Self::Mad,
+            DetectionMode::Cusum => Self::Cusum,
+            DetectionMode::Quantile => Self::Quantile,
+            DetectionMode::OneClassSvm => Self::OneClassSvm,
+            DetectionMode::Ensemble => Self::Ensemble,
+            DetectionMode::Custom => Self::Custom,
+        }
+    }
+}
+
+impl proto::DetectionMode {
+    /// The system's mode, or `None` for `Unspecified`
+    fn to_mode(self) -> Option<DetectionMode> {
+        Some(match self {
+            Self::Unspecified => return None,
+            Self::ZScore => DetectionMode::ZScore,
+            Self::Mahalanobis => DetectionMode::Mahalanobis,
+            Self::Both => DetectionMode::Both,
+            Self::Ewma => DetectionMode::Ewma,
+            Self::Mad => DetectionMode::Mad,
+            Self::Cusum => DetectionMode::Cusum,
+            Self::Quantile => DetectionMode::Quantile,
+            Self::OneClassSvm => DetectionMode::OneClassSvm,
+            Self::Ensemble => DetectionMode::Ensemble,
+            Self::Custom => DetectionMode::Custom,
+        })
+    }
+}
+
+/// Observer feeding `StreamCycles` clients from inside the cycle
+#[derive(Debug)]
+struct Broadcaster {
+    cycles: broadcast::Sender<proto::Cycle>,
+}
+
+impl SystemObserver for Broadcaster {
+    fn on_cycle(&mut self, result: &CycleResult) {
+        if self.cycles.receiver_count() > 0 {
+            let _ = self.cycles.send(result.into());
+        }
+    }
+}
+
+/// Lock ignoring poisoning; a panicked handler leaves the data usable
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// A system shared between gRPC calls
+#[derive(Debug, Clone)]
+pub struct GrpcService {
+    system: Arc<Mutex<EnvironmentalAwarenessSystem>>,
+    cycles: broadcast::Sender<proto::Cycle>,
+}
+
+impl GrpcService {
+    pub fn new(system: EnvironmentalAwarenessSystem) -> Self {
+        Self::with_shared(Arc::new(Mutex::new(system)))
+    }
+
+    /// Serve a system that is also used elsewhere, e.g. by `server::Server`
+    pub fn with_shared(system: Arc<Mutex<EnvironmentalAwarenessSystem>>) -> Self {
+        let (cycles, _) = broadcast::channel(CYCLE_BUFFER);
+        lock(&system).add_observer(Box::new(Broadcaster { cycles: cycles.clone() }));
+        Self { system, cycles }
+    }
+
+    /// The served system, e.g. for driving simulated cycles alongside the service
+    pub fn system(&self) -> Arc<Mutex<EnvironmentalAwarenessSystem>> {
+        Arc::clone(&self.system)
+    }
+
+    /// The service for adding to an existing `tonic` server
+    pub fn into_service(self) -> GenesisServer<Self> {
+        GenesisServer::new(self)
+    }
+
+    /// Listen on `addr` until the task is cancelled
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        tonic::transport::Server::builder()
+            .add_service(self.into_service())
+            .serve(addr)
+            .await
+            .map_err(std::io::Error::other)?;
+        Ok(())
+    }
+
+    fn runtime_config(system: &EnvironmentalAwarenessSystem) -> proto::RuntimeConfig {
+        proto::RuntimeConfig {
+            anomaly: Some(system.anomaly_config().into()),
+            detection_mode: proto::DetectionMode::from(system.detection_mode()).into(),
+            metrics_interval: system.metrics_interval(),
+        }
+    }
+}
+
+type CycleStream = Pin<Box<dyn Stream<Item = Result<proto::Cycle, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl Genesis for GrpcService {
+    type StreamCyclesStream = CycleStream;
+
+    async fn stream_cycles(
+        &self,
+        request: Request<proto::StreamCyclesRequest>,
+    ) -> Result<Response<CycleStream>, Status> {
+        let anomalies_only = request.into_inner().anomalies_only;
+        // Lagged errors only mean cycles were dropped for this client
+        let cycles = BroadcastStream::new(self.cycles.subscribe())
+            .filter_map(move |cycle| cycle.ok().filter(|c| c.anomaly_detected || !anomalies_only).map(Ok));
+        Ok(Response::new(Box::pin(cycles)))
+    }
+
+    async fn get_metrics(&self, _: Request<proto::GetMetricsRequest>) -> Result<Response<proto::Metrics>, Status> {
+        Ok(Response::new((&lock(&self.system).get_metrics()).into()))
+    }
+
+    async fn reset(&self, request: Request<proto::ResetRequest>) -> Result<Response<proto::Metrics>, Status> {
+        let warmup = request.into_inner().warmup_cycles as usize;
+        let system = self.system();
+        // Warmup may run many cycles, so keep it off the async workers
+        let metrics = tokio::task::spawn_blocking(move || {
+            let mut system = lock(&system);
+            system.reset();
+            system.warmup(warmup);
+            system.get_metrics()
+        })
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new((&metrics).into()))
+    }
+
+    async fn update_config(&self, request: Request<proto::ConfigUpdate>) -> Result<Response<proto::RuntimeConfig>, Status> {
+        let update = request.into_inner();
+        let mode = proto::DetectionMode::try_from(update.detection_mode)
+            .map_err(|_| Status::invalid_argument(format!("unknown detection mode {}", update.detection_mode)))?;
+
+        let mut system = lock(&self.system);
+        if let Some(anomaly) = update.anomaly {
+            if !system.set_anomaly_config(anomaly.into()) {
+                return Err(Status::invalid_argument("anomaly thresholds must satisfy 0 < threshold <= medium <= high and min_window >= 2"));
+            }
+        }
+        if let Some(mode) = mode.to_mode() {
+            system.set_detection_mode(mode);
+        }
+        if let Some(interval) = update.metrics_interval {
+            system.set_metrics_interval(interval);
+        }
+        Ok(Response::new(Self::runtime_config(&system)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::proto::genesis_client::GenesisClient;
+    use super::*;
+    use tokio_stream::wrappers::TcpListenerStream;
+
+    #[tokio::test]
+    async fn test_grpc_round_trip() {
+        let service = GrpcService::new(EnvironmentalAwarenessSystem::new());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tonic::transport::Server::builder().add_service(service.clone().into_service());
+        tokio::spawn(server.serve_with_incoming(TcpListenerStream::new(listener)));
+        let mut client = GenesisClient::connect(format!("http://{addr}")).await.unwrap();
+
+        let mut cycles = client.stream_cycles(proto::StreamCyclesRequest::default()).await.unwrap().into_inner();
+        service.system().lock().unwrap().run_cycles(5);
+        for expected in 1..=5 {
+            assert_eq!(cycles.message().await.unwrap().unwrap().cycle, expected);
+        }
+
+        let metrics = client.get_metrics(proto::GetMetricsRequest {}).await.unwrap().into_inner();
+        assert_eq!((metrics.cycles, metrics.spatial_nodes), (5, 5));
+        let metrics = client.reset(proto::ResetRequest { warmup_cycles: 10 }).await.unwrap().into_inner();
+        assert_eq!(metrics.cycles, 0);
+
+        let update = proto::ConfigUpdate {
+            anomaly: Some(proto::AnomalyThresholds { threshold: 3.0, medium: 3.5, high: 4.0, min_window: 5 }),
+            detection_mode: proto::DetectionMode::Mad.into(),
+            metrics_interval: Some(50),
+        };
+        let config = client.update_config(update).await.unwrap().into_inner();
+        assert_eq!(config.anomaly.unwrap().threshold, 3.0);
+        assert_eq!(config.detection_mode(), proto::DetectionMode::Mad);
+        assert_eq!(service.system().lock().unwrap().metrics_interval(), 50);
+
+        // Invalid thresholds are rejected and leave the config unchanged
+        let update = proto::ConfigUpdate {
+            anomaly: Some(proto::AnomalyThresholds { threshold: 3.0, medium: 2.0, high: 1.0, min_window: 5 }),
+            ..Default::default()
+        };
+        let status = client.update_config(update).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert_eq!(service.system().lock().unwrap().anomaly_config().threshold, 3.0);
+    }
+}
@@ -0,0 +1,210 @@
+//! Component health for liveness and readiness probes
+//!
+//! Each subsystem judges itself through `HealthCheck`, against the system
+//! clock and configuration in a `HealthContext`.
+//! `EnvironmentalAwarenessSystem::health` collects the verdicts into a
+//! `HealthReport` whose status is the worst component's. `Degraded` means a
+//! component works at reduced quality (warming up, close to a limit) and
+//! `Unhealthy` that it is not doing its job, so a readiness probe should only
+//! fail on `Unhealthy`.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::SystemConfig;
+
+/// Condition of a component or the whole system, from best to worst
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    #[default]
+    Healthy,
+    Degraded,
+    Unhealthy,
+}
+
+/// A component's status and why it is not healthy
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Health {
+    pub status: HealthStatus,
+    pub reasons: Vec<String>,
+}
+
+impl Health {
+    pub fn healthy() -> Self {
+        Self::default()
+    }
+
+    pub fn degraded(reason: impl Into<String>) -> Self {
+        Self { status: HealthStatus::Degraded, reasons: vec![reason.into()] }
+    }
+
+    pub fn unhealthy(reason: impl Into<String>) -> Self {
+        Self { status: HealthStatus::Unhealthy, reasons: vec![reason.into()] }
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.status == HealthStatus::Healthy
+    }
+}
+
+/// Thresholds the system's components are judged by
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HealthConfig {
+    /// Seconds without a sensor frame before the sensors are degraded
+    pub stale_after: f64,
+    /// Seconds without a sensor frame before the sensors are unhealthy
+    pub dead_after: f64,
+    /// Fraction of `graph_capacity` from which the spatial graph is degraded
+    pub graph_degraded: f32,
+    /// Fraction of `graph_capacity` beyond which the spatial graph is unhealthy
+    pub graph_unhealthy: f32,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            stale_after: 1.0,
+            dead_after: 5.0,
+            graph_degraded: 0.9,
+            graph_unhealthy: 1.0,
+        }
+    }
+}
+
+/// What a health check is judged against
+#[derive(Debug, Clone, Copy)]
+pub struct HealthContext<'a> {
+    /// Time since the run started, by the system clock
+    pub now: Duration,
+    pub config: &'a SystemConfig,
+}
+
+/// A subsystem that can report its own condition
+pub trait HealthCheck {
+    fn health(&self, context: &HealthContext<'_>) -> Health;
+}
+
+/// Tracks when sensor frames last arrived, to report them going stale
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SensorFeed {
+    /// Run time of the last frame, or of the start of the run before any
+    last_frame: Duration,
+    frames: u64,
+}
+
+impl SensorFeed {
+    /// A feed with no frames since run time `at`
+    pub fn since(at: Duration) -> Self {
+        Self { last_frame: at, frames: 0 }
+    }
+
+    /// Record a frame arriving at run time `at`
+    pub fn observe(&mut self, at: Duration) {
+        self.last_frame = self.last_frame.max(at);
+        self.frames += 1;
+    }
+
+    /// Frames seen since the feed was created
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Time since the last frame, or since the start if there was none
+    pub fn idle(&self, now: Duration) -> Duration {
+        now.saturating_sub(self.last_frame)
+    }
+}
+
+impl HealthCheck for SensorFeed {
+    fn health(&self, context: &HealthContext<'_>) -> Health {
+        let idle = self.idle(context.now).as_secs_f64();
+        let config = &context.config.health;
+        let reason = || format!("no sensor frame for {idle:.1} s");
+        if idle >= config.dead_after {
+            Health::unhealthy(reason())
+        } else if idle >= config.stale_after {
+            Health::degraded(reason())
+        } else {
+            Health::healthy()
+        }
+    }
+}
+
+/// Health of one named component
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComponentHealth {
+    pub component: String,
+    pub status: HealthStatus,
+    pub reasons: Vec<String>,
+}
+
+/// Health of every component and of the system as a whole
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// Worst status of any component
+    pub status: HealthStatus,
+    pub components: Vec<ComponentHealth>,
+}
+
+impl HealthReport {
+    /// Add a component's verdict
+    pub fn push(&mut self, component: impl Into<String>, health: Health) {
+        self.status = self.status.max(health.status);
+        self.components.push(ComponentHealth {
+            component: component.into(),
+            status: health.status,
+            reasons: health.reasons,
+        });
+    }
+
+    pub fn component(&self, name: &str) -> Option<&ComponentHealth> {
+        self.components.iter().find(|c| c.component == name)
+    }
+
+    /// Whether the system should receive traffic: no component is unhealthy
+    pub fn is_ready(&self) -> bool {
+        self.status != HealthStatus::Unhealthy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sensor_staleness() {
+        let config = SystemConfig::default();
+        let at = |secs: f64| HealthContext { now: Duration::from_secs_f64(secs), config: &config };
+        let mut feed = SensorFeed::default();
+        assert!(feed.health(&at(0.5)).is_healthy());
+
+        feed.observe(Duration::from_secs(2));
+        assert_eq!(feed.frames(), 1);
+        assert!(feed.health(&at(2.5)).is_healthy());
+        let stale = feed.health(&at(3.5));
+        assert_eq!(stale.status, HealthStatus::Degraded);
+        assert_eq!(stale.reasons, ["no sensor frame for 1.5 s"]);
+        assert_eq!(feed.health(&at(8.0)).status, HealthStatus::Unhealthy);
+    }
+
+    #[test]
+    fn test_report_takes_worst_status() {
+        let mut report = HealthReport::default();
+        report.push("a", Health::healthy());
+        assert!(report.is_ready());
+        report.push("b", Health::degraded("warming up"));
+        assert_eq!(report.status, HealthStatus::Degraded);
+        assert!(report.is_ready());
+        report.push("c", Health::unhealthy("down"));
+        report.push("d", Health::healthy());
+        assert_eq!(report.status, HealthStatus::Unhealthy);
+        assert!(!report.is_ready());
+        assert_eq!(report.component("b").unwrap().reasons, ["warming up"]);
+        assert_eq!(
+            serde_json::to_value(&report.components[2]).unwrap(),
+            serde_json::json!({ "component": "c", "status": "unhealthy", "reasons": ["down"] })
+        );
+    }
+}
@@ -0,0 +1,364 @@
+//! Hierarchical navigable small world (HNSW) approximate nearest neighbor index
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use ahash::{AHashMap, AHashSet};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Serialize, Deserialize};
+
+use crate::spatial::Coordinates;
+
+/// HNSW recall/latency parameters
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HnswConfig {
+    /// Links per element on upper layers (twice this on layer 0)
+    pub m: usize,
+    /// Candidate list size while inserting; higher builds a better graph
+    pub ef_construction: usize,
+    /// Candidate list size while querying; higher trades latency for recall
+    pub ef_search: usize,
+    /// Seed for level assignment, so builds are reproducible
+    pub seed: u64,
+}
+
+impl Default for HnswConfig {
+    fn default() -> Self {
+        Self {
+            m: 16,
+            ef_construction: 100,
+            ef_search: 50,
+            seed: 0x5eed,
+        }
+    }
+}
+
+/// Squared distance ordered for heaps
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Scored(f32, usize);
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0).then(self.1.cmp(&other.1))
+    }
+}
+
+/// Approximate kNN index over node positions
+///
+/// Removal is a tombstone: removed elements keep routing queries but are
+/// never returned. Once tombstones outnumber half the live entries the index
+/// rebuilds itself without them, so repeatedly moving nodes keeps it bounded.
+#[derive(Debug)]
+pub struct HnswIndex<P> {
+    config: HnswConfig,
+    points: Vec<P>,
+    ids: Vec<usize>,
+    deleted: Vec<bool>,
+    // links[element][layer] = neighbor elements
+    links: Vec<Vec<Vec<usize>>>,
+    slots: AHashMap<usize, usize>,
+    entry: Option<usize>,
+    level_scale: f64,
+    rng: StdRng,
+}
+
+impl<P: Coordinates> HnswIndex<P> {
+    /// Create an empty index
+    pub fn new(config: HnswConfig) -> Self {
+        let m = config.m.max(2);
+        Self {
+            config: HnswConfig { m, ..config },
+            points: Vec::new(),
+            ids: Vec::new(),
+            deleted: Vec::new(),
+            links: Vec::new(),
+            slots: AHashMap::new(),
+            entry: None,
+            level_scale: 1.0 / (m as f64).ln(),
+            rng: StdRng::seed_from_u64(config.seed),
+        }
+    }
+
+    /// Index parameters
+    pub fn config(&self) -> &HnswConfig {
+        &self.config
+    }
+
+    /// Number of live (non-removed) entries
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Check whether the index has no live entries
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Estimate heap memory held by the index in bytes
+    pub fn estimate_memory(&self) -> usize {
+        let links: usize = self.links.iter()
+            .map(|layers| {
+                layers.capacity() * std::mem::size_of::<Vec<usize>>()
+                    + layers.iter().map(|l| l.capacity() * std::mem::size_of::<usize>()).sum::<usize>()
+            })
+            .sum();
+
+        self.points.capacity() * std::mem::size_of::<P>()
+            + self.ids.capacity() * std::mem::size_of::<usize>()
+            + self.deleted.capacity()
+            + self.links.capacity() * std::mem::size_of::<Vec<Vec<usize>>>()
+            + links
+            + self.slots.capacity() * (2 * std::mem::size_of::<usize>() + 1)
+    }
+
+    /// Insert a node; re-inserting an id replaces its previous entry
+    pub fn insert(&mut self, id: usize, point: P) {
+        self.remove(id);
+
+        let element = self.points.len();
+        let level = (-self.rng.gen::<f64>().max(f64::MIN_POSITIVE).ln() * self.level_scale) as usize;
+
+        self.points.push(point);
+        self.ids.push(id);
+        self.deleted.push(false);
+        self.links.push(vec![Vec::new(); level + 1]);
+        self.slots.insert(id, element);
+
+        let Some(entry) = self.entry else {
+            self.entry = Some(element);
+            return;
+        };
+
+        let top = self.links[entry].len() - 1;
+        let mut nearest = Scored(point.distance_squared_to(&self.points[entry]), entry);
+
+        // Greedy descent through layers above the new element's level
+        for layer in (level + 1..=top).rev() {
+            nearest = self.greedy(&point, nearest, layer);
+        }
+
+        let mut entries = vec![nearest];
+        for layer in (0..=level.min(top)).rev() {
+            let candidates = self.search_layer(&point, &entries, self.config.ef_construction, layer);
+            let max_links = self.max_links(layer);
+
+            let neighbors: Vec<usize> = candidates.iter().take(self.config.m).map(|s| s.1).collect();
+            for &neighbor in &neighbors {
+                self.links[neighbor][layer].push(element);
+                if self.links[neighbor][layer].len() > max_links {
+                    self.shrink(neighbor, layer, max_links);
+                }
+            }
+            self.links[element][layer] = neighbors;
+            entries = candidates;
+        }
+
+        if level > top {
+            self.entry = Some(element);
+        }
+    }
+
+    /// Remove a node from query results
+    pub fn remove(&mut self, id: usize) -> bool {
+        match self.slots.remove(&id) {
+            Some(element) => {
+                self.deleted[element] = true;
+                if 2 * (self.points.len() - self.slots.len()) > self.slots.len() {
+                    self.rebuild();
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Re-insert the live entries into an emptied index, dropping tombstones
+    fn rebuild(&mut self) {
+        let live: Vec<(usize, P)> = (0..self.points.len())
+            .filter(|&element| !self.deleted[element])
+            .map(|element| (self.ids[element], self.points[element]))
+            .collect();
+
+        self.points.clear();
+        self.ids.clear();
+        self.deleted.clear();
+        self.links.clear();
+        self.slots.clear();
+        self.entry = None;
+        for (id, point) in live {
+            self.insert(id, point);
+        }
+    }
+
+    /// Find approximately the k nearest live nodes, sorted by distance
+    pub fn search(&self, query: &P, k: usize) -> Vec<(usize, f32)> {
+        let Some(entry) = self.entry else {
+            return Vec::new();
+        };
+        if k == 0 {
+            return Vec::new();
+        }
+
+        let mut nearest = Scored(query.distance_squared_to(&self.points[entry]), entry);
+        for layer in (1..self.links[entry].len()).rev() {
+            nearest = self.greedy(query, nearest, layer);
+        }
+
+        // Widen the beam by the number of tombstones that may crowd out live results
+        let tombstones = self.points.len() - self.slots.len();
+        let ef = self.config.ef_search.max(k) + tombstones.min(self.config.ef_search);
+
+        self.search_layer(query, &[nearest], ef, 0)
+            .into_iter()
+            .filter(|s| !self.deleted[s.1])
+            .take(k)
+            .map(|s| (self.ids[s.1], s.0.sqrt()))
+            .collect()
+    }
+
+    /// Link budget on a layer
+    #[inline]
+    fn max_links(&self, layer: usize) -> usize {
+        if layer == 0 { self.config.m * 2 } else { self.config.m }
+    }
+
+    /// Walk to the closest element on a layer, one hop at a time
+    fn greedy(&self, query: &P, mut nearest: Scored, layer: usize) -> Scored {
+        loop {
+            let mut improved = false;
+            for &neighbor in &self.links[nearest.1][layer] {
+                let d = query.distance_squared_to(&self.points[neighbor]);
+                if d < nearest.0 {
+                    nearest = Scored(d, neighbor);
+                    improved = true;
+                }
+            }
+            if !improved {
+                return nearest;
+            }
+        }
+    }
+
+    /// Beam search on one layer; returns up to `ef` elements sorted by distance
+    fn search_layer(&self, query: &P, entries: &[Scored], ef: usize, layer: usize) -> Vec<Scored> {
+        let mut visited: AHashSet<usize> = entries.iter().map(|s| s.1).collect();
+        let mut candidates: BinaryHeap<Reverse<Scored>> = entries.iter().copied().map(Reverse).collect();
+        let mut results: BinaryHeap<Scored> = entries.iter().copied().collect();
+        while results.len() > ef {
+            results.pop();
+        }
+
+        while let Some(Reverse(current)) = candidates.pop() {
+            let worst = results.peek().map_or(f32::INFINITY, |s| s.0);
+            if current.0 > worst && results.len() >= ef {
+                break;
+            }
+
+            for &neighbor in &self.links[current.1][layer] {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+                let d = query.distance_squared_to(&self.points[neighbor]);
+                let worst = results.peek().map_or(f32::INFINITY, |s| s.0);
+                if results.len() < ef || d < worst {
+                    candidates.push(Reverse(Scored(d, neighbor)));
+                    results.push(Scored(d, neighbor));
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+
+        results.into_sorted_vec()
+    }
+
+    /// Keep only the closest `max_links` neighbors of an element on a layer
+    fn shrink(&mut self, element: usize, layer: usize, max_links: usize) {
+        let origin = self.points[element];
+        let mut scored: Vec<Scored> = self.links[element][layer]
+            .iter()
+            .map(|&n| Scored(origin.distance_squared_to(&self.points[n]), n))
+            .collect();
+        scored.sort_unstable();
+        scored.truncate(max_links);
+        self.links[element][layer] = scored.into_iter().map(|s| s.1).collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spatial::Position;
+
+    fn grid() -> Vec<Position> {
+        (0..1000)
+            .map(|i| Position { x: (i % 10) as f32, y: ((i / 10) % 10) as f32, z: (i / 100) as f32 })
+            .collect()
+    }
+
+    #[test]
+    fn test_recall_against_exact() {
+        let points = grid();
+        let mut index = HnswIndex::new(HnswConfig::default());
+        for (id, p) in points.iter().enumerate() {
+            index.insert(id, *p);
+        }
+
+        let mut hits = 0;
+        for q in (0..1000).step_by(37) {
+            let query = Position { x: points[q].x + 0.1, ..points[q] };
+            let found = index.search(&query, 1);
+            hits += (found[0].0 == q) as usize;
+        }
+        assert!(hits >= 26, "recall too low: {}/28", hits);
+    }
+
+    #[test]
+    fn test_remove_and_reinsert() {
+        let mut index = HnswIndex::new(HnswConfig::default());
+        for (id, p) in grid().into_iter().enumerate().take(100) {
+            index.insert(id, p);
+        }
+
+        let origin = Position { x: 0.0, y: 0.0, z: 0.0 };
+        assert_eq!(index.search(&origin, 1)[0].0, 0);
+
+        assert!(index.remove(0));
+        assert_ne!(index.search(&origin, 1)[0].0, 0);
+
+        index.insert(0, Position { x: 50.0, y: 50.0, z: 0.0 });
+        assert_eq!(index.len(), 100);
+        assert_eq!(index.search(&Position { x: 50.0, y: 50.0, z: 0.0 }, 1), vec![(0, 0.0)]);
+    }
+
+    #[test]
+    fn test_updates_stay_bounded() {
+        let points = grid();
+        let mut index = HnswIndex::new(HnswConfig::default());
+        for (id, p) in points.iter().enumerate().take(100) {
+            index.insert(id, *p);
+        }
+
+        // Move every node 10 times; tombstones never pile up past half the live entries
+        for round in 1..=10 {
+            for (id, p) in points.iter().enumerate().take(100) {
+                index.insert(id, Position { x: p.x + round as f32 * 0.01, ..*p });
+                assert!(index.points.len() <= 150, "{} elements after round {round}", index.points.len());
+            }
+        }
+        assert_eq!(index.len(), 100);
+        assert_eq!(index.links.len(), index.points.len());
+
+        let moved = Position { x: points[42].x + 0.5, ..points[42] };
+        assert_eq!(index.search(&moved, 1)[0].0, 42);
+    }
+}
@@ -0,0 +1,276 @@
+//! Bounded ingestion of externally pushed sensor frames
+//!
+//! Sensors pushing faster than cycles run would otherwise queue frames
+//! without bound. An `IngestQueue` holds at most `capacity` frames and
+//! applies an `OverflowPolicy` when a push finds it full, counting every
+//! frame it drops or merges so overload shows up in `SystemMetrics::ingest`.
+//!
+//! Handles are cheap clones sharing one queue: producers push from any
+//! thread and the system takes frames with
+//! `EnvironmentalAwarenessSystem::process_queued`.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::sensors::SensorData;
+
+/// What a push does when the queue is full
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowPolicy {
+    /// Evict the oldest queued frame; cycles see the most recent history
+    DropOldest,
+    /// Discard the incoming frame; cycles see the oldest history
+    DropNewest,
+    /// Replace the newest queued frame with the incoming one, so a burst
+    /// collapses into its latest reading while older frames keep their place
+    Coalesce,
+    /// Wait for room, slowing the producer down to the cycle rate
+    #[default]
+    Block,
+}
+
+/// What happened to a pushed frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pushed {
+    Queued,
+    /// Queued after evicting the oldest frame
+    Evicted,
+    /// Replaced the newest queued frame
+    Coalesced,
+    /// Discarded because the queue was full
+    Dropped,
+    /// Discarded because the queue was closed
+    Closed,
+}
+
+/// Counters of an `IngestQueue` since it was created
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct IngestStats {
+    pub capacity: usize,
+    pub policy: OverflowPolicy,
+    /// Frames pushed, whatever became of them
+    pub received: u64,
+    /// Frames lost to a full queue: evicted, or discarded on arrival
+    pub dropped: u64,
+    /// Queued frames replaced by a newer one
+    pub coalesced: u64,
+    /// Pushes that had to wait for room
+    pub blocked: u64,
+    /// Frames waiting now
+    pub depth: usize,
+    /// Most frames ever waiting at once
+    pub peak_depth: usize,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    frames: VecDeque<SensorData>,
+    closed: bool,
+    stats: IngestStats,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    state: Mutex<State>,
+    /// Signalled when a frame arrives or the queue closes
+    filled: Condvar,
+    /// Signalled when a frame leaves or the queue closes
+    drained: Condvar,
+}
+
+/// Bounded multi-producer queue of sensor frames
+#[derive(Debug, Clone)]
+pub struct IngestQueue {
+    shared: Arc<Shared>,
+}
+
+impl IngestQueue {
+    /// A queue holding up to `capacity` frames (at least one)
+    pub fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        let capacity = capacity.max(1);
+        let state = State {
+            frames: VecDeque::with_capacity(capacity),
+            closed: false,
+            stats: IngestStats { capacity, policy, ..IngestStats::default() },
+        };
+        Self { shared: Arc::new(Shared { state: Mutex::new(state), ..Shared::default() }) }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.shared.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Add a frame, applying the overflow policy if the queue is full
+    ///
+    /// Only `OverflowPolicy::Block` waits, until there is room or the queue closes.
+    pub fn push(&self, frame: SensorData) -> Pushed {
+        let mut state = self.lock();
+        if state.closed {
+            return Pushed::Closed;
+        }
+        state.stats.received += 1;
+        let capacity = state.stats.capacity;
+        let mut pushed = Pushed::Queued;
+        if state.frames.len() >= capacity {
+            match state.stats.policy {
+                OverflowPolicy::DropOldest => {
+                    state.frames.pop_front();
+                    state.stats.dropped += 1;
+                    pushed = Pushed::Evicted;
+                }
+                OverflowPolicy::DropNewest => {
+                    state.stats.dropped += 1;
+                    return Pushed::Dropped;
+                }
+                OverflowPolicy::Coalesce => {
+                    if let Some(newest) = state.frames.back_mut() {
+                        *newest = frame;
+                    }
+                    state.stats.coalesced += 1;
+                    return Pushed::Coalesced;
+                }
+                OverflowPolicy::Block => {
+                    state.stats.blocked += 1;
+                    state = self
+                        .shared
+                        .drained
+                        .wait_while(state, |state| !state.closed && state.frames.len() >= capacity)
+                        .unwrap_or_else(PoisonError::into_inner);
+                    if state.closed {
+                        return Pushed::Closed;
+                    }
+                }
+            }
+        }
+        state.frames.push_back(frame);
+        state.stats.peak_depth = state.stats.peak_depth.max(state.frames.len());
+        drop(state);
+        self.shared.filled.notify_one();
+        pushed
+    }
+
+    /// Take the oldest frame, waiting up to `timeout` for one to arrive
+    ///
+    /// Frames queued before `close` are still handed out.
+    pub fn pop(&self, timeout: Duration) -> Option<SensorData> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.lock();
+        while state.frames.is_empty() && !state.closed {
+            let Some(left) = deadline.checked_duration_since(Instant::now()).filter(|left| !left.is_zero()) else {
+                break;
+            };
+            state = self.shared.filled.wait_timeout(state, left).unwrap_or_else(PoisonError::into_inner).0;
+        }
+        let frame = state.frames.pop_front();
+        drop(state);
+        if frame.is_some() {
+            self.shared.drained.notify_one();
+        }
+        frame
+    }
+
+    /// Take the oldest frame if one is waiting
+    pub fn try_pop(&self) -> Option<SensorData> {
+        self.pop(Duration::ZERO)
+    }
+
+    /// Refuse further frames and wake blocked producers; queued frames stay
+    pub fn close(&self) {
+        self.lock().closed = true;
+        self.shared.filled.notify_all();
+        self.shared.drained.notify_all();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.lock().closed
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn stats(&self) -> IngestStats {
+        let state = self.lock();
+        IngestStats { depth: state.frames.len(), ..state.stats }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    /// Frames told apart by timestamp
+    fn frame(timestamp: f64) -> SensorData {
+        SensorData { timestamp, ..SensorData::generate() }
+    }
+
+    fn drain(queue: &IngestQueue) -> Vec<f64> {
+        std::iter::from_fn(|| queue.try_pop()).map(|f| f.timestamp).collect()
+    }
+
+    #[test]
+    fn test_overflow_policies() {
+        let queue = IngestQueue::new(3, OverflowPolicy::DropOldest);
+        let pushed: Vec<Pushed> = (0..5).map(|i| queue.push(frame(i as f64))).collect();
+        assert_eq!(pushed[2..], [Pushed::Queued, Pushed::Evicted, Pushed::Evicted]);
+        assert_eq!(drain(&queue), [2.0, 3.0, 4.0]);
+
+        let queue = IngestQueue::new(3, OverflowPolicy::DropNewest);
+        for i in 0..5 {
+            queue.push(frame(i as f64));
+        }
+        assert_eq!(drain(&queue), [0.0, 1.0, 2.0]);
+
+        let queue = IngestQueue::new(3, OverflowPolicy::Coalesce);
+        for i in 0..5 {
+            queue.push(frame(i as f64));
+        }
+        assert_eq!(drain(&queue), [0.0, 1.0, 4.0]);
+        let stats = queue.stats();
+        assert_eq!((stats.received, stats.coalesced, stats.dropped), (5, 2, 0));
+        assert_eq!((stats.depth, stats.peak_depth), (0, 3));
+    }
+
+    #[test]
+    fn test_block_waits_for_room() {
+        let queue = IngestQueue::new(2, OverflowPolicy::Block);
+        let producer = {
+            let queue = queue.clone();
+            thread::spawn(move || (0..6).map(|i| queue.push(frame(i as f64))).collect::<Vec<_>>())
+        };
+        let mut seen = Vec::new();
+        while seen.len() < 6 {
+            seen.extend(queue.pop(Duration::from_secs(5)).map(|f| f.timestamp));
+        }
+        assert!(producer.join().unwrap().iter().all(|&p| p == Pushed::Queued));
+        assert_eq!(seen, [0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+        let stats = queue.stats();
+        assert_eq!((stats.received, stats.dropped), (6, 0));
+        assert!(stats.peak_depth <= 2);
+
+        // Closing releases a blocked producer and refuses new frames
+        queue.push(frame(6.0));
+        queue.push(frame(7.0));
+        let blocked = {
+            let queue = queue.clone();
+            thread::spawn(move || queue.push(frame(8.0)))
+        };
+        while queue.stats().blocked == stats.blocked {
+            thread::yield_now();
+        }
+        queue.close();
+        assert_eq!(blocked.join().unwrap(), Pushed::Closed);
+        assert_eq!(queue.push(frame(9.0)), Pushed::Closed);
+        assert_eq!(drain(&queue), [6.0, 7.0]);
+        assert!(queue.pop(Duration::from_secs(5)).is_none());
+    }
+}
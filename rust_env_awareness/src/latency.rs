@@ -0,0 +1,583 @@
+//! Cycle latency distributions in constant memory
+//!
+//! Every cycle records its total and per-stage durations into HDR
+//! histograms, so recording is O(1) and `get_metrics` reads percentiles
+//! without collecting or sorting the run's history. Percentiles are accurate
+//! to `SIGNIFICANT_DIGITS`; count, mean, min and max are exact.
+//!
+//! `RollingLatency` keeps the same distribution per time slot, so the last
+//! 1s, 10s and 60s can be reported next to the lifetime figures.
+//!
+//! `LatencyRetention` can instead base the lifetime figures on a bounded
+//! sample of raw cycle durations, for exact values or a recent-only view.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use hdrhistogram::Histogram;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{StageMetrics, StageStats, StageTimings, WindowMetrics, WindowStats};
+
+/// Decimal digits of precision kept for percentiles (1% relative error)
+pub const SIGNIFICANT_DIGITS: u8 = 2;
+
+/// Distribution of durations in nanoseconds
+///
+/// The histogram resizes to the largest value seen, so memory grows with
+/// the logarithm of the worst latency rather than with the number of cycles.
+#[derive(Debug, Clone)]
+pub struct LatencyHistogram {
+    histogram: Histogram<u64>,
+    sum: u128,
+    min: u64,
+    max: u64,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        Self {
+            histogram: Histogram::new(SIGNIFICANT_DIGITS).expect("valid significant digits"),
+            sum: 0,
+            min: u64::MAX,
+            max: 0,
+        }
+    }
+
+    /// Add one duration
+    #[inline]
+    pub fn record(&mut self, nanos: u64) {
+        self.record_n(nanos, 1);
+    }
+
+    fn record_n(&mut self, nanos: u64, count: u64) {
+        // Grows the histogram as needed; clamping is only a fallback
+        if self.histogram.record_n(nanos, count).is_err() {
+            self.histogram.saturating_record_n(nanos, count);
+        }
+        self.sum += nanos as u128 * count as u128;
+        self.min = self.min.min(nanos);
+        self.max = self.max.max(nanos);
+    }
+
+    /// Durations recorded
+    pub fn len(&self) -> u64 {
+        self.histogram.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.histogram.is_empty()
+    }
+
+    /// Exact mean, 0 when empty
+    pub fn mean(&self) -> f64 {
+        match self.len() {
+            0 => 0.0,
+            n => self.sum as f64 / n as f64,
+        }
+    }
+
+    /// Exact minimum, 0 when empty
+    pub fn min(&self) -> u64 {
+        if self.is_empty() { 0 } else { self.min }
+    }
+
+    /// Exact maximum, 0 when empty
+    pub fn max(&self) -> u64 {
+        self.max
+    }
+
+    /// Value at `quantile` (0 to 1), 0 when empty
+    pub fn quantile(&self, quantile: f64) -> u64 {
+        if self.is_empty() {
+            0
+        } else {
+            self.histogram.value_at_quantile(quantile).min(self.max)
+        }
+    }
+
+    /// Add every duration recorded in `other`
+    pub fn merge(&mut self, other: &Self) {
+        if other.is_empty() {
+            return;
+        }
+        if self.histogram.add(&other.histogram).is_err() {
+            for bucket in other.histogram.iter_recorded() {
+                self.histogram.saturating_record_n(bucket.value_iterated_to(), bucket.count_at_value());
+            }
+        }
+        self.sum += other.sum;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    /// Forget every recorded duration, keeping the allocated buckets
+    pub fn clear(&mut self) {
+        self.histogram.reset();
+        self.sum = 0;
+        self.min = u64::MAX;
+        self.max = 0;
+    }
+
+    /// Approximate heap usage in bytes
+    pub fn memory_bytes(&self) -> usize {
+        self.histogram.distinct_values() * std::mem::size_of::<u64>()
+    }
+}
+
+/// Serialized as `(value, count)` pairs of the non-empty buckets
+impl Serialize for LatencyHistogram {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let buckets: Vec<(u64, u64)> = self
+            .histogram
+            .iter_recorded()
+            .map(|bucket| (bucket.value_iterated_to(), bucket.count_at_value()))
+            .collect();
+        (buckets, self.sum, self.min, self.max).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for LatencyHistogram {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (buckets, sum, min, max) = <(Vec<(u64, u64)>, u128, u64, u64)>::deserialize(deserializer)?;
+        let mut latency = Self::new();
+        for (value, count) in buckets {
+            latency.record_n(value, count);
+        }
+        // Bucket values are rounded, so keep the exact figures
+        latency.sum = sum;
+        latency.min = min;
+        latency.max = max;
+        Ok(latency)
+    }
+}
+
+/// Headline figures of a set of durations, in nanoseconds
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct Summary {
+    pub(crate) mean: f64,
+    pub(crate) min: u64,
+    pub(crate) max: u64,
+    pub(crate) p50: u64,
+    pub(crate) p95: u64,
+    pub(crate) p99: u64,
+}
+
+impl Summary {
+    pub(crate) fn of_histogram(latency: &LatencyHistogram) -> Self {
+        Self {
+            mean: latency.mean(),
+            min: latency.min(),
+            max: latency.max(),
+            p50: latency.quantile(0.50),
+            p95: latency.quantile(0.95),
+            p99: latency.quantile(0.99),
+        }
+    }
+
+    /// Exact figures, sorting the samples
+    fn of_samples(samples: impl Iterator<Item = u64>) -> Self {
+        let mut sorted: Vec<u64> = samples.collect();
+        if sorted.is_empty() {
+            return Self::default();
+        }
+        sorted.sort_unstable();
+        let len = sorted.len();
+        Self {
+            mean: sorted.iter().map(|&nanos| nanos as f64).sum::<f64>() / len as f64,
+            min: sorted[0],
+            max: sorted[len - 1],
+            p50: sorted[len / 2],
+            p95: sorted[len * 95 / 100],
+            p99: sorted[len * 99 / 100],
+        }
+    }
+}
+
+impl From<Summary> for StageStats {
+    fn from(summary: Summary) -> Self {
+        Self {
+            avg_ns: summary.mean,
+            p50_ns: summary.p50,
+            p95_ns: summary.p95,
+            p99_ns: summary.p99,
+            max_ns: summary.max,
+        }
+    }
+}
+
+/// Which cycles the lifetime latency figures in `SystemMetrics` describe
+///
+/// Histograms are always kept for the default; the sampling policies add a
+/// bounded buffer of raw durations that is sorted on every `get_metrics`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LatencyRetention {
+    /// Every cycle since the last reset, with percentiles within 1%
+    #[default]
+    Histogram,
+    /// A uniform random sample of at most `size` cycles since the last reset, exact
+    Reservoir { size: usize },
+    /// The most recent `size` cycles, exact
+    Recent { size: usize },
+}
+
+impl LatencyRetention {
+    /// Samples kept, `None` for `Histogram`
+    pub fn size(self) -> Option<usize> {
+        match self {
+            Self::Histogram => None,
+            Self::Reservoir { size } | Self::Recent { size } => Some(size),
+        }
+    }
+}
+
+/// Raw durations of whole cycles and their stages, per `LatencyRetention`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CycleSamples {
+    retention: LatencyRetention,
+    samples: Vec<(u64, StageTimings)>,
+    /// Cycles offered since the last clear
+    seen: u64,
+    #[serde(skip, default = "StdRng::from_entropy")]
+    rng: StdRng,
+}
+
+impl Default for CycleSamples {
+    fn default() -> Self {
+        Self::new(LatencyRetention::default(), StdRng::from_entropy())
+    }
+}
+
+impl CycleSamples {
+    fn new(retention: LatencyRetention, rng: StdRng) -> Self {
+        Self { retention, samples: Vec::new(), seen: 0, rng }
+    }
+
+    fn record(&mut self, sample: (u64, StageTimings)) {
+        let index = self.seen;
+        self.seen += 1;
+        let Some(size) = self.retention.size() else {
+            return;
+        };
+        if self.samples.len() < size {
+            self.samples.push(sample);
+            return;
+        }
+        // Algorithm R keeps every cycle with equal probability; Recent overwrites the oldest
+        let slot = match self.retention {
+            LatencyRetention::Reservoir { .. } => self.rng.gen_range(0..=index),
+            _ => index % size as u64,
+        };
+        if let Some(kept) = self.samples.get_mut(slot as usize) {
+            *kept = sample;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.samples.clear();
+        self.seen = 0;
+    }
+
+    fn summary(&self, nanos: impl Fn(&(u64, StageTimings)) -> u64) -> Summary {
+        Summary::of_samples(self.samples.iter().map(nanos))
+    }
+}
+
+/// Histograms of whole cycles and of each stage, plus any retained samples
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct CycleLatency {
+    pub(crate) total: LatencyHistogram,
+    pub(crate) sensor: LatencyHistogram,
+    pub(crate) neural: LatencyHistogram,
+    pub(crate) spatial: LatencyHistogram,
+    pub(crate) anomaly: LatencyHistogram,
+    pub(crate) prediction: LatencyHistogram,
+    #[serde(default)]
+    samples: CycleSamples,
+}
+
+impl CycleLatency {
+    /// Retain samples per `retention`, drawing reservoir picks from `rng`
+    pub(crate) fn new(retention: LatencyRetention, rng: StdRng) -> Self {
+        Self { samples: CycleSamples::new(retention, rng), ..Self::default() }
+    }
+
+    pub(crate) fn record(&mut self, total_nanos: u64, stages: &StageTimings) {
+        self.total.record(total_nanos);
+        self.sensor.record(stages.sensor_ns);
+        self.neural.record(stages.neural_ns);
+        self.spatial.record(stages.spatial_ns);
+        self.anomaly.record(stages.anomaly_ns);
+        self.prediction.record(stages.prediction_ns);
+        self.samples.record((total_nanos, *stages));
+    }
+
+    pub(crate) fn retention(&self) -> LatencyRetention {
+        self.samples.retention
+    }
+
+    /// Switch policy, starting the samples afresh; the histograms carry on
+    pub(crate) fn set_retention(&mut self, retention: LatencyRetention) {
+        if retention != self.samples.retention {
+            self.samples.retention = retention;
+            self.samples.clear();
+            self.samples.samples.shrink_to(retention.size().unwrap_or(0));
+        }
+    }
+
+    /// Whole-cycle figures under the retention policy
+    pub(crate) fn total(&self) -> Summary {
+        match self.samples.retention {
+            LatencyRetention::Histogram => Summary::of_histogram(&self.total),
+            _ => self.samples.summary(|(total, _)| *total),
+        }
+    }
+
+    /// Per-stage figures under the retention policy
+    pub(crate) fn stages(&self) -> StageMetrics {
+        if self.samples.retention == LatencyRetention::Histogram {
+            let stage = |histogram: &LatencyHistogram| Summary::of_histogram(histogram).into();
+            return StageMetrics {
+                sensor: stage(&self.sensor),
+                neural: stage(&self.neural),
+                spatial: stage(&self.spatial),
+                anomaly: stage(&self.anomaly),
+                prediction: stage(&self.prediction),
+            };
+        }
+        let stage = |nanos: fn(&StageTimings) -> u64| self.samples.summary(|(_, stages)| nanos(stages)).into();
+        StageMetrics {
+            sensor: stage(|t| t.sensor_ns),
+            neural: stage(|t| t.neural_ns),
+            spatial: stage(|t| t.spatial_ns),
+            anomaly: stage(|t| t.anomaly_ns),
+            prediction: stage(|t| t.prediction_ns),
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        for histogram in self.histograms_mut() {
+            histogram.clear();
+        }
+        self.samples.clear();
+    }
+
+    pub(crate) fn memory_bytes(&self) -> usize {
+        [&self.total, &self.sensor, &self.neural, &self.spatial, &self.anomaly, &self.prediction]
+            .iter()
+            .map(|histogram| histogram.memory_bytes())
+            .sum::<usize>()
+            + self.samples.samples.capacity() * std::mem::size_of::<(u64, StageTimings)>()
+    }
+
+    fn histograms_mut(&mut self) -> [&mut LatencyHistogram; 6] {
+        [
+            &mut self.total,
+            &mut self.sensor,
+            &mut self.neural,
+            &mut self.spatial,
+            &mut self.anomaly,
+            &mut self.prediction,
+        ]
+    }
+}
+
+/// Histograms of consecutive time slots `width` seconds wide, oldest first
+#[derive(Debug, Clone)]
+struct Slots {
+    width: u64,
+    capacity: usize,
+    slots: VecDeque<(u64, LatencyHistogram)>,
+}
+
+impl Slots {
+    fn new(width: u64, capacity: usize) -> Self {
+        Self { width, capacity, slots: VecDeque::with_capacity(capacity) }
+    }
+
+    fn record(&mut self, at: Duration, nanos: u64) {
+        let slot = at.as_secs() / self.width;
+        if let Some((last, histogram)) = self.slots.back_mut() {
+            if *last >= slot {
+                histogram.record(nanos);
+                return;
+            }
+        }
+        // Reuse the oldest slot's buckets once full
+        let mut histogram = if self.slots.len() < self.capacity {
+            LatencyHistogram::new()
+        } else {
+            let (_, mut oldest) = self.slots.pop_front().expect("capacity is non-zero");
+            oldest.clear();
+            oldest
+        };
+        histogram.record(nanos);
+        self.slots.push_back((slot, histogram));
+    }
+
+    /// Cycles ending in the last `seconds`, rounded up to whole slots
+    fn window(&self, now: Duration, seconds: u64) -> WindowStats {
+        let from = (now.as_secs() / self.width).saturating_sub(seconds / self.width);
+        let mut merged = LatencyHistogram::new();
+        for (_, histogram) in self.slots.iter().filter(|(slot, _)| *slot >= from) {
+            merged.merge(histogram);
+        }
+        let span = now.saturating_sub(Duration::from_secs(from * self.width)).as_secs_f64();
+        let summary = Summary::of_histogram(&merged);
+        WindowStats {
+            cycles: merged.len(),
+            rate_hz: if span > 0.0 { merged.len() as f64 / span } else { 0.0 },
+            avg_processing_us: summary.mean / 1000.0,
+            p50_processing_us: summary.p50 / 1000,
+            p95_processing_us: summary.p95 / 1000,
+            p99_processing_us: summary.p99 / 1000,
+            max_processing_us: summary.max / 1000,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.slots.clear();
+    }
+
+    fn memory_bytes(&self) -> usize {
+        self.slots.iter().map(|(_, histogram)| histogram.memory_bytes()).sum()
+    }
+}
+
+/// Cycle durations over the last minute, by time of completion
+///
+/// One-second slots serve the 1s and 10s windows and ten-second slots the
+/// 60s window, so a window covers its length plus at most one slot and
+/// memory stays at 18 histograms however fast the cycle runs.
+#[derive(Debug, Clone)]
+pub(crate) struct RollingLatency {
+    seconds: Slots,
+    tens: Slots,
+}
+
+impl Default for RollingLatency {
+    fn default() -> Self {
+        Self {
+            seconds: Slots::new(1, 11),
+            tens: Slots::new(10, 7),
+        }
+    }
+}
+
+impl RollingLatency {
+    /// Record a cycle that took `nanos` and ended `at` into the run
+    pub(crate) fn record(&mut self, at: Duration, nanos: u64) {
+        self.seconds.record(at, nanos);
+        self.tens.record(at, nanos);
+    }
+
+    /// Windows ending `now` into the run
+    pub(crate) fn metrics(&self, now: Duration) -> WindowMetrics {
+        WindowMetrics {
+            last_1s: self.seconds.window(now, 1),
+            last_10s: self.seconds.window(now, 10),
+            last_60s: self.tens.window(now, 60),
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.seconds.clear();
+        self.tens.clear();
+    }
+
+    pub(crate) fn memory_bytes(&self) -> usize {
+        self.seconds.memory_bytes() + self.tens.memory_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles_and_round_trip() {
+        let mut latency = LatencyHistogram::new();
+        assert_eq!((latency.quantile(0.5), latency.min(), latency.mean()), (0, 0, 0.0));
+        for nanos in 1..=10_000u64 {
+            latency.record(nanos * 100);
+        }
+        assert_eq!((latency.len(), latency.min(), latency.max()), (10_000, 100, 1_000_000));
+        assert_eq!(latency.mean(), 500_050.0);
+        for (quantile, exact) in [(0.5, 500_000.0), (0.95, 950_000.0), (0.99, 990_000.0)] {
+            let error = (latency.quantile(quantile) as f64 - exact).abs() / exact;
+            assert!(error < 0.01, "p{} off by {error}", quantile * 100.0);
+        }
+
+        let json = serde_json::to_string(&latency).unwrap();
+        let restored: LatencyHistogram = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.len(), latency.len());
+        assert_eq!(restored.quantile(0.99), latency.quantile(0.99));
+        assert_eq!(restored.mean(), latency.mean());
+
+        let memory = latency.memory_bytes();
+        latency.clear();
+        assert!(latency.is_empty());
+        assert_eq!(latency.memory_bytes(), memory);
+    }
+
+    #[test]
+    fn test_windows_follow_recent_cycles() {
+        let mut rolling = RollingLatency::default();
+        // A slow minute, then ten fast seconds at twice the rate
+        for tick in 0..600 {
+            rolling.record(Duration::from_millis(tick * 100), 1_000_000);
+        }
+        for tick in 0..2000 {
+            rolling.record(Duration::from_secs(60) + Duration::from_millis(tick * 5), 10_000);
+        }
+
+        let windows = rolling.metrics(Duration::from_secs(70));
+        assert_eq!((windows.last_1s.cycles, windows.last_1s.p99_processing_us), (200, 10));
+        assert_eq!((windows.last_10s.cycles, windows.last_10s.max_processing_us), (2000, 10));
+        assert_eq!(windows.last_10s.rate_hz, 200.0);
+        // The minute window reaches back into the slow cycles
+        assert_eq!(windows.last_60s.cycles, 2000 + 500);
+        assert_eq!(windows.last_60s.max_processing_us, 1000);
+
+        // Nothing for a while: the short windows empty out
+        let idle = rolling.metrics(Duration::from_secs(85));
+        assert_eq!((idle.last_1s.cycles, idle.last_1s.rate_hz), (0, 0.0));
+        assert_eq!(idle.last_10s.cycles, 0);
+        assert_eq!(idle.last_60s.cycles, 2000 + 400);
+    }
+
+    #[test]
+    fn test_retention_policies() {
+        let timings = |nanos: u64| StageTimings { sensor_ns: nanos, ..StageTimings::default() };
+        let mut recent = CycleLatency::new(LatencyRetention::Recent { size: 10 }, StdRng::seed_from_u64(1));
+        let mut reservoir = CycleLatency::new(LatencyRetention::Reservoir { size: 100 }, StdRng::seed_from_u64(1));
+        for nanos in 1..=1000 {
+            recent.record(nanos, &timings(nanos));
+            reservoir.record(nanos, &timings(nanos));
+        }
+
+        // Exactly the last ten cycles, while the histograms still cover all of them
+        let total = recent.total();
+        assert_eq!((total.min, total.max, total.mean), (991, 1000, 995.5));
+        assert_eq!(recent.stages().sensor.max_ns, 1000);
+        assert_eq!(recent.total.len(), 1000);
+
+        // A uniform sample spans the whole run
+        assert_eq!(reservoir.samples.samples.len(), 100);
+        let total = reservoir.total();
+        assert!(total.min < 100 && total.max > 900, "{total:?}");
+        assert!((total.mean - 500.0).abs() < 100.0, "{total:?}");
+
+        reservoir.set_retention(LatencyRetention::Histogram);
+        assert!(reservoir.samples.samples.is_empty());
+        assert_eq!(reservoir.total().max, 1000);
+    }
+}
@@ -6,71 +6,442 @@
 //! - Cache-friendly data structures  
 //! - Lock-free concurrent operations
 //! - Memory pool allocation strategies
+//!
+//! The default `std` feature enables the full system. Without it the crate is
+//! `no_std` + `alloc` and exposes sensor fusion, inference and the
+//! fixed-capacity `embedded::EmbeddedSystem`.
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![allow(dead_code)]
 
+extern crate alloc;
+
+pub mod float;
 pub mod neural;
+#[cfg(feature = "std")]
 pub mod spatial;
 pub mod sensors;
+#[cfg(feature = "std")]
 pub mod anomaly;
+#[cfg(feature = "std")]
 pub mod predictor;
+#[cfg(feature = "std")]
+pub mod frames;
+#[cfg(feature = "std")]
+pub mod geodetic;
+#[cfg(feature = "std")]
+pub mod hnsw;
+#[cfg(feature = "std")]
+pub mod rtree;
+#[cfg(feature = "std")]
+pub mod connectivity;
+#[cfg(feature = "std")]
+pub mod pointcloud;
+#[cfg(feature = "std")]
+pub mod loop_closure;
+#[cfg(feature = "std")]
+pub mod trajectory;
+#[cfg(feature = "std")]
+pub mod costmap;
+#[cfg(feature = "std")]
+pub mod anomaly_map;
+#[cfg(feature = "std")]
+pub mod drift;
+#[cfg(feature = "std")]
+pub mod alerts;
+#[cfg(feature = "std")]
+pub mod config;
+pub mod clock;
+pub mod error;
+#[cfg(feature = "std")]
+pub mod observer;
+#[cfg(feature = "std")]
+pub mod pipeline;
+#[cfg(feature = "std")]
+pub mod swarm;
+#[cfg(feature = "std")]
+pub mod sink;
+#[cfg(feature = "std")]
+pub mod replay;
+#[cfg(feature = "parquet")]
+pub mod columnar;
+#[cfg(feature = "std")]
+pub mod telemetry;
+#[cfg(feature = "std")]
+pub mod latency;
+#[cfg(feature = "std")]
+pub mod health;
+#[cfg(feature = "std")]
+pub mod schedule;
+#[cfg(feature = "std")]
+pub mod ingest;
+#[cfg(feature = "std")]
+pub mod stage;
+#[cfg(feature = "std")]
+pub mod mode;
+#[cfg(feature = "std")]
+pub mod calibration;
+#[cfg(feature = "std")]
+pub mod debug;
+#[cfg(feature = "std")]
+pub mod sim;
+#[cfg(feature = "std")]
+pub mod fleet;
+pub mod embedded;
+#[cfg(feature = "tokio")]
+pub mod stream;
+#[cfg(feature = "visualization")]
+pub mod visualization;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "otel")]
+pub mod otel;
 
-use std::time::{Duration, Instant};
+#[cfg(feature = "std")]
+use std::time::Duration;
+#[cfg(feature = "std")]
+use std::borrow::Cow;
+#[cfg(feature = "std")]
 use std::collections::VecDeque;
+#[cfg(feature = "std")]
+use std::fs::File;
+#[cfg(feature = "std")]
+use std::io::{BufReader, BufWriter, Write};
+#[cfg(feature = "std")]
+use std::path::Path;
+#[cfg(feature = "std")]
 use std::sync::Arc;
+#[cfg(feature = "std")]
+use std::sync::mpsc::{self, Receiver, Sender};
+#[cfg(feature = "std")]
 use serde::{Serialize, Deserialize};
+#[cfg(feature = "std")]
+use rand::rngs::StdRng;
+#[cfg(feature = "std")]
+use rand::SeedableRng;
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
+#[cfg(feature = "std")]
 use neural::NeuralNetwork;
-use spatial::SpatialGraph;
-use sensors::{SensorData, SensorProcessor};
-use anomaly::AnomalyDetector;
-use predictor::Predictor;
+#[cfg(feature = "std")]
+use spatial::{GraphMemory, GraphSnapshot, Position, SpatialGraph};
+#[cfg(feature = "std")]
+use sensors::{ProcessedSensorData, SensorConfidences, SensorData, SensorProcessor};
+#[cfg(feature = "std")]
+use anomaly::{
+    Anomaly, AnomalyConfig, AnomalyDetection, AnomalyDetector, AnomalyEpisode, AnomalyFilter, AnomalyMatcher, AnomalyRateTracker,
+    AnomalyRates, CombinationRule, CusumDetector, DetectionMode, DetectorKind, Detectors, EnsembleConfig, EpisodeConfig,
+    EpisodeTracker, EwmaDetector, MadDetector, MahalanobisDetector, OneClassSvmDetector, QuantileDetector, RateAlert, RateAlertConfig,
+    SeasonalBaseline,
+};
+#[cfg(feature = "std")]
+use anomaly_map::{AnomalyMap, RegionDensity, SpatialAnomaly};
+#[cfg(feature = "std")]
+use alerts::{AlertQueue, AlertQueueConfig, AlertSink};
+#[cfg(feature = "std")]
+use drift::{DriftConfig, DriftEvent, DriftMonitor};
+#[cfg(feature = "std")]
+use config::{ConfigChange, SystemBuilder, SystemConfig};
+#[cfg(feature = "std")]
+use clock::{Clock, SystemClock};
+#[cfg(feature = "std")]
+use error::{GenesisError, Result};
+#[cfg(feature = "std")]
+use observer::SystemObserver;
+#[cfg(feature = "std")]
+use telemetry::{LiveMetrics, MetricsHandle};
+#[cfg(feature = "std")]
+use latency::{CycleLatency, RollingLatency};
+#[cfg(feature = "std")]
+use ingest::{IngestQueue, IngestStats, OverflowPolicy};
+#[cfg(feature = "std")]
+use mode::{BehaviorMode, ModeMachine, ModeTransition};
+#[cfg(feature = "std")]
+use calibration::{CalibrationReport, Calibrator};
+#[cfg(feature = "std")]
+use debug::{BufferOccupancy, DebugDump, ForecastFit, GraphSummary, Occupancy};
+#[cfg(feature = "std")]
+use stage::{BuiltinStage, CustomStageStats, CycleContext, InstalledStage, Stage, StagePosition};
+#[cfg(feature = "std")]
+use schedule::{ScheduleReport, Scheduler, StopSignal};
+#[cfg(feature = "std")]
+use health::{Health, HealthCheck, HealthContext, HealthReport, SensorFeed};
+#[cfg(feature = "std")]
+use predictor::{AdaptiveWindow, ForecastModel, Forecaster, MultiPredictor, Prediction, Predictor, Trend};
+#[cfg(feature = "std")]
+use loop_closure::{LoopClosure, LoopClosureDetector};
+#[cfg(feature = "std")]
+use trajectory::Trajectory;
+#[cfg(feature = "std")]
+use connectivity::GraphStats;
+
+/// Guard for a pipeline stage span; zero-sized without the `tracing` feature
+#[cfg(feature = "std")]
+#[must_use]
+struct StageSpan {
+    #[cfg(feature = "tracing")]
+    _entered: tracing::span::EnteredSpan,
+}
+
+#[cfg(feature = "std")]
+impl StageSpan {
+    /// Leave the span before the guard goes out of scope
+    fn exit(self) {}
+}
+
+/// Enter a debug-level `tracing` span until the returned guard drops
+#[cfg(feature = "std")]
+macro_rules! stage_span {
+    ($name:literal $(, $($field:tt)*)?) => {
+        StageSpan {
+            #[cfg(feature = "tracing")]
+            _entered: tracing::debug_span!($name $(, $($field)*)?).entered(),
+        }
+    };
+}
+
+/// Source nodes sampled for betweenness in `graph_summary`
+#[cfg(feature = "std")]
+const BETWEENNESS_SAMPLES: usize = 4;
+
+/// Anomaly detector state persisted across restarts
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AnomalyState {
+    z_score: AnomalyDetector,
+    mahalanobis: MahalanobisDetector,
+    ewma: EwmaDetector,
+    mad: MadDetector,
+    cusum: CusumDetector,
+    #[serde(default)]
+    quantile: QuantileDetector,
+    #[serde(default)]
+    one_class_svm: OneClassSvmDetector,
+    episodes: EpisodeTracker,
+    #[serde(default)]
+    filter: AnomalyFilter,
+}
+
+/// Forecaster state persisted across restarts
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ForecastState {
+    predictor: Predictor,
+    #[serde(default)]
+    feature_predictor: Option<MultiPredictor>,
+}
+
+/// Complete system state captured by `EnvironmentalAwarenessSystem::snapshot`
+///
+/// Subscribers, observers, alert sinks, user stages and user-supplied detectors or
+/// forecasters are not part of a snapshot; restoring keeps the ones already installed.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemSnapshot {
+    config: SystemConfig,
+    cycle_count: u32,
+    runtime: Duration,
+    #[serde(default)]
+    anomalies_reported: usize,
+    sensor_buffer: VecDeque<ProcessedData>,
+    #[serde(default)]
+    latency: CycleLatency,
+    graph: GraphSnapshot,
+    neural_net: NeuralNetwork,
+    anomaly: AnomalyState,
+    forecast: ForecastState,
+    anomaly_rates: AnomalyRateTracker,
+    anomaly_map: AnomalyMap,
+    drift_monitor: DriftMonitor,
+    detection_mode: DetectionMode,
+    ensemble: EnsembleConfig,
+    last_prediction: Option<(f32, f32)>,
+    loop_closure: LoopClosureDetector,
+    #[serde(default)]
+    behavior: ModeMachine,
+    #[serde(default)]
+    metrics_baseline: MetricsBaseline,
+}
+
+#[cfg(feature = "std")]
+impl SystemSnapshot {
+    /// Cycle counter at the time of the snapshot
+    pub fn cycle(&self) -> u32 {
+        self.cycle_count
+    }
+    
+    /// Configuration the snapshotted system was running with
+    pub fn config(&self) -> &SystemConfig {
+        &self.config
+    }
+}
+
+/// Lifetime counters as of the last `reset_metrics`, which `get_metrics` reports relative to
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+struct MetricsBaseline {
+    /// Run time at the reset
+    runtime: Duration,
+    /// Cycle counter at the reset
+    #[serde(default)]
+    cycles: u32,
+    anomalies: usize,
+    episodes: usize,
+    drift_events: usize,
+    predictions: usize,
+    loop_closures: usize,
+}
+
+/// Neighbor ids reported per `CycleResult`
+#[cfg(feature = "std")]
+pub const RESULT_NEIGHBORS: usize = 8;
+
+/// Feature and output buffers kept for reuse by later cycles
+#[cfg(feature = "std")]
+const BUFFER_POOL_CAPACITY: usize = 16;
 
 /// Memory pool for reducing allocations
+#[cfg(feature = "std")]
+#[derive(Debug)]
 struct MemoryPool<T> {
     pool: Vec<T>,
     capacity: usize,
+    stats: PoolStats,
 }
 
+#[cfg(feature = "std")]
 impl<T: Default + Clone> MemoryPool<T> {
     fn new(capacity: usize) -> Self {
         Self {
             pool: Vec::with_capacity(capacity),
             capacity,
+            stats: PoolStats::default(),
         }
     }
     
     fn get(&mut self) -> T {
-        self.pool.pop().unwrap_or_default()
+        match self.pool.pop() {
+            Some(item) => {
+                self.stats.hits += 1;
+                item
+            }
+            None => {
+                self.stats.misses += 1;
+                T::default()
+            }
+        }
     }
     
     fn return_to_pool(&mut self, item: T) {
         if self.pool.len() < self.capacity {
             self.pool.push(item);
+        } else {
+            self.stats.discarded += 1;
+        }
+    }
+    
+    fn stats(&self) -> PoolStats {
+        PoolStats { available: self.pool.len(), ..self.stats }
+    }
+}
+
+/// How often cycles reused a pooled buffer instead of allocating one
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PoolStats {
+    /// Buffers taken from the pool
+    pub hits: u64,
+    /// Buffers allocated because the pool was empty
+    pub misses: u64,
+    /// Buffers dropped because the pool was full
+    pub discarded: u64,
+    /// Buffers waiting in the pool
+    pub available: usize,
+}
+
+#[cfg(feature = "std")]
+impl PoolStats {
+    /// Fraction of requests served from the pool, 0 before the first
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            requests => self.hits as f64 / requests as f64,
         }
     }
 }
 
 /// Main Environmental Awareness System - Optimized Version
+#[cfg(feature = "std")]
 #[derive(Debug)]
 pub struct EnvironmentalAwarenessSystem {
+    config: SystemConfig,
     neural_net: Arc<NeuralNetwork>,
     spatial_graph: SpatialGraph,
     sensor_processor: SensorProcessor,
-    anomaly_detector: AnomalyDetector,
+    detectors: Detectors,
+    episodes: EpisodeTracker,
+    anomaly_subscribers: Vec<Sender<Anomaly>>,
+    observers: Vec<Box<dyn SystemObserver>>,
+    /// Counters and snapshots shared with `MetricsHandle`s, once one is requested
+    live_metrics: Option<Arc<LiveMetrics>>,
+    /// Cycles between metrics snapshots published to handles
+    metrics_interval: u32,
+    anomaly_filter: AnomalyFilter,
+    anomaly_rates: AnomalyRateTracker,
+    anomaly_map: AnomalyMap,
+    alert_queue: AlertQueue,
+    drift_monitor: DriftMonitor,
+    detection_mode: DetectionMode,
+    ensemble: EnsembleConfig,
+    /// Value predicted for the upcoming cycle and its confidence
+    last_prediction: Option<(f32, f32)>,
     predictor: Predictor,
+    /// User-supplied forecaster used instead of `predictor`
+    custom_forecaster: Option<Box<dyn Forecaster>>,
+    /// Per-channel forecasts of the feature vector, when enabled
+    feature_predictor: Option<MultiPredictor>,
+    loop_closure: LoopClosureDetector,
+    /// Decides the behavior mode reported every cycle
+    behavior: ModeMachine,
+    /// Calibration phase in progress; anomalies are not reported until it ends
+    calibration: Option<Calibrator>,
+    /// What the last completed calibration learned from
+    calibration_report: Option<CalibrationReport>,
+    /// The last `debug_history` results, for `debug_dump`
+    recent_results: VecDeque<CycleResult>,
     sensor_buffer: VecDeque<ProcessedData>,
-    processing_times: Vec<Duration>,
+    /// When frames last arrived, for health checks
+    sensor_feed: SensorFeed,
+    /// Cycle and stage duration histograms
+    latency: CycleLatency,
+    /// Cycle durations over the last minute
+    recent_latency: RollingLatency,
     cycle_count: u32,
-    start_time: Instant,
-    // Optimization: Pre-allocated buffers
-    feature_buffer: Vec<f32>,
-    neural_output_buffer: Vec<f32>,
+    /// Anomalies returned in a `CycleResult` since the last `reset`
+    anomalies_reported: usize,
+    /// Counter readings at the last `reset_metrics`
+    metrics_baseline: MetricsBaseline,
+    /// Timestamps and processing times are read from this
+    clock: Box<dyn Clock>,
+    /// Clock reading when the run started
+    start_time: Duration,
+    /// Drives simulated sensor readings; seeded from the config when set
+    rng: StdRng,
+    /// Recycled feature and network output buffers
+    buffers: MemoryPool<Vec<f32>>,
+    /// Queue external frames are pushed into, once one is attached
+    ingest: Option<IngestQueue>,
+    /// User stages run inside each cycle, in the order added
+    custom_stages: Vec<InstalledStage>,
 }
 
+#[cfg(feature = "std")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessedData {
     pub cycle: u32,
@@ -80,6 +451,7 @@ pub struct ProcessedData {
     pub processing_time_us: u64,
 }
 
+#[cfg(feature = "std")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CycleResult {
     pub cycle: u32,
@@ -87,18 +459,141 @@ pub struct CycleResult {
     pub neural_output: Vec<f32>,
     pub node_id: usize,
     pub anomaly_detected: bool,
+    /// Id to pass to `acknowledge_anomaly`
+    pub anomaly_id: Option<u64>,
+    /// Raw score of the active detector this cycle (z-score scale for `Both`)
+    pub anomaly_score: f32,
+    /// The detected anomaly with severity and attribution, also when suppressed
+    #[serde(default)]
+    pub anomaly: Option<Anomaly>,
+    /// Nodes the new node was connected to, nearest first (at most `RESULT_NEIGHBORS`)
+    #[serde(default)]
+    pub neighbors: Vec<usize>,
+    /// What each sensor contributed to `confidence`
+    #[serde(default)]
+    pub sensor_confidences: SensorConfidences,
+    /// `NeuralNetwork::version` of the network that produced `neural_output`
+    #[serde(default)]
+    pub model_version: u64,
+    /// Set when an anomaly episode starts or escalates outside its cooldown
+    pub anomaly_alert: Option<AnomalyEpisode>,
+    /// Set when the anomaly rate crosses a configured limit
+    pub rate_alert: Option<RateAlert>,
+    /// Set when the feature distributions shift to a new regime
+    pub drift: Option<DriftEvent>,
+    pub loop_closure: Option<LoopClosure>,
     pub prediction: Option<PredictionResult>,
+    /// Forecast of each feature channel (`SystemConfig::features` order), in multivariate mode
+    pub feature_predictions: Option<Vec<PredictionResult>>,
+    /// Behavior mode after this cycle, decided by `SystemConfig::behavior`
+    #[serde(default)]
+    pub mode: BehaviorMode,
+    /// Set when this cycle changed `mode`
+    #[serde(default)]
+    pub mode_transition: Option<ModeTransition>,
+    /// Set while the system calibrates; anomalies are then not reported
+    #[serde(default)]
+    pub calibrating: bool,
     pub processing_us: u64,
+    /// Breakdown of `processing_us` by built-in stage; user stages are timed separately
+    #[serde(default)]
+    pub stages: StageTimings,
+}
+
+/// Time spent in each stage of one cycle, in nanoseconds
+///
+/// In a `pipeline::Pipeline` sensor fusion and inference run on other
+/// threads and are reported as zero.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StageTimings {
+    pub sensor_ns: u64,
+    pub neural_ns: u64,
+    /// Graph insert and loop closure check
+    pub spatial_ns: u64,
+    /// Detection, alerting and drift monitoring
+    pub anomaly_ns: u64,
+    /// Forecasting and the behavior mode decision
+    pub prediction_ns: u64,
+}
+
+/// Distribution of one stage's duration over the run, in nanoseconds
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct StageStats {
+    pub avg_ns: f64,
+    pub p50_ns: u64,
+    pub p95_ns: u64,
+    pub p99_ns: u64,
+    pub max_ns: u64,
+}
+
+/// Per-stage breakdown of the processing times in `SystemMetrics`
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct StageMetrics {
+    pub sensor: StageStats,
+    pub neural: StageStats,
+    pub spatial: StageStats,
+    pub anomaly: StageStats,
+    pub prediction: StageStats,
+}
+
+/// Cycle rate and processing time over a recent window
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct WindowStats {
+    pub cycles: u64,
+    pub rate_hz: f64,
+    pub avg_processing_us: f64,
+    pub p50_processing_us: u64,
+    pub p95_processing_us: u64,
+    pub p99_processing_us: u64,
+    pub max_processing_us: u64,
 }
 
+/// Recent counterparts of the lifetime rate and percentiles in `SystemMetrics`
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct WindowMetrics {
+    pub last_1s: WindowStats,
+    pub last_10s: WindowStats,
+    pub last_60s: WindowStats,
+}
+
+#[cfg(feature = "std")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PredictionResult {
     pub values: Vec<f32>,
+    /// Lower bound of the ~95% prediction interval per step
+    pub lower: Vec<f32>,
+    /// Upper bound of the ~95% prediction interval per step
+    pub upper: Vec<f32>,
+    /// Confidence of the one-step forecast
     pub confidence: f32,
-    pub trend: String,
+    /// Confidence per step, decaying with horizon
+    #[serde(default)]
+    pub confidences: Vec<f32>,
+    pub trend: Trend,
+}
+
+#[cfg(feature = "std")]
+impl From<Prediction> for PredictionResult {
+    fn from(p: Prediction) -> Self {
+        let trend = p.direction();
+        Self {
+            values: p.values,
+            lower: p.lower,
+            upper: p.upper,
+            confidence: p.confidence,
+            confidences: p.confidences,
+            trend,
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemMetrics {
     pub runtime_seconds: f64,
     pub cycles: u32,
@@ -113,10 +608,30 @@ pub struct SystemMetrics {
     pub spatial_nodes: usize,
     pub spatial_edges: usize,
     pub anomalies_detected: usize,
+    pub anomaly_episodes: usize,
+    pub anomaly_rates: AnomalyRates,
+    pub drift_events: usize,
     pub predictions_made: usize,
+    pub loop_closures: usize,
     pub memory_usage_mb: f64,
+    pub graph_memory: GraphMemory,
+    /// Where the processing time goes, e.g. to spot the stage driving p99
+    #[serde(default)]
+    pub stages: StageMetrics,
+    /// The last 1s, 10s and 60s, where regressions show before they move the lifetime figures
+    #[serde(default)]
+    pub windows: WindowMetrics,
+    #[serde(default)]
+    pub buffer_pool: PoolStats,
+    /// Counters of the attached ingestion queue, including frames it dropped
+    #[serde(default)]
+    pub ingest: Option<IngestStats>,
+    /// Time spent in each user stage, which `stages` leaves out
+    #[serde(default)]
+    pub custom_stages: Vec<CustomStageStats>,
 }
 
+#[cfg(feature = "std")]
 impl EnvironmentalAwarenessSystem {
     /// Create a new Environmental Awareness System
     pub fn new() -> Self {
@@ -125,87 +640,549 @@ impl EnvironmentalAwarenessSystem {
     
     /// Create with specific capacity for optimization
     pub fn with_capacity(buffer_capacity: usize, processing_capacity: usize) -> Self {
-        Self {
-            neural_net: Arc::new(NeuralNetwork::new(4, 8, 2)),
-            spatial_graph: SpatialGraph::with_capacity(1000),
-            sensor_processor: SensorProcessor::new(),
-            anomaly_detector: AnomalyDetector::new(20),
-            predictor: Predictor::new(10),
-            sensor_buffer: VecDeque::with_capacity(buffer_capacity),
-            processing_times: Vec::with_capacity(processing_capacity),
+        SystemBuilder::new()
+            .buffers(buffer_capacity.max(1), processing_capacity)
+            .build()
+            .expect("default configuration is valid")
+    }
+    
+    /// Start configuring a system
+    pub fn builder() -> SystemBuilder {
+        SystemBuilder::new()
+    }
+    
+    /// Create a system with every component configured by `config`, after validating it
+    pub fn with_config(config: SystemConfig) -> Result<Self> {
+        config.validate()?;
+        let sensor_processor = SensorProcessor::with_channels(config.features.clone())?;
+        let features = sensor_processor.feature_count();
+        
+        let mut spatial_graph = SpatialGraph::with_capacity(config.graph_capacity);
+        spatial_graph.set_connection_radius(config.connection_radius);
+        let mut predictor = Predictor::with_model(config.forecast_window, config.forecast_model);
+        predictor.set_adaptive_window(config.adaptive_window);
+        let mut rng = config.seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+        let neural_net = NeuralNetwork::with_rng(features, config.hidden_size, config.output_size, &mut rng);
+        // Separate stream so sampling latencies leaves the simulated readings unchanged
+        let latency_rng = config.seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+        let clock = SystemClock::new();
+        
+        Ok(Self {
+            neural_net: Arc::new(neural_net),
+            spatial_graph,
+            sensor_processor,
+            detectors: Detectors::new(
+                AnomalyDetector::with_config(config.anomaly_window, config.anomaly),
+                MahalanobisDetector::new(features),
+            ),
+            episodes: EpisodeTracker::new(config.episodes),
+            anomaly_subscribers: Vec::new(),
+            observers: Vec::new(),
+            live_metrics: None,
+            metrics_interval: 10,
+            anomaly_filter: AnomalyFilter::default(),
+            anomaly_rates: AnomalyRateTracker::new(config.rate_alerts),
+            anomaly_map: AnomalyMap::new(config.anomaly_map_capacity),
+            alert_queue: AlertQueue::new(config.alert_queue),
+            drift_monitor: DriftMonitor::new(config.drift),
+            detection_mode: config.detection_mode,
+            ensemble: config.ensemble.clone(),
+            last_prediction: None,
+            predictor,
+            custom_forecaster: None,
+            feature_predictor: config.multivariate_model
+                .map(|model| MultiPredictor::new(features, config.forecast_window, model)),
+            loop_closure: LoopClosureDetector::with_config(config.loop_closure),
+            behavior: ModeMachine::new(config.behavior),
+            calibration: Calibrator::start(config.calibration_cycles),
+            calibration_report: None,
+            recent_results: VecDeque::with_capacity(config.debug_history),
+            sensor_buffer: VecDeque::with_capacity(config.buffer_capacity),
+            sensor_feed: SensorFeed::default(),
+            latency: CycleLatency::new(config.latency_retention, latency_rng),
+            recent_latency: RollingLatency::default(),
             cycle_count: 0,
-            start_time: Instant::now(),
-            // Pre-allocate buffers
-            feature_buffer: vec![0.0; 4],
-            neural_output_buffer: vec![0.0; 2],
+            anomalies_reported: 0,
+            metrics_baseline: MetricsBaseline::default(),
+            start_time: clock.now(),
+            clock: Box::new(clock),
+            rng,
+            buffers: MemoryPool::new(BUFFER_POOL_CAPACITY),
+            ingest: None,
+            custom_stages: Vec::new(),
+            config,
+        })
+    }
+    
+    /// Configuration the system was built with, updated by `apply_config` (other setters are not reflected)
+    pub fn config(&self) -> &SystemConfig {
+        &self.config
+    }
+    
+    /// Apply the parameters of `config` that are safe to change while running
+    ///
+    /// Anomaly thresholds, detection mode, episode grouping and alert
+    /// cooldowns, rate limits, alert queue bounds, the forecast horizon and the
+    /// connection radius take effect from the next cycle. Differences in any
+    /// other field are reported with `applied: false`; they resize buffers or
+    /// discard learned state, so they need a rebuild. Fields are compared
+    /// against `config()`, so a value changed through a setter is only
+    /// overridden when the new configuration changes it too.
+    pub fn apply_config(&mut self, config: &SystemConfig) -> Result<Vec<ConfigChange>> {
+        config.validate()?;
+        let mut changes = Vec::new();
+        macro_rules! diff {
+            ($($field:ident: $applied:expr),* $(,)?) => {
+                $(if self.config.$field != config.$field {
+                    changes.push(ConfigChange::new(stringify!($field), &self.config.$field, &config.$field, $applied));
+                })*
+            };
+        }
+        diff!(
+            anomaly: true,
+            detection_mode: true,
+            ensemble: true,
+            episodes: true,
+            rate_alerts: true,
+            alert_queue: true,
+            forecast_horizon: true,
+            connection_radius: true,
+            health: true,
+            behavior: true,
+            debug_history: true,
+            buffer_capacity: false,
+            processing_capacity: true,
+            latency_retention: true,
+            features: false,
+            hidden_size: false,
+            output_size: false,
+            graph_capacity: false,
+            anomaly_window: false,
+            anomaly_map_capacity: false,
+            drift: false,
+            forecast_window: false,
+            forecast_model: false,
+            adaptive_window: false,
+            multivariate_model: false,
+            loop_closure: false,
+            calibration_cycles: false,
+            seed: false,
+        );
+        
+        #[cfg(feature = "tracing")]
+        for change in &changes {
+            if change.applied {
+                tracing::info!(field = change.field, old = %change.old, new = %change.new, "config reloaded");
+            } else {
+                tracing::warn!(field = change.field, old = %change.old, new = %change.new, "config change needs a restart");
+            }
+        }
+        
+        // Keep the running values of restart-only fields so they are reported again until rebuilt
+        let old = std::mem::replace(&mut self.config, config.clone());
+        self.config.buffer_capacity = old.buffer_capacity;
+        self.config.features = old.features;
+        self.config.hidden_size = old.hidden_size;
+        self.config.output_size = old.output_size;
+        self.config.graph_capacity = old.graph_capacity;
+        self.config.anomaly_window = old.anomaly_window;
+        self.config.anomaly_map_capacity = old.anomaly_map_capacity;
+        self.config.drift = old.drift;
+        self.config.forecast_window = old.forecast_window;
+        self.config.forecast_model = old.forecast_model;
+        self.config.adaptive_window = old.adaptive_window;
+        self.config.multivariate_model = old.multivariate_model;
+        self.config.loop_closure = old.loop_closure;
+        self.config.calibration_cycles = old.calibration_cycles;
+        self.config.seed = old.seed;
+        
+        if old.anomaly != config.anomaly {
+            self.detectors.z_score.set_config(config.anomaly);
+        }
+        if old.detection_mode != config.detection_mode {
+            self.detection_mode = config.detection_mode;
+        }
+        if old.ensemble != config.ensemble {
+            self.ensemble = config.ensemble.clone();
+        }
+        if old.episodes != config.episodes {
+            self.episodes.set_config(config.episodes);
+        }
+        if old.rate_alerts != config.rate_alerts {
+            self.anomaly_rates.set_config(config.rate_alerts);
+        }
+        if old.alert_queue != config.alert_queue {
+            self.alert_queue.set_config(config.alert_queue);
+        }
+        if old.connection_radius != config.connection_radius {
+            self.spatial_graph.set_connection_radius(config.connection_radius);
         }
+        if old.latency_retention != config.latency_retention {
+            self.latency.set_retention(config.latency_retention);
+        }
+        if config.debug_history < self.recent_results.len() {
+            self.recent_results.drain(..self.recent_results.len() - config.debug_history);
+        }
+        if old.behavior != config.behavior {
+            self.behavior.set_config(config.behavior);
+        }
+        Ok(changes)
+    }
+
+    /// Read time from `clock` from now on; elapsed run time carries over
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        let elapsed = self.elapsed();
+        self.start_time = clock.now().saturating_sub(elapsed);
+        self.clock = clock;
+    }
+    
+    /// Time since the run started, by the system clock
+    fn elapsed(&self) -> Duration {
+        self.clock.now().saturating_sub(self.start_time)
     }
 
     /// Run a single processing cycle (optimized)
     #[inline]
     pub fn run_cycle(&mut self) -> CycleResult {
-        let cycle_start = Instant::now();
-        self.cycle_count += 1;
+        // Simulated readings are always finite
+        let timestamp = self.elapsed().as_secs_f64();
+        let sensor_data = SensorData::generate_with(&mut self.rng, timestamp);
+        self.process_validated(&sensor_data)
+    }
+
+    /// Run a processing cycle on sensor data supplied by the caller, rejecting NaN or infinite readings
+    pub fn process_sensor_data(&mut self, sensor_data: &SensorData) -> Result<CycleResult> {
+        let valid = sensor_data.validate();
+        #[cfg(feature = "tracing")]
+        if let Err(error) = &valid {
+            tracing::warn!(%error, timestamp = sensor_data.timestamp, "sensor frame rejected");
+        }
+        valid?;
+        Ok(self.process_validated(sensor_data))
+    }
 
-        // Generate sensor data
-        let sensor_data = SensorData::generate();
+    fn process_validated(&mut self, sensor_data: &SensorData) -> CycleResult {
+        let cycle_start = self.clock.now();
+        let _cycle_span = stage_span!("cycle", cycle = self.cycle_count + 1);
+        let mut stages = StageTimings::default();
+        let mut mark = cycle_start;
+        let mut ctx = CycleContext {
+            cycle: self.cycle_count + 1,
+            frame: Some(Cow::Borrowed(sensor_data)),
+            ..CycleContext::default()
+        };
 
         // Process sensors (reuse buffers)
-        let processed = self.sensor_processor.process_with_buffer(
-            &sensor_data, 
-            &mut self.feature_buffer
-        );
+        self.run_stages(StagePosition::Before(BuiltinStage::Sensor), &mut ctx, &mut mark);
+        let processed = {
+            let _span = stage_span!("sensor");
+            let frame = ctx.frame.as_deref().unwrap_or(sensor_data);
+            self.sensor_processor.process_with_buffer(frame, self.buffers.get())
+        };
+        ctx.features = processed.features;
+        ctx.fused_confidence = processed.fused_confidence;
+        stages.sensor_ns = self.lap(&mut mark);
+        self.run_stages(StagePosition::After(BuiltinStage::Sensor), &mut ctx, &mut mark);
 
         // Neural network inference (optimized)
-        self.neural_net.forward_with_buffer(
-            &processed.features,
-            &mut self.neural_output_buffer
-        );
+        self.run_stages(StagePosition::Before(BuiltinStage::Neural), &mut ctx, &mut mark);
+        ctx.neural_output = self.buffers.get();
+        {
+            let _span = stage_span!("neural");
+            self.neural_net.forward_with_buffer(&ctx.features, &mut ctx.neural_output);
+        }
+        stages.neural_ns = self.lap(&mut mark);
+        self.run_stages(StagePosition::After(BuiltinStage::Neural), &mut ctx, &mut mark);
+
+        self.integrate(cycle_start, mark, ctx, stages)
+    }
+    
+    /// Nanoseconds since `mark`, moving `mark` to now
+    #[inline]
+    fn lap(&self, mark: &mut Duration) -> u64 {
+        let now = self.clock.now();
+        let elapsed = now.saturating_sub(*mark).as_nanos() as u64;
+        *mark = now;
+        elapsed
+    }
+
+    /// Run the user stages placed at `position`, timing each from `mark` and moving it past them
+    fn run_stages(&mut self, position: StagePosition, ctx: &mut CycleContext<'_>, mark: &mut Duration) {
+        for installed in self.custom_stages.iter_mut().filter(|installed| installed.position == position) {
+            {
+                let _span = stage_span!("custom", stage = installed.stage.name());
+                installed.stage.process(ctx);
+            }
+            let now = self.clock.now();
+            installed.latency.record(now.saturating_sub(*mark).as_nanos() as u64);
+            *mark = now;
+        }
+    }
+
+    /// Finish a cycle whose sensor and inference stages ran elsewhere (see `pipeline`)
+    fn process_inferred(&mut self, processed: ProcessedSensorData, neural_output: Vec<f32>) -> CycleResult {
+        let cycle_start = self.clock.now();
+        let _cycle_span = stage_span!("cycle", cycle = self.cycle_count + 1);
+        let mut mark = cycle_start;
+        let mut ctx = CycleContext {
+            cycle: self.cycle_count + 1,
+            features: processed.features,
+            fused_confidence: processed.fused_confidence,
+            neural_output,
+            ..CycleContext::default()
+        };
+        if !self.custom_stages.is_empty() {
+            for stage in [BuiltinStage::Sensor, BuiltinStage::Neural] {
+                self.run_stages(StagePosition::Before(stage), &mut ctx, &mut mark);
+                self.run_stages(StagePosition::After(stage), &mut ctx, &mut mark);
+            }
+        }
+        self.integrate(cycle_start, mark, ctx, StageTimings::default())
+    }
+
+    /// Spatial, anomaly and prediction stages, given features and the network
+    /// output; both buffers end up in the sensor buffer
+    ///
+    /// `mark` is where the earlier stages stopped, so the stages add up to the total.
+    fn integrate(
+        &mut self,
+        cycle_start: Duration,
+        mut mark: Duration,
+        mut ctx: CycleContext<'_>,
+        mut stages: StageTimings,
+    ) -> CycleResult {
+        self.cycle_count += 1;
+        self.sensor_feed.observe(cycle_start.saturating_sub(self.start_time));
 
         // Update spatial map
-        let node_id = self.spatial_graph.add_node(&processed.features);
+        self.run_stages(StagePosition::Before(BuiltinStage::Spatial), &mut ctx, &mut mark);
+        let (node_id, neighbors, loop_closure) = {
+            let _span = stage_span!("spatial");
+            let node_id = self.spatial_graph.add_node(&ctx.features);
+            let mut nearest = self.spatial_graph.neighbors(node_id).to_vec();
+            // Only the closest few are reported; order just those
+            if nearest.len() > RESULT_NEIGHBORS {
+                nearest.select_nth_unstable_by(RESULT_NEIGHBORS, |a, b| a.1.total_cmp(&b.1));
+                nearest.truncate(RESULT_NEIGHBORS);
+            }
+            nearest.sort_unstable_by(|a, b| a.1.total_cmp(&b.1));
+            let neighbors = nearest.into_iter().map(|(id, _)| id).collect();
+            (node_id, neighbors, self.loop_closure.check(&self.spatial_graph, node_id))
+        };
+        #[cfg(feature = "tracing")]
+        if let Some(closure) = &loop_closure {
+            tracing::debug!(node_id, matched_id = closure.matched_id, "loop closure");
+        }
+        ctx.node_id = Some(node_id);
+        stages.spatial_ns = self.lap(&mut mark);
+        self.run_stages(StagePosition::After(BuiltinStage::Spatial), &mut ctx, &mut mark);
 
         // Detect anomalies
-        let anomaly = self.anomaly_detector.detect(
-            processed.fused_confidence,
-            self.start_time.elapsed().as_secs_f64(),
-        );
+        self.run_stages(StagePosition::Before(BuiltinStage::Anomaly), &mut ctx, &mut mark);
+        let anomaly_span = stage_span!("anomaly");
+        let timestamp = self.elapsed().as_secs_f64();
+        let (mut anomaly, anomaly_score) = self.detect_anomalies(&ctx.features, ctx.fused_confidence, timestamp);
+        let calibrating = self.calibration.is_some();
+        if let Some(calibration) = &mut self.calibration {
+            calibration.observe(&ctx.features, ctx.fused_confidence, anomaly.take().is_some());
+        }
+        if let Some(a) = &mut anomaly {
+            self.anomaly_filter.assign_id(a);
+            a.label_features(self.sensor_processor.channels());
+            #[cfg(feature = "tracing")]
+            tracing::info!(id = a.id, severity = ?a.severity, z_score = a.z_score, detectors = ?a.detectors, "anomaly detected");
+            a.explanation.node_id = Some(node_id);
+            a.explanation.position = self.spatial_graph.node(node_id).map(|node| node.position);
+            a.explanation.predicted = self.last_prediction.map(|(value, _)| value);
+            a.explanation.prediction_confidence = self.last_prediction.map(|(_, confidence)| confidence);
+        }
+        // Stages after detection see the anomaly before it is filtered and alerted on
+        stages.anomaly_ns = self.lap(&mut mark);
+        ctx.anomaly = anomaly;
+        self.run_stages(StagePosition::After(BuiltinStage::Anomaly), &mut ctx, &mut mark);
+        let anomaly = ctx.anomaly.take();
+        self.anomalies_reported += anomaly.is_some() as usize;
+        let mut suppressed = false;
+        if let Some(a) = &anomaly {
+            if let Some(position) = a.explanation.position {
+                self.anomaly_map.record(a, node_id, position);
+            }
+            suppressed = self.anomaly_filter.is_suppressed(a, timestamp);
+            if !suppressed {
+                anomaly::notify(&mut self.anomaly_subscribers, a);
+                self.alert_queue.push(a.clone());
+            }
+        }
+        self.alert_queue.dispatch();
+        let anomaly_alert = self.episodes.observe(anomaly.as_ref(), timestamp).filter(|_| !suppressed);
+        if let Some(a) = &anomaly {
+            self.anomaly_rates.record(timestamp, a.severity);
+        }
+        let rate_alert = self.anomaly_rates.check(timestamp);
+        #[cfg(feature = "tracing")]
+        if let Some(alert) = &rate_alert {
+            tracing::warn!(last_minute = alert.rates.last_minute, last_hour = alert.rates.last_hour, "anomaly rate limit exceeded");
+        }
+        let mut drift = self.drift_monitor.observe(&ctx.features, timestamp);
+        if let Some(event) = &mut drift {
+            event.label_features(self.sensor_processor.channels());
+            #[cfg(feature = "tracing")]
+            tracing::warn!(test = ?event.test, features = event.features.len(), "feature drift detected");
+        }
+        anomaly_span.exit();
+        stages.anomaly_ns += self.lap(&mut mark);
 
         // Make predictions
-        self.predictor.add_observation(processed.fused_confidence);
-        let prediction = self.predictor.predict(5);
+        self.run_stages(StagePosition::Before(BuiltinStage::Prediction), &mut ctx, &mut mark);
+        let predictor_span = stage_span!("predictor");
+        let horizon = self.config.forecast_horizon;
+        let forecaster = self.forecaster_mut();
+        forecaster.add_observation_at(timestamp, ctx.fused_confidence);
+        let prediction = forecaster.predict(horizon);
+        let feature_predictions = self.feature_predictor.as_mut().and_then(|fp| {
+            fp.add_observation_at(timestamp, &ctx.features);
+            fp.predict(horizon)
+        });
+        // Stages after the forecast can adjust it before it informs the next cycle's explanation
+        stages.prediction_ns = self.lap(&mut mark);
+        ctx.prediction = prediction;
+        self.run_stages(StagePosition::After(BuiltinStage::Prediction), &mut ctx, &mut mark);
+        let prediction = ctx.prediction.take();
+        self.last_prediction = prediction.as_ref()
+            .and_then(|p| p.values.first().map(|&value| (value, p.confidence)));
+        predictor_span.exit();
+        let mode_transition = self.behavior.update(
+            ctx.fused_confidence,
+            anomaly.as_ref().map(|a| a.severity),
+            prediction.as_ref().map(Prediction::direction),
+        );
+        #[cfg(feature = "tracing")]
+        if let Some(transition) = &mode_transition {
+            tracing::info!(from = %transition.from, to = %transition.to, trigger = ?transition.trigger, "behavior mode changed");
+        }
+        stages.prediction_ns += self.lap(&mut mark);
 
         // Store processing time
-        let processing_time = cycle_start.elapsed();
-        self.processing_times.push(processing_time);
+        let processing_time = mark.saturating_sub(cycle_start);
+        self.latency.record(processing_time.as_nanos() as u64, &stages);
+        self.recent_latency.record(mark.saturating_sub(self.start_time), processing_time.as_nanos() as u64);
+        #[cfg(feature = "tracing")]
+        tracing::debug!(processing_us = processing_time.as_micros() as u64, node_id, "cycle complete");
 
-        // Store in buffer (with capacity check)
+        // Store in buffer (with capacity check), recycling the evicted cycle's buffers
         if self.sensor_buffer.len() >= self.sensor_buffer.capacity() {
-            self.sensor_buffer.pop_front();
+            if let Some(evicted) = self.sensor_buffer.pop_front() {
+                self.recycle_buffers(evicted);
+            }
         }
         
+        let mut result_output = self.buffers.get();
+        result_output.extend_from_slice(&ctx.neural_output);
+        let sensor_confidences = self.sensor_processor.confidences(&ctx.features);
         let processed_data = ProcessedData {
             cycle: self.cycle_count,
-            features: processed.features.clone(),
-            neural_output: self.neural_output_buffer.clone(),
-            fused_confidence: processed.fused_confidence,
+            features: ctx.features,
+            neural_output: ctx.neural_output,
+            fused_confidence: ctx.fused_confidence,
             processing_time_us: processing_time.as_micros() as u64,
         };
         self.sensor_buffer.push_back(processed_data);
 
-        CycleResult {
+        let result = CycleResult {
             cycle: self.cycle_count,
-            confidence: processed.fused_confidence,
-            neural_output: self.neural_output_buffer.clone(),
+            confidence: ctx.fused_confidence,
+            neural_output: result_output,
             node_id,
             anomaly_detected: anomaly.is_some(),
-            prediction: prediction.map(|p| PredictionResult {
-                values: p.values,
-                confidence: p.confidence,
-                trend: if p.trend > 0.0 { "increasing".to_string() } else { "decreasing".to_string() },
-            }),
+            anomaly_id: anomaly.as_ref().map(|a| a.id),
+            anomaly_score,
+            anomaly,
+            neighbors,
+            sensor_confidences,
+            model_version: self.neural_net.version(),
+            anomaly_alert,
+            rate_alert,
+            drift,
+            loop_closure,
+            prediction: prediction.map(PredictionResult::from),
+            feature_predictions: feature_predictions
+                .map(|forecasts| forecasts.into_iter().map(PredictionResult::from).collect()),
+            mode: self.behavior.mode(),
+            mode_transition,
+            calibrating,
             processing_us: processing_time.as_micros() as u64,
+            stages,
+        };
+        
+        if let Some(live) = &self.live_metrics {
+            live.record(&result, self.metrics_cycles());
+            if live.due(result.cycle) {
+                live.publish(self.get_metrics());
+            }
+        }
+        
+        if !self.observers.is_empty() {
+            let mut observers = std::mem::take(&mut self.observers);
+            let reported = result.anomaly.as_ref().filter(|_| !suppressed);
+            observer::notify(&mut observers, &result, reported, || self.get_metrics());
+            self.observers = observers;
+        }
+        if self.config.debug_history > 0 {
+            if self.recent_results.len() >= self.config.debug_history {
+                self.recent_results.pop_front();
+            }
+            self.recent_results.push_back(result.clone());
+        }
+        if self.calibration.as_ref().is_some_and(Calibrator::is_done) {
+            self.finish_calibration();
+        }
+        result
+    }
+
+    /// Switch to detection, keeping what calibration learned and counting metrics from here
+    fn finish_calibration(&mut self) {
+        if let Some(calibration) = self.calibration.take() {
+            let report = calibration.report(self.sensor_processor.channels());
+            #[cfg(feature = "tracing")]
+            tracing::info!(cycles = report.cycles, suppressed = report.suppressed, "calibration complete");
+            self.calibration_report = Some(report);
+            self.reset_metrics();
+        }
+    }
+
+    /// Run the active detectors and combine their verdicts.
+    ///
+    /// Returns the most severe firing anomaly, tagged with every detector that
+    /// fired, when the combination rule carries; and the highest raw score.
+    fn detect_anomalies(&mut self, features: &[f32], value: f32, timestamp: f64) -> (Option<Anomaly>, f32) {
+        let single = EnsembleConfig {
+            members: self.detection_mode.members().iter().map(|&kind| (kind, 1.0)).collect(),
+            rule: CombinationRule::Any,
+        };
+        let ensemble = if self.detection_mode == DetectionMode::Ensemble { &self.ensemble } else { &single };
+        
+        let mut fired: Vec<(DetectorKind, Anomaly)> = Vec::new();
+        let mut score = f32::NEG_INFINITY;
+        for &(kind, _) in &ensemble.members {
+            let Some(detector) = self.detectors.get_mut(kind) else {
+                continue;
+            };
+            let found = detector.observe_cycle(value, features, timestamp);
+            score = score.max(detector.last_score());
+            fired.extend(found.map(|a| (kind, a)));
         }
+        
+        let kinds: Vec<DetectorKind> = fired.iter().map(|(kind, _)| *kind).collect();
+        let anomaly = if ensemble.combine(&kinds) {
+            fired.into_iter()
+                .map(|(_, a)| a)
+                .max_by(|a, b| a.severity.cmp(&b.severity).then(a.z_score.total_cmp(&b.z_score)))
+                .map(|a| Anomaly { detectors: kinds, ..a })
+        } else {
+            None
+        };
+        
+        (anomaly, if score.is_finite() { score } else { 0.0 })
     }
 
     /// Run multiple cycles with batch optimization
@@ -218,158 +1195,1070 @@ impl EnvironmentalAwarenessSystem {
             .collect()
     }
     
+    /// Run sensor fusion, inference and integration on separate threads; see `pipeline::Pipeline`
+    pub fn into_pipeline(self, capacity: usize) -> Result<pipeline::Pipeline> {
+        pipeline::Pipeline::spawn(self, capacity)
+    }
+    
+    /// Stream cycles on generated sensor data, one per `period`; must be polled inside a tokio runtime
+    #[cfg(feature = "tokio")]
+    pub fn run_stream(&mut self, period: Duration) -> stream::CycleStream<'_> {
+        stream::CycleStream::new(self, period)
+    }
+    
+    /// Stream one cycle per reading from an async source such as `stream::sensor_channel`;
+    /// invalid readings yield an error and leave the system untouched
+    #[cfg(feature = "tokio")]
+    pub fn process_stream<S>(&mut self, input: S) -> stream::ProcessStream<'_, S>
+    where
+        S: tokio_stream::Stream<Item = SensorData> + Unpin,
+    {
+        stream::ProcessStream::new(self, input)
+    }
+
     /// Run cycles sequentially (optimized)
     pub fn run_cycles(&mut self, count: usize) -> Vec<CycleResult> {
-        let mut results = Vec::with_capacity(count);
-        for _ in 0..count {
-            results.push(self.run_cycle());
+        self.cycles().take(count).collect()
+    }
+    
+    /// Attach a queue of at most `capacity` frames for sensors to push into
+    /// from any thread, replacing and closing an earlier one
+    ///
+    /// `policy` decides what a push into a full queue does; the queue's
+    /// counters appear in `SystemMetrics::ingest`.
+    pub fn ingest_queue(&mut self, capacity: usize, policy: OverflowPolicy) -> IngestQueue {
+        let queue = IngestQueue::new(capacity, policy);
+        if let Some(old) = self.ingest.replace(queue.clone()) {
+            old.close();
+        }
+        queue
+    }
+
+    /// Process the oldest queued frame, waiting up to `timeout` for one
+    ///
+    /// `None` if no queue is attached or no frame arrived in time.
+    pub fn process_queued(&mut self, timeout: Duration) -> Option<Result<CycleResult>> {
+        let frame = self.ingest.as_ref()?.pop(timeout)?;
+        Some(self.process_sensor_data(&frame))
+    }
+
+    /// Run simulated cycles `rate_hz` times a second until `stop` fires
+    ///
+    /// Results reach observers, sinks and subscribers as usual and are then
+    /// recycled. See `schedule` for how drift and overruns are handled.
+    pub fn run_at_hz(&mut self, rate_hz: f64, stop: &StopSignal) -> Result<ScheduleReport> {
+        self.run_at_hz_with(rate_hz, stop, |system, result| system.recycle(result))
+    }
+
+    /// `run_at_hz`, handing every result to `on_cycle`, which may also stop the run
+    pub fn run_at_hz_with<F>(&mut self, rate_hz: f64, stop: &StopSignal, mut on_cycle: F) -> Result<ScheduleReport>
+    where
+        F: FnMut(&mut Self, CycleResult),
+    {
+        let mut scheduler = Scheduler::new(rate_hz)?;
+        while scheduler.wait(stop) {
+            let result = self.run_cycle();
+            on_cycle(self, result);
+            scheduler.finish_cycle();
         }
-        results
+        Ok(scheduler.report())
+    }
+
+    /// Endless iterator running one cycle per `next`; bound it with `take`, `take_while` and the like
+    pub fn cycles(&mut self) -> Cycles<'_> {
+        Cycles { system: self }
+    }
+    
+    /// Hand a result that is no longer needed back, so a later cycle reuses
+    /// its buffer instead of allocating one
+    pub fn recycle(&mut self, mut result: CycleResult) {
+        result.neural_output.clear();
+        self.buffers.return_to_pool(result.neural_output);
+    }
+    
+    fn recycle_buffers(&mut self, mut processed: ProcessedData) {
+        processed.features.clear();
+        processed.neural_output.clear();
+        self.buffers.return_to_pool(processed.features);
+        self.buffers.return_to_pool(processed.neural_output);
+    }
+    
+    /// Reuse of the feature and output buffers since the last reset
+    pub fn pool_stats(&self) -> PoolStats {
+        self.buffers.stats()
     }
 
     /// Get system metrics with percentiles
     pub fn get_metrics(&self) -> SystemMetrics {
-        let runtime = self.start_time.elapsed().as_secs_f64();
-        
-        let mut processing_times_us: Vec<u64> = self.processing_times
-            .iter()
-            .map(|d| d.as_micros() as u64)
-            .collect();
-        
-        processing_times_us.sort_unstable();
+        let elapsed = self.elapsed();
+        let baseline = &self.metrics_baseline;
+        let runtime = elapsed.saturating_sub(baseline.runtime).as_secs_f64();
         
-        let len = processing_times_us.len();
-        let avg_processing = if len > 0 {
-            processing_times_us.iter().sum::<u64>() as f64 / len as f64
-        } else {
-            0.0
-        };
-
-        let min_processing = processing_times_us.first().copied().unwrap_or(0);
-        let max_processing = processing_times_us.last().copied().unwrap_or(0);
-        
-        // Calculate percentiles
-        let p50 = if len > 0 { processing_times_us[len / 2] } else { 0 };
-        let p95 = if len > 0 { processing_times_us[len * 95 / 100] } else { 0 };
-        let p99 = if len > 0 { processing_times_us[len * 99 / 100] } else { 0 };
+        // Latencies are kept in nanoseconds; the headline figures are microseconds
+        let total = self.latency.total();
+        let avg_processing = total.mean / 1000.0;
+        let micros = |nanos: u64| nanos / 1000;
         
         // Estimate memory usage
         let memory_usage_mb = Self::estimate_memory_usage(self) / 1_048_576.0;
 
         SystemMetrics {
             runtime_seconds: runtime,
-            cycles: self.cycle_count,
-            processing_rate_hz: self.cycle_count as f64 / runtime,
+            cycles: self.metrics_cycles(),
+            processing_rate_hz: if runtime > 0.0 { self.metrics_cycles() as f64 / runtime } else { 0.0 },
             avg_processing_us: avg_processing,
-            min_processing_us: min_processing,
-            max_processing_us: max_processing,
-            p50_processing_us: p50,
-            p95_processing_us: p95,
-            p99_processing_us: p99,
+            min_processing_us: micros(total.min),
+            max_processing_us: micros(total.max),
+            p50_processing_us: micros(total.p50),
+            p95_processing_us: micros(total.p95),
+            p99_processing_us: micros(total.p99),
             theoretical_max_hz: if avg_processing > 0.0 { 1_000_000.0 / avg_processing } else { 0.0 },
             spatial_nodes: self.spatial_graph.node_count(),
             spatial_edges: self.spatial_graph.edge_count(),
-            anomalies_detected: self.anomaly_detector.anomaly_count(),
-            predictions_made: self.predictor.prediction_count(),
+            anomalies_detected: self.anomalies_reported.saturating_sub(baseline.anomalies),
+            anomaly_episodes: self.episodes.episode_count().saturating_sub(baseline.episodes),
+            anomaly_rates: self.anomaly_rates.rates(elapsed.as_secs_f64()),
+            drift_events: self.drift_monitor.event_count().saturating_sub(baseline.drift_events),
+            predictions_made: self.forecaster().prediction_count().saturating_sub(baseline.predictions),
+            loop_closures: self.loop_closure.closure_count().saturating_sub(baseline.loop_closures),
             memory_usage_mb,
+            graph_memory: self.spatial_graph.memory_breakdown(),
+            stages: self.latency.stages(),
+            windows: self.recent_latency.metrics(elapsed),
+            buffer_pool: self.pool_stats(),
+            ingest: self.ingest.as_ref().map(IngestQueue::stats),
+            custom_stages: self.custom_stages.iter().map(InstalledStage::stats).collect(),
         }
     }
-    
-    /// Estimate memory usage in bytes
-    fn estimate_memory_usage(&self) -> f64 {
-        let base = std::mem::size_of::<Self>();
-        let buffer = self.sensor_buffer.len() * std::mem::size_of::<ProcessedData>();
-        let times = self.processing_times.len() * std::mem::size_of::<Duration>();
-        let graph = self.spatial_graph.estimate_memory();
-        
-        (base + buffer + times + graph) as f64
-    }
 
-    /// Reset the system
-    pub fn reset(&mut self) {
-        self.cycle_count = 0;
-        self.sensor_buffer.clear();
-        self.processing_times.clear();
-        self.start_time = Instant::now();
-        self.spatial_graph = SpatialGraph::with_capacity(1000);
-        self.anomaly_detector = AnomalyDetector::new(20);
-        self.predictor = Predictor::new(10);
+    /// Cycles completed since the last `reset_metrics`
+    fn metrics_cycles(&self) -> u32 {
+        self.cycle_count.saturating_sub(self.metrics_baseline.cycles)
     }
-    
-    /// Warm up the system (for benchmarking)
-    pub fn warmup(&mut self, cycles: usize) {
-        for _ in 0..cycles {
-            self.run_cycle();
+
+    /// Size, memory and connectivity of the spatial graph
+    pub fn graph_summary(&self) -> GraphSummary {
+        GraphSummary {
+            nodes: self.spatial_graph.node_count(),
+            edges: self.spatial_graph.edge_count(),
+            memory: self.spatial_graph.memory_breakdown(),
+            stats: self.graph_stats(BETWEENNESS_SAMPLES),
         }
-        self.reset();
     }
-}
 
-impl Default for EnvironmentalAwarenessSystem {
-    fn default() -> Self {
-        Self::new()
+    /// Degree, component and betweenness analysis of the spatial graph
+    ///
+    /// Walks the whole graph, with a breadth-first search from each of
+    /// `samples` source nodes for betweenness, so unlike `get_metrics` it
+    /// is meant to be called on demand rather than every cycle.
+    pub fn graph_stats(&self, samples: usize) -> GraphStats {
+        self.spatial_graph.stats(samples)
     }
-}
 
-// ============= Comprehensive Tests =============
+    /// Capture buffers, detector and forecaster internals, the graph and
+    /// recent results for a bug report; see `debug`
+    pub fn debug_dump(&self) -> Result<DebugDump> {
+        let buffers = BufferOccupancy {
+            sensor_buffer: Occupancy::new(self.sensor_buffer.len(), self.config.buffer_capacity),
+            spatial_graph: Occupancy::new(self.spatial_graph.node_count(), self.config.graph_capacity),
+            anomaly_map: Occupancy::new(self.anomaly_map.len(), self.config.anomaly_map_capacity),
+            alert_queue: Occupancy::new(self.alert_queue.len(), self.config.alert_queue.capacity * 3),
+            alerts_dropped: self.alert_queue.dropped(),
+            buffer_pool: self.pool_stats(),
+            ingest: self.ingest.as_ref().map(IngestQueue::stats),
+            recent_results: Occupancy::new(self.recent_results.len(), self.config.debug_history),
+        };
+        let forecast_fit = ForecastFit {
+            model: self.predictor.model(),
+            window_size: self.predictor.window_size(),
+            line: self.predictor.fit_line::<f32>().map(|fit| (fit.slope, fit.intercept, fit.r_squared)),
+            arima: self.predictor.arima_coefficients().map(<[f32]>::to_vec),
+        };
+        Ok(DebugDump {
+            cycle: self.cycle_count,
+            runtime_seconds: self.elapsed().as_secs_f64(),
+            calibrating: self.is_calibrating(),
+            config: self.config.clone(),
+            buffers,
+            graph: self.graph_summary(),
+            detectors: serde_json::to_value(self.anomaly_state())?,
+            forecasters: serde_json::to_value(self.forecast_state())?,
+            forecast_fit,
+            custom_detector: self.detectors.custom.as_ref().map(|d| format!("{d:?}")),
+            custom_forecaster: self.custom_forecaster.as_ref().map(|f| format!("{f:?}")),
+            custom_stages: self.custom_stages.iter().map(|s| format!("{:?}", s.stage)).collect(),
+            recent_results: self.recent_results.iter().cloned().collect(),
+        })
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Status of the sensor feed, the active windowed detectors, the
+    /// forecaster and the spatial graph, judged by `config().health`
+    pub fn health(&self) -> HealthReport {
+        let context = HealthContext { now: self.elapsed(), config: &self.config };
+        let mut report = HealthReport::default();
+        report.push("sensors", self.sensor_feed.health(&context));
+
+        let active = if self.detection_mode == DetectionMode::Ensemble {
+            self.ensemble.members.iter().map(|&(kind, _)| kind).collect()
+        } else {
+            self.detection_mode.members().to_vec()
+        };
+        if active.contains(&DetectorKind::ZScore) {
+            report.push("anomaly_detector", self.detectors.z_score.health(&context));
+        }
+        if active.contains(&DetectorKind::Mahalanobis) {
+            report.push("feature_detector", self.detectors.mahalanobis.health(&context));
+        }
+
+        let forecaster = match &self.custom_forecaster {
+            // Custom forecasters are judged by their last forecast
+            Some(_) if self.last_prediction.is_none() => Health::degraded("no forecast yet"),
+            Some(_) => Health::healthy(),
+            None => self.predictor.health(&context),
+        };
+        report.push("predictor", forecaster);
+        report.push("spatial_graph", self.spatial_graph.health(&context));
+        report
+    }
     
-    #[test]
-    fn test_system_creation() {
-        let system = EnvironmentalAwarenessSystem::new();
-        assert_eq!(system.cycle_count, 0);
+    /// Choose which anomaly detectors run each cycle
+    pub fn set_detection_mode(&mut self, mode: DetectionMode) {
+        self.detection_mode = mode;
     }
     
-    #[test]
-    fn test_single_cycle() {
-        let mut system = EnvironmentalAwarenessSystem::new();
-        let result = system.run_cycle();
-        assert_eq!(result.cycle, 1);
-        assert!(result.processing_us > 0);
-        assert!(result.confidence >= 0.0 && result.confidence <= 1.0);
+    /// Run several detectors together and combine their votes (switches to `DetectionMode::Ensemble`)
+    pub fn set_ensemble(&mut self, config: EnsembleConfig) {
+        self.ensemble = config;
+        self.detection_mode = DetectionMode::Ensemble;
     }
     
-    #[test]
-    fn test_multiple_cycles() {
-        let mut system = EnvironmentalAwarenessSystem::new();
-        let results = system.run_cycles(10);
-        assert_eq!(results.len(), 10);
-        assert_eq!(results.last().unwrap().cycle, 10);
+    /// Install a custom detector and run it alone (switches to `DetectionMode::Custom`);
+    /// it can also join an ensemble as `DetectorKind::Custom`
+    pub fn set_detector(&mut self, detector: Box<dyn AnomalyDetection>) -> Option<Box<dyn AnomalyDetection>> {
+        self.detection_mode = DetectionMode::Custom;
+        self.detectors.custom.replace(detector)
     }
     
-    #[test]
-    fn test_metrics() {
-        let mut system = EnvironmentalAwarenessSystem::new();
-        system.run_cycles(100);
-        
-        let metrics = system.get_metrics();
-        assert_eq!(metrics.cycles, 100);
-        assert!(metrics.avg_processing_us > 0.0);
-        assert!(metrics.p50_processing_us > 0);
-        assert!(metrics.p95_processing_us >= metrics.p50_processing_us);
-        assert!(metrics.p99_processing_us >= metrics.p95_processing_us);
-        assert!(metrics.spatial_nodes == 100);
+    /// Remove the custom detector, falling back to the default mode if it was the active one
+    pub fn take_detector(&mut self) -> Option<Box<dyn AnomalyDetection>> {
+        if self.detection_mode == DetectionMode::Custom {
+            self.detection_mode = DetectionMode::default();
+        }
+        self.detectors.custom.take()
     }
     
-    #[test]
-    fn test_reset() {
-        let mut system = EnvironmentalAwarenessSystem::new();
-        system.run_cycles(10);
-        assert_eq!(system.cycle_count, 10);
+    /// The installed custom detector
+    pub fn detector(&self) -> Option<&dyn AnomalyDetection> {
+        self.detectors.custom.as_deref()
+    }
+    
+    /// Currently selected anomaly detectors
+    pub fn detection_mode(&self) -> DetectionMode {
+        self.detection_mode
+    }
+    
+    /// Behavior mode decided by the last cycle
+    pub fn behavior_mode(&self) -> BehaviorMode {
+        self.behavior.mode()
+    }
+    
+    /// Adjust z-score thresholds and severity bands; returns false if the config is invalid
+    pub fn set_anomaly_config(&mut self, config: AnomalyConfig) -> bool {
+        self.detectors.z_score.set_config(config)
+    }
+    
+    /// Current z-score thresholds and severity bands
+    pub fn anomaly_config(&self) -> AnomalyConfig {
+        self.detectors.z_score.config()
+    }
+    
+    /// Receive the full `Anomaly` from whichever detector fires, the cycle it is detected
+    pub fn subscribe_anomalies(&mut self) -> Receiver<Anomaly> {
+        let (tx, rx) = mpsc::channel();
+        self.anomaly_subscribers.push(tx);
+        rx
+    }
+    
+    /// Handle for reading progress and metrics from other threads without locking the system
+    pub fn metrics_handle(&mut self) -> MetricsHandle {
+        let live = match &self.live_metrics {
+            Some(live) => Arc::clone(live),
+            None => {
+                let live = Arc::new(LiveMetrics::new(self.get_metrics(), self.metrics_interval));
+                self.live_metrics = Some(Arc::clone(&live));
+                live
+            }
+        };
+        MetricsHandle::new(live)
+    }
+    
+    /// Publish full metrics to handles every `cycles` cycles (default 10)
+    pub fn set_metrics_interval(&mut self, cycles: u32) {
+        self.metrics_interval = cycles.max(1);
+        if let Some(live) = &self.live_metrics {
+            live.set_interval(self.metrics_interval);
+        }
+    }
+    
+    /// Cycles between metrics publications to handles
+    pub fn metrics_interval(&self) -> u32 {
+        self.metrics_interval
+    }
+    
+    /// Call an observer's hooks from every cycle from now on
+    pub fn add_observer(&mut self, observer: Box<dyn SystemObserver>) {
+        self.observers.push(observer);
+    }
+    
+    /// Number of registered observers
+    pub fn observer_count(&self) -> usize {
+        self.observers.len()
+    }
+    
+    /// Run `stage` at `position` in every cycle from now on, after any stage already there
+    pub fn add_stage(&mut self, position: StagePosition, stage: Box<dyn Stage>) {
+        self.custom_stages.push(InstalledStage::new(position, stage));
+    }
+    
+    /// Number of user stages added
+    pub fn stage_count(&self) -> usize {
+        self.custom_stages.len()
+    }
+    
+    /// Log every cycle's result to `sink` from now on; flushed by `shutdown`
+    ///
+    /// The sink runs as an observer and is skipped from its first failed write
+    /// on; call `ResultSink::write` directly to handle errors instead.
+    pub fn add_result_sink(&mut self, sink: Box<dyn sink::ResultSink>) {
+        self.add_observer(Box::new(sink::SinkObserver::new(sink)));
+    }
+    
+    /// Deliver alerts for unsuppressed anomalies, most severe first, a bounded batch per cycle
+    pub fn add_alert_sink(&mut self, sink: Box<dyn AlertSink>) {
+        self.alert_queue.add_sink(sink);
+    }
+    
+    /// Bound the alert queues and per-cycle dispatch batch
+    pub fn set_alert_queue_config(&mut self, config: AlertQueueConfig) {
+        self.alert_queue.set_config(config);
+    }
+    
+    /// Pending and delivered alert counts
+    pub fn alert_queue(&self) -> &AlertQueue {
+        &self.alert_queue
+    }
+    
+    /// Alert when the anomaly rate itself exceeds these limits
+    pub fn set_rate_alerts(&mut self, config: RateAlertConfig) {
+        self.anomaly_rates.set_config(config);
+    }
+    
+    /// Mark an anomaly as handled by an operator
+    pub fn acknowledge_anomaly(&mut self, id: u64) -> bool {
+        self.anomaly_filter.acknowledge(id)
+    }
+    
+    /// Check whether an anomaly was acknowledged
+    pub fn is_anomaly_acknowledged(&self, id: u64) -> bool {
+        self.anomaly_filter.is_acknowledged(id)
+    }
+    
+    /// Mute alerts for matching anomalies for a while; detection keeps running
+    pub fn suppress_anomalies(&mut self, matcher: AnomalyMatcher, duration: Duration) -> u64 {
+        let now = self.elapsed().as_secs_f64();
+        self.anomaly_filter.suppress(matcher, duration.as_secs_f64(), now)
+    }
+    
+    /// Remove a suppression before it expires
+    pub fn lift_suppression(&mut self, suppression_id: u64) -> bool {
+        self.anomaly_filter.lift(suppression_id)
+    }
+    
+    fn anomaly_state(&self) -> AnomalyState {
+        AnomalyState {
+            z_score: self.detectors.z_score.clone(),
+            mahalanobis: self.detectors.mahalanobis.clone(),
+            ewma: self.detectors.ewma.clone(),
+            mad: self.detectors.mad.clone(),
+            cusum: self.detectors.cusum.clone(),
+            quantile: self.detectors.quantile.clone(),
+            one_class_svm: self.detectors.one_class_svm.clone(),
+            episodes: self.episodes.clone(),
+            filter: self.anomaly_filter.clone(),
+        }
+    }
+    
+    fn apply_anomaly_state(&mut self, state: AnomalyState) {
+        self.detectors.z_score.restore(state.z_score);
+        self.detectors.mahalanobis = state.mahalanobis;
+        self.detectors.ewma = state.ewma;
+        self.detectors.mad = state.mad;
+        self.detectors.cusum = state.cusum;
+        self.detectors.quantile = state.quantile;
+        self.detectors.one_class_svm = state.one_class_svm;
+        self.episodes = state.episodes;
+        self.anomaly_filter = state.filter;
+    }
+    
+    /// Save every anomaly detector's baseline and history so a restart resumes warm
+    pub fn save_anomaly_state<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(&mut out, &self.anomaly_state())?;
+        Ok(out.flush()?)
+    }
+    
+    /// Restore state written by `save_anomaly_state`, keeping current subscribers
+    pub fn load_anomaly_state<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let state: AnomalyState = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        self.apply_anomaly_state(state);
+        Ok(())
+    }
+    
+    fn forecast_state(&self) -> ForecastState {
+        ForecastState {
+            predictor: self.predictor.clone(),
+            feature_predictor: self.feature_predictor.clone(),
+        }
+    }
+    
+    fn apply_forecast_state(&mut self, state: ForecastState) {
+        self.predictor = state.predictor;
+        self.feature_predictor = state.feature_predictor;
+    }
+    
+    /// Save the built-in predictor (and per-feature predictors, if enabled) so a restart resumes forecasting
+    pub fn save_forecast_state<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(&mut out, &self.forecast_state())?;
+        Ok(out.flush()?)
+    }
+    
+    /// Restore predictors written by `save_forecast_state`; a custom forecaster is left in place
+    pub fn load_forecast_state<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let state: ForecastState = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        self.apply_forecast_state(state);
+        Ok(())
+    }
+    
+    /// Capture the full system state: cycle counter, buffers, spatial graph,
+    /// detector and forecaster state, and network weights
+    pub fn snapshot(&self) -> SystemSnapshot {
+        SystemSnapshot {
+            config: self.config.clone(),
+            cycle_count: self.cycle_count,
+            runtime: self.elapsed(),
+            anomalies_reported: self.anomalies_reported,
+            sensor_buffer: self.sensor_buffer.clone(),
+            latency: self.latency.clone(),
+            graph: self.spatial_graph.snapshot(),
+            neural_net: (*self.neural_net).clone(),
+            anomaly: self.anomaly_state(),
+            forecast: self.forecast_state(),
+            anomaly_rates: self.anomaly_rates.clone(),
+            anomaly_map: self.anomaly_map.clone(),
+            drift_monitor: self.drift_monitor.clone(),
+            detection_mode: self.detection_mode,
+            ensemble: self.ensemble.clone(),
+            last_prediction: self.last_prediction,
+            loop_closure: self.loop_closure.clone(),
+            behavior: self.behavior.clone(),
+            metrics_baseline: self.metrics_baseline,
+        }
+    }
+    
+    /// Resume from a snapshot. Subscribers, alert sinks, pending alerts and
+    /// custom detectors or forecasters stay as they are.
+    ///
+    /// Sensor frames are processed with the snapshot's feature channels from
+    /// then on. Fails without changing anything if the snapshot's network was
+    /// built for a different number of features than those channels produce.
+    pub fn restore(&mut self, snapshot: SystemSnapshot) -> Result<()> {
+        let sensor_processor = SensorProcessor::with_channels(snapshot.config.features.clone())?;
+        let features = sensor_processor.feature_count();
+        if snapshot.neural_net.input_size() != features {
+            return Err(GenesisError::DimensionMismatch {
+                what: "snapshot network input",
+                expected: features,
+                actual: snapshot.neural_net.input_size(),
+            });
+        }
+        self.sensor_processor = sensor_processor;
+        self.config = snapshot.config;
+        self.cycle_count = snapshot.cycle_count;
+        self.anomalies_reported = snapshot.anomalies_reported;
+        self.start_time = self.clock.now().saturating_sub(snapshot.runtime);
+        self.sensor_feed = SensorFeed::since(snapshot.runtime);
+        self.sensor_buffer = snapshot.sensor_buffer;
+        self.latency = snapshot.latency;
+        self.recent_latency.clear();
+        self.spatial_graph = SpatialGraph::from_snapshot(snapshot.graph);
+        self.neural_net = Arc::new(snapshot.neural_net);
+        self.apply_anomaly_state(snapshot.anomaly);
+        self.apply_forecast_state(snapshot.forecast);
+        self.anomaly_rates = snapshot.anomaly_rates;
+        self.anomaly_map = snapshot.anomaly_map;
+        self.drift_monitor = snapshot.drift_monitor;
+        self.detection_mode = snapshot.detection_mode;
+        self.ensemble = snapshot.ensemble;
+        self.last_prediction = snapshot.last_prediction;
+        self.loop_closure = snapshot.loop_closure;
+        self.behavior = snapshot.behavior;
+        self.metrics_baseline = snapshot.metrics_baseline;
+        Ok(())
+    }
+    
+    /// Write `snapshot()` to a JSON file
+    pub fn save_snapshot<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(&mut out, &self.snapshot())?;
+        Ok(out.flush()?)
+    }
+    
+    /// Restore a snapshot written by `save_snapshot`
+    pub fn load_snapshot<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let snapshot: SystemSnapshot = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        self.restore(snapshot)
+    }
+    
+    /// Bound how many anomalies each detector retains
+    pub fn set_anomaly_history_capacity(&mut self, capacity: usize) {
+        for detector in self.detectors.iter_mut() {
+            detector.set_history_capacity(capacity);
+        }
+    }
+    
+    /// Adjust how anomalies are grouped into episodes and how often alerts fire
+    pub fn set_episode_config(&mut self, config: EpisodeConfig) {
+        self.episodes.set_config(config);
+    }
+    
+    /// Anomaly episodes grouped so far
+    pub fn anomaly_episodes(&self) -> &EpisodeTracker {
+        &self.episodes
+    }
+    
+    /// Remove a learned periodic baseline (timestamps in seconds) before z-scoring
+    pub fn set_seasonal_baseline(&mut self, baseline: Option<SeasonalBaseline>) {
+        self.detectors.z_score.set_seasonal_baseline(baseline);
+    }
+    
+    /// Seed the z-score detector with historical statistics so it is not blind while its window fills
+    pub fn warm_start_anomalies(&mut self, mean: f32, stdev: f32, samples: usize) {
+        self.detectors.z_score.warm_start(mean, stdev, samples);
+    }
+    
+    /// Select the forecasting model (linear or polynomial regression, exponential smoothing, Holt-Winters, ARIMA or seasonal decomposition)
+    pub fn set_forecast_model(&mut self, model: ForecastModel) {
+        self.predictor.set_model(model);
+    }
+    
+    /// Size the built-in predictor's window by signal volatility (`None` keeps its current size fixed)
+    pub fn set_adaptive_forecast_window(&mut self, config: Option<AdaptiveWindow>) {
+        self.predictor.set_adaptive_window(config);
+    }
+    
+    /// Forecast with a custom model instead of the built-in predictor; returns the previous one
+    pub fn set_forecaster(&mut self, forecaster: Box<dyn Forecaster>) -> Option<Box<dyn Forecaster>> {
+        self.custom_forecaster.replace(forecaster)
+    }
+    
+    /// Remove the custom forecaster, returning to the built-in predictor
+    pub fn take_forecaster(&mut self) -> Option<Box<dyn Forecaster>> {
+        self.custom_forecaster.take()
+    }
+    
+    /// The forecaster in use (custom if installed, otherwise the built-in predictor)
+    pub fn forecaster(&self) -> &dyn Forecaster {
+        self.custom_forecaster.as_deref().unwrap_or(&self.predictor)
+    }
+    
+    fn forecaster_mut(&mut self) -> &mut dyn Forecaster {
+        match &mut self.custom_forecaster {
+            Some(forecaster) => forecaster.as_mut(),
+            None => &mut self.predictor,
+        }
+    }
+    
+    /// Also forecast every feature channel with `model` (`None` turns multivariate mode off)
+    pub fn set_multivariate_forecasting(&mut self, model: Option<ForecastModel>) {
+        let window = self.config.forecast_window;
+        let features = self.sensor_processor.feature_count();
+        self.feature_predictor = model.map(|m| MultiPredictor::new(features, window, m));
+    }
+    
+    /// Configure distribution drift detection (restarts reference learning)
+    pub fn set_drift_config(&mut self, config: DriftConfig) {
+        self.drift_monitor.set_config(config);
+    }
+    
+    /// Anomalies detected within `radius` of a position, nearest first
+    pub fn anomalies_near(&self, position: &Position, radius: f32) -> Vec<&SpatialAnomaly> {
+        self.anomaly_map.anomalies_near(position, radius)
+    }
+    
+    /// Anomaly counts per map cell, densest first
+    pub fn anomaly_density(&self, cell_size: f32) -> Vec<RegionDensity> {
+        self.anomaly_map.density(cell_size)
+    }
+    
+    /// Where anomalies have occurred on the map
+    pub fn anomaly_map(&self) -> &AnomalyMap {
+        &self.anomaly_map
+    }
+    
+    /// Read-only access to the spatial map
+    pub fn spatial_graph(&self) -> &SpatialGraph {
+        &self.spatial_graph
+    }
+    
+    /// Extract the path traced through the spatial graph so far
+    pub fn trajectory(&self) -> Trajectory {
+        Trajectory::from_graph(&self.spatial_graph)
+    }
+    
+    /// Estimate memory usage in bytes
+    fn estimate_memory_usage(&self) -> f64 {
+        let base = std::mem::size_of::<Self>();
+        let buffer = self.sensor_buffer.capacity() * std::mem::size_of::<ProcessedData>()
+            + self.sensor_buffer.iter()
+                .map(|d| (d.features.capacity() + d.neural_output.capacity()) * std::mem::size_of::<f32>())
+                .sum::<usize>();
+        let times = self.latency.memory_bytes() + self.recent_latency.memory_bytes();
+        let scratch = self.buffers.pool.iter().map(Vec::capacity).sum::<usize>() * std::mem::size_of::<f32>();
+        let graph = self.spatial_graph.memory_breakdown().total();
+        
+        (base + buffer + times + scratch + graph) as f64
+    }
+
+    /// Reset the system
+    pub fn reset(&mut self) {
+        self.cycle_count = 0;
+        self.anomalies_reported = 0;
+        self.metrics_baseline = MetricsBaseline::default();
+        for processed in std::mem::take(&mut self.sensor_buffer) {
+            self.recycle_buffers(processed);
+        }
+        self.buffers.stats = PoolStats::default();
+        self.latency.clear();
+        self.recent_latency.clear();
+        self.start_time = self.clock.now();
+        self.sensor_feed = SensorFeed::default();
+        self.spatial_graph = SpatialGraph::with_capacity(self.config.graph_capacity);
+        self.spatial_graph.set_connection_radius(self.config.connection_radius);
+        for detector in self.detectors.iter_mut() {
+            detector.clear();
+        }
+        self.last_prediction = None;
+        self.episodes.clear();
+        self.anomaly_rates.clear();
+        self.anomaly_map.clear();
+        self.alert_queue.clear();
+        self.drift_monitor.clear();
+        self.predictor.clear();
+        if let Some(forecaster) = &mut self.custom_forecaster {
+            forecaster.clear();
+        }
+        if let Some(fp) = &mut self.feature_predictor {
+            fp.clear();
+        }
+        self.loop_closure = LoopClosureDetector::with_config(self.config.loop_closure);
+        self.behavior.clear();
+        self.calibration = Calibrator::start(self.config.calibration_cycles);
+        self.calibration_report = None;
+        self.recent_results.clear();
+        for installed in &mut self.custom_stages {
+            installed.latency.clear();
+        }
+    }
+    
+    /// Restart timing and counters while keeping everything learned
+    ///
+    /// Latency histograms, pool and user stage statistics are cleared and
+    /// `get_metrics` counts cycles, anomalies, episodes, drift events,
+    /// forecasts, loop closures and runtime from now on. Cycle numbering,
+    /// the spatial graph, detectors, forecasters and the anomaly rate
+    /// windows that drive rate alerts are untouched, so a warmed system can
+    /// be benchmarked as it is. Metrics handles restart their counts too.
+    pub fn reset_metrics(&mut self) {
+        self.latency.clear();
+        self.recent_latency.clear();
+        self.buffers.stats = PoolStats::default();
+        for installed in &mut self.custom_stages {
+            installed.latency.clear();
+        }
+        self.metrics_baseline = MetricsBaseline {
+            runtime: self.elapsed(),
+            cycles: self.cycle_count,
+            anomalies: self.anomalies_reported,
+            episodes: self.episodes.episode_count(),
+            drift_events: self.drift_monitor.event_count(),
+            predictions: self.forecaster().prediction_count(),
+            loop_closures: self.loop_closure.closure_count(),
+        };
+        if let Some(live) = &self.live_metrics {
+            live.reset(self.get_metrics());
+        }
+    }
+
+    /// Quiesce the system before it is stopped, dropped or persisted
+    ///
+    /// Closes the anomaly episode in progress, delivers every queued alert
+    /// to the sinks, publishes a final snapshot to metrics handles and passes
+    /// it to each observer's `on_shutdown`. Returns the snapshot. The system
+    /// remains usable; cycles run afterwards simply start a new episode.
+    pub fn shutdown(&mut self) -> SystemMetrics {
+        self.episodes.close();
+        while self.alert_queue.dispatch() > 0 {}
+        let metrics = self.get_metrics();
+        if let Some(live) = &self.live_metrics {
+            live.publish(metrics.clone());
+        }
+        for observer in &mut self.observers {
+            observer.on_shutdown(&metrics);
+        }
+        metrics
+    }
+    
+    /// Calibrate over the next `cycles` cycles, restarting a calibration in progress
+    ///
+    /// Those cycles run as usual on whatever frames arrive, and detectors,
+    /// forecasters and the drift reference learn from them, but anomalies
+    /// are not reported. Afterwards detection starts with what was learned,
+    /// `calibration_report` describes the data and `get_metrics` counts from
+    /// the first detecting cycle on. Cycle numbering carries on through
+    /// calibration. `cycles == 0` ends a calibration in progress right away.
+    pub fn calibrate(&mut self, cycles: usize) {
+        match Calibrator::start(cycles) {
+            Some(calibration) => self.calibration = Some(calibration),
+            None => self.finish_calibration(),
+        }
+    }
+
+    /// Whether anomalies are held back while calibrating
+    pub fn is_calibrating(&self) -> bool {
+        self.calibration.is_some()
+    }
+
+    /// Cycles left in the calibration in progress
+    pub fn calibration_remaining(&self) -> usize {
+        self.calibration.as_ref().map_or(0, Calibrator::remaining)
+    }
+
+    /// Statistics of the last completed calibration
+    pub fn calibration_report(&self) -> Option<&CalibrationReport> {
+        self.calibration_report.as_ref()
+    }
+
+    /// Calibrate on `cycles` simulated cycles (for benchmarking a warmed system)
+    ///
+    /// Unlike `reset`, everything learned is kept; only metrics start over,
+    /// when the calibration finishes.
+    pub fn warmup(&mut self, cycles: usize) {
+        self.calibrate(cycles);
+        while self.is_calibrating() {
+            let result = self.run_cycle();
+            self.recycle(result);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for EnvironmentalAwarenessSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lazily runs cycles on generated sensor data (see `EnvironmentalAwarenessSystem::cycles`)
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct Cycles<'a> {
+    system: &'a mut EnvironmentalAwarenessSystem,
+}
+
+#[cfg(feature = "std")]
+impl Iterator for Cycles<'_> {
+    type Item = CycleResult;
+
+    fn next(&mut self) -> Option<CycleResult> {
+        Some(self.system.run_cycle())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+// ============= Comprehensive Tests =============
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::anomaly::Severity;
+    
+    #[test]
+    fn test_system_creation() {
+        let system = EnvironmentalAwarenessSystem::new();
+        assert_eq!(system.cycle_count, 0);
+    }
+    
+    #[test]
+    fn test_single_cycle() {
+        let mut system = EnvironmentalAwarenessSystem::new();
+        let result = system.run_cycle();
+        assert_eq!(result.cycle, 1);
+        assert!(result.processing_us > 0);
+        assert!(result.confidence >= 0.0 && result.confidence <= 1.0);
+    }
+    
+    #[test]
+    fn test_multiple_cycles() {
+        let mut system = EnvironmentalAwarenessSystem::new();
+        let results = system.run_cycles(10);
+        assert_eq!(results.len(), 10);
+        assert_eq!(results.last().unwrap().cycle, 10);
+        
+        // Neighbors come nearest first and the untrained network is version 0
+        let graph = system.spatial_graph();
+        for result in &results {
+            let position = graph.node(result.node_id).unwrap().position;
+            let distances: Vec<f32> = result.neighbors.iter()
+                .map(|&id| graph.node(id).unwrap().position.distance_to(&position))
+                .collect();
+            assert!(distances.len() <= RESULT_NEIGHBORS);
+            assert!(distances.windows(2).all(|pair| pair[0] <= pair[1]));
+            assert_eq!(result.model_version, 0);
+        }
+    }
+    
+    #[test]
+    fn test_metrics() {
+        let mut system = EnvironmentalAwarenessSystem::new();
+        system.run_cycles(100);
+        
+        let metrics = system.get_metrics();
+        assert_eq!(metrics.cycles, 100);
+        assert!(metrics.avg_processing_us > 0.0);
+        assert!(metrics.p50_processing_us > 0);
+        assert!(metrics.p95_processing_us >= metrics.p50_processing_us);
+        assert!(metrics.p99_processing_us >= metrics.p95_processing_us);
+        assert!(metrics.spatial_nodes == 100);
+
+        // Connectivity is computed on demand, over the same graph
+        let stats = system.graph_stats(4);
+        assert!(stats.largest_component > 0 && stats.largest_component <= metrics.spatial_nodes);
+        assert!(stats.max_degree >= stats.min_degree);
+    }
+    
+    #[test]
+    fn test_reset() {
+        let mut system = EnvironmentalAwarenessSystem::new();
+        system.run_cycles(10);
+        assert_eq!(system.cycle_count, 10);
         
         system.reset();
         assert_eq!(system.cycle_count, 0);
         assert_eq!(system.sensor_buffer.len(), 0);
     }
+
+    #[test]
+    fn test_calibration() {
+        let mut system = EnvironmentalAwarenessSystem::builder().seed(5).calibration(30).build().unwrap();
+        assert_eq!(system.calibration_remaining(), 30);
+        let calibrating = system.run_cycles(30);
+        assert!(calibrating.iter().all(|r| r.calibrating && !r.anomaly_detected && r.anomaly_alert.is_none()));
+        assert!(!system.is_calibrating());
+        assert_eq!(system.get_metrics().cycles, 0);
+
+        let report = system.calibration_report().unwrap();
+        assert_eq!((report.cycles, report.features.len()), (30, sensors::FEATURE_NAMES.len()));
+        let lidar = report.features[1].stats;
+        assert!(lidar.min >= 500.0 / 1500.0 && lidar.max <= 1.0 && lidar.std_dev > 0.0);
+
+        // Detection starts with the learned state: the graph and the forecaster kept their history
+        let result = system.run_cycle();
+        assert!(!result.calibrating);
+        assert_eq!((result.cycle, system.get_metrics().cycles), (31, 1));
+        assert!(result.prediction.is_some());
+        assert_eq!(system.spatial_graph().node_count(), 31);
+
+        // warmup calibrates on simulated data and keeps it, unlike reset
+        system.warmup(10);
+        assert_eq!((system.cycle_count, system.spatial_graph().node_count()), (41, 41));
+        assert_eq!(system.get_metrics().cycles, 0);
+        system.reset();
+        assert!(system.is_calibrating() && system.calibration_report().is_none());
+        system.calibrate(0);
+        assert!(!system.is_calibrating());
+    }
+
+    #[test]
+    fn test_debug_dump() {
+        let mut system = EnvironmentalAwarenessSystem::builder().seed(2).debug_history(5).build().unwrap();
+        let results = system.run_cycles(12);
+        let dump = system.debug_dump().unwrap();
+        assert_eq!(dump.cycle, 12);
+        assert_eq!(dump.buffers.sensor_buffer, debug::Occupancy::new(12, 100));
+        assert_eq!(dump.buffers.recent_results, debug::Occupancy::new(5, 5));
+        assert_eq!(dump.recent_results.first().map(|r| r.cycle), Some(8));
+        assert_eq!(dump.recent_results.last().map(|r| r.confidence), results.last().map(|r| r.confidence));
+        assert_eq!(dump.graph.nodes, 12);
+        assert!(dump.forecast_fit.line.is_some());
+        assert!(dump.detectors.get("z_score").is_some());
+
+        let parsed: debug::DebugDump = serde_json::from_str(&dump.to_json().unwrap()).unwrap();
+        assert_eq!(parsed.recent_results.len(), 5);
+
+        // Shrinking the history on reload drops the oldest results
+        let config = SystemConfig { debug_history: 2, ..system.config().clone() };
+        system.apply_config(&config).unwrap();
+        assert_eq!(system.debug_dump().unwrap().recent_results.first().map(|r| r.cycle), Some(11));
+    }
+
+    #[test]
+    fn test_reset_metrics() {
+        let mut system = EnvironmentalAwarenessSystem::builder().seed(11).build().unwrap();
+        let handle = system.metrics_handle();
+        system.run_cycles(100);
+        let warmed = system.get_metrics();
+        let predictions = system.forecaster().prediction_count();
+
+        system.reset_metrics();
+        let metrics = system.get_metrics();
+        assert_eq!((metrics.cycles, metrics.anomalies_detected, metrics.predictions_made), (0, 0, 0));
+        assert_eq!((metrics.max_processing_us, metrics.stages.sensor.max_ns), (0, 0));
+        assert_eq!(handle.cycles(), 0);
+        // Learned state survives
+        assert_eq!(metrics.spatial_nodes, warmed.spatial_nodes);
+        assert_eq!(system.forecaster().prediction_count(), predictions);
+
+        let results = system.run_cycles(20);
+        assert_eq!(results[0].cycle, 101);
+        let metrics = system.get_metrics();
+        assert_eq!(metrics.cycles, 20);
+        assert_eq!(metrics.anomalies_detected, results.iter().filter(|r| r.anomaly_detected).count());
+        assert_eq!(metrics.predictions_made, results.iter().filter(|r| r.prediction.is_some()).count());
+        assert_eq!(metrics.spatial_nodes, warmed.spatial_nodes + 20);
+
+        // No time has passed on a manual clock right after a reset; the rate must not be NaN
+        let clock = clock::ManualClock::new();
+        system.set_clock(Box::new(clock.clone()));
+        clock.advance(Duration::from_secs(1));
+        system.run_cycles(10);
+        system.reset_metrics();
+        assert_eq!(system.get_metrics().processing_rate_hz, 0.0);
+        assert!(handle.metrics().processing_rate_hz.is_finite());
+    }
+
+    #[test]
+    fn test_stage_timings() {
+        let mut system = EnvironmentalAwarenessSystem::new();
+        system.set_clock(Box::new(clock::ManualClock::new()));
+        let result = system.run_cycle();
+        // A clock that never moves attributes no time to any stage
+        assert_eq!(result.stages, StageTimings::default());
+        
+        system.set_clock(Box::new(SystemClock::new()));
+        let results = system.run_cycles(50);
+        for result in &results {
+            let stages = result.stages;
+            let total = stages.sensor_ns + stages.neural_ns + stages.spatial_ns + stages.anomaly_ns + stages.prediction_ns;
+            assert_eq!(total / 1000, result.processing_us);
+        }
+        let metrics = system.get_metrics();
+        assert!(metrics.stages.spatial.max_ns >= metrics.stages.spatial.p50_ns);
+        assert!(metrics.stages.anomaly.avg_ns > 0.0);
+    }
+    
+    #[test]
+    fn test_windowed_rates() {
+        let mut system = EnvironmentalAwarenessSystem::new();
+        let clock = clock::ManualClock::new();
+        system.set_clock(Box::new(clock.clone()));
+        // 20s at 10 Hz, then 5s at 100 Hz
+        for (cycles, period) in [(200, 100), (500, 10)] {
+            for _ in 0..cycles {
+                clock.advance(Duration::from_millis(period));
+                system.run_cycle();
+            }
+        }
+        
+        let metrics = system.get_metrics();
+        assert_eq!(metrics.processing_rate_hz, 28.0);
+        // Cycles ending in [24s, 25s] and [15s, 25s]
+        assert_eq!(metrics.windows.last_1s.cycles, 101);
+        assert_eq!(metrics.windows.last_10s.rate_hz, 55.1);
+        assert_eq!(metrics.windows.last_60s.rate_hz, metrics.processing_rate_hz);
+        
+        system.reset();
+        assert_eq!(system.get_metrics().windows, WindowMetrics::default());
+    }
+    
+    #[test]
+    fn test_buffer_pool_reuse() {
+        let mut system = EnvironmentalAwarenessSystem::with_capacity(10, 0);
+        // Fill the sensor buffer so every cycle evicts one
+        for _ in 0..30 {
+            let result = system.run_cycle();
+            system.recycle(result);
+        }
+        let filled = system.pool_stats();
+        assert!(filled.misses > 0);
+        
+        // From then on the evicted and recycled buffers cover every cycle
+        for _ in 0..100 {
+            let result = system.run_cycle();
+            assert_eq!(result.neural_output.len(), system.config().output_size);
+            system.recycle(result);
+        }
+        let stats = system.get_metrics().buffer_pool;
+        assert_eq!(stats.misses, filled.misses);
+        assert_eq!(stats.hits, filled.hits + 300);
+        assert!(stats.hit_rate() > 0.8);
+        
+        system.reset();
+        assert_eq!(system.pool_stats().hits, 0);
+        assert_eq!(system.pool_stats().available, BUFFER_POOL_CAPACITY);
+    }
+    
+    #[test]
+    fn test_shutdown_flushes_alerts() {
+        use crate::alerts::AlertQueueConfig;
+        
+        #[derive(Debug)]
+        struct Final(Sender<u32>);
+        impl SystemObserver for Final {
+            fn on_shutdown(&mut self, metrics: &SystemMetrics) {
+                self.0.send(metrics.cycles).unwrap();
+            }
+        }
+        
+        let sensitive = AnomalyConfig { threshold: 0.1, medium: 0.2, high: 0.3, min_window: 3 };
+        let mut system = SystemBuilder::new().anomaly(20, sensitive).seed(3).build().unwrap();
+        system.set_alert_queue_config(AlertQueueConfig { dispatch_batch: 1, ..AlertQueueConfig::default() });
+        let (final_tx, finals) = mpsc::channel();
+        system.add_observer(Box::new(Final(final_tx)));
+        let handle = system.metrics_handle();
+        
+        // Without a sink alerts only queue up; shutdown delivers all of them, not one batch
+        system.run_cycles(100);
+        assert!(system.alert_queue().len() > 1);
+        let (alerts_tx, alerts) = mpsc::channel();
+        system.add_alert_sink(Box::new(alerts_tx));
+        let metrics = system.shutdown();
+        assert!(system.alert_queue().is_empty());
+        assert_eq!(alerts.try_iter().count(), system.alert_queue().delivered());
+        assert!(system.anomaly_episodes().current().is_none());
+        assert_eq!(finals.try_recv(), Ok(100));
+        assert_eq!((metrics.cycles, handle.metrics().cycles), (100, 100));
+    }
     
     #[test]
     fn test_warmup() {
         let mut system = EnvironmentalAwarenessSystem::new();
         system.warmup(50);
-        assert_eq!(system.cycle_count, 0); // Should be reset after warmup
+        assert_eq!(system.cycle_count, 50);
+        assert_eq!(system.get_metrics().cycles, 0); // Metrics start over after warmup
     }
     
     #[test]
@@ -382,13 +2271,502 @@ mod tests {
             if result.anomaly_detected {
                 anomalies += 1;
             }
+            assert_eq!(result.anomaly_detected, result.anomaly_score > system.anomaly_config().threshold);
+            assert_eq!(result.anomaly.as_ref().map(|a| a.id), result.anomaly_id);
+            if let Some(anomaly) = &result.anomaly {
+                assert_eq!(anomaly.explanation.node_id, Some(result.node_id));
+            }
         }
         
         // Should detect some anomalies in 100 cycles
         let metrics = system.get_metrics();
         assert_eq!(metrics.anomalies_detected, anomalies);
+        
+        // Every anomaly is pinned to the node created in its cycle
+        assert_eq!(system.anomaly_map().len(), anomalies);
+        let total: usize = system.anomaly_density(10.0).iter().map(|r| r.count).sum();
+        assert_eq!(total, anomalies);
+    }
+    
+    /// Flags every cycle whose first feature exceeds a fixed limit
+    #[derive(Debug, Default)]
+    struct ThresholdDetector {
+        seen: usize,
+        fired: usize,
+    }
+    
+    impl AnomalyDetection for ThresholdDetector {
+        fn observe(&mut self, features: &[f32], timestamp: f64) -> Option<Anomaly> {
+            self.seen += 1;
+            (features[0] > 0.5).then(|| {
+                self.fired += 1;
+                Anomaly {
+                    id: 0,
+                    timestamp,
+                    value: features[0],
+                    z_score: 3.0,
+                    severity: Severity::High,
+                    mean: 0.0,
+                    stdev: 1.0,
+                    contributions: Vec::new(),
+                    explanation: Default::default(),
+                    detectors: Vec::new(),
+                }
+            })
+        }
+        
+        fn anomaly_count(&self) -> usize {
+            self.fired
+        }
+    }
+    
+    #[test]
+    fn test_custom_detector() {
+        let mut system = EnvironmentalAwarenessSystem::new();
+        assert!(system.set_detector(Box::new(ThresholdDetector::default())).is_none());
+        assert_eq!(system.detection_mode(), DetectionMode::Custom);
+        
+        let mut reported = 0;
+        for _ in 0..50 {
+            let result = system.run_cycle();
+            if result.anomaly_detected {
+                reported += 1;
+                assert_eq!(system.get_metrics().anomalies_detected, reported);
+            }
+        }
+        
+        assert_eq!(system.detector().unwrap().anomaly_count(), reported);
+        assert!(system.take_detector().is_some());
+        assert_eq!(system.detection_mode(), DetectionMode::ZScore);
+        // Without an installed detector, the `Custom` member is skipped
+        system.set_ensemble(EnsembleConfig::new(CombinationRule::Any).with(DetectorKind::Custom, 1.0));
+        assert!(!system.run_cycle().anomaly_detected);
+    }
+    
+    #[test]
+    fn test_multivariate_forecasting() {
+        let mut system = EnvironmentalAwarenessSystem::new();
+        assert!(system.run_cycle().feature_predictions.is_none());
+        
+        system.set_multivariate_forecasting(Some(ForecastModel::LinearRegression));
+        let results = system.run_cycles(5);
+        let forecasts = results.last().unwrap().feature_predictions.as_ref().unwrap();
+        assert_eq!(forecasts.len(), system.config().features.len());
+        assert!(forecasts.iter().all(|f| f.values.len() == 5));
+    }
+    
+    /// Always forecasts the last value with full confidence
+    #[derive(Debug, Default)]
+    struct Persistence {
+        last: Option<f32>,
+        count: usize,
+    }
+    
+    impl Forecaster for Persistence {
+        fn add_observation(&mut self, value: f32) {
+            self.last = Some(value);
+        }
+        
+        fn predict(&mut self, horizon: usize) -> Option<Prediction> {
+            let last = self.last?;
+            self.count += 1;
+            Some(Prediction {
+                values: vec![last; horizon],
+                lower: vec![last; horizon],
+                upper: vec![last; horizon],
+                confidence: 1.0,
+                confidences: vec![1.0; horizon],
+                trend: 0.0,
+            })
+        }
+        
+        fn prediction_count(&self) -> usize {
+            self.count
+        }
+    }
+    
+    #[test]
+    fn test_custom_forecaster() {
+        let mut system = EnvironmentalAwarenessSystem::new();
+        assert!(system.set_forecaster(Box::new(Persistence::default())).is_none());
+        
+        for result in system.run_cycles(5) {
+            let prediction = result.prediction.unwrap();
+            assert_eq!(prediction.values, vec![result.confidence; 5]);
+        }
+        assert_eq!(system.get_metrics().predictions_made, 5);
+        
+        assert!(system.take_forecaster().is_some());
+        assert_eq!(system.forecaster().prediction_count(), 0);
+    }
+    
+    #[test]
+    fn test_detector_ensemble() {
+        let mut system = EnvironmentalAwarenessSystem::new();
+        system.set_ensemble(EnsembleConfig::new(CombinationRule::Any)
+            .with(DetectorKind::ZScore, 1.0)
+            .with(DetectorKind::Mad, 1.0));
+        
+        let mut reported = 0;
+        for _ in 0..200 {
+            let result = system.run_cycle();
+            reported += result.anomaly_detected as usize;
+        }
+        
+        // With `Any`, every detection by either member is reported
+        assert_eq!(system.detection_mode(), DetectionMode::Ensemble);
+        let either = system.detectors.z_score.anomaly_count().max(system.detectors.mad.anomaly_count());
+        assert!(reported >= either);
+        // A cycle both members flag is one anomaly in the metrics, not two
+        assert_eq!(system.get_metrics().anomalies_detected, reported);
+    }
+    
+    #[test]
+    fn test_anomaly_state_persistence() {
+        let mut system = EnvironmentalAwarenessSystem::new();
+        system.run_cycles(50);
+        
+        let path = std::env::temp_dir().join(format!("genesis_anomaly_state_{}.json", std::process::id()));
+        system.save_anomaly_state(&path).unwrap();
+        
+        let mut restarted = EnvironmentalAwarenessSystem::new();
+        restarted.load_anomaly_state(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        
+        assert_eq!(restarted.detectors.z_score.anomaly_count(), system.detectors.z_score.anomaly_count());
+        assert_eq!(restarted.detectors.z_score.get_anomalies().len(), system.detectors.z_score.get_anomalies().len());
+    }
+    
+    #[test]
+    fn test_forecast_state_persistence() {
+        let mut system = EnvironmentalAwarenessSystem::new();
+        system.set_multivariate_forecasting(Some(ForecastModel::Holt { alpha: 0.5, beta: 0.2 }));
+        system.run_cycles(20);
+        
+        let path = std::env::temp_dir().join(format!("genesis_forecast_state_{}.json", std::process::id()));
+        system.save_forecast_state(&path).unwrap();
+        
+        let mut restarted = EnvironmentalAwarenessSystem::new();
+        restarted.load_forecast_state(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        
+        assert!(restarted.feature_predictor.is_some());
+        assert_eq!(restarted.predictor.predict(3).unwrap().values, system.predictor.predict(3).unwrap().values);
+    }
+    
+    #[test]
+    fn test_cycles_iterator() {
+        let mut system = EnvironmentalAwarenessSystem::new();
+        let first_anomaly = system.cycles().take(500).find(|r| r.anomaly_detected);
+        let stopped_at = system.cycle_count;
+        assert_eq!(first_anomaly.map_or(500, |r| r.cycle), stopped_at);
+        
+        let cycles: Vec<u32> = system.cycles().take_while(|r| r.cycle < stopped_at + 3).map(|r| r.cycle).collect();
+        assert_eq!(cycles, vec![stopped_at + 1, stopped_at + 2]);
+        assert_eq!(system.cycle_count, stopped_at + 3);
     }
     
+    #[test]
+    fn test_seeded_runs_are_identical() {
+        let run = || {
+            let clock = clock::ManualClock::new();
+            let mut system = EnvironmentalAwarenessSystem::builder().seed(7).build().unwrap();
+            system.set_clock(Box::new(clock.clone()));
+            (0..50)
+                .map(|_| {
+                    clock.advance(Duration::from_millis(10));
+                    serde_json::to_string(&system.run_cycle()).unwrap()
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(run(), run());
+    }
+    
+    #[test]
+    fn test_snapshot_restore() {
+        let mut system = EnvironmentalAwarenessSystem::new();
+        system.run_cycles(30);
+        
+        let json = serde_json::to_string(&system.snapshot()).unwrap();
+        let snapshot: SystemSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(snapshot.cycle(), 30);
+        
+        let mut restored = EnvironmentalAwarenessSystem::new();
+        restored.restore(snapshot).unwrap();
+        assert_eq!(restored.cycle_count, system.cycle_count);
+        assert_eq!(restored.sensor_buffer.len(), system.sensor_buffer.len());
+        assert_eq!(restored.spatial_graph.node_count(), system.spatial_graph.node_count());
+        assert_eq!(restored.spatial_graph.edge_count(), system.spatial_graph.edge_count());
+        assert_eq!(restored.detectors.z_score.anomaly_count(), system.detectors.z_score.anomaly_count());
+        assert_eq!(restored.predictor.predict(3).unwrap().values, system.predictor.predict(3).unwrap().values);
+        
+        // Both continue identically from the same input
+        let data = SensorData::generate();
+        let a = system.process_sensor_data(&data).unwrap();
+        let b = restored.process_sensor_data(&data).unwrap();
+        assert_eq!(a.cycle, b.cycle);
+        assert_eq!(a.neural_output, b.neural_output);
+        assert_eq!(a.node_id, b.node_id);
+    }
+    
+    #[test]
+    fn test_rejects_invalid_input() {
+        let mut system = EnvironmentalAwarenessSystem::new();
+        let mut data = SensorData::generate();
+        data.lidar.max_range = f32::INFINITY;
+        assert!(matches!(system.process_sensor_data(&data), Err(GenesisError::InvalidReading { .. })));
+        assert_eq!(system.cycle_count, 0);
+        
+        let mut snapshot = system.snapshot();
+        snapshot.neural_net = NeuralNetwork::new(7, 4, 2);
+        let error = system.restore(snapshot).unwrap_err();
+        assert_eq!(error.to_string(), "snapshot network input has 7 dimensions, expected 4");
+    }
+    
+    #[test]
+    fn test_configured_feature_channels() {
+        let mut channels = sensors::default_channels();
+        channels.push(sensors::FeatureChannel::new(sensors::Reading::ImuGyro, 0.1, 0.2).absolute());
+        let config = SystemConfig { features: channels, seed: Some(5), ..SystemConfig::default() };
+        let mut system = EnvironmentalAwarenessSystem::with_config(config).unwrap();
+        assert_eq!(system.neural_net.input_size(), 5);
+        
+        let results = system.run_cycles(300);
+        assert!(system.sensor_buffer.iter().all(|d| d.features.len() == 5));
+        assert!(results.iter().all(|r| (0.0..=1.0).contains(&r.sensor_confidences.imu)));
+        let anomaly = results.iter().find_map(|r| r.anomaly.as_ref()).expect("an anomaly in 300 cycles");
+        assert_eq!(anomaly.contributions.len(), 5);
+        assert!(anomaly.contributions.iter().all(|c| c.reading.is_some()));
+        assert!(anomaly.contributions.iter().any(|c| c.name() == "imu_gyro"));
+        
+        // A snapshot carries its channels into a system built with the defaults
+        let mut restored = EnvironmentalAwarenessSystem::new();
+        restored.restore(system.snapshot()).unwrap();
+        let data = SensorData::generate();
+        let a = system.process_sensor_data(&data).unwrap();
+        let b = restored.process_sensor_data(&data).unwrap();
+        assert_eq!((a.confidence, a.neural_output), (b.confidence, b.neural_output));
+        
+        let config = SystemConfig { features: Vec::new(), ..SystemConfig::default() };
+        let error = EnvironmentalAwarenessSystem::with_config(config).unwrap_err();
+        assert_eq!(error.to_string(), "invalid features: needs at least one channel");
+    }
+
+    #[test]
+    fn test_ingest_queue() {
+        let mut system = EnvironmentalAwarenessSystem::new();
+        assert!(system.process_queued(Duration::ZERO).is_none());
+        assert!(system.get_metrics().ingest.is_none());
+        
+        let queue = system.ingest_queue(4, OverflowPolicy::DropOldest);
+        let mut frames: Vec<SensorData> = (0..10).map(|_| SensorData::generate()).collect();
+        frames[9].imu.gyro = f32::NAN;
+        for frame in frames {
+            queue.push(frame);
+        }
+        let results: Vec<_> = std::iter::from_fn(|| system.process_queued(Duration::ZERO)).collect();
+        assert_eq!(results.len(), 4);
+        assert!(matches!(results[3], Err(GenesisError::InvalidReading { .. })));
+        
+        let ingest = system.get_metrics().ingest.unwrap();
+        assert_eq!((ingest.received, ingest.dropped, ingest.depth), (10, 6, 0));
+        assert_eq!(system.cycle_count, 3);
+        
+        // A new queue retires the old one
+        system.ingest_queue(4, OverflowPolicy::Block);
+        assert!(queue.is_closed());
+    }
+    
+    #[test]
+    fn test_custom_stages() {
+        #[derive(Debug)]
+        struct Trace(&'static str, Sender<&'static str>);
+        impl Stage for Trace {
+            fn name(&self) -> &str {
+                self.0
+            }
+            
+            fn process(&mut self, _ctx: &mut CycleContext<'_>) {
+                self.1.send(self.0).unwrap();
+            }
+        }
+        
+        /// Business rule dropping every anomaly before it is alerted on
+        #[derive(Debug)]
+        struct Veto;
+        impl Stage for Veto {
+            fn name(&self) -> &str {
+                "veto"
+            }
+            
+            fn process(&mut self, ctx: &mut CycleContext<'_>) {
+                assert!(ctx.node_id.is_some() && !ctx.neural_output.is_empty());
+                ctx.anomaly = None;
+            }
+        }
+        
+        /// Replaces every frame with the same reading
+        #[derive(Debug)]
+        struct Fixed(SensorData);
+        impl Stage for Fixed {
+            fn name(&self) -> &str {
+                "fixed"
+            }
+            
+            fn process(&mut self, ctx: &mut CycleContext<'_>) {
+                ctx.frame = Some(Cow::Owned(self.0.clone()));
+            }
+        }
+        
+        let mut system = EnvironmentalAwarenessSystem::new();
+        let (tx, order) = mpsc::channel();
+        for position in [
+            StagePosition::After(BuiltinStage::Prediction),
+            StagePosition::Before(BuiltinStage::Spatial),
+            StagePosition::After(BuiltinStage::Sensor),
+            StagePosition::Before(BuiltinStage::Sensor),
+        ] {
+            let name = match position {
+                StagePosition::Before(BuiltinStage::Sensor) => "first",
+                StagePosition::After(BuiltinStage::Sensor) => "fused",
+                StagePosition::Before(_) => "spatial",
+                StagePosition::After(_) => "last",
+            };
+            system.add_stage(position, Box::new(Trace(name, tx.clone())));
+        }
+        system.run_cycle();
+        assert_eq!(order.try_iter().collect::<Vec<_>>(), ["first", "fused", "spatial", "last"]);
+        
+        let frame = SensorData::generate();
+        system.add_stage(StagePosition::Before(BuiltinStage::Sensor), Box::new(Fixed(frame.clone())));
+        let confidence = system.run_cycle().confidence;
+        assert_eq!(system.run_cycle().confidence, confidence);
+        let metrics = system.get_metrics();
+        assert_eq!(system.stage_count(), 5);
+        assert_eq!(metrics.custom_stages[4].name, "fixed");
+        assert!(metrics.custom_stages[4].stats.max_ns >= metrics.custom_stages[4].stats.p50_ns);
+        
+        // The same run with and without the rule: anomalies are found but never reported
+        let sensitive = AnomalyConfig { threshold: 0.1, medium: 0.2, high: 0.3, min_window: 3 };
+        let found = |veto: bool| {
+            let mut system = SystemBuilder::new().anomaly(20, sensitive).seed(3).build().unwrap();
+            if veto {
+                system.add_stage(StagePosition::After(BuiltinStage::Anomaly), Box::new(Veto));
+            }
+            let anomalies = system.subscribe_anomalies();
+            let detected = system.run_cycles(30).iter().filter(|r| r.anomaly_detected).count();
+            (detected, anomalies.try_iter().count())
+        };
+        assert!(found(false).0 > 0);
+        assert_eq!(found(true), (0, 0));
+    }
+    
+    #[test]
+    fn test_behavior_mode() {
+        let sensitive = AnomalyConfig { threshold: 0.1, medium: 0.2, high: 0.3, min_window: 3 };
+        let mut system = SystemBuilder::new().anomaly(20, sensitive).seed(3).build().unwrap();
+        let results = system.run_cycles(40);
+        assert!(results.iter().any(|r| r.anomaly_detected));
+        let mut mode = BehaviorMode::Idle;
+        for result in &results {
+            if result.anomaly_detected {
+                assert_eq!(result.mode, BehaviorMode::Avoiding);
+            }
+            match result.mode_transition {
+                Some(transition) => assert_eq!((transition.from, transition.to), (mode, result.mode)),
+                None => assert_eq!(result.mode, mode),
+            }
+            mode = result.mode;
+        }
+        assert_eq!(system.behavior_mode(), mode);
+        
+        // Thresholds change live; an unreachable navigate threshold rules navigating out
+        let config = SystemConfig {
+            behavior: mode::ModeConfig { explore_confidence: 0.0, navigate_confidence: 1.0, avoid_cycles: 0, ..Default::default() },
+            ..system.config().clone()
+        };
+        let changes = system.apply_config(&config).unwrap();
+        assert!(changes.iter().any(|c| c.field == "behavior" && c.applied));
+        // Without a custom detector installed no detector runs, so confidence alone decides
+        system.set_detection_mode(DetectionMode::Custom);
+        let result = system.run_cycle();
+        assert_eq!(result.mode, BehaviorMode::Exploring);
+        
+        let snapshot = system.snapshot();
+        system.reset();
+        assert_eq!(system.behavior_mode(), BehaviorMode::Idle);
+        system.restore(snapshot).unwrap();
+        assert_eq!(system.behavior_mode(), BehaviorMode::Exploring);
+    }
+    
+    #[test]
+    fn test_run_at_hz() {
+        let mut system = EnvironmentalAwarenessSystem::new();
+        let stop = schedule::StopSignal::new();
+        let report = system
+            .run_at_hz_with(500.0, &stop, |system, result| {
+                if result.cycle == 20 {
+                    stop.stop();
+                }
+                system.recycle(result);
+            })
+            .unwrap();
+        assert_eq!((report.cycles, system.cycle_count), (20, 20));
+        // The 20th cycle starts 19 periods in, later only after overruns
+        assert!(report.runtime_seconds >= 0.038 - 1e-6);
+        assert!(report.skipped_ticks >= report.overruns);
+        assert!(report.jitter.max_ns >= report.jitter.p50_ns);
+
+        assert_eq!(system.run_at_hz(500.0, &stop).unwrap().cycles, 0);
+        assert!(matches!(system.run_at_hz(f64::NAN, &stop), Err(GenesisError::InvalidRate(_))));
+    }
+
+    #[test]
+    fn test_health() {
+        use health::HealthStatus;
+        let config = SystemConfig {
+            graph_capacity: 10,
+            detection_mode: DetectionMode::Both,
+            seed: Some(2),
+            ..SystemConfig::default()
+        };
+        let mut system = EnvironmentalAwarenessSystem::with_config(config).unwrap();
+        let clock = clock::ManualClock::new();
+        system.set_clock(Box::new(clock.clone()));
+        let status = |system: &EnvironmentalAwarenessSystem, name: &str| system.health().component(name).unwrap().status;
+
+        // Nothing is fitted before the first frames
+        let report = system.health();
+        assert_eq!(report.status, HealthStatus::Degraded);
+        assert!(report.is_ready());
+        assert_eq!(report.component("feature_detector").unwrap().reasons, ["warming up: 0 of 6 samples"]);
+        assert_eq!(status(&system, "sensors"), HealthStatus::Healthy);
+        assert_eq!(status(&system, "predictor"), HealthStatus::Degraded);
+
+        for _ in 0..9 {
+            clock.advance(Duration::from_millis(10));
+            system.run_cycle();
+        }
+        assert_eq!(status(&system, "anomaly_detector"), HealthStatus::Healthy);
+        assert_eq!(status(&system, "feature_detector"), HealthStatus::Healthy);
+        assert_eq!(status(&system, "predictor"), HealthStatus::Healthy);
+        assert_eq!(status(&system, "spatial_graph"), HealthStatus::Degraded);
+
+        system.run_cycles(2);
+        let report = system.health();
+        assert!(!report.is_ready());
+        assert_eq!(report.component("spatial_graph").unwrap().reasons, ["11 nodes for a capacity of 10"]);
+
+        // A feed that stops goes stale, then dead
+        system.reset();
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(status(&system, "sensors"), HealthStatus::Degraded);
+        clock.advance(Duration::from_secs(4));
+        let report = system.health();
+        assert_eq!(report.status, HealthStatus::Unhealthy);
+        assert_eq!(report.component("sensors").unwrap().reasons, ["no sensor frame for 6.0 s"]);
+    }
+
     #[test]
     fn test_predictions() {
         let mut system = EnvironmentalAwarenessSystem::new();
@@ -431,19 +2809,21 @@ mod tests {
             .map(|r| r.processing_us)
             .collect();
         
-        // Calculate variance
-        let mean = processing_times.iter().sum::<u64>() as f64 / processing_times.len() as f64;
-        let variance = processing_times.iter()
-            .map(|&x| {
-                let diff = x as f64 - mean;
-                diff * diff
-            })
-            .sum::<f64>() / processing_times.len() as f64;
-        
-        let std_dev = variance.sqrt();
-        let cv = std_dev / mean; // Coefficient of variation
-        
-        // Performance should be consistent (low variance)
-        assert!(cv < 0.5, "Performance variance too high: CV={}", cv);
+        // Cycles slow down as the graph grows and every tenth one also runs the
+        // drift test, so judge how far typical cycles stray from the median over
+        // windows short enough for the graph to stay about the same size
+        let median = |values: &mut Vec<f64>| {
+            values.sort_unstable_by(f64::total_cmp);
+            values[values.len() / 2]
+        };
+        for (window, times) in processing_times.chunks(100).enumerate() {
+            let mut times: Vec<f64> = times.iter().map(|&t| t as f64).collect();
+            let mid = median(&mut times);
+            let mut deviations: Vec<f64> = times.iter().map(|t| (t - mid).abs()).collect();
+            let spread = median(&mut deviations) / mid;
+            
+            // Performance should be consistent (low variance)
+            assert!(spread < 0.5, "Performance variance too high in window {}: MAD/median={}", window, spread);
+        }
     }
 }
\ No newline at end of file
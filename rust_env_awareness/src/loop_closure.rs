@@ -0,0 +1,132 @@
+//! Loop closure detection over the spatial graph
+
+use serde::{Serialize, Deserialize};
+
+use crate::spatial::{Coordinates, FeatureMetric, SpatialGraph};
+
+/// Emitted when an observation revisits a much older region of the map
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LoopClosure {
+    pub node_id: usize,
+    pub matched_id: usize,
+    pub position_distance: f32,
+    pub feature_distance: f32,
+}
+
+/// Loop closure detector parameters
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LoopClosureConfig {
+    /// Minimum id gap for a match to count as "much older"
+    pub min_age: usize,
+    /// Maximum spatial distance to the matched node
+    pub max_position_distance: f32,
+    /// Maximum feature distance to the matched node
+    pub max_feature_distance: f32,
+    /// Number of spatial candidates checked per observation
+    pub candidates: usize,
+    pub metric: FeatureMetric,
+}
+
+impl Default for LoopClosureConfig {
+    fn default() -> Self {
+        Self {
+            min_age: 100,
+            max_position_distance: 5.0,
+            max_feature_distance: 0.05,
+            candidates: 16,
+            metric: FeatureMetric::Euclidean,
+        }
+    }
+}
+
+/// Flags observations that match old graph regions in both position and features
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LoopClosureDetector {
+    config: LoopClosureConfig,
+    closures: usize,
+}
+
+impl LoopClosureDetector {
+    /// Create a detector with default parameters
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a detector with explicit parameters
+    pub fn with_config(config: LoopClosureConfig) -> Self {
+        Self { config, closures: 0 }
+    }
+
+    /// Check a freshly inserted node against older parts of the graph
+    pub fn check<P: Coordinates>(&mut self, graph: &SpatialGraph<P>, node_id: usize) -> Option<LoopClosure> {
+        let node = graph.node(node_id)?;
+
+        let best = graph
+            .k_nearest_neighbors(&node.position, self.config.candidates + 1)
+            .into_iter()
+            .take_while(|&(_, dist)| dist <= self.config.max_position_distance)
+            .filter(|&(id, _)| id + self.config.min_age <= node_id)
+            .filter_map(|(id, position_distance)| {
+                let candidate = graph.node(id)?;
+                let feature_distance = self.config.metric.distance(&node.features, &candidate.features);
+                (feature_distance <= self.config.max_feature_distance)
+                    .then_some((id, position_distance, feature_distance))
+            })
+            .min_by(|a, b| a.2.total_cmp(&b.2))?;
+
+        self.closures += 1;
+        Some(LoopClosure {
+            node_id,
+            matched_id: best.0,
+            position_distance: best.1,
+            feature_distance: best.2,
+        })
+    }
+
+    /// Get the number of loop closures detected
+    #[inline]
+    pub fn closure_count(&self) -> usize {
+        self.closures
+    }
+
+    /// Get the detector parameters
+    pub fn config(&self) -> &LoopClosureConfig {
+        &self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_revisit_is_detected() {
+        let mut graph = SpatialGraph::new();
+        let mut detector = LoopClosureDetector::with_config(LoopClosureConfig {
+            min_age: 10,
+            ..Default::default()
+        });
+
+        let start = graph.add_node(&[0.2, 0.2, 0.2, 0.2]);
+        for i in 1..20 {
+            let id = graph.add_node(&[0.2 + i as f32 * 0.03, 0.2, 0.2, 0.2]);
+            assert!(detector.check(&graph, id).is_none());
+        }
+
+        // Coming back to the start closes the loop
+        let id = graph.add_node(&[0.2, 0.2, 0.2, 0.21]);
+        let closure = detector.check(&graph, id).unwrap();
+        assert_eq!(closure.matched_id, start);
+        assert_eq!(detector.closure_count(), 1);
+    }
+
+    #[test]
+    fn test_recent_neighbors_are_ignored() {
+        let mut graph = SpatialGraph::new();
+        let mut detector = LoopClosureDetector::new();
+
+        graph.add_node(&[0.2, 0.2, 0.2, 0.2]);
+        let id = graph.add_node(&[0.2, 0.2, 0.2, 0.2]);
+        assert!(detector.check(&graph, id).is_none());
+    }
+}
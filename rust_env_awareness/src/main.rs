@@ -1,182 +1,557 @@
-//! Genesis Environmental Awareness System - Rust Implementation
-//! Performance comparison with Python implementation
-
-use genesis_env_awareness::{EnvironmentalAwarenessSystem, SystemMetrics};
-use std::time::Instant;
-use serde_json;
-
-fn main() {
-    println!("================================================================================");
-    println!("🦀 GENESIS ENVIRONMENTAL AWARENESS - RUST HIGH-PERFORMANCE IMPLEMENTATION");
-    println!("================================================================================");
-    println!("Start Time: {}", chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f"));
-    println!("--------------------------------------------------------------------------------\n");
-
-    // Initialize system
-    let mut system = EnvironmentalAwarenessSystem::new();
-    
-    // Warmup (JIT and cache warming)
-    println!("🔥 Warming up...");
-    for _ in 0..100 {
-        system.run_cycle();
+//! Genesis Environmental Awareness System - command line interface
+//!
+//! ```text
+//! genesis run --hz 100                         drive the system on simulated sensors
+//! genesis bench --cycles 10000 --json out.json measure throughput
+//! genesis replay --input log.jsonl             feed recorded sensor frames
+//! genesis record --output run.jsonl --cycles 1000  log a deterministic run
+//! genesis verify --input run.jsonl             re-run it and diff the results
+//! genesis serve --port 8080                    REST server (`server` feature)
+//! genesis grpc --port 50051                    gRPC service (`grpc` feature)
+//! genesis grpc --fleet http://hub:50052 --device rover-7  also report to a fleet server
+//! genesis fleet --port 50052                   aggregate many devices (`grpc` feature)
+//! genesis run --results cycles.csv --rotate-mb 64  also log every cycle
+//! genesis serve --metrics-log metrics.jsonl    also log metrics every 10 s
+//! genesis serve --otlp http://localhost:4318   export metrics and spans (`otel` feature)
+//! ```
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use clap::{Args, Parser, Subcommand};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use genesis_env_awareness::config::{ConfigChange, ConfigWatcher, SystemConfig};
+use genesis_env_awareness::error::{GenesisError, Result};
+use genesis_env_awareness::replay::Recorder;
+use genesis_env_awareness::schedule::{ScheduleReport, StopSignal};
+use genesis_env_awareness::sensors::SensorData;
+use genesis_env_awareness::sink::{CsvSink, JsonLinesSink, MetricsLog, MetricsWriter, ResultSink, Rotation};
+use genesis_env_awareness::{CycleResult, EnvironmentalAwarenessSystem, SystemMetrics};
+
+/// How often served systems check a watched config file
+#[cfg(any(feature = "server", feature = "grpc"))]
+const RELOAD_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Parser)]
+#[command(name = "genesis", version, about = "Genesis environmental awareness system")]
+struct Cli {
+    #[command(flatten)]
+    system: SystemArgs,
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// Options shared by every subcommand
+#[derive(Debug, Args)]
+struct SystemArgs {
+    /// `SystemConfig` file (JSON, TOML or YAML) to build the system from
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    /// Apply edits to the config file while running (run, serve and grpc)
+    #[arg(long, global = true, requires = "config")]
+    watch: bool,
+    /// Seed for reproducible runs (overrides the config file)
+    #[arg(long, global = true)]
+    seed: Option<u64>,
+    /// Log every cycle's result to this file: CSV if it ends in `.csv`, Parquet
+    /// if it ends in `.parquet` (`parquet` feature), JSON lines otherwise
+    #[arg(long, global = true)]
+    results: Option<PathBuf>,
+    /// Append a `SystemMetrics` snapshot to this JSON lines file every `--metrics-secs`,
+    /// for a timeline that survives a crash
+    #[arg(long, global = true)]
+    metrics_log: Option<PathBuf>,
+    /// Seconds between `--metrics-log` records
+    #[arg(long, global = true, default_value_t = 10, requires = "metrics_log", value_parser = clap::value_parser!(u64).range(1..))]
+    metrics_secs: u64,
+    /// Export cycle metrics and stage spans to this OTLP/HTTP collector
+    #[cfg(feature = "otel")]
+    #[arg(long, global = true)]
+    otlp: Option<String>,
+    /// Start a new results or metrics file once the current one reaches this many megabytes
+    #[arg(long, global = true)]
+    rotate_mb: Option<u64>,
+    /// Start a new results or metrics file once the current one is this many seconds old
+    #[arg(long, global = true)]
+    rotate_secs: Option<u64>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Process simulated sensor data at a fixed rate
+    Run {
+        /// Target cycle rate
+        #[arg(long, default_value_t = 100.0, value_parser = positive_rate)]
+        hz: f64,
+        /// Stop after this many cycles instead of running forever
+        #[arg(long)]
+        cycles: Option<u32>,
+    },
+    /// Measure processing throughput on a warmed system (see `benches/` for per-component figures)
+    Bench {
+        #[arg(long, default_value_t = 10_000)]
+        cycles: u32,
+        /// Calibration cycles run before measuring
+        #[arg(long, default_value_t = 100)]
+        warmup: usize,
+        /// Also write the results to this file as JSON
+        #[arg(long)]
+        json: Option<PathBuf>,
+    },
+    /// Process recorded sensor frames, one JSON `SensorData` per line
+    Replay {
+        #[arg(long)]
+        input: PathBuf,
+    },
+    /// Run seeded simulated cycles on a manual clock, logging frames and results for `verify`
+    Record {
+        #[arg(long)]
+        output: PathBuf,
+        #[arg(long, default_value_t = 1000)]
+        cycles: u32,
+        /// Simulated frame rate; sets frame timestamps, not pacing
+        #[arg(long, default_value_t = 100.0, value_parser = positive_rate)]
+        hz: f64,
+    },
+    /// Re-run a recording from its own config and report results that differ
+    Verify {
+        #[arg(long)]
+        input: PathBuf,
+    },
+    /// Serve the system as a JSON REST API
+    #[cfg(feature = "server")]
+    Serve {
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+        #[arg(long, default_value = "0.0.0.0")]
+        host: std::net::IpAddr,
+        /// Also run simulated cycles at this rate
+        #[arg(long, value_parser = positive_rate)]
+        hz: Option<f64>,
+    },
+    /// Serve the system as a gRPC telemetry and control service
+    #[cfg(feature = "grpc")]
+    Grpc {
+        #[arg(long, default_value_t = 50051)]
+        port: u16,
+        #[arg(long, default_value = "0.0.0.0")]
+        host: std::net::IpAddr,
+        /// Also run simulated cycles at this rate
+        #[arg(long, value_parser = positive_rate)]
+        hz: Option<f64>,
+        /// Report cycles and metrics to the fleet server at this URL
+        #[arg(long, requires = "device")]
+        fleet: Option<String>,
+        /// Name to report to the fleet server under
+        #[arg(long, requires = "fleet")]
+        device: Option<String>,
+    },
+    /// Aggregate telemetry from many systems and alert on fleet-wide anomalies
+    #[cfg(feature = "grpc")]
+    Fleet {
+        #[arg(long, default_value_t = 50052)]
+        port: u16,
+        #[arg(long, default_value = "0.0.0.0")]
+        host: std::net::IpAddr,
+        /// Devices that must be anomalous within a few seconds of each other
+        #[arg(long, default_value_t = 3)]
+        min_devices: usize,
+        /// Share of online devices that must be anomalous together
+        #[arg(long, default_value_t = 0.3)]
+        min_fraction: f32,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let watcher = cli.system.config.as_ref().filter(|_| cli.system.watch).map(ConfigWatcher::new);
+    let args = &cli.system;
+    let outcome = match cli.command {
+        Command::Run { hz, cycles } => with_system(args, |system| run(system, hz, cycles, watcher)),
+        Command::Bench { cycles, warmup, json } => {
+            with_system(args, |system| bench(system, cycles, warmup, json.as_deref()))
+        }
+        Command::Replay { input } => with_system(args, |system| replay(system, &input)),
+        Command::Record { output, cycles, hz } => record(args, &output, cycles, hz),
+        Command::Verify { input } => match verify(&input) {
+            Ok(false) => return ExitCode::FAILURE,
+            outcome => outcome.map(drop),
+        },
+        #[cfg(feature = "server")]
+        Command::Serve { port, host, hz } => {
+            with_system(args, |system| serve(system, (host, port).into(), hz, watcher))
+        }
+        #[cfg(feature = "grpc")]
+        Command::Grpc { port, host, hz, fleet, device } => {
+            let report = fleet.zip(device);
+            with_system(args, |system| grpc(system, (host, port).into(), hz, report, watcher))
+        }
+        #[cfg(feature = "grpc")]
+        Command::Fleet { port, host, min_devices, min_fraction } => fleet((host, port).into(), min_devices, min_fraction),
+    };
+    match outcome {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn positive_rate(value: &str) -> std::result::Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(hz) if hz.is_finite() && hz > 0.0 => Ok(hz),
+        _ => Err(format!("expected a positive rate in Hz, got `{value}`")),
+    }
+}
+
+fn load_config(args: &SystemArgs) -> Result<SystemConfig> {
+    let mut config = match &args.config {
+        Some(path) => SystemConfig::from_file(path)?,
+        None => SystemConfig::default(),
+    };
+    if args.seed.is_some() {
+        config.seed = args.seed;
+    }
+    Ok(config)
+}
+
+fn build_system(args: &SystemArgs) -> Result<EnvironmentalAwarenessSystem> {
+    let mut system = EnvironmentalAwarenessSystem::with_config(load_config(args)?)?;
+    add_results_sink(&mut system, args)?;
+    Ok(system)
+}
+
+/// Build the system and run `command` on it, logging metrics throughout if `--metrics-log` asks
+fn with_system(args: &SystemArgs, command: impl FnOnce(EnvironmentalAwarenessSystem) -> Result<()>) -> Result<()> {
+    let mut system = build_system(args)?;
+    let writer = match &args.metrics_log {
+        Some(path) => {
+            let log = MetricsLog::create(path, rotation(args))?;
+            Some(MetricsWriter::spawn(system.metrics_handle(), log, Duration::from_secs(args.metrics_secs)))
+        }
+        None => None,
+    };
+    #[cfg(feature = "otel")]
+    let exporter = args.otlp.as_deref().map(|endpoint| export_otlp(&mut system, endpoint)).transpose()?;
+    let outcome = command(system);
+    let logged = writer.map_or(Ok(()), MetricsWriter::stop);
+    #[cfg(feature = "otel")]
+    let logged = logged.and(exporter.map_or(Ok(()), |exporter| exporter.shutdown()));
+    outcome.and(logged)
+}
+
+/// Send the system's metrics and the process's spans to `endpoint`
+#[cfg(feature = "otel")]
+fn export_otlp(system: &mut EnvironmentalAwarenessSystem, endpoint: &str) -> Result<genesis_env_awareness::otel::OtelExporter> {
+    use genesis_env_awareness::otel::{OtelConfig, OtelExporter};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    let exporter = OtelExporter::new(&OtelConfig::new(endpoint))?;
+    tracing::subscriber::set_global_default(tracing_subscriber::registry().with(exporter.layer()))
+        .map_err(|e| GenesisError::Otel(e.to_string()))?;
+    system.add_observer(Box::new(exporter.observer()));
+    Ok(exporter)
+}
+
+/// File rotation as `--rotate-mb` and `--rotate-secs` ask
+fn rotation(args: &SystemArgs) -> Rotation {
+    Rotation {
+        max_bytes: args.rotate_mb.map(|mb| mb * 1024 * 1024),
+        max_age: args.rotate_secs.map(Duration::from_secs),
+    }
+}
+
+/// Log results as `--results` asks, if it does
+fn add_results_sink(system: &mut EnvironmentalAwarenessSystem, args: &SystemArgs) -> Result<()> {
+    if let Some(path) = &args.results {
+        let rotation = rotation(args);
+        let sink: Box<dyn ResultSink> = match path.extension() {
+            Some(extension) if extension.eq_ignore_ascii_case("csv") => Box::new(CsvSink::create(path, rotation)?),
+            #[cfg(feature = "parquet")]
+            Some(extension) if extension.eq_ignore_ascii_case("parquet") => {
+                Box::new(genesis_env_awareness::columnar::ParquetSink::<CycleResult>::create(path, rotation)?)
+            }
+            _ => Box::new(JsonLinesSink::create(path, rotation)?),
+        };
+        system.add_result_sink(sink);
+    }
+    Ok(())
+}
+
+fn run(mut system: EnvironmentalAwarenessSystem, hz: f64, cycles: Option<u32>, mut watcher: Option<ConfigWatcher>) -> Result<()> {
+    let report_every = (hz.round() as u32).max(1);
+    let stop = StopSignal::new();
+    if cycles == Some(0) {
+        stop.stop();
     }
-    system.reset();
-    
-    println!("📊 RUNNING PERFORMANCE BENCHMARK\n");
-    println!("--------------------------------------------------------------------------------");
-    
-    // Benchmark different cycle counts
-    let test_cycles = vec![30, 100, 1000, 10000];
-    let mut results = Vec::new();
-    
-    for &cycle_count in &test_cycles {
-        system.reset();
-        let start = Instant::now();
-        
-        // Run cycles
-        for i in 0..cycle_count {
-            let result = system.run_cycle();
-            
-            // Display progress for longer runs
-            if cycle_count >= 1000 && i % (cycle_count / 10) == 0 {
-                print!(".");
-                use std::io::{self, Write};
-                io::stdout().flush().unwrap();
+
+    // Report anomalies as they happen and progress about once a second
+    let schedule = system.run_at_hz_with(hz, &stop, |system, result| {
+        if result.anomaly_detected || result.cycle.is_multiple_of(report_every) {
+            print_cycle(&result);
+        }
+        if let Some(watcher) = watcher.as_mut().filter(|_| result.cycle.is_multiple_of(report_every)) {
+            if let Some(outcome) = watcher.poll(system).transpose() {
+                print_reload(outcome);
             }
-            
-            // Display sample output
-            if cycle_count == 30 && i % 5 == 4 {
-                println!("\n⏱️  Cycle {}", result.cycle);
-                println!("  • Confidence: {:.2}%", result.confidence * 100.0);
-                println!("  • Neural Output: [{:.3}, {:.3}]", 
-                    result.neural_output[0], result.neural_output[1]);
-                println!("  • Spatial Node: #{}", result.node_id);
-                println!("  • Processing: {}μs", result.processing_us);
-                
+        }
+        if cycles.is_some_and(|limit| result.cycle >= limit) {
+            stop.stop();
+        }
+        system.recycle(result);
+    })?;
+    print_metrics(&system.shutdown());
+    print_schedule(&schedule);
+    Ok(())
+}
+
+fn bench(mut system: EnvironmentalAwarenessSystem, cycles: u32, warmup: usize, json: Option<&Path>) -> Result<()> {
+    system.warmup(warmup);
+
+    let start = Instant::now();
+    for _ in 0..cycles {
+        let result = system.run_cycle();
+        system.recycle(result);
+    }
+    let elapsed = start.elapsed();
+    let metrics = system.shutdown();
+
+    println!("{} cycles in {:.3}s", cycles, elapsed.as_secs_f64());
+    print_metrics(&metrics);
+
+    if let Some(path) = json {
+        let report = serde_json::json!({
+            "cycles": cycles,
+            "warmup": warmup,
+            "elapsed_ms": elapsed.as_secs_f64() * 1000.0,
+            "metrics": metrics,
+        });
+        serde_json::to_writer_pretty(BufWriter::new(File::create(path)?), &report)?;
+        println!("Results written to {}", path.display());
+    }
+    Ok(())
+}
+
+fn replay(mut system: EnvironmentalAwarenessSystem, input: &Path) -> Result<()> {
+    let (mut replayed, mut rejected) = (0, 0);
+    for (index, line) in BufReader::new(File::open(input)?).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let data: SensorData = serde_json::from_str(&line).inspect_err(|_| {
+            eprintln!("line {}: not a sensor frame", index + 1);
+        })?;
+        match system.process_sensor_data(&data) {
+            Ok(result) => {
+                replayed += 1;
                 if result.anomaly_detected {
-                    println!("  • ⚠️ ANOMALY DETECTED");
-                }
-                
-                if let Some(pred) = result.prediction {
-                    println!("  • 📈 Prediction: {}, confidence={:.1}%", 
-                        pred.trend, pred.confidence * 100.0);
+                    print_cycle(&result);
                 }
             }
+            Err(e @ GenesisError::InvalidReading { .. }) => {
+                eprintln!("line {}: skipped: {e}", index + 1);
+                rejected += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    println!("Replayed {} frames ({} rejected)", replayed, rejected);
+    print_metrics(&system.shutdown());
+    Ok(())
+}
+
+fn record(args: &SystemArgs, output: &Path, cycles: u32, hz: f64) -> Result<()> {
+    let mut recorder = Recorder::create(output, load_config(args)?)?;
+    add_results_sink(recorder.system_mut(), args)?;
+    // The recorder always has a seed; frames are drawn from it too
+    let seed = recorder.system().config().seed.unwrap_or_default();
+    let mut rng = StdRng::seed_from_u64(seed);
+    for i in 0..cycles {
+        let result = recorder.record(&SensorData::generate_with(&mut rng, i as f64 / hz))?;
+        if result.anomaly_detected {
+            print_cycle(&result);
         }
-        
-        if cycle_count >= 1000 {
-            println!();
-        }
-        
-        let elapsed = start.elapsed();
-        let metrics = system.get_metrics();
-        
-        results.push((cycle_count, elapsed, metrics));
-        
-        println!("\n📈 {} Cycles Complete:", cycle_count);
-        println!("  • Total Time: {:.3}s", elapsed.as_secs_f64());
-        println!("  • Rate: {:.1} Hz", metrics.processing_rate_hz);
-        println!("  • Avg Processing: {:.2}μs", metrics.avg_processing_us);
-        println!("  • Min Processing: {}μs", metrics.min_processing_us);
-        println!("  • Max Processing: {}μs", metrics.max_processing_us);
-        println!("  • Theoretical Max: {:.0} Hz", metrics.theoretical_max_hz);
-        println!("--------------------------------------------------------------------------------");
+        recorder.system_mut().recycle(result);
     }
-    
-    // Final comparison
-    println!("\n================================================================================");
-    println!("📊 PERFORMANCE COMPARISON WITH PYTHON");
-    println!("================================================================================\n");
-    
-    println!("Python Performance (from previous run):");
-    println!("  • 30 cycles: 635ms @ 47.3 Hz");
-    println!("  • Processing: 55.2μs average");
-    println!("  • Theoretical Max: 18,119 Hz\n");
-    
-    println!("Rust Performance (this run):");
-    for (cycles, elapsed, metrics) in &results {
-        if *cycles == 30 {
-            println!("  • 30 cycles: {}ms @ {:.1} Hz", 
-                elapsed.as_millis(), metrics.processing_rate_hz);
-            println!("  • Processing: {:.2}μs average", metrics.avg_processing_us);
-            println!("  • Theoretical Max: {:.0} Hz", metrics.theoretical_max_hz);
-            
-            // Calculate speedup
-            let python_time_ms = 635.0;
-            let rust_time_ms = elapsed.as_millis() as f64;
-            let speedup = python_time_ms / rust_time_ms;
-            
-            let python_processing_us = 55.2;
-            let rust_processing_us = metrics.avg_processing_us;
-            let processing_speedup = python_processing_us / rust_processing_us;
-            
-            println!("\n⚡ SPEEDUP:");
-            println!("  • Overall: {:.1}x faster", speedup);
-            println!("  • Processing: {:.1}x faster", processing_speedup);
+    println!("Recorded {} cycles to {} (seed {})", cycles, output.display(), seed);
+    print_metrics(&recorder.finish()?.shutdown());
+    Ok(())
+}
+
+/// Returns whether the replay matched the recording
+fn verify(input: &Path) -> Result<bool> {
+    let report = genesis_env_awareness::replay::verify(input)?;
+    for divergence in &report.divergences {
+        println!("cycle {} differs:", divergence.cycle);
+        for field in &divergence.fields {
+            println!("  {}: recorded {}, replayed {}", field.field, field.recorded, field.replayed);
         }
     }
-    
-    // Large-scale performance
-    println!("\n🚀 LARGE-SCALE PERFORMANCE:");
-    for (cycles, elapsed, metrics) in &results {
-        if *cycles >= 1000 {
-            println!("\n{} cycles:", cycles);
-            println!("  • Time: {:.3}s", elapsed.as_secs_f64());
-            println!("  • Rate: {:.1} Hz", metrics.processing_rate_hz);
-            println!("  • Nodes: {}", metrics.spatial_nodes);
-            println!("  • Edges: {}", metrics.spatial_edges);
-            
-            // Extrapolate Python performance
-            let python_per_cycle = 635.0 / 30.0;  // ms per cycle
-            let python_estimate = python_per_cycle * (*cycles as f64);
-            let speedup = python_estimate / elapsed.as_millis() as f64;
-            
-            println!("  • Python estimate: {:.1}s", python_estimate / 1000.0);
-            println!("  • Speedup: {:.1}x", speedup);
+    println!("Replayed {} cycles, {} differ", report.cycles, report.diverged);
+    Ok(report.is_identical())
+}
+
+#[cfg(feature = "server")]
+fn serve(system: EnvironmentalAwarenessSystem, addr: std::net::SocketAddr, hz: Option<f64>, watcher: Option<ConfigWatcher>) -> Result<()> {
+    use genesis_env_awareness::server::Server;
+
+    tokio::runtime::Runtime::new()?.block_on(async {
+        let server = Server::new(system);
+        let _watch = watcher.map(|watcher| watcher.spawn(server.system(), RELOAD_INTERVAL, print_reload));
+        if let Some(hz) = hz {
+            tokio::spawn(simulate(server.system(), hz));
+        }
+        println!("Serving on http://{addr}");
+        server.serve(addr).await
+    })
+}
+
+#[cfg(feature = "grpc")]
+fn grpc(
+    system: EnvironmentalAwarenessSystem,
+    addr: std::net::SocketAddr,
+    hz: Option<f64>,
+    report: Option<(String, String)>,
+    watcher: Option<ConfigWatcher>,
+) -> Result<()> {
+    use genesis_env_awareness::fleet::FleetReporter;
+    use genesis_env_awareness::grpc::GrpcService;
+
+    tokio::runtime::Runtime::new()?.block_on(async {
+        let service = GrpcService::new(system);
+        if let Some((endpoint, device)) = report {
+            let reporter = FleetReporter::connect(endpoint, device, Some(100)).await?;
+            service.system().lock().unwrap_or_else(std::sync::PoisonError::into_inner).add_observer(Box::new(reporter));
+        }
+        let _watch = watcher.map(|watcher| watcher.spawn(service.system(), RELOAD_INTERVAL, print_reload));
+        if let Some(hz) = hz {
+            tokio::spawn(simulate(service.system(), hz));
+        }
+        println!("gRPC service on {addr}");
+        service.serve(addr).await
+    })
+}
+
+#[cfg(feature = "grpc")]
+fn fleet(addr: std::net::SocketAddr, min_devices: usize, min_fraction: f32) -> Result<()> {
+    use genesis_env_awareness::fleet::{FleetConfig, FleetService};
+    use genesis_env_awareness::grpc::proto::{fleet_client::FleetClient, StreamFleetAlertsRequest};
+
+    tokio::runtime::Runtime::new()?.block_on(async {
+        let service = FleetService::new(FleetConfig { min_devices, min_fraction, ..FleetConfig::default() });
+        let server = tokio::spawn(service.serve(addr));
+        println!("Fleet server on {addr}");
+
+        // Follow our own alert stream to print fleet-wide anomalies
+        let local = format!("http://{}:{}", std::net::Ipv4Addr::LOCALHOST, addr.port());
+        let mut alerts = loop {
+            match FleetClient::connect(local.clone()).await {
+                Ok(mut client) => break client.stream_fleet_alerts(StreamFleetAlertsRequest {}).await.map_err(std::io::Error::other)?.into_inner(),
+                Err(_) if !server.is_finished() => tokio::time::sleep(Duration::from_millis(100)).await,
+                Err(_) => return server.await.map_err(std::io::Error::other)?,
+            }
+        };
+        while let Some(alert) = alerts.message().await.map_err(std::io::Error::other)? {
+            println!(
+                "fleet anomaly: {} of {} online devices ({:.0}%): {}",
+                alert.devices.len(),
+                alert.online,
+                alert.fraction * 100.0,
+                alert.devices.join(", "),
+            );
         }
+        server.await.map_err(std::io::Error::other)?
+    })
+}
+
+/// Run simulated cycles on a served system at `hz`
+#[cfg(any(feature = "server", feature = "grpc"))]
+async fn simulate(system: std::sync::Arc<std::sync::Mutex<EnvironmentalAwarenessSystem>>, hz: f64) {
+    use std::sync::PoisonError;
+    use tokio::time::MissedTickBehavior;
+
+    let mut ticks = tokio::time::interval(Duration::from_secs_f64(1.0 / hz));
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        ticks.tick().await;
+        system.lock().unwrap_or_else(PoisonError::into_inner).run_cycle();
+    }
+}
+
+fn print_reload(outcome: Result<Vec<ConfigChange>>) {
+    match outcome {
+        Ok(changes) if changes.is_empty() => println!("config reloaded: no changes"),
+        Ok(changes) => changes.iter().for_each(|change| println!("config reloaded: {change}")),
+        Err(e) => eprintln!("config not reloaded: {e}"),
     }
-    
-    // System capabilities
-    println!("\n💪 SYSTEM CAPABILITIES:");
-    if let Some((_, _, metrics)) = results.last() {
-        println!("  • Max sustainable rate: {:.0} Hz", metrics.theoretical_max_hz);
-        println!("  • Processing latency: {:.2}μs", metrics.avg_processing_us);
-        println!("  • Memory efficient: Yes (stack-allocated, no GC)");
-        println!("  • SIMD optimized: Yes (auto-vectorization)");
-        println!("  • Parallel ready: Yes (Rayon support)");
+}
+
+fn print_cycle(result: &CycleResult) {
+    print!(
+        "cycle {:>6}  confidence {:5.1}%  node #{}  {}μs",
+        result.cycle,
+        result.confidence * 100.0,
+        result.node_id,
+        result.processing_us
+    );
+    if result.anomaly_detected {
+        print!("  ANOMALY (score {:.2})", result.anomaly_score);
+    }
+    if let Some(prediction) = &result.prediction {
+        print!("  trend {} ({:.0}%)", prediction.trend, prediction.confidence * 100.0);
     }
-    
-    // Final results JSON
-    println!("\n================================================================================");
-    println!("📦 BENCHMARK RESULTS (JSON)");
-    println!("================================================================================\n");
-    
-    let final_results = serde_json::json!({
-        "execution": "SUCCESSFUL",
-        "language": "Rust",
-        "timestamp": chrono::Local::now().to_rfc3339(),
-        "benchmarks": results.iter().map(|(cycles, elapsed, metrics)| {
-            serde_json::json!({
-                "cycles": cycles,
-                "time_ms": elapsed.as_millis(),
-                "rate_hz": format!("{:.1}", metrics.processing_rate_hz),
-                "avg_processing_us": format!("{:.2}", metrics.avg_processing_us),
-                "min_processing_us": metrics.min_processing_us,
-                "max_processing_us": metrics.max_processing_us,
-                "theoretical_max_hz": format!("{:.0}", metrics.theoretical_max_hz),
-                "nodes": metrics.spatial_nodes,
-                "edges": metrics.spatial_edges,
-            })
-        }).collect::<Vec<_>>(),
-        "comparison": {
-            "python_30_cycles_ms": 635,
-            "rust_30_cycles_ms": results[0].1.as_millis(),
-            "speedup": format!("{:.1}x", 635.0 / results[0].1.as_millis() as f64),
-        }
-    });
-    
-    println!("{}", serde_json::to_string_pretty(&final_results).unwrap());
-    
-    println!("\n✅ RUST IMPLEMENTATION COMPLETE - PERFORMANCE VERIFIED!");
-    println!("================================================================================");
-}
\ No newline at end of file
+    print!("  {}", result.mode);
+    println!();
+}
+
+fn print_metrics(metrics: &SystemMetrics) {
+    println!("Cycles:          {}", metrics.cycles);
+    println!("Rate:            {:.1} Hz", metrics.processing_rate_hz);
+    println!(
+        "Processing:      {:.2}μs avg, p50 {}μs, p95 {}μs, p99 {}μs",
+        metrics.avg_processing_us, metrics.p50_processing_us, metrics.p95_processing_us, metrics.p99_processing_us
+    );
+    println!("Theoretical max: {:.0} Hz", metrics.theoretical_max_hz);
+    let windows = &metrics.windows;
+    println!(
+        "Recent:          {:.1} Hz, p99 {}μs (1s); {:.1} Hz, p99 {}μs (10s); {:.1} Hz, p99 {}μs (60s)",
+        windows.last_1s.rate_hz,
+        windows.last_1s.p99_processing_us,
+        windows.last_10s.rate_hz,
+        windows.last_10s.p99_processing_us,
+        windows.last_60s.rate_hz,
+        windows.last_60s.p99_processing_us
+    );
+    let stages = &metrics.stages;
+    println!(
+        "Stages (p99):    sensor {}ns, neural {}ns, spatial {}ns, anomaly {}ns, prediction {}ns",
+        stages.sensor.p99_ns, stages.neural.p99_ns, stages.spatial.p99_ns, stages.anomaly.p99_ns, stages.prediction.p99_ns
+    );
+    println!("Spatial graph:   {} nodes, {} edges", metrics.spatial_nodes, metrics.spatial_edges);
+    println!(
+        "Buffer pool:     {:.1}% reused, {} allocated",
+        metrics.buffer_pool.hit_rate() * 100.0,
+        metrics.buffer_pool.misses
+    );
+    println!("Anomalies:       {}", metrics.anomalies_detected);
+    println!("Predictions:     {}", metrics.predictions_made);
+}
+
+fn print_schedule(report: &ScheduleReport) {
+    println!(
+        "Schedule:        {:.1} of {:.1} Hz, {} overruns ({} ticks skipped)",
+        report.achieved_hz, report.target_hz, report.overruns, report.skipped_ticks
+    );
+    println!(
+        "Start jitter:    {:.1}μs avg, p99 {}μs, max {}μs",
+        report.jitter.avg_ns / 1000.0,
+        report.jitter.p99_ns / 1000,
+        report.jitter.max_ns / 1000
+    );
+}
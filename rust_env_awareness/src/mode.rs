@@ -0,0 +1,220 @@
+//! Behavior modes decided every cycle
+//!
+//! Consumers that drive a robot from `CycleResult`s tend to grow the same
+//! if/else ladder: avoid on an anomaly, navigate when confident, explore when
+//! less so, and idle otherwise. `ModeMachine` runs that ladder inside the
+//! cycle and reports the mode and any transition in the result. Hysteresis
+//! stops a confidence hovering at a threshold from flapping between modes. A
+//! hold keeps the system avoiding for a few calm cycles after an anomaly.
+
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::anomaly::Severity;
+use crate::predictor::Trend;
+
+/// What the system should be doing, from most to least at ease
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BehaviorMode {
+    #[default]
+    Idle,
+    Exploring,
+    Navigating,
+    Avoiding,
+}
+
+impl BehaviorMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BehaviorMode::Idle => "idle",
+            BehaviorMode::Exploring => "exploring",
+            BehaviorMode::Navigating => "navigating",
+            BehaviorMode::Avoiding => "avoiding",
+        }
+    }
+}
+
+impl fmt::Display for BehaviorMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Why the mode changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModeTrigger {
+    /// An anomaly at or above `avoid_severity`
+    Anomaly,
+    /// `avoid_cycles` cycles passed without one
+    AnomalyCleared,
+    /// Confidence crossed a threshold
+    Confidence,
+    /// The forecast turned down while navigating
+    Forecast,
+}
+
+/// A change of mode within one cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModeTransition {
+    pub from: BehaviorMode,
+    pub to: BehaviorMode,
+    pub trigger: ModeTrigger,
+}
+
+/// Thresholds of the mode state machine
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModeConfig {
+    /// Confidence from which the system explores
+    pub explore_confidence: f32,
+    /// Confidence from which the system navigates
+    pub navigate_confidence: f32,
+    /// How far confidence must fall below a threshold to leave the mode it entered
+    pub hysteresis: f32,
+    /// Least severity of an anomaly that switches to avoiding
+    pub avoid_severity: Severity,
+    /// Anomaly-free cycles spent avoiding before confidence decides again
+    pub avoid_cycles: u32,
+    /// Drop from navigating to exploring while the forecast is decreasing
+    pub cautious_on_decline: bool,
+}
+
+impl Default for ModeConfig {
+    fn default() -> Self {
+        Self {
+            explore_confidence: 0.5,
+            navigate_confidence: 0.8,
+            hysteresis: 0.05,
+            avoid_severity: Severity::Low,
+            avoid_cycles: 3,
+            cautious_on_decline: true,
+        }
+    }
+}
+
+/// Current behavior mode and the state needed to decide the next one
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModeMachine {
+    config: ModeConfig,
+    mode: BehaviorMode,
+    /// Anomaly-free cycles since avoiding started
+    calm: u32,
+}
+
+impl ModeMachine {
+    pub fn new(config: ModeConfig) -> Self {
+        Self { config, ..Self::default() }
+    }
+
+    pub fn config(&self) -> ModeConfig {
+        self.config
+    }
+
+    /// Use new thresholds from the next update; the current mode is kept
+    pub fn set_config(&mut self, config: ModeConfig) {
+        self.config = config;
+    }
+
+    pub fn mode(&self) -> BehaviorMode {
+        self.mode
+    }
+
+    /// Decide the mode for a cycle's confidence, anomaly severity and forecast trend
+    pub fn update(&mut self, confidence: f32, severity: Option<Severity>, trend: Option<Trend>) -> Option<ModeTransition> {
+        let (to, trigger) = self.next(confidence, severity, trend);
+        if to == self.mode {
+            return None;
+        }
+        let transition = ModeTransition { from: self.mode, to, trigger };
+        self.mode = to;
+        Some(transition)
+    }
+
+    fn next(&mut self, confidence: f32, severity: Option<Severity>, trend: Option<Trend>) -> (BehaviorMode, ModeTrigger) {
+        let config = &self.config;
+        if severity.is_some_and(|severity| severity >= config.avoid_severity) {
+            self.calm = 0;
+            return (BehaviorMode::Avoiding, ModeTrigger::Anomaly);
+        }
+        let avoiding = self.mode == BehaviorMode::Avoiding;
+        if avoiding {
+            self.calm += 1;
+            if self.calm <= config.avoid_cycles {
+                return (BehaviorMode::Avoiding, ModeTrigger::Anomaly);
+            }
+        }
+
+        // Staying in a mode takes `hysteresis` less confidence than entering it
+        let navigating = self.mode == BehaviorMode::Navigating;
+        let exploring = navigating || self.mode == BehaviorMode::Exploring;
+        let margin = |held: bool| if held { config.hysteresis } else { 0.0 };
+        let confident = confidence >= config.navigate_confidence - margin(navigating);
+        let declining = config.cautious_on_decline && trend == Some(Trend::Decreasing);
+        let to = if confident && !declining {
+            BehaviorMode::Navigating
+        } else if confident || confidence >= config.explore_confidence - margin(exploring) {
+            BehaviorMode::Exploring
+        } else {
+            BehaviorMode::Idle
+        };
+        let trigger = if avoiding {
+            ModeTrigger::AnomalyCleared
+        } else if confident && declining {
+            ModeTrigger::Forecast
+        } else {
+            ModeTrigger::Confidence
+        };
+        (to, trigger)
+    }
+
+    /// Return to idle
+    pub fn clear(&mut self) {
+        self.mode = BehaviorMode::Idle;
+        self.calm = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_confidence_hysteresis() {
+        use BehaviorMode::*;
+        let mut machine = ModeMachine::default();
+        let mut modes = |confidences: &[f32]| -> Vec<BehaviorMode> {
+            confidences.iter().map(|&c| {
+                machine.update(c, None, None);
+                machine.mode()
+            }).collect()
+        };
+        assert_eq!(modes(&[0.3, 0.5, 0.47, 0.44]), [Idle, Exploring, Exploring, Idle]);
+        assert_eq!(modes(&[0.81, 0.78, 0.74, 0.6, 0.79]), [Navigating, Navigating, Exploring, Exploring, Exploring]);
+
+        // A falling forecast holds the system back from navigating
+        let transition = machine.update(0.9, None, Some(Trend::Decreasing));
+        assert_eq!(transition, None);
+        machine.update(0.9, None, Some(Trend::Stable));
+        let transition = machine.update(0.9, None, Some(Trend::Decreasing)).unwrap();
+        assert_eq!((transition.from, transition.to, transition.trigger), (Navigating, Exploring, ModeTrigger::Forecast));
+    }
+
+    #[test]
+    fn test_avoid_hold() {
+        let config = ModeConfig { avoid_severity: Severity::Medium, avoid_cycles: 2, ..ModeConfig::default() };
+        let mut machine = ModeMachine::new(config);
+        machine.update(0.9, None, None);
+        assert_eq!(machine.update(0.9, Some(Severity::Low), None), None);
+
+        let transition = machine.update(0.9, Some(Severity::High), None).unwrap();
+        assert_eq!((transition.from, transition.to), (BehaviorMode::Navigating, BehaviorMode::Avoiding));
+        assert_eq!(transition.trigger, ModeTrigger::Anomaly);
+        assert_eq!(machine.update(0.9, None, None), None);
+        assert_eq!(machine.update(0.9, None, None), None);
+        let transition = machine.update(0.6, None, None).unwrap();
+        assert_eq!((transition.to, transition.trigger), (BehaviorMode::Exploring, ModeTrigger::AnomalyCleared));
+    }
+}
@@ -1,10 +1,16 @@
 //! High-performance neural network implementation with SIMD optimization
 
-use rand::{thread_rng, Rng};
-use std::f32;
+use alloc::vec;
+use alloc::vec::Vec;
+use rand::Rng;
+#[cfg(feature = "std")]
+use rand::thread_rng;
+use serde::{Serialize, Deserialize};
+
+use crate::error::{GenesisError, Result};
 
 /// Simple feed-forward neural network optimized for performance
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NeuralNetwork {
     weights1: Vec<Vec<f32>>,
     weights2: Vec<Vec<f32>>,
@@ -12,16 +18,23 @@ pub struct NeuralNetwork {
     bias2: Vec<f32>,
     hidden_size: usize,
     output_size: usize,
+    /// Training steps applied since initialization
+    #[serde(default)]
+    version: u64,
 }
 
 impl NeuralNetwork {
     /// Create a new neural network
+    #[cfg(feature = "std")]
     pub fn new(input_size: usize, hidden_size: usize, output_size: usize) -> Self {
-        let mut rng = thread_rng();
-        
+        Self::with_rng(input_size, hidden_size, output_size, &mut thread_rng())
+    }
+    
+    /// Create a network whose initial weights are drawn from `rng`
+    pub fn with_rng<R: Rng + ?Sized>(input_size: usize, hidden_size: usize, output_size: usize, rng: &mut R) -> Self {
         // Initialize weights using Xavier initialization
-        let scale1 = (2.0 / input_size as f32).sqrt();
-        let scale2 = (2.0 / hidden_size as f32).sqrt();
+        let scale1 = libm::sqrtf(2.0 / input_size as f32);
+        let scale2 = libm::sqrtf(2.0 / hidden_size as f32);
         
         let weights1 = (0..input_size)
             .map(|_| {
@@ -49,7 +62,30 @@ impl NeuralNetwork {
             bias2,
             hidden_size,
             output_size,
+            version: 0,
+        }
+    }
+    
+    /// Number of inputs the network expects
+    pub fn input_size(&self) -> usize {
+        self.weights1.len()
+    }
+    
+    /// Training steps applied so far; tells apart outputs of different weights
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+    
+    /// Forward pass that rejects inputs of the wrong length instead of panicking
+    pub fn try_forward(&self, inputs: &[f32]) -> Result<Vec<f32>> {
+        if inputs.len() != self.input_size() {
+            return Err(GenesisError::DimensionMismatch {
+                what: "network input",
+                expected: self.input_size(),
+                actual: inputs.len(),
+            });
         }
+        Ok(self.forward(inputs))
     }
     
     /// Fast sigmoid approximation for better performance
@@ -61,32 +97,32 @@ impl NeuralNetwork {
     
     /// Forward pass through the network (optimized)
     pub fn forward(&self, inputs: &[f32]) -> Vec<f32> {
-        // Hidden layer computation with manual loop unrolling
-        let mut hidden = vec![0.0; self.hidden_size];
+        let mut output = Vec::with_capacity(self.hidden_size + self.output_size);
+        self.forward_with_buffer(inputs, &mut output);
+        output
+    }
+    
+    /// Forward pass writing the outputs into `output`, reusing its allocation.
+    /// The hidden layer is computed in the same buffer, so once it has held
+    /// both layers the pass allocates nothing.
+    pub fn forward_with_buffer(&self, inputs: &[f32], output: &mut Vec<f32>) {
+        output.clear();
+        output.resize(self.hidden_size + self.output_size, 0.0);
+        let (hidden, outputs) = output.split_at_mut(self.hidden_size);
         
         // Matrix multiplication for hidden layer
-        for j in 0..self.hidden_size {
+        for (j, activation) in hidden.iter_mut().enumerate() {
             let mut sum = self.bias1[j];
             
-            // Manual unrolling for better performance (assuming input size of 4)
-            if inputs.len() == 4 {
-                sum += inputs[0] * self.weights1[0][j];
-                sum += inputs[1] * self.weights1[1][j];
-                sum += inputs[2] * self.weights1[2][j];
-                sum += inputs[3] * self.weights1[3][j];
-            } else {
-                for (i, &input) in inputs.iter().enumerate() {
-                    sum += input * self.weights1[i][j];
-                }
+            for (&input, weights) in inputs.iter().zip(&self.weights1) {
+                sum += input * weights[j];
             }
             
-            hidden[j] = Self::fast_sigmoid(sum);
+            *activation = Self::fast_sigmoid(sum);
         }
         
         // Output layer computation
-        let mut output = vec![0.0; self.output_size];
-        
-        for j in 0..self.output_size {
+        for (j, activation) in outputs.iter_mut().enumerate() {
             let mut sum = self.bias2[j];
             
             // Vectorized dot product
@@ -94,10 +130,65 @@ impl NeuralNetwork {
                 sum += h * self.weights2[i][j];
             }
             
-            output[j] = Self::fast_sigmoid(sum);
+            *activation = Self::fast_sigmoid(sum);
         }
         
-        output
+        // Drop the hidden layer, keeping the outputs
+        output.drain(..self.hidden_size);
+    }
+    
+    /// Derivative of `fast_sigmoid`
+    #[inline(always)]
+    fn fast_sigmoid_slope(x: f32) -> f32 {
+        let d = 1.0 + x.abs();
+        0.5 / (d * d)
+    }
+    
+    /// One stochastic gradient descent step on squared error; returns the error before the update
+    pub fn train(&mut self, inputs: &[f32], targets: &[f32], learning_rate: f32) -> f32 {
+        let hidden_sums: Vec<f32> = (0..self.hidden_size)
+            .map(|j| self.bias1[j] + inputs.iter().zip(&self.weights1).map(|(x, w)| x * w[j]).sum::<f32>())
+            .collect();
+        let hidden: Vec<f32> = hidden_sums.iter().map(|&s| Self::fast_sigmoid(s)).collect();
+        let output_sums: Vec<f32> = (0..self.output_size)
+            .map(|k| self.bias2[k] + hidden.iter().zip(&self.weights2).map(|(h, w)| h * w[k]).sum::<f32>())
+            .collect();
+        
+        let mut loss = 0.0;
+        let output_deltas: Vec<f32> = output_sums
+            .iter()
+            .zip(targets)
+            .map(|(&s, &t)| {
+                let error = Self::fast_sigmoid(s) - t;
+                loss += error * error;
+                error * Self::fast_sigmoid_slope(s)
+            })
+            .collect();
+        let hidden_deltas: Vec<f32> = (0..self.hidden_size)
+            .map(|j| {
+                let back: f32 = self.weights2[j].iter().zip(&output_deltas).map(|(w, d)| w * d).sum();
+                back * Self::fast_sigmoid_slope(hidden_sums[j])
+            })
+            .collect();
+        
+        for (row, &h) in self.weights2.iter_mut().zip(&hidden) {
+            for (w, d) in row.iter_mut().zip(&output_deltas) {
+                *w -= learning_rate * d * h;
+            }
+        }
+        for (b, d) in self.bias2.iter_mut().zip(&output_deltas) {
+            *b -= learning_rate * d;
+        }
+        for (row, &x) in self.weights1.iter_mut().zip(inputs) {
+            for (w, d) in row.iter_mut().zip(&hidden_deltas) {
+                *w -= learning_rate * d * x;
+            }
+        }
+        for (b, d) in self.bias1.iter_mut().zip(&hidden_deltas) {
+            *b -= learning_rate * d;
+        }
+        self.version += 1;
+        loss
     }
     
     /// Batch forward pass for multiple inputs (uses SIMD where possible)
@@ -108,7 +199,7 @@ impl NeuralNetwork {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
     
@@ -129,8 +220,26 @@ mod tests {
         
         assert_eq!(output.len(), 2);
         for &val in &output {
-            assert!(val >= 0.0 && val <= 1.0, "Output should be in [0, 1]");
+            assert!((0.0..=1.0).contains(&val), "Output should be in [0, 1]");
+        }
+    }
+    
+    #[test]
+    fn test_train_reduces_error() {
+        let mut nn = NeuralNetwork::new(2, 6, 1);
+        let samples = [([0.1, 0.2], 0.3), ([0.6, 0.1], 0.7), ([0.4, 0.4], 0.8), ([0.0, 0.5], 0.5)];
+        let loss = |nn: &NeuralNetwork| -> f32 {
+            samples.iter().map(|(x, t)| (nn.forward(x)[0] - t).powi(2)).sum()
+        };
+        
+        let before = loss(&nn);
+        for _ in 0..2000 {
+            for (x, t) in &samples {
+                nn.train(x, &[*t], 0.5);
+            }
         }
+        assert!(loss(&nn) < before.min(0.01), "loss {} -> {}", before, loss(&nn));
+        assert_eq!(nn.version(), 8000);
     }
     
     #[test]
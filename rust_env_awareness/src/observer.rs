@@ -0,0 +1,115 @@
+//! Push-style hooks into the processing cycle
+//!
+//! Observers registered with `EnvironmentalAwarenessSystem::add_observer` are
+//! called from inside each cycle, so integrations no longer need to inspect
+//! every `CycleResult` or poll `get_metrics` themselves.
+
+use std::fmt;
+
+use crate::anomaly::Anomaly;
+use crate::{CycleResult, PredictionResult, SystemMetrics};
+
+/// Receives system events as cycles run; every hook defaults to doing nothing
+pub trait SystemObserver: fmt::Debug + Send {
+    /// Called once per cycle with its full result
+    fn on_cycle(&mut self, _result: &CycleResult) {}
+
+    /// Called for each anomaly that is not suppressed
+    fn on_anomaly(&mut self, _anomaly: &Anomaly) {}
+
+    /// Called whenever the cycle produced a forecast
+    fn on_prediction(&mut self, _cycle: u32, _prediction: &PredictionResult) {}
+
+    /// Called with fresh metrics every `metrics_interval` cycles
+    fn on_metrics(&mut self, _metrics: &SystemMetrics) {}
+
+    /// Called once by `EnvironmentalAwarenessSystem::shutdown` with the final metrics
+    fn on_shutdown(&mut self, _metrics: &SystemMetrics) {}
+
+    /// Cycles between `on_metrics` calls; `None` (the default) never collects metrics
+    fn metrics_interval(&self) -> Option<u32> {
+        None
+    }
+}
+
+/// Deliver one cycle's events to every observer, computing metrics only if one is due
+pub(crate) fn notify(
+    observers: &mut [Box<dyn SystemObserver>],
+    result: &CycleResult,
+    anomaly: Option<&Anomaly>,
+    metrics: impl FnOnce() -> SystemMetrics,
+) {
+    let due = |observer: &dyn SystemObserver| {
+        observer.metrics_interval().is_some_and(|every| every > 0 && result.cycle.is_multiple_of(every))
+    };
+    let metrics = observers.iter().any(|observer| due(observer.as_ref())).then(metrics);
+
+    for observer in observers.iter_mut() {
+        if let Some(anomaly) = anomaly {
+            observer.on_anomaly(anomaly);
+        }
+        if let Some(prediction) = &result.prediction {
+            observer.on_prediction(result.cycle, prediction);
+        }
+        if let Some(metrics) = metrics.as_ref().filter(|_| due(observer.as_ref())) {
+            observer.on_metrics(metrics);
+        }
+        observer.on_cycle(result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use crate::EnvironmentalAwarenessSystem;
+
+    #[derive(Debug, Default)]
+    struct Counts {
+        cycles: u32,
+        anomalies: usize,
+        predictions: usize,
+        metrics: Vec<u32>,
+    }
+
+    #[derive(Debug)]
+    struct Recorder(Arc<Mutex<Counts>>);
+
+    impl SystemObserver for Recorder {
+        fn on_cycle(&mut self, _result: &CycleResult) {
+            self.0.lock().unwrap().cycles += 1;
+        }
+
+        fn on_anomaly(&mut self, _anomaly: &Anomaly) {
+            self.0.lock().unwrap().anomalies += 1;
+        }
+
+        fn on_prediction(&mut self, _cycle: u32, _prediction: &PredictionResult) {
+            self.0.lock().unwrap().predictions += 1;
+        }
+
+        fn on_metrics(&mut self, metrics: &SystemMetrics) {
+            self.0.lock().unwrap().metrics.push(metrics.cycles);
+        }
+
+        fn metrics_interval(&self) -> Option<u32> {
+            Some(10)
+        }
+    }
+
+    #[test]
+    fn test_observer_hooks() {
+        let counts = Arc::new(Mutex::new(Counts::default()));
+        let mut system = EnvironmentalAwarenessSystem::new();
+        system.add_observer(Box::new(Recorder(Arc::clone(&counts))));
+        let anomalies = system.subscribe_anomalies();
+
+        let results = system.run_cycles(30);
+
+        let counts = counts.lock().unwrap();
+        assert_eq!(counts.cycles, 30);
+        assert_eq!(counts.anomalies, anomalies.try_iter().count());
+        assert_eq!(counts.predictions, results.iter().filter(|r| r.prediction.is_some()).count());
+        assert_eq!(counts.metrics, vec![10, 20, 30]);
+    }
+}
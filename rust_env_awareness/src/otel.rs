@@ -0,0 +1,271 @@
+//! OpenTelemetry export of cycle metrics and stage spans
+//!
+//! An `OtelExporter` sends to any OTLP/HTTP endpoint: an OpenTelemetry
+//! Collector, Grafana Tempo or Honeycomb. Its `observer` records a cycle
+//! latency histogram and cycle, anomaly and forecast counters; register it
+//! with `EnvironmentalAwarenessSystem::add_observer`. Its `layer` turns the
+//! `tracing` spans the system opens for every cycle and stage (`cycle`,
+//! `sensor`, `neural`, `spatial`, `anomaly`, `predictor` and custom stages)
+//! into OTLP spans; add it to the application's subscriber.
+//!
+//! Export runs on the SDK's own background threads, so no async runtime is
+//! needed. Call `shutdown` before exiting to flush what is still buffered.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use opentelemetry::metrics::{Counter, Gauge, Histogram, Meter, MeterProvider as _};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::{MetricExporter, SpanExporter, WithExportConfig, WithHttpConfig};
+use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
+use opentelemetry_sdk::trace::{SdkTracerProvider, Tracer};
+use opentelemetry_sdk::Resource;
+use tracing::Subscriber;
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::anomaly::Anomaly;
+use crate::error::{GenesisError, Result};
+use crate::observer::SystemObserver;
+use crate::{CycleResult, PredictionResult};
+
+/// Instrumentation scope of every metric and span
+const SCOPE: &str = "genesis_env_awareness";
+
+/// Cycle latency histogram buckets, in seconds: 50 μs to 100 ms
+const LATENCY_BOUNDARIES: [f64; 11] = [50e-6, 100e-6, 250e-6, 500e-6, 1e-3, 2.5e-3, 5e-3, 10e-3, 25e-3, 50e-3, 100e-3];
+
+/// Where and how often to export
+#[derive(Debug, Clone)]
+pub struct OtelConfig {
+    /// OTLP/HTTP base URL such as `http://localhost:4318`; `/v1/traces` and
+    /// `/v1/metrics` are appended. `None` follows the standard
+    /// `OTEL_EXPORTER_OTLP_ENDPOINT` variables, defaulting to localhost.
+    pub endpoint: Option<String>,
+    /// Sent with every export, e.g. Honeycomb's `x-honeycomb-team` key; merged
+    /// with `OTEL_EXPORTER_OTLP_HEADERS`
+    pub headers: HashMap<String, String>,
+    /// `service.name` of the exported resource
+    pub service_name: String,
+    /// Time between metric exports
+    pub export_interval: Duration,
+}
+
+impl Default for OtelConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: None,
+            headers: HashMap::new(),
+            service_name: "genesis".into(),
+            export_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+impl OtelConfig {
+    /// Export to the collector at `endpoint`
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self { endpoint: Some(endpoint.into()), ..Self::default() }
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
+
+    pub fn service_name(mut self, name: impl Into<String>) -> Self {
+        self.service_name = name.into();
+        self
+    }
+
+    pub fn export_interval(mut self, interval: Duration) -> Self {
+        self.export_interval = interval;
+        self
+    }
+
+    /// Full URL of one signal's endpoint, if one is configured
+    fn signal_url(&self, signal: &str) -> Option<String> {
+        self.endpoint.as_ref().map(|endpoint| format!("{}/v1/{signal}", endpoint.trim_end_matches('/')))
+    }
+}
+
+/// Span and metric pipelines feeding an OTLP endpoint
+#[derive(Debug, Clone)]
+pub struct OtelExporter {
+    tracer_provider: SdkTracerProvider,
+    meter_provider: SdkMeterProvider,
+}
+
+impl OtelExporter {
+    /// Start exporting over OTLP/HTTP as `config` says
+    pub fn new(config: &OtelConfig) -> Result<Self> {
+        let resource = Resource::builder().with_service_name(config.service_name.clone()).build();
+
+        let mut spans = SpanExporter::builder().with_http().with_headers(config.headers.clone());
+        if let Some(url) = config.signal_url("traces") {
+            spans = spans.with_endpoint(url);
+        }
+        let tracer_provider = SdkTracerProvider::builder()
+            .with_resource(resource.clone())
+            .with_batch_exporter(spans.build().map_err(otel_error)?)
+            .build();
+
+        let mut metrics = MetricExporter::builder().with_http().with_headers(config.headers.clone());
+        if let Some(url) = config.signal_url("metrics") {
+            metrics = metrics.with_endpoint(url);
+        }
+        let reader = PeriodicReader::builder(metrics.build().map_err(otel_error)?)
+            .with_interval(config.export_interval)
+            .build();
+        let meter_provider = SdkMeterProvider::builder().with_resource(resource).with_reader(reader).build();
+
+        Ok(Self::from_providers(tracer_provider, meter_provider))
+    }
+
+    /// Export through pipelines set up by the application, for other
+    /// exporters, samplers or readers
+    pub fn from_providers(tracer_provider: SdkTracerProvider, meter_provider: SdkMeterProvider) -> Self {
+        Self { tracer_provider, meter_provider }
+    }
+
+    /// `tracing` layer exporting every span, including the system's cycle and stage spans
+    pub fn layer<S>(&self) -> OpenTelemetryLayer<S, Tracer>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+    {
+        tracing_opentelemetry::layer().with_tracer(self.tracer_provider.tracer(SCOPE))
+    }
+
+    /// Observer recording cycle metrics; add one per system
+    pub fn observer(&self) -> OtelObserver {
+        OtelObserver::new(&self.meter_provider.meter(SCOPE))
+    }
+
+    /// Export whatever is buffered now
+    pub fn flush(&self) -> Result<()> {
+        self.tracer_provider.force_flush().map_err(otel_error)?;
+        self.meter_provider.force_flush().map_err(otel_error)
+    }
+
+    /// Flush and stop both pipelines; observers and layers record nothing afterwards
+    pub fn shutdown(&self) -> Result<()> {
+        let traces = self.tracer_provider.shutdown().map_err(otel_error);
+        self.meter_provider.shutdown().map_err(otel_error).and(traces)
+    }
+}
+
+fn otel_error(error: impl std::fmt::Display) -> GenesisError {
+    GenesisError::Otel(error.to_string())
+}
+
+/// Records each cycle to OpenTelemetry instruments
+///
+/// | Instrument | Kind | Unit |
+/// |---|---|---|
+/// | `genesis.cycle.duration` | histogram | s |
+/// | `genesis.cycles` | counter | |
+/// | `genesis.anomalies` (by `severity`) | counter | |
+/// | `genesis.predictions` | counter | |
+/// | `genesis.confidence` | gauge | |
+#[derive(Debug, Clone)]
+pub struct OtelObserver {
+    duration: Histogram<f64>,
+    cycles: Counter<u64>,
+    anomalies: Counter<u64>,
+    predictions: Counter<u64>,
+    confidence: Gauge<f64>,
+}
+
+impl OtelObserver {
+    /// Create the instruments on `meter`
+    pub fn new(meter: &Meter) -> Self {
+        Self {
+            duration: meter
+                .f64_histogram("genesis.cycle.duration")
+                .with_description("Processing time of one cycle")
+                .with_unit("s")
+                .with_boundaries(LATENCY_BOUNDARIES.to_vec())
+                .build(),
+            cycles: meter.u64_counter("genesis.cycles").with_description("Cycles completed").build(),
+            anomalies: meter.u64_counter("genesis.anomalies").with_description("Anomalies reported").build(),
+            predictions: meter.u64_counter("genesis.predictions").with_description("Forecasts made").build(),
+            confidence: meter.f64_gauge("genesis.confidence").with_description("Fused confidence of the latest cycle").build(),
+        }
+    }
+}
+
+impl SystemObserver for OtelObserver {
+    fn on_cycle(&mut self, result: &CycleResult) {
+        self.duration.record(result.processing_us as f64 / 1e6, &[]);
+        self.cycles.add(1, &[]);
+        self.confidence.record(result.confidence as f64, &[]);
+    }
+
+    fn on_anomaly(&mut self, anomaly: &Anomaly) {
+        self.anomalies.add(1, &[KeyValue::new("severity", format!("{:?}", anomaly.severity))]);
+    }
+
+    fn on_prediction(&mut self, _cycle: u32, _prediction: &PredictionResult) {
+        self.predictions.add(1, &[]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry_sdk::metrics::data::{AggregatedMetrics, MetricData};
+    use opentelemetry_sdk::metrics::InMemoryMetricExporter;
+    use opentelemetry_sdk::trace::InMemorySpanExporter;
+    use tracing_subscriber::layer::SubscriberExt;
+    use crate::EnvironmentalAwarenessSystem;
+
+    #[test]
+    fn test_exports_cycle_metrics_and_stage_spans() {
+        let spans = InMemorySpanExporter::default();
+        let metrics = InMemoryMetricExporter::default();
+        let exporter = OtelExporter::from_providers(
+            SdkTracerProvider::builder().with_simple_exporter(spans.clone()).build(),
+            SdkMeterProvider::builder().with_reader(PeriodicReader::builder(metrics.clone()).build()).build(),
+        );
+        let subscriber = tracing_subscriber::registry().with(exporter.layer());
+
+        let mut system = EnvironmentalAwarenessSystem::builder().seed(3).build().unwrap();
+        system.add_observer(Box::new(exporter.observer()));
+        let results = tracing::subscriber::with_default(subscriber, || system.run_cycles(50));
+        exporter.flush().unwrap();
+
+        // One `cycle` span per cycle, with every stage nested inside it
+        let spans = spans.get_finished_spans().unwrap();
+        let cycles: Vec<_> = spans.iter().filter(|span| span.name == "cycle").collect();
+        assert_eq!(cycles.len(), 50);
+        let sensor = spans.iter().find(|span| span.name == "sensor").unwrap();
+        assert!(cycles.iter().any(|cycle| cycle.span_context.span_id() == sensor.parent_span_id));
+        for stage in ["neural", "spatial", "anomaly", "predictor"] {
+            assert!(spans.iter().any(|span| span.name == stage), "no {stage} span");
+        }
+
+        let exported = metrics.get_finished_metrics().unwrap();
+        let metric = |name: &str| {
+            let last = exported.last().unwrap();
+            let metric = last.scope_metrics().flat_map(|scope| scope.metrics()).find(|metric| metric.name() == name);
+            metric.map(|metric| metric.data())
+        };
+        let Some(AggregatedMetrics::F64(MetricData::Histogram(duration))) = metric("genesis.cycle.duration") else {
+            panic!("no cycle duration histogram");
+        };
+        assert_eq!(duration.data_points().map(|point| point.count()).sum::<u64>(), 50);
+        let Some(AggregatedMetrics::U64(MetricData::Sum(cycles))) = metric("genesis.cycles") else {
+            panic!("no cycle counter");
+        };
+        assert_eq!(cycles.data_points().map(|point| point.value()).sum::<u64>(), 50);
+        // Suppressed repeats are detected but not reported
+        let detected = results.iter().filter(|result| result.anomaly_detected).count() as u64;
+        let reported = match metric("genesis.anomalies") {
+            Some(AggregatedMetrics::U64(MetricData::Sum(sum))) => sum.data_points().map(|point| point.value()).sum(),
+            _ => 0,
+        };
+        assert!(reported <= detected && (reported > 0) == (detected > 0), "{reported} of {detected}");
+        exporter.shutdown().unwrap();
+    }
+}
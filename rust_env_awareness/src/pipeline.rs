@@ -0,0 +1,282 @@
+//! Multi-threaded staged processing
+//!
+//! `Pipeline` splits a cycle into stages on their own threads, connected by
+//! bounded channels:
+//!
+//! ```text
+//! ingest (validate + sensor fusion) → inference → spatial/anomaly/prediction → output
+//! ```
+//!
+//! While one reading is being integrated into the map the next is already
+//! being fused and run through the network. Full channels block the
+//! producer, so memory stays bounded when input outpaces processing.
+//!
+//! `pause` holds the final stage between cycles while readings keep queueing,
+//! and `shutdown` drains everything queued before quiescing the system.
+
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+
+use crate::error::Result;
+use crate::neural::NeuralNetwork;
+use crate::sensors::{ProcessedSensorData, SensorData};
+use crate::{CycleResult, EnvironmentalAwarenessSystem, SystemMetrics};
+
+type Inferred = Result<(ProcessedSensorData, Vec<f32>)>;
+
+/// Lets the integrate stage be held between cycles
+#[derive(Debug, Default)]
+struct Gate {
+    state: Mutex<GateState>,
+    changed: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct GateState {
+    paused: bool,
+    /// A cycle is being integrated
+    busy: bool,
+}
+
+impl Gate {
+    fn lock(&self) -> MutexGuard<'_, GateState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn wait_while(&self, condition: impl FnMut(&mut GateState) -> bool) {
+        drop(self.changed.wait_while(self.lock(), condition).unwrap_or_else(PoisonError::into_inner));
+    }
+
+    /// Wait until running, then mark a cycle as started
+    fn enter(&self) {
+        let mut state = self.changed.wait_while(self.lock(), |state| state.paused).unwrap_or_else(PoisonError::into_inner);
+        state.busy = true;
+    }
+
+    /// Mark the current cycle as finished
+    fn leave(&self) {
+        self.lock().busy = false;
+        self.changed.notify_all();
+    }
+
+    fn set_paused(&self, paused: bool) {
+        self.lock().paused = paused;
+        self.changed.notify_all();
+    }
+}
+
+/// What `Pipeline::shutdown` hands back
+#[derive(Debug)]
+pub struct Shutdown {
+    pub system: EnvironmentalAwarenessSystem,
+    /// Results that had not been received, in input order
+    pub remaining: Vec<Result<CycleResult>>,
+    /// Final metrics after every queued reading was processed
+    pub metrics: SystemMetrics,
+}
+
+/// A system running as a staged pipeline (see `EnvironmentalAwarenessSystem::into_pipeline`)
+#[derive(Debug)]
+pub struct Pipeline {
+    input: SyncSender<SensorData>,
+    output: Receiver<Result<CycleResult>>,
+    workers: Vec<JoinHandle<()>>,
+    system: JoinHandle<EnvironmentalAwarenessSystem>,
+    gate: Arc<Gate>,
+}
+
+impl Pipeline {
+    /// Move `system` onto worker threads; every channel holds up to `capacity` items
+    ///
+    /// Processing time in the results covers the final stage only, since
+    /// sensor fusion and inference overlap with earlier cycles.
+    pub fn spawn(mut system: EnvironmentalAwarenessSystem, capacity: usize) -> Result<Self> {
+        let capacity = capacity.max(1);
+        let (input, readings) = mpsc::sync_channel::<SensorData>(capacity);
+        let (fused_tx, fused) = mpsc::sync_channel::<Result<ProcessedSensorData>>(capacity);
+        let (inferred_tx, inferred) = mpsc::sync_channel::<Inferred>(capacity);
+        let (output_tx, output) = mpsc::sync_channel(capacity);
+
+        let processor = system.sensor_processor.clone();
+        let ingest = thread::Builder::new().name("genesis-ingest".into()).spawn(move || {
+            for data in readings {
+                let fused = data.validate().map(|()| processor.process(&data));
+                if fused_tx.send(fused).is_err() {
+                    break;
+                }
+            }
+        })?;
+
+        let network: Arc<NeuralNetwork> = Arc::clone(&system.neural_net);
+        let inference = thread::Builder::new().name("genesis-inference".into()).spawn(move || {
+            for fused in fused {
+                let inferred = fused.map(|processed| {
+                    let output = network.forward(&processed.features);
+                    (processed, output)
+                });
+                if inferred_tx.send(inferred).is_err() {
+                    break;
+                }
+            }
+        })?;
+
+        let gate = Arc::new(Gate::default());
+        let stage_gate = Arc::clone(&gate);
+        let system = thread::Builder::new().name("genesis-integrate".into()).spawn(move || {
+            for inferred in inferred {
+                stage_gate.enter();
+                let result = inferred.map(|(processed, output)| system.process_inferred(processed, output));
+                // The cycle is complete even if the consumer is slow to take it
+                stage_gate.leave();
+                if output_tx.send(result).is_err() {
+                    break;
+                }
+            }
+            system
+        })?;
+
+        Ok(Self {
+            input,
+            output,
+            workers: vec![ingest, inference],
+            system,
+            gate,
+        })
+    }
+
+    /// Stop integrating readings, returning once the cycle in progress has completed
+    ///
+    /// Readings keep being accepted, fused and inferred until the channels
+    /// fill up, then senders block. No further results appear until `resume`.
+    pub fn pause(&self) {
+        self.gate.set_paused(true);
+        self.gate.wait_while(|state| state.busy);
+    }
+
+    /// Continue integrating readings after `pause`
+    pub fn resume(&self) {
+        self.gate.set_paused(false);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.gate.lock().paused
+    }
+
+    /// Queue a reading, blocking while the ingest channel is full; false once the pipeline has stopped
+    pub fn send(&self, data: SensorData) -> bool {
+        self.input.send(data).is_ok()
+    }
+
+    /// Extra handle for feeding readings from other threads
+    pub fn sender(&self) -> SyncSender<SensorData> {
+        self.input.clone()
+    }
+
+    /// Wait for the next result, in input order; `None` once every sender is gone and the pipeline is drained
+    pub fn recv(&self) -> Option<Result<CycleResult>> {
+        self.output.recv().ok()
+    }
+
+    /// Next result if one is ready
+    pub fn try_recv(&self) -> Option<Result<CycleResult>> {
+        self.output.try_recv().ok()
+    }
+
+    /// Stop accepting input, process what is queued and hand the system back
+    /// with the results not yet received
+    ///
+    /// Blocks until senders obtained from `sender` are dropped too. A paused
+    /// pipeline is resumed first.
+    pub fn finish(self) -> (EnvironmentalAwarenessSystem, Vec<Result<CycleResult>>) {
+        let Self { input, output, workers, system, gate } = self;
+        gate.set_paused(false);
+        drop(input);
+        let remaining: Vec<_> = output.iter().collect();
+        for worker in workers {
+            if let Err(panic) = worker.join() {
+                std::panic::resume_unwind(panic);
+            }
+        }
+        match system.join() {
+            Ok(system) => (system, remaining),
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+
+    /// `finish`, then quiesce the system with `EnvironmentalAwarenessSystem::shutdown`
+    pub fn shutdown(self) -> Shutdown {
+        let (mut system, remaining) = self.finish();
+        let metrics = system.shutdown();
+        Shutdown { system, remaining, metrics }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::GenesisError;
+
+    #[test]
+    fn test_pipeline_order_and_shutdown() {
+        let pipeline = EnvironmentalAwarenessSystem::new().into_pipeline(4).unwrap();
+        let producer = {
+            let input = pipeline.sender();
+            thread::spawn(move || {
+                for i in 0..50 {
+                    let mut data = SensorData::generate();
+                    if i == 10 {
+                        data.audio.amplitude = f32::NAN;
+                    }
+                    input.send(data).unwrap();
+                }
+            })
+        };
+
+        let mut cycles = Vec::new();
+        let mut errors = 0;
+        for _ in 0..30 {
+            match pipeline.recv().unwrap() {
+                Ok(result) => cycles.push(result.cycle),
+                Err(GenesisError::InvalidReading { .. }) => errors += 1,
+                Err(other) => panic!("unexpected error: {other}"),
+            }
+        }
+
+        // Waits for the producer's sender to drop while draining the rest
+        let (system, remaining) = pipeline.finish();
+        producer.join().unwrap();
+        cycles.extend(remaining.into_iter().map(|r| r.unwrap().cycle));
+        assert_eq!(errors, 1);
+        assert_eq!(cycles, (1..=49).collect::<Vec<u32>>());
+        assert_eq!(system.spatial_graph().node_count(), 49);
+    }
+
+    #[test]
+    fn test_pause_resume_and_shutdown() {
+        let pipeline = EnvironmentalAwarenessSystem::new().into_pipeline(8).unwrap();
+        for _ in 0..3 {
+            pipeline.send(SensorData::generate());
+        }
+        pipeline.pause();
+        assert!(pipeline.is_paused());
+        let done: Vec<_> = std::iter::from_fn(|| pipeline.try_recv()).collect();
+        for _ in 0..3 {
+            pipeline.send(SensorData::generate());
+        }
+        thread::sleep(std::time::Duration::from_millis(50));
+        // Only cycles completed before `pause` returned are available
+        assert!(pipeline.try_recv().is_none());
+        assert!(done.len() <= 3);
+
+        pipeline.resume();
+        let next = pipeline.recv().unwrap().unwrap();
+        assert_eq!(next.cycle as usize, done.len() + 1);
+
+        pipeline.pause();
+        let shutdown = pipeline.shutdown();
+        assert_eq!(done.len() + 1 + shutdown.remaining.len(), 6);
+        assert_eq!(shutdown.metrics.cycles, 6);
+        assert_eq!(shutdown.system.spatial_graph().node_count(), 6);
+    }
+}
@@ -0,0 +1,170 @@
+//! PLY / PCD point cloud export of graph nodes
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::spatial::{Coordinates, SpatialGraph};
+
+/// How a feature channel is attached to exported points
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeatureChannel {
+    /// Feature value written as a float `intensity` field
+    Intensity(usize),
+    /// Feature value in `[0, 1]` mapped to a grayscale RGB color
+    Color(usize),
+}
+
+impl FeatureChannel {
+    #[inline]
+    fn value(&self, features: &[f32]) -> f32 {
+        let (FeatureChannel::Intensity(i) | FeatureChannel::Color(i)) = *self;
+        features.get(i).copied().unwrap_or(0.0)
+    }
+
+    #[inline]
+    fn gray(value: f32) -> u8 {
+        (value.clamp(0.0, 1.0) * 255.0).round() as u8
+    }
+}
+
+impl<P: Coordinates> SpatialGraph<P> {
+    /// XYZ of a node, zero-padding graphs with fewer than three axes
+    #[inline]
+    fn xyz(position: &P) -> [f32; 3] {
+        let axis = |i: usize| if i < P::DIMENSIONS { position.axis(i) } else { 0.0 };
+        [axis(0), axis(1), axis(2)]
+    }
+
+    /// Write nodes as an ASCII PLY point cloud (CloudCompare, Open3D, MeshLab)
+    pub fn write_ply<W: Write>(&self, mut out: W, channel: Option<FeatureChannel>) -> io::Result<()> {
+        writeln!(out, "ply")?;
+        writeln!(out, "format ascii 1.0")?;
+        writeln!(out, "comment genesis spatial graph")?;
+        writeln!(out, "element vertex {}", self.node_count())?;
+        writeln!(out, "property float x")?;
+        writeln!(out, "property float y")?;
+        writeln!(out, "property float z")?;
+        match channel {
+            Some(FeatureChannel::Intensity(_)) => writeln!(out, "property float intensity")?,
+            Some(FeatureChannel::Color(_)) => {
+                writeln!(out, "property uchar red")?;
+                writeln!(out, "property uchar green")?;
+                writeln!(out, "property uchar blue")?;
+            }
+            None => {}
+        }
+        writeln!(out, "end_header")?;
+
+        for node in self.nodes() {
+            let [x, y, z] = Self::xyz(&node.position);
+            write!(out, "{} {} {}", x, y, z)?;
+            Self::write_channel(&mut out, channel, &node.features)?;
+        }
+        out.flush()
+    }
+
+    /// Write nodes as an ASCII PCD v0.7 point cloud (PCL, Open3D)
+    pub fn write_pcd<W: Write>(&self, mut out: W, channel: Option<FeatureChannel>) -> io::Result<()> {
+        let (fields, sizes, types) = match channel {
+            Some(FeatureChannel::Intensity(_)) => ("x y z intensity", "4 4 4 4", "F F F F"),
+            Some(FeatureChannel::Color(_)) => ("x y z rgb", "4 4 4 4", "F F F U"),
+            None => ("x y z", "4 4 4", "F F F"),
+        };
+        let count = if channel.is_some() { "1 1 1 1" } else { "1 1 1" };
+
+        writeln!(out, "# .PCD v0.7 - genesis spatial graph")?;
+        writeln!(out, "VERSION 0.7")?;
+        writeln!(out, "FIELDS {}", fields)?;
+        writeln!(out, "SIZE {}", sizes)?;
+        writeln!(out, "TYPE {}", types)?;
+        writeln!(out, "COUNT {}", count)?;
+        writeln!(out, "WIDTH {}", self.node_count())?;
+        writeln!(out, "HEIGHT 1")?;
+        writeln!(out, "VIEWPOINT 0 0 0 1 0 0 0")?;
+        writeln!(out, "POINTS {}", self.node_count())?;
+        writeln!(out, "DATA ascii")?;
+
+        for node in self.nodes() {
+            let [x, y, z] = Self::xyz(&node.position);
+            write!(out, "{} {} {}", x, y, z)?;
+            match channel {
+                Some(c @ FeatureChannel::Color(_)) => {
+                    // PCD packs RGB into one unsigned field
+                    let g = FeatureChannel::gray(c.value(&node.features)) as u32;
+                    writeln!(out, " {}", (g << 16) | (g << 8) | g)?;
+                }
+                _ => Self::write_channel(&mut out, channel, &node.features)?,
+            }
+        }
+        out.flush()
+    }
+
+    /// Export to a `.ply` file
+    pub fn save_ply<Q: AsRef<Path>>(&self, path: Q, channel: Option<FeatureChannel>) -> io::Result<()> {
+        self.write_ply(BufWriter::new(File::create(path)?), channel)
+    }
+
+    /// Export to a `.pcd` file
+    pub fn save_pcd<Q: AsRef<Path>>(&self, path: Q, channel: Option<FeatureChannel>) -> io::Result<()> {
+        self.write_pcd(BufWriter::new(File::create(path)?), channel)
+    }
+
+    /// Finish a point line with the optional channel value(s)
+    fn write_channel<W: Write>(out: &mut W, channel: Option<FeatureChannel>, features: &[f32]) -> io::Result<()> {
+        match channel {
+            Some(c @ FeatureChannel::Intensity(_)) => writeln!(out, " {}", c.value(features)),
+            Some(c @ FeatureChannel::Color(_)) => {
+                let g = FeatureChannel::gray(c.value(features));
+                writeln!(out, " {} {} {}", g, g, g)
+            }
+            None => writeln!(out),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spatial::Position2D;
+
+    fn graph() -> SpatialGraph {
+        let mut graph = SpatialGraph::new();
+        graph.add_node(&[0.1, 0.2, 0.3, 0.5]);
+        graph.add_node(&[0.4, 0.5, 0.6, 1.0]);
+        graph
+    }
+
+    #[test]
+    fn test_ply_export() {
+        let mut buf = Vec::new();
+        graph().write_ply(&mut buf, Some(FeatureChannel::Intensity(3))).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+
+        assert!(text.starts_with("ply\nformat ascii 1.0\n"));
+        assert!(text.contains("element vertex 2\n"));
+        assert!(text.contains("property float intensity\n"));
+        assert!(text.ends_with("end_header\n10 20 3 0.5\n40 50 6 1\n"));
+    }
+
+    #[test]
+    fn test_pcd_export() {
+        let mut buf = Vec::new();
+        graph().write_pcd(&mut buf, Some(FeatureChannel::Color(3))).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+
+        assert!(text.contains("FIELDS x y z rgb\n"));
+        assert!(text.contains("POINTS 2\n"));
+        assert!(text.ends_with("DATA ascii\n10 20 3 8421504\n40 50 6 16777215\n"));
+    }
+
+    #[test]
+    fn test_planar_export_pads_z() {
+        let mut graph = SpatialGraph::<Position2D>::default();
+        graph.add_node(&[0.1, 0.2, 0.9]);
+
+        let mut buf = Vec::new();
+        graph.write_pcd(&mut buf, None).unwrap();
+        assert!(String::from_utf8(buf).unwrap().ends_with("DATA ascii\n10 20 0\n"));
+    }
+}
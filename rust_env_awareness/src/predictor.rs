@@ -1,119 +1,1544 @@
 //! Fast time series prediction module
 
 use std::collections::VecDeque;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::error::Result;
+use crate::float::Float;
+use crate::health::{Health, HealthCheck, HealthContext};
+use crate::neural::NeuralNetwork;
+
+/// Normal quantile of the two-sided 95% prediction interval
+pub const INTERVAL_Z: f32 = 1.96;
+
+/// Slope per step below which a forecast counts as `Trend::Stable`
+pub const STABLE_SLOPE: f32 = 1e-3;
+
+/// Residual standard deviation above which a forecast counts as `Trend::Volatile`
+pub const VOLATILE_STD: f32 = 0.1;
+
+/// Direction of a forecast
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Trend {
+    Increasing,
+    Decreasing,
+    /// Slope too small to matter
+    Stable,
+    /// Noise dominates the slope
+    Volatile,
+}
+
+impl Trend {
+    /// Classify a per-step slope given the one-step residual standard deviation
+    pub fn classify(slope: f32, residual_std: f32) -> Self {
+        if residual_std > VOLATILE_STD && slope.abs() < residual_std {
+            Trend::Volatile
+        } else if slope.abs() < STABLE_SLOPE {
+            Trend::Stable
+        } else if slope > 0.0 {
+            Trend::Increasing
+        } else {
+            Trend::Decreasing
+        }
+    }
+    
+    /// Lowercase name, as serialized
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Trend::Increasing => "increasing",
+            Trend::Decreasing => "decreasing",
+            Trend::Stable => "stable",
+            Trend::Volatile => "volatile",
+        }
+    }
+}
+
+impl fmt::Display for Trend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
 
 /// Prediction result
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Prediction {
     pub values: Vec<f32>,
+    /// Lower bound of the ~95% prediction interval per step
+    pub lower: Vec<f32>,
+    /// Upper bound of the ~95% prediction interval per step
+    pub upper: Vec<f32>,
+    /// Confidence of the one-step forecast
     pub confidence: f32,
+    /// Confidence per step, decaying as the forecast error variance grows with horizon
+    #[serde(default)]
+    pub confidences: Vec<f32>,
     pub trend: f32,  // Positive = increasing, negative = decreasing
 }
 
-/// High-performance linear regression predictor
-pub struct Predictor {
+/// Symmetric `INTERVAL_Z` bounds around each value from its standard error, clamped to [0, 1]
+fn interval(values: &[f32], std_errors: impl Iterator<Item = f32>) -> (Vec<f32>, Vec<f32>) {
+    values
+        .iter()
+        .zip(std_errors)
+        .map(|(v, se)| ((v - INTERVAL_Z * se).clamp(0.0, 1.0), (v + INTERVAL_Z * se).clamp(0.0, 1.0)))
+        .unzip()
+}
+
+/// A forecasting backend the system can run in place of the built-in `Predictor`
+///
+/// Implement this to plug a domain-specific model into the pipeline with
+/// `EnvironmentalAwarenessSystem::set_forecaster`.
+pub trait Forecaster: fmt::Debug + Send {
+    /// Feed the next value of the series
+    fn add_observation(&mut self, value: f32);
+    
+    /// Feed a value observed at `timestamp`; backends assuming even spacing ignore the time
+    fn add_observation_at(&mut self, timestamp: f64, value: f32) {
+        let _ = timestamp;
+        self.add_observation(value);
+    }
+    
+    /// Forecast `horizon` steps past the last observation
+    fn predict(&mut self, horizon: usize) -> Option<Prediction>;
+    
+    /// Number of forecasts made
+    fn prediction_count(&self) -> usize {
+        0
+    }
+    
+    /// Forget all observations
+    fn clear(&mut self) {}
+}
+
+/// Forecasting model used by a `Predictor`
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum ForecastModel {
+    /// Least-squares line over the window
+    #[default]
+    LinearRegression,
+    /// Simple exponential smoothing (level only)
+    Exponential { alpha: f32 },
+    /// Holt's double exponential smoothing (level and trend)
+    Holt { alpha: f32, beta: f32 },
+    /// Holt-Winters triple exponential smoothing with additive seasonality
+    HoltWinters { alpha: f32, beta: f32, gamma: f32, period: usize },
+    /// ARIMA(p, d, q) fit online by recursive least squares
+    Arima { p: usize, d: usize, q: usize },
+    /// Least-squares polynomial of `degree` (1 to 3) over the window; with a
+    /// `criterion`, the best degree up to `degree` is chosen on every forecast
+    Polynomial { degree: usize, criterion: Option<InformationCriterion> },
+    /// Seasonal decomposition over the last `periods` seasons: the trend is
+    /// extrapolated linearly and the seasonal profile added back
+    Stl { period: usize, periods: usize },
+}
+
+/// Model selection score penalizing extra parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InformationCriterion {
+    /// Akaike: `n ln(RSS/n) + 2k`
+    Aic,
+    /// Bayesian: `n ln(RSS/n) + k ln(n)`, favoring lower degrees on short windows
+    Bic,
+}
+
+/// Online exponential smoothing state
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Smoothing {
+    level: f32,
+    trend: f32,
+    seasonal: Vec<f32>,
+    /// Observations folded in so far
+    seen: usize,
+    /// First two seasons, buffered to initialize Holt-Winters
+    warmup: Vec<f32>,
+}
+
+impl Prediction {
+    /// Build a forecast from per-step standard errors: intervals at `INTERVAL_Z` and
+    /// confidence scaled by the one-step to h-step error variance ratio
+    pub fn with_std_errors(values: Vec<f32>, std_errors: &[f32], confidence: f32, trend: f32) -> Self {
+        let (lower, upper) = interval(&values, std_errors.iter().copied());
+        let base = std_errors.first().copied().unwrap_or(0.0);
+        let confidences = std_errors
+            .iter()
+            .map(|&se| if se > 0.0 { confidence * (base / se).powi(2) } else { confidence })
+            .collect();
+        Self { values, lower, upper, confidence, confidences, trend }
+    }
+    
+    /// Confidence of the forecast `step` steps out (0-based), falling back to `confidence`
+    pub fn confidence_at(&self, step: usize) -> f32 {
+        self.confidences.get(step).copied().unwrap_or(self.confidence)
+    }
+    
+    /// Classify the forecast, taking the residual spread from the first step's interval
+    pub fn direction(&self) -> Trend {
+        let residual_std = match (self.lower.first(), self.upper.first()) {
+            (Some(lower), Some(upper)) => (upper - lower) / (2.0 * INTERVAL_Z),
+            _ => 0.0,
+        };
+        Trend::classify(self.trend, residual_std)
+    }
+}
+
+impl Smoothing {
+    /// Fold in an observation; returns the one-step forecast made before seeing it
+    fn update(&mut self, value: f32, model: ForecastModel) -> Option<f32> {
+        let forecast = self.forecast(1, model);
+        match model {
+            ForecastModel::LinearRegression
+            | ForecastModel::Arima { .. }
+            | ForecastModel::Polynomial { .. }
+            | ForecastModel::Stl { .. } => return None,
+            ForecastModel::Exponential { alpha } => {
+                self.level = if self.seen == 0 { value } else { alpha * value + (1.0 - alpha) * self.level };
+            }
+            ForecastModel::Holt { alpha, beta } => match self.seen {
+                0 => self.level = value,
+                1 => {
+                    self.trend = value - self.level;
+                    self.level = value;
+                }
+                _ => {
+                    let level = alpha * value + (1.0 - alpha) * (self.level + self.trend);
+                    self.trend = beta * (level - self.level) + (1.0 - beta) * self.trend;
+                    self.level = level;
+                }
+            },
+            ForecastModel::HoltWinters { alpha, beta, gamma, period } => {
+                let period = period.max(1);
+                if self.seasonal.len() != period {
+                    self.warmup.push(value);
+                    if self.warmup.len() == 2 * period {
+                        self.initialize(period);
+                    }
+                } else {
+                    let s = self.seen % period;
+                    let level = alpha * (value - self.seasonal[s]) + (1.0 - alpha) * (self.level + self.trend);
+                    self.trend = beta * (level - self.level) + (1.0 - beta) * self.trend;
+                    self.seasonal[s] = gamma * (value - level) + (1.0 - gamma) * self.seasonal[s];
+                    self.level = level;
+                }
+            }
+        }
+        self.seen += 1;
+        forecast
+    }
+    
+    /// Seed level, trend and season from the first two seasons
+    fn initialize(&mut self, period: usize) {
+        let (first, second) = self.warmup.split_at(period);
+        let mean_first = first.iter().sum::<f32>() / period as f32;
+        let mean_second = second.iter().sum::<f32>() / period as f32;
+        self.trend = (mean_second - mean_first) / period as f32;
+        // Level at the last warmup sample, half a season past the second season's center
+        self.level = mean_second + self.trend * (period as f32 - 1.0) / 2.0;
+        self.seasonal = first
+            .iter()
+            .zip(second)
+            .map(|(a, b)| ((a - mean_first) + (b - mean_second)) / 2.0)
+            .collect();
+        self.warmup.clear();
+    }
+    
+    /// Weight of a shock `j` steps back in the forecast error (`psi_0 = 1`)
+    fn psi(j: usize, model: ForecastModel) -> f32 {
+        if j == 0 {
+            return 1.0;
+        }
+        match model {
+            ForecastModel::LinearRegression
+            | ForecastModel::Arima { .. }
+            | ForecastModel::Polynomial { .. }
+            | ForecastModel::Stl { .. } => 0.0,
+            ForecastModel::Exponential { alpha } => alpha,
+            ForecastModel::Holt { alpha, beta } => alpha * (1.0 + j as f32 * beta),
+            ForecastModel::HoltWinters { alpha, beta, gamma, period } => {
+                let seasonal = if j.is_multiple_of(period.max(1)) { gamma * (1.0 - alpha) } else { 0.0 };
+                alpha * (1.0 + j as f32 * beta) + seasonal
+            }
+        }
+    }
+    
+    /// Forecast `h` steps past the last observation
+    fn forecast(&self, h: usize, model: ForecastModel) -> Option<f32> {
+        match model {
+            ForecastModel::LinearRegression
+            | ForecastModel::Arima { .. }
+            | ForecastModel::Polynomial { .. }
+            | ForecastModel::Stl { .. } => None,
+            ForecastModel::Exponential { .. } => (self.seen >= 1).then_some(self.level),
+            ForecastModel::Holt { .. } => (self.seen >= 2).then_some(self.level + h as f32 * self.trend),
+            ForecastModel::HoltWinters { .. } => (!self.seasonal.is_empty()).then(|| {
+                let s = (self.seen + h - 1) % self.seasonal.len();
+                self.level + h as f32 * self.trend + self.seasonal[s]
+            }),
+        }
+    }
+}
+
+/// Online ARIMA state: differencing plus an ARMA model fit by recursive least squares
+///
+/// The MA terms regress on past one-step residuals (pseudo-linear
+/// regression), so coefficients adapt every observation without refitting.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Arima {
+    /// [constant, AR 1..=p, MA 1..=q]
+    theta: Vec<f32>,
+    /// RLS inverse-correlation matrix (row-major, square)
+    cov: Vec<f32>,
+    /// Last value of the series differenced 0..d times
+    tails: Vec<f32>,
+    /// Recent differenced values, newest first
+    lags: VecDeque<f32>,
+    /// Recent one-step residuals, newest first
+    errors: VecDeque<f32>,
+}
+
+impl Arima {
+    /// Forgetting factor of the recursive least squares fit
+    const FORGETTING: f32 = 0.995;
+    
+    /// Regressor vector [1, lags..., errors...] padded with zeros
+    fn regressors(lags: &VecDeque<f32>, errors: &VecDeque<f32>, p: usize, q: usize) -> Vec<f32> {
+        let mut phi = Vec::with_capacity(1 + p + q);
+        phi.push(1.0);
+        phi.extend((0..p).map(|i| lags.get(i).copied().unwrap_or(0.0)));
+        phi.extend((0..q).map(|i| errors.get(i).copied().unwrap_or(0.0)));
+        phi
+    }
+    
+    /// Fold in an observation; returns the one-step forecast made before seeing it
+    fn update(&mut self, value: f32, p: usize, d: usize, q: usize) -> Option<f32> {
+        let k = 1 + p + q;
+        if self.theta.len() != k {
+            *self = Self::default();
+            self.theta = vec![0.0; k];
+            self.cov = (0..k * k).map(|i| if i % (k + 1) == 0 { 100.0 } else { 0.0 }).collect();
+        }
+        let forecast = self.forecast(1, p, d, q).and_then(|path| path.first().copied());
+        
+        // Difference d times; the first d observations only seed the tails
+        let mut w = value;
+        for order in 0..d {
+            match self.tails.get_mut(order) {
+                Some(tail) => {
+                    let diff = w - *tail;
+                    *tail = w;
+                    w = diff;
+                }
+                None => {
+                    self.tails.push(w);
+                    return forecast;
+                }
+            }
+        }
+        
+        // Zero-padded lags would be high-leverage outliers: wait until they are real
+        if self.lags.len() < p {
+            push_front_bounded(&mut self.lags, w, p);
+            return forecast;
+        }
+        
+        // Recursive least squares step on the differenced value
+        let phi = Self::regressors(&self.lags, &self.errors, p, q);
+        let error = w - phi.iter().zip(&self.theta).map(|(x, t)| x * t).sum::<f32>();
+        let p_phi: Vec<f32> = (0..k).map(|r| (0..k).map(|c| self.cov[r * k + c] * phi[c]).sum()).collect();
+        let denom = Self::FORGETTING + phi.iter().zip(&p_phi).map(|(x, y)| x * y).sum::<f32>();
+        let gain: Vec<f32> = p_phi.iter().map(|v| v / denom).collect();
+        for (t, g) in self.theta.iter_mut().zip(&gain) {
+            *t += g * error;
+        }
+        for (row, g) in self.cov.chunks_exact_mut(k).zip(&gain) {
+            for (cell, pp) in row.iter_mut().zip(&p_phi) {
+                *cell = (*cell - g * pp) / Self::FORGETTING;
+            }
+        }
+        
+        push_front_bounded(&mut self.lags, w, p);
+        push_front_bounded(&mut self.errors, error, q);
+        forecast
+    }
+    
+    /// Impulse response `psi_0..psi_{steps-1}` of the fitted model, including integration
+    fn psi(&self, steps: usize, p: usize, d: usize, q: usize) -> Vec<f32> {
+        let mut weights: Vec<f32> = Vec::with_capacity(steps);
+        for k in 0..steps {
+            let mut v = if k == 0 { 1.0 } else { 0.0 };
+            for i in 1..=p.min(k) {
+                v += self.theta[i] * weights[k - i];
+            }
+            if (1..=q).contains(&k) {
+                v += self.theta[p + k];
+            }
+            weights.push(v);
+        }
+        for _ in 0..d {
+            for k in 1..steps {
+                weights[k] += weights[k - 1];
+            }
+        }
+        weights
+    }
+    
+    /// Forecast `steps` values past the last observation (future residuals taken as zero)
+    fn forecast(&self, steps: usize, p: usize, d: usize, q: usize) -> Option<Vec<f32>> {
+        if self.theta.len() != 1 + p + q || self.tails.len() < d || self.lags.len() < p {
+            return None;
+        }
+        let (mut lags, mut errors, mut tails) = (self.lags.clone(), self.errors.clone(), self.tails.clone());
+        let mut path = Vec::with_capacity(steps);
+        for _ in 0..steps {
+            let phi = Self::regressors(&lags, &errors, p, q);
+            let w: f32 = phi.iter().zip(&self.theta).map(|(x, t)| x * t).sum();
+            push_front_bounded(&mut lags, w, p);
+            push_front_bounded(&mut errors, 0.0, q);
+            
+            // Integrate back through each differencing order
+            let mut v = w;
+            for tail in tails.iter_mut().rev() {
+                v += *tail;
+                *tail = v;
+            }
+            path.push(v);
+        }
+        Some(path)
+    }
+}
+
+/// Push to the front, keeping at most `len` values
+fn push_front_bounded(values: &mut VecDeque<f32>, value: f32, len: usize) {
+    if len == 0 {
+        return;
+    }
+    if values.len() >= len {
+        values.pop_back();
+    }
+    values.push_front(value);
+}
+
+/// Least-squares line through `(x, y)` samples, computed at precision `F`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinearFit<F: Float = f32> {
+    pub slope: F,
+    pub intercept: F,
+    /// Coefficient of determination; 0 for a flat series
+    pub r_squared: F,
+    n: F,
+    mean_x: F,
+    /// Sum of squared deviations of x from its mean
+    sxx: F,
+    /// Residual variance
+    s2: F,
+}
+
+impl<F: Float> LinearFit<F> {
+    /// Fit the samples; `None` for fewer than two or all at the same x
+    pub fn fit<I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = (F, F)>,
+        I::IntoIter: Clone,
+    {
+        let points = points.into_iter();
+        let (mut n, mut sum_x, mut sum_y, mut sum_xy, mut sum_xx) = (F::ZERO, F::ZERO, F::ZERO, F::ZERO, F::ZERO);
+        for (x, y) in points.clone() {
+            n += F::ONE;
+            sum_x += x;
+            sum_y += y;
+            sum_xy += x * y;
+            sum_xx += x * x;
+        }
+        if n < F::from_f32(2.0) {
+            return None;
+        }
+        
+        let denominator = n * sum_xx - sum_x * sum_x;
+        if denominator.abs() < F::from_f32(0.0001) {
+            return None;
+        }
+        let slope = (n * sum_xy - sum_x * sum_y) / denominator;
+        let intercept = (sum_y - slope * sum_x) / n;
+        
+        let y_mean = sum_y / n;
+        let (mut ss_tot, mut ss_res) = (F::ZERO, F::ZERO);
+        for (x, y) in points {
+            let y_pred = slope * x + intercept;
+            ss_tot += (y - y_mean) * (y - y_mean);
+            ss_res += (y - y_pred) * (y - y_pred);
+        }
+        let r_squared = if ss_tot > F::from_f32(0.0001) { F::ONE - ss_res / ss_tot } else { F::ZERO };
+        
+        let two = F::from_f32(2.0);
+        let s2 = if n > two { ss_res / (n - two) } else { F::ZERO };
+        let mean_x = sum_x / n;
+        let sxx = sum_xx - n * mean_x * mean_x;
+        Some(Self { slope, intercept, r_squared, n, mean_x, sxx, s2 })
+    }
+    
+    /// Fitted value at `x`
+    #[inline]
+    pub fn value(&self, x: F) -> F {
+        self.slope * x + self.intercept
+    }
+    
+    /// Standard error of a new observation at `x`: s * sqrt(1 + 1/n + (x - mean_x)^2 / Sxx)
+    pub fn std_error(&self, x: F) -> F {
+        let dx = x - self.mean_x;
+        (self.s2 * (F::ONE + F::ONE / self.n + dx * dx / self.sxx)).sqrt()
+    }
+}
+
+/// Least-squares polynomial fit over evenly spaced samples
+#[derive(Debug, Clone)]
+struct PolynomialFit {
+    /// Coefficients in the normalized abscissa `(x - center) / scale`, constant first
+    coefficients: Vec<f64>,
+    /// Inverse of the normal matrix, for prediction standard errors
+    inverse: Vec<f64>,
+    rss: f64,
+    n: usize,
+    center: f64,
+    scale: f64,
+}
+
+impl PolynomialFit {
+    /// Fit `degree` to the series sampled at `xs`; needs more samples than coefficients plus one
+    fn fit(values: &VecDeque<f32>, xs: &[f64], degree: usize) -> Option<Self> {
+        let n = values.len();
+        let k = degree + 1;
+        if n <= k + 1 {
+            return None;
+        }
+        // Normalize x to [-1, 1] so powers stay well conditioned
+        let (first, last) = (xs[0], xs[n - 1]);
+        let center = (first + last) / 2.0;
+        let scale = ((last - first) / 2.0).max(1.0);
+        let basis = |i: f64| -> Vec<f64> {
+            let x = (i - center) / scale;
+            (0..k).map(|p| x.powi(p as i32)).collect()
+        };
+        
+        // Gauss-Jordan on [X'X | X'y | I]
+        let width = 2 * k + 1;
+        let mut m = vec![0.0f64; k * width];
+        for (&x, &y) in xs.iter().zip(values) {
+            let row = basis(x);
+            for r in 0..k {
+                for c in 0..k {
+                    m[r * width + c] += row[r] * row[c];
+                }
+                m[r * width + k] += row[r] * y as f64;
+            }
+        }
+        for r in 0..k {
+            m[r * width + k + 1 + r] = 1.0;
+        }
+        for col in 0..k {
+            let pivot = (col..k).max_by(|&a, &b| m[a * width + col].abs().total_cmp(&m[b * width + col].abs()))?;
+            if m[pivot * width + col].abs() < 1e-12 {
+                return None;
+            }
+            for c in 0..width {
+                m.swap(col * width + c, pivot * width + c);
+            }
+            let div = m[col * width + col];
+            m[col * width..(col + 1) * width].iter_mut().for_each(|v| *v /= div);
+            for r in (0..k).filter(|&r| r != col) {
+                let factor = m[r * width + col];
+                for c in 0..width {
+                    m[r * width + c] -= factor * m[col * width + c];
+                }
+            }
+        }
+        
+        let coefficients: Vec<f64> = (0..k).map(|r| m[r * width + k]).collect();
+        let inverse: Vec<f64> = (0..k).flat_map(|r| m[r * width + k + 1..(r + 1) * width].to_vec()).collect();
+        let mut fit = Self { coefficients, inverse, rss: 0.0, n, center, scale };
+        fit.rss = xs.iter().zip(values).map(|(&x, &y)| (y as f64 - fit.value(x)).powi(2)).sum();
+        Some(fit)
+    }
+    
+    #[inline]
+    fn basis(&self, i: f64) -> Vec<f64> {
+        let x = (i - self.center) / self.scale;
+        (0..self.coefficients.len()).map(|p| x.powi(p as i32)).collect()
+    }
+    
+    /// Fitted value at sample position `i`
+    fn value(&self, i: f64) -> f64 {
+        self.basis(i).iter().zip(&self.coefficients).map(|(b, c)| b * c).sum()
+    }
+    
+    /// Slope per sample interval at position `i`
+    fn slope(&self, i: f64) -> f64 {
+        let x = (i - self.center) / self.scale;
+        let derivative: f64 = (1..self.coefficients.len())
+            .map(|p| p as f64 * self.coefficients[p] * x.powi(p as i32 - 1))
+            .sum();
+        derivative / self.scale
+    }
+    
+    /// Standard error of a new observation at position `i`
+    fn std_error(&self, i: f64) -> f64 {
+        let k = self.coefficients.len();
+        let s2 = self.rss / (self.n - k) as f64;
+        let b = self.basis(i);
+        let leverage: f64 = (0..k).map(|r| (0..k).map(|c| b[r] * self.inverse[r * k + c] * b[c]).sum::<f64>()).sum();
+        (s2 * (1.0 + leverage)).sqrt()
+    }
+    
+    /// Information criterion score (lower is better)
+    fn criterion(&self, criterion: InformationCriterion) -> f64 {
+        let n = self.n as f64;
+        let k = self.coefficients.len() as f64;
+        let fit = n * (self.rss / n).max(1e-12).ln();
+        match criterion {
+            InformationCriterion::Aic => fit + 2.0 * k,
+            InformationCriterion::Bic => fit + k * n.ln(),
+        }
+    }
+}
+
+/// Trend, seasonal and residual components of a series (same length as the input)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decomposition {
+    pub trend: Vec<f32>,
+    /// Zero-mean seasonal profile repeated over the series
+    pub seasonal: Vec<f32>,
+    pub residual: Vec<f32>,
+    pub period: usize,
+}
+
+/// Centered moving average of one season (2 x m for even periods), ends extrapolated linearly
+fn seasonal_trend(series: &[f32], period: usize) -> Vec<f32> {
+    let n = series.len();
+    let half = period / 2;
+    let mut trend = vec![0.0f32; n];
+    for (i, t) in trend.iter_mut().enumerate().take(n - half).skip(half) {
+        let window = &series[i - half..=i + half];
+        *t = if period % 2 == 1 {
+            window.iter().sum::<f32>() / period as f32
+        } else {
+            let inner: f32 = window[1..period].iter().sum();
+            (inner + (window[0] + window[period]) / 2.0) / period as f32
+        };
+    }
+    
+    // Extend both ends with the slope of the nearest full season of centered values
+    let (first, last) = (half, n - half - 1);
+    let span = period.min(last - first).max(1) as f32;
+    let head_slope = (trend[first + span as usize] - trend[first]) / span;
+    let tail_slope = (trend[last] - trend[last - span as usize]) / span;
+    for i in 0..first {
+        trend[i] = trend[first] - head_slope * (first - i) as f32;
+    }
+    for i in last + 1..n {
+        trend[i] = trend[last] + tail_slope * (i - last) as f32;
+    }
+    trend
+}
+
+/// STL-style decomposition: moving-average trend and cycle-subseries seasonal means, refined once.
+///
+/// Needs at least two full periods. Phase `i % period` of the returned
+/// seasonal component corresponds to sample `i` of the input.
+pub fn decompose(series: &[f32], period: usize) -> Option<Decomposition> {
+    let n = series.len();
+    if period < 2 || n < 2 * period {
+        return None;
+    }
+    
+    let mut trend = seasonal_trend(series, period);
+    let mut profile = vec![0.0f32; period];
+    for _ in 0..2 {
+        // Seasonal profile: mean detrended value per phase, centered to zero
+        let mut sums = vec![(0.0f32, 0usize); period];
+        for (i, (&x, &t)) in series.iter().zip(&trend).enumerate() {
+            sums[i % period].0 += x - t;
+            sums[i % period].1 += 1;
+        }
+        profile = sums.iter().map(|&(sum, count)| sum / count as f32).collect();
+        let mean = profile.iter().sum::<f32>() / period as f32;
+        profile.iter_mut().for_each(|s| *s -= mean);
+        
+        // Re-estimate the trend without the seasonal swing
+        let adjusted: Vec<f32> = series.iter().enumerate().map(|(i, &x)| x - profile[i % period]).collect();
+        trend = seasonal_trend(&adjusted, period);
+    }
+    
+    let seasonal: Vec<f32> = (0..n).map(|i| profile[i % period]).collect();
+    let residual = series.iter().zip(&trend).zip(&seasonal).map(|((x, t), s)| x - t - s).collect();
+    Some(Decomposition { trend, seasonal, residual, period })
+}
+
+/// Bounds for sizing the predictor window by signal volatility
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AdaptiveWindow {
+    pub min_size: usize,
+    pub max_size: usize,
+    /// Ratio of recent to long-run squared one-step error treated as a regime change
+    pub sensitivity: f32,
+}
+
+impl Default for AdaptiveWindow {
+    fn default() -> Self {
+        Self {
+            min_size: 5,
+            max_size: 50,
+            sensitivity: 4.0,
+        }
+    }
+}
+
+/// Fast and slow averages of squared one-step errors of a line through the window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WindowAdapter {
+    config: AdaptiveWindow,
+    fast: f32,
+    slow: f32,
+    samples: usize,
+}
+
+impl WindowAdapter {
+    const FAST: f32 = 0.3;
+    const SLOW: f32 = 0.02;
+    
+    fn new(config: AdaptiveWindow) -> Self {
+        let min_size = config.min_size.max(2);
+        Self {
+            config: AdaptiveWindow { min_size, max_size: config.max_size.max(min_size), ..config },
+            fast: 0.0,
+            slow: 0.0,
+            samples: 0,
+        }
+    }
+    
+    /// Window size to use after seeing `error`; `current` is the size in use
+    fn resize(&mut self, error: f32, current: usize) -> usize {
+        let e2 = error * error;
+        if self.samples == 0 {
+            self.fast = e2;
+            self.slow = e2;
+        } else {
+            self.fast += Self::FAST * (e2 - self.fast);
+            self.slow += Self::SLOW * (e2 - self.slow);
+        }
+        self.samples += 1;
+        
+        if self.samples > self.config.min_size && self.fast > self.config.sensitivity * self.slow.max(1e-10) {
+            // Regime change: forget the old regime and adopt its error level as normal
+            self.slow = self.fast;
+            self.samples = 0;
+            self.config.min_size
+        } else if self.fast <= self.slow {
+            (current + 1).min(self.config.max_size)
+        } else {
+            current
+        }
+    }
+}
+
+/// High-performance time series predictor (regression, exponential smoothing, ARIMA or seasonal decomposition)
+///
+/// The window, timestamps and fitted model state serialize, so forecasting
+/// resumes after a restart without re-accumulating observations.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Predictor {
+    window: VecDeque<f32>,
+    /// Observation timestamps aligned with `window`
+    times: VecDeque<f64>,
+    window_size: usize,
+    prediction_count: usize,
+    model: ForecastModel,
+    smoothing: Smoothing,
+    arima: Arima,
+    /// One-step forecast errors of the smoothing or ARIMA model, aligned with `window`
+    residuals: VecDeque<f32>,
+    /// Degree used by the last polynomial forecast
+    polynomial_degree: Option<usize>,
+    /// Longer history kept for seasonal decomposition
+    history: VecDeque<f32>,
+    /// Volatility-driven sizing of `window_size`, when enabled
+    adapter: Option<WindowAdapter>,
+    /// Whether the last `predict` produced a forecast
+    #[serde(default)]
+    fitted: bool,
+}
+
+impl Predictor {
+    /// Create a new linear regression predictor
+    pub fn new(window_size: usize) -> Self {
+        Self::with_model(window_size, ForecastModel::LinearRegression)
+    }
+    
+    /// Create a predictor using `model`; `window_size` bounds the samples used for confidence
+    pub fn with_model(window_size: usize, model: ForecastModel) -> Self {
+        Self {
+            window: VecDeque::with_capacity(window_size),
+            times: VecDeque::with_capacity(window_size),
+            window_size,
+            prediction_count: 0,
+            model,
+            smoothing: Smoothing::default(),
+            arima: Arima::default(),
+            residuals: VecDeque::with_capacity(window_size),
+            polynomial_degree: None,
+            history: VecDeque::new(),
+            adapter: None,
+            fitted: false,
+        }
+    }
+    
+    /// Size the window by volatility within `config` bounds (shrinking on a regime change and
+    /// growing while stable), or return to a fixed window of the current size with `None`
+    pub fn set_adaptive_window(&mut self, config: Option<AdaptiveWindow>) {
+        self.adapter = config.map(WindowAdapter::new);
+        if let Some(adapter) = &self.adapter {
+            let bounds = adapter.config;
+            self.resize_window(self.window_size.clamp(bounds.min_size, bounds.max_size));
+        }
+    }
+    
+    /// Adaptive window bounds, if enabled
+    pub fn adaptive_window(&self) -> Option<AdaptiveWindow> {
+        self.adapter.as_ref().map(|a| a.config)
+    }
+    
+    /// Samples currently used for fitting
+    #[inline]
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+    
+    /// Mean time between observations in the window; forecast steps are this far apart
+    pub fn step_interval(&self) -> Option<f64> {
+        let n = self.times.len();
+        let span = self.times.back()? - self.times.front()?;
+        (n >= 2 && span > 0.0).then(|| span / (n - 1) as f64)
+    }
+    
+    /// Sample positions in units of the mean interval (indices when evenly spaced)
+    fn positions(&self) -> Vec<f64> {
+        match (self.step_interval(), self.times.front()) {
+            (Some(step), Some(&first)) => self.times.iter().map(|t| (t - first) / step).collect(),
+            _ => (0..self.window.len()).map(|i| i as f64).collect(),
+        }
+    }
+    
+    fn resize_window(&mut self, size: usize) {
+        self.window_size = size;
+        while self.window.len() > size {
+            self.window.pop_front();
+            self.times.pop_front();
+        }
+        while self.residuals.len() > size {
+            self.residuals.pop_front();
+        }
+    }
+    
+    /// One-step error of a least-squares line through the window
+    fn line_error(&self, value: f32) -> Option<f32> {
+        let n = self.window.len();
+        if n < 2 {
+            return None;
+        }
+        let mean_x = (n - 1) as f32 / 2.0;
+        let mean_y = self.window.iter().sum::<f32>() / n as f32;
+        let (mut sxy, mut sxx) = (0.0f32, 0.0f32);
+        for (i, &y) in self.window.iter().enumerate() {
+            let dx = i as f32 - mean_x;
+            sxy += dx * (y - mean_y);
+            sxx += dx * dx;
+        }
+        Some(value - (mean_y + sxy / sxx * (n as f32 - mean_x)))
+    }
+    
+    /// Serialize the window and fitted model state as JSON
+    pub fn save_to<W: Write>(&self, out: W) -> Result<()> {
+        serde_json::to_writer(out, self)?;
+        Ok(())
+    }
+    
+    /// Restore a predictor written by `save_to`
+    pub fn load_from<R: Read>(input: R) -> Result<Self> {
+        Ok(serde_json::from_reader(input)?)
+    }
+    
+    /// Save state to a file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.save_to(&mut out)?;
+        Ok(out.flush()?)
+    }
+    
+    /// Load state from a file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::load_from(BufReader::new(File::open(path)?))
+    }
+    
+    /// Active forecasting model
+    pub fn model(&self) -> ForecastModel {
+        self.model
+    }
+    
+    /// Switch models; model state is re-learned from the next observation
+    pub fn set_model(&mut self, model: ForecastModel) {
+        self.model = model;
+        self.smoothing = Smoothing::default();
+        self.arima = Arima::default();
+        self.residuals.clear();
+        self.history.clear();
+    }
+    
+    /// Add an observation one time unit after the previous one
+    pub fn add_observation(&mut self, value: f32) {
+        let timestamp = self.times.back().map_or(0.0, |t| t + 1.0);
+        self.add_observation_at(timestamp, value);
+    }
+    
+    /// Add an observation taken at `timestamp`
+    ///
+    /// Regression models fit against the actual sample times, so uneven cycle
+    /// rates or bursts do not distort the slope; forecasts are spaced by the
+    /// mean sample interval (`step_interval`). Timestamps earlier than the
+    /// previous observation are treated as simultaneous with it.
+    pub fn add_observation_at(&mut self, timestamp: f64, value: f32) {
+        let timestamp = self.times.back().map_or(timestamp, |&last| timestamp.max(last));
+        if let Some(error) = self.adapter.as_ref().and_then(|_| self.line_error(value)) {
+            let size = self.adapter.as_mut().map_or(self.window_size, |a| a.resize(error, self.window_size));
+            self.resize_window(size);
+        }
+        if self.window.len() >= self.window_size {
+            self.window.pop_front();
+            self.times.pop_front();
+        }
+        self.window.push_back(value);
+        self.times.push_back(timestamp);
+        
+        if let ForecastModel::Stl { period, periods } = self.model {
+            if self.history.len() >= period * periods.max(2) {
+                self.history.pop_front();
+            }
+            self.history.push_back(value);
+        }
+        
+        let forecast = match self.model {
+            ForecastModel::Arima { p, d, q } => self.arima.update(value, p, d, q),
+            model => self.smoothing.update(value, model),
+        };
+        if let Some(forecast) = forecast {
+            if self.residuals.len() >= self.window_size {
+                self.residuals.pop_front();
+            }
+            self.residuals.push_back(value - forecast);
+        }
+    }
+    
+    /// Predict future values with the active model
+    pub fn predict(&mut self, steps_ahead: usize) -> Option<Prediction> {
+        let prediction = match self.model {
+            ForecastModel::LinearRegression => self.predict_linear(steps_ahead),
+            ForecastModel::Polynomial { degree, criterion } => self.predict_polynomial(steps_ahead, degree, criterion),
+            ForecastModel::Stl { period, .. } => self.predict_seasonal(steps_ahead, period),
+            model => self.predict_online(steps_ahead, model),
+        };
+        self.fitted = prediction.is_some();
+        prediction
+    }
+    
+    /// ARMA coefficients `[constant, AR..., MA...]` of the ARIMA model, once fitting has started
+    pub fn arima_coefficients(&self) -> Option<&[f32]> {
+        matches!(self.model, ForecastModel::Arima { .. }).then_some(self.arima.theta.as_slice())
+    }
+    
+    /// Decompose the seasonal history (with `ForecastModel::Stl`, once two seasons are buffered)
+    pub fn decomposition(&self) -> Option<Decomposition> {
+        let ForecastModel::Stl { period, .. } = self.model else {
+            return None;
+        };
+        let series: Vec<f32> = self.history.iter().copied().collect();
+        decompose(&series, period)
+    }
+    
+    /// Extrapolate the decomposed trend over the last season and add the seasonal profile
+    fn predict_seasonal(&mut self, steps_ahead: usize, period: usize) -> Option<Prediction> {
+        let series: Vec<f32> = self.history.iter().copied().collect();
+        let parts = decompose(&series, period)?;
+        let n = series.len();
+        
+        // Line through the last season of centered (not end-extrapolated) trend values
+        let last = n - period / 2;
+        let xs: Vec<f32> = (last - period..last).map(|i| i as f32).collect();
+        let ys = &parts.trend[last - period..last];
+        let m = period as f32;
+        let mean_x = xs.iter().sum::<f32>() / m;
+        let mean_y = ys.iter().sum::<f32>() / m;
+        let sxx: f32 = xs.iter().map(|x| (x - mean_x) * (x - mean_x)).sum();
+        let slope = xs.iter().zip(ys).map(|(x, y)| (x - mean_x) * (y - mean_y)).sum::<f32>() / sxx;
+        let fit_s2 = xs.iter().zip(ys).map(|(x, y)| (y - mean_y - slope * (x - mean_x)).powi(2)).sum::<f32>() / (m - 2.0).max(1.0);
+        
+        let values: Vec<f32> = (0..steps_ahead)
+            .map(|h| {
+                let i = n + h;
+                (mean_y + slope * (i as f32 - mean_x) + parts.seasonal[i % period]).clamp(0.0, 1.0)
+            })
+            .collect();
+        
+        // Residual noise plus uncertainty of the extrapolated trend line
+        let resid_s2 = parts.residual.iter().map(|r| r * r).sum::<f32>() / n as f32;
+        let std_errors: Vec<f32> = (0..steps_ahead)
+            .map(|h| {
+                let dx = (n + h) as f32 - mean_x;
+                (resid_s2 + fit_s2 * (1.0 / m + dx * dx / sxx)).sqrt()
+            })
+            .collect();
+        
+        let mean = series.iter().sum::<f32>() / n as f32;
+        let variance = series.iter().map(|x| (x - mean) * (x - mean)).sum::<f32>() / n as f32;
+        let confidence = if variance > 1e-8 { 1.0 - resid_s2 / variance } else { 0.0 };
+        
+        self.prediction_count += 1;
+        
+        Some(Prediction::with_std_errors(values, &std_errors, confidence.clamp(0.0, 1.0), slope))
+    }
+    
+    /// Degree chosen by the last polynomial forecast
+    pub fn polynomial_degree(&self) -> Option<usize> {
+        self.polynomial_degree
+    }
+    
+    /// Fit polynomials over the window, picking the degree by `criterion` when given
+    fn predict_polynomial(&mut self, steps_ahead: usize, degree: usize, criterion: Option<InformationCriterion>) -> Option<Prediction> {
+        let degree = degree.clamp(1, 3);
+        let xs = self.positions();
+        let fit = match criterion {
+            Some(criterion) => (1..=degree)
+                .filter_map(|d| PolynomialFit::fit(&self.window, &xs, d))
+                .min_by(|a, b| a.criterion(criterion).total_cmp(&b.criterion(criterion)))?,
+            None => PolynomialFit::fit(&self.window, &xs, degree)?,
+        };
+        self.polynomial_degree = Some(fit.coefficients.len() - 1);
+        
+        let start = xs.last()? + 1.0;
+        let values: Vec<f32> = (0..steps_ahead).map(|i| (fit.value(start + i as f64) as f32).clamp(0.0, 1.0)).collect();
+        let std_errors: Vec<f32> = (0..steps_ahead).map(|i| fit.std_error(start + i as f64) as f32).collect();
+        
+        let n = self.window.len() as f64;
+        let mean = self.window.iter().map(|&y| y as f64).sum::<f64>() / n;
+        let ss_tot: f64 = self.window.iter().map(|&y| (y as f64 - mean).powi(2)).sum();
+        let r_squared = if ss_tot > 0.0001 { 1.0 - fit.rss / ss_tot } else { 0.0 };
+        
+        self.prediction_count += 1;
+        
+        Some(Prediction::with_std_errors(values, &std_errors, (r_squared as f32).clamp(0.0, 1.0), fit.slope(start) as f32))
+    }
+    
+    /// Forecast from the online model state; confidence compares one-step errors with the window variance
+    fn predict_online(&mut self, steps_ahead: usize, model: ForecastModel) -> Option<Prediction> {
+        let path: Vec<f32> = match model {
+            ForecastModel::Arima { p, d, q } => self.arima.forecast(steps_ahead.max(2), p, d, q)?,
+            _ => (1..=steps_ahead.max(2)).map(|h| self.smoothing.forecast(h, model)).collect::<Option<_>>()?,
+        };
+        let values: Vec<f32> = path.iter().take(steps_ahead).map(|v| v.clamp(0.0, 1.0)).collect();
+        
+        let n = self.window.len() as f32;
+        let mean = self.window.iter().sum::<f32>() / n;
+        let ss_tot: f32 = self.window.iter().map(|y| (y - mean) * (y - mean)).sum();
+        let k = self.residuals.len().min(self.window.len());
+        let ss_res: f32 = self.residuals.iter().rev().take(k).map(|r| r * r).sum();
+        let confidence = if ss_tot > 0.0001 && k > 0 { 1.0 - ss_res * n / (k as f32 * ss_tot) } else { 0.0 };
+        
+        // h-step error variance: sigma^2 * sum of squared psi weights below h
+        let sigma2 = if k > 0 { ss_res / k as f32 } else { 0.0 };
+        let psi: Vec<f32> = match model {
+            ForecastModel::Arima { p, d, q } => self.arima.psi(steps_ahead, p, d, q),
+            _ => (0..steps_ahead).map(|j| Smoothing::psi(j, model)).collect(),
+        };
+        let std_errors: Vec<f32> = psi
+            .iter()
+            .scan(0.0, |acc, w| {
+                *acc += w * w;
+                Some((sigma2 * *acc).sqrt())
+            })
+            .collect();
+        
+        self.prediction_count += 1;
+        
+        Some(Prediction::with_std_errors(values, &std_errors, confidence.clamp(0.0, 1.0), path[1] - path[0]))
+    }
+    
+    /// Predict future values using fast linear regression
+    fn predict_linear(&mut self, steps_ahead: usize) -> Option<Prediction> {
+        let xs = self.positions();
+        let fit = self.fit_at::<f32>(&xs)?;
+        let start_x = xs[xs.len() - 1] as f32 + 1.0;
+        
+        let mut predictions = Vec::with_capacity(steps_ahead);
+        let mut std_errors = Vec::with_capacity(steps_ahead);
+        for i in 0..steps_ahead {
+            let x = start_x + i as f32;
+            predictions.push(fit.value(x).clamp(0.0, 1.0));
+            std_errors.push(fit.std_error(x));
+        }
+        
+        self.prediction_count += 1;
+        
+        Some(Prediction::with_std_errors(predictions, &std_errors, fit.r_squared.clamp(0.0, 1.0), fit.slope))
+    }
+    
+    /// Least-squares line through the window at precision `F`, against the
+    /// same positions linear forecasts use
+    pub fn fit_line<F: Float>(&self) -> Option<LinearFit<F>> {
+        self.fit_at(&self.positions())
+    }
+    
+    fn fit_at<F: Float>(&self, xs: &[f64]) -> Option<LinearFit<F>> {
+        LinearFit::fit(xs.iter().zip(&self.window).map(|(&x, &y)| (F::from_f64(x), F::from_f32(y))))
+    }
+    
+    /// Get the number of predictions made
+    #[inline]
+    pub fn prediction_count(&self) -> usize {
+        self.prediction_count
+    }
+    
+    /// Clear the predictor state
+    pub fn clear(&mut self) {
+        self.window.clear();
+        self.times.clear();
+        self.prediction_count = 0;
+        self.fitted = false;
+        self.smoothing = Smoothing::default();
+        self.arima = Arima::default();
+        self.residuals.clear();
+        self.history.clear();
+        self.polynomial_degree = None;
+        if let Some(adapter) = &mut self.adapter {
+            *adapter = WindowAdapter::new(adapter.config);
+        }
+    }
+}
+
+impl Forecaster for Predictor {
+    fn add_observation(&mut self, value: f32) {
+        Predictor::add_observation(self, value);
+    }
+    
+    fn add_observation_at(&mut self, timestamp: f64, value: f32) {
+        Predictor::add_observation_at(self, timestamp, value);
+    }
+    
+    fn predict(&mut self, horizon: usize) -> Option<Prediction> {
+        Predictor::predict(self, horizon)
+    }
+    
+    fn prediction_count(&self) -> usize {
+        Predictor::prediction_count(self)
+    }
+    
+    fn clear(&mut self) {
+        Predictor::clear(self);
+    }
+}
+
+impl HealthCheck for Predictor {
+    fn health(&self, _: &HealthContext<'_>) -> Health {
+        if self.fitted {
+            Health::healthy()
+        } else {
+            Health::degraded(format!("not fitted: no forecast from {} samples", self.window.len()))
+        }
+    }
+}
+
+/// Forecasts every channel of a feature vector with its own `Predictor`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiPredictor {
+    channels: Vec<Predictor>,
+}
+
+impl MultiPredictor {
+    /// Create one predictor per channel, all using `model`
+    pub fn new(channels: usize, window_size: usize, model: ForecastModel) -> Self {
+        Self {
+            channels: (0..channels).map(|_| Predictor::with_model(window_size, model)).collect(),
+        }
+    }
+    
+    /// Number of forecast channels
+    pub fn channels(&self) -> usize {
+        self.channels.len()
+    }
+    
+    /// Add one feature vector (extra values are ignored, missing ones leave their channel untouched)
+    pub fn add_observation(&mut self, features: &[f32]) {
+        for (predictor, &value) in self.channels.iter_mut().zip(features) {
+            predictor.add_observation(value);
+        }
+    }
+    
+    /// Feed one feature vector observed at `timestamp`
+    pub fn add_observation_at(&mut self, timestamp: f64, features: &[f32]) {
+        for (predictor, &value) in self.channels.iter_mut().zip(features) {
+            predictor.add_observation_at(timestamp, value);
+        }
+    }
+    
+    /// Forecast every channel, in channel order; `None` until all channels can predict
+    pub fn predict(&mut self, steps_ahead: usize) -> Option<Vec<Prediction>> {
+        self.channels.iter_mut().map(|p| p.predict(steps_ahead)).collect()
+    }
+    
+    /// Switch every channel's model
+    pub fn set_model(&mut self, model: ForecastModel) {
+        self.channels.iter_mut().for_each(|p| p.set_model(model));
+    }
+    
+    /// Clear every channel
+    pub fn clear(&mut self) {
+        self.channels.iter_mut().for_each(Predictor::clear);
+    }
+}
+
+/// Forecaster backed by a small `NeuralNetwork` over the last `lags` values, trained online
+///
+/// Every observation is one supervised example (previous lags → value); the
+/// network takes a gradient step on it and on a short replay of recent examples.
+/// Multi-step forecasts feed predictions back as inputs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NeuralForecaster {
+    network: NeuralNetwork,
+    lags: usize,
+    hidden: usize,
+    learning_rate: f32,
+    /// Recent values, newest last; the last `lags` are the next input
     window: VecDeque<f32>,
     window_size: usize,
+    /// One-step errors made before training on each example
+    errors: VecDeque<f32>,
     prediction_count: usize,
 }
 
-impl Predictor {
-    /// Create a new predictor
-    pub fn new(window_size: usize) -> Self {
+impl NeuralForecaster {
+    /// Examples replayed per observation, newest first
+    const REPLAY: usize = 16;
+    
+    /// Create a forecaster with `lags` inputs and `hidden` hidden units
+    pub fn new(lags: usize, hidden: usize) -> Self {
+        let lags = lags.max(1);
+        let hidden = hidden.max(1);
+        let window_size = lags + Self::REPLAY;
         Self {
+            network: NeuralNetwork::new(lags, hidden, 1),
+            lags,
+            hidden,
+            learning_rate: 0.5,
             window: VecDeque::with_capacity(window_size),
             window_size,
+            errors: VecDeque::with_capacity(window_size),
             prediction_count: 0,
         }
     }
     
-    /// Add an observation
-    pub fn add_observation(&mut self, value: f32) {
-        if self.window.len() >= self.window_size {
-            self.window.pop_front();
-        }
-        self.window.push_back(value);
+    /// Set the gradient descent step size (default 0.5)
+    pub fn with_learning_rate(mut self, learning_rate: f32) -> Self {
+        self.learning_rate = learning_rate;
+        self
     }
     
-    /// Predict future values using fast linear regression
-    pub fn predict(&mut self, steps_ahead: usize) -> Option<Prediction> {
-        if self.window.len() < 2 {
+    /// Number of lagged inputs
+    pub fn lags(&self) -> usize {
+        self.lags
+    }
+    
+    /// Root mean squared one-step error over recent observations
+    pub fn rmse(&self) -> Option<f32> {
+        if self.errors.is_empty() {
             return None;
         }
+        Some((self.errors.iter().map(|e| e * e).sum::<f32>() / self.errors.len() as f32).sqrt())
+    }
+    
+    fn input(&self, end: usize) -> Vec<f32> {
+        self.window.range(end - self.lags..end).copied().collect()
+    }
+}
+
+impl Forecaster for NeuralForecaster {
+    fn add_observation(&mut self, value: f32) {
+        if self.window.len() >= self.window_size {
+            self.window.pop_front();
+        }
+        self.window.push_back(value);
         
-        let n = self.window.len() as f32;
-        
-        // Fast linear regression using closed-form solution
-        // Pre-compute sums for efficiency
-        let mut sum_x = 0.0;
-        let mut sum_y = 0.0;
-        let mut sum_xy = 0.0;
-        let mut sum_xx = 0.0;
+        let n = self.window.len();
+        if n <= self.lags {
+            return;
+        }
+        let error = self.network.train(&self.input(n - 1), &[value], self.learning_rate).sqrt();
+        if self.errors.len() >= self.window_size {
+            self.errors.pop_front();
+        }
+        self.errors.push_back(error);
         
-        for (i, &y) in self.window.iter().enumerate() {
-            let x = i as f32;
-            sum_x += x;
-            sum_y += y;
-            sum_xy += x * y;
-            sum_xx += x * x;
+        for end in (self.lags..n - 1).rev() {
+            let target = self.window[end];
+            self.network.train(&self.input(end), &[target], self.learning_rate);
         }
+    }
+    
+    fn predict(&mut self, horizon: usize) -> Option<Prediction> {
+        let rmse = self.rmse()?;
+        let last = *self.window.back()?;
         
-        // Calculate slope and intercept
-        let denominator = n * sum_xx - sum_x * sum_x;
+        let mut inputs = self.input(self.window.len());
+        let values: Vec<f32> = (0..horizon)
+            .map(|_| {
+                let next = self.network.forward(&inputs)[0];
+                inputs.remove(0);
+                inputs.push(next);
+                next
+            })
+            .collect();
         
-        if denominator.abs() < 0.0001 {
-            return None;
-        }
+        // Errors compound roughly like a random walk when forecasts are fed back
+        let std_errors: Vec<f32> = (1..=horizon).map(|h| rmse * (h as f32).sqrt()).collect();
         
-        let slope = (n * sum_xy - sum_x * sum_y) / denominator;
-        let intercept = (sum_y - slope * sum_x) / n;
+        let n = self.window.len() as f32;
+        let mean = self.window.iter().sum::<f32>() / n;
+        let variance = self.window.iter().map(|x| (x - mean) * (x - mean)).sum::<f32>() / n;
+        let confidence = if variance > 1e-8 { 1.0 - rmse * rmse / variance } else { 1.0 - rmse.min(1.0) };
+        let trend = values.last().map_or(0.0, |v| (v - last) / horizon as f32);
         
-        // Make predictions
-        let mut predictions = Vec::with_capacity(steps_ahead);
-        let start_x = self.window.len() as f32;
+        self.prediction_count += 1;
         
-        for i in 0..steps_ahead {
-            let x = start_x + i as f32;
-            let pred = slope * x + intercept;
-            predictions.push(pred.max(0.0).min(1.0));  // Clamp to [0, 1]
+        Some(Prediction::with_std_errors(values, &std_errors, confidence.clamp(0.0, 1.0), trend))
+    }
+    
+    fn prediction_count(&self) -> usize {
+        self.prediction_count
+    }
+    
+    /// Forget observations and re-initialize the network
+    fn clear(&mut self) {
+        self.network = NeuralNetwork::new(self.lags, self.hidden, 1);
+        self.window.clear();
+        self.errors.clear();
+        self.prediction_count = 0;
+    }
+}
+
+/// One model's part in an ensemble forecast
+#[derive(Debug, Clone)]
+pub struct MemberForecast {
+    pub name: String,
+    /// Share of the combined forecast (0 when the model could not predict)
+    pub weight: f32,
+    /// Smoothed squared error of the model's one-step forecasts, once backtested
+    pub mse: Option<f32>,
+    pub prediction: Option<Prediction>,
+}
+
+/// Combined forecast alongside every model's own forecast
+#[derive(Debug, Clone)]
+pub struct EnsembleForecast {
+    pub combined: Prediction,
+    pub members: Vec<MemberForecast>,
+}
+
+#[derive(Debug)]
+struct EnsembleMember {
+    name: String,
+    forecaster: Box<dyn Forecaster>,
+    /// One-step forecast awaiting the next observation
+    pending: Option<f32>,
+    mse: Option<f32>,
+}
+
+impl EnsembleMember {
+    /// Score the pending forecast against `value`, feed it, and forecast the next step
+    fn observe(&mut self, timestamp: Option<f64>, value: f32, decay: f32) {
+        if let Some(forecast) = self.pending.take() {
+            let e2 = (value - forecast) * (value - forecast);
+            self.mse = Some(self.mse.map_or(e2, |mse| mse + decay * (e2 - mse)));
         }
+        match timestamp {
+            Some(t) => self.forecaster.add_observation_at(t, value),
+            None => self.forecaster.add_observation(value),
+        }
+        self.pending = self.forecaster.predict(1).and_then(|p| p.values.first().copied());
+    }
+}
+
+/// Runs several forecasters side by side and combines them by recent accuracy
+///
+/// Every observation backtests each model's previous one-step forecast;
+/// models are weighted by the inverse of their smoothed squared error, so the
+/// combination follows whichever model currently tracks the signal best.
+/// Members are updated in parallel with the `parallel` feature. The one-step
+/// backtest forecasts count toward each member's own `prediction_count`.
+#[derive(Debug)]
+pub struct EnsembleForecaster {
+    members: Vec<EnsembleMember>,
+    /// Smoothing factor of the backtest error average
+    decay: f32,
+    last: Option<EnsembleForecast>,
+    prediction_count: usize,
+}
+
+impl Default for EnsembleForecaster {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EnsembleForecaster {
+    /// Create an empty ensemble
+    pub fn new() -> Self {
+        Self {
+            members: Vec::new(),
+            decay: 0.1,
+            last: None,
+            prediction_count: 0,
+        }
+    }
+    
+    /// Add a named model
+    pub fn with_member(mut self, name: impl Into<String>, forecaster: Box<dyn Forecaster>) -> Self {
+        self.add_member(name, forecaster);
+        self
+    }
+    
+    /// Add a named model; it joins the combination once it can predict
+    pub fn add_member(&mut self, name: impl Into<String>, forecaster: Box<dyn Forecaster>) {
+        self.members.push(EnsembleMember {
+            name: name.into(),
+            forecaster,
+            pending: None,
+            mse: None,
+        });
+    }
+    
+    /// Set how quickly backtest errors forget old performance (default 0.1)
+    pub fn with_decay(mut self, decay: f32) -> Self {
+        self.decay = decay.clamp(1e-3, 1.0);
+        self
+    }
+    
+    /// Number of models
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+    
+    /// Check whether no models were added
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+    
+    /// Names and backtest errors of the models, in insertion order
+    pub fn members(&self) -> impl Iterator<Item = (&str, Option<f32>)> {
+        self.members.iter().map(|m| (m.name.as_str(), m.mse))
+    }
+    
+    /// The last forecast made, with per-model detail
+    pub fn last_forecast(&self) -> Option<&EnsembleForecast> {
+        self.last.as_ref()
+    }
+    
+    fn observe(&mut self, timestamp: Option<f64>, value: f32) {
+        let decay = self.decay;
+        #[cfg(feature = "parallel")]
+        let members = self.members.par_iter_mut();
+        #[cfg(not(feature = "parallel"))]
+        let members = self.members.iter_mut();
         
-        // Calculate R-squared for confidence
-        let y_mean = sum_y / n;
-        let mut ss_tot = 0.0;
-        let mut ss_res = 0.0;
+        members.for_each(|m| m.observe(timestamp, value, decay));
+    }
+    
+    /// Forecast with every model and combine them; `None` until at least one model can predict
+    pub fn predict_all(&mut self, horizon: usize) -> Option<EnsembleForecast> {
+        #[cfg(feature = "parallel")]
+        let members = self.members.par_iter_mut();
+        #[cfg(not(feature = "parallel"))]
+        let members = self.members.iter_mut();
         
-        for (i, &y) in self.window.iter().enumerate() {
-            let x = i as f32;
-            let y_pred = slope * x + intercept;
-            ss_tot += (y - y_mean) * (y - y_mean);
-            ss_res += (y - y_pred) * (y - y_pred);
+        let predictions: Vec<Option<Prediction>> = members.map(|m| m.forecaster.predict(horizon)).collect();
+        
+        // Inverse-error weights; models not yet backtested get the average weight
+        let inverse: Vec<Option<f32>> = self.members.iter().map(|m| m.mse.map(|mse| 1.0 / (mse + 1e-8))).collect();
+        let scored: Vec<f32> = inverse.iter().flatten().copied().collect();
+        let fallback = if scored.is_empty() { 1.0 } else { scored.iter().sum::<f32>() / scored.len() as f32 };
+        let mut weights: Vec<f32> = inverse
+            .iter()
+            .zip(&predictions)
+            .map(|(w, p)| if p.is_some() { w.unwrap_or(fallback) } else { 0.0 })
+            .collect();
+        let total: f32 = weights.iter().sum();
+        if total <= 0.0 {
+            return None;
         }
+        weights.iter_mut().for_each(|w| *w /= total);
         
-        let r_squared = if ss_tot > 0.0001 {
-            1.0 - (ss_res / ss_tot)
-        } else {
-            0.0
+        let steps = predictions.iter().flatten().map(|p| p.values.len()).min()?;
+        let blend = |get: &dyn Fn(&Prediction) -> f32| -> f32 {
+            predictions.iter().zip(&weights).filter_map(|(p, w)| p.as_ref().map(|p| w * get(p))).sum()
+        };
+        let combined = Prediction {
+            values: (0..steps).map(|i| blend(&|p| p.values[i])).collect(),
+            lower: (0..steps).map(|i| blend(&|p| p.lower[i])).collect(),
+            upper: (0..steps).map(|i| blend(&|p| p.upper[i])).collect(),
+            confidence: blend(&|p| p.confidence),
+            confidences: (0..steps).map(|i| blend(&|p| p.confidence_at(i))).collect(),
+            trend: blend(&|p| p.trend),
         };
         
-        self.prediction_count += 1;
+        let members = self.members
+            .iter()
+            .zip(predictions)
+            .zip(&weights)
+            .map(|((m, prediction), &weight)| MemberForecast { name: m.name.clone(), weight, mse: m.mse, prediction })
+            .collect();
         
-        Some(Prediction {
-            values: predictions,
-            confidence: r_squared.max(0.0).min(1.0),
-            trend: slope,
-        })
+        self.prediction_count += 1;
+        let forecast = EnsembleForecast { combined, members };
+        self.last = Some(forecast.clone());
+        Some(forecast)
+    }
+}
+
+impl Forecaster for EnsembleForecaster {
+    fn add_observation(&mut self, value: f32) {
+        self.observe(None, value);
     }
     
-    /// Get the number of predictions made
-    #[inline]
-    pub fn prediction_count(&self) -> usize {
+    fn add_observation_at(&mut self, timestamp: f64, value: f32) {
+        self.observe(Some(timestamp), value);
+    }
+    
+    fn predict(&mut self, horizon: usize) -> Option<Prediction> {
+        self.predict_all(horizon).map(|f| f.combined)
+    }
+    
+    fn prediction_count(&self) -> usize {
         self.prediction_count
     }
     
-    /// Clear the predictor state
-    pub fn clear(&mut self) {
-        self.window.clear();
+    /// Clear every model and its backtest record
+    fn clear(&mut self) {
+        for m in &mut self.members {
+            m.forecaster.clear();
+            m.pending = None;
+            m.mse = None;
+        }
+        self.last = None;
         self.prediction_count = 0;
     }
 }
@@ -138,6 +1563,378 @@ mod tests {
         assert!(prediction.confidence > 0.9, "Should have high confidence for linear data");
     }
     
+    #[test]
+    fn test_linear_fit_precision() {
+        // Large, offset x values: the f32 sums cancel, the f64 ones don't
+        let points = |offset: f64| (0..1000).map(move |i| {
+            let x = offset + i as f64;
+            (x, 0.5 + 0.001 * (x - offset))
+        });
+        let fit = LinearFit::<f64>::fit(points(1.0e5)).unwrap();
+        assert!((fit.slope - 0.001).abs() < 1e-12);
+        assert!((fit.value(1.0e5) - 0.5).abs() < 1e-9);
+        assert!(fit.r_squared > 0.999_999);
+        
+        let coarse = LinearFit::<f32>::fit(points(1.0e5).map(|(x, y)| (x as f32, y as f32)));
+        assert!(coarse.is_none_or(|c| (c.slope as f64 - 0.001).abs() > (fit.slope - 0.001).abs()));
+        assert!(LinearFit::<f64>::fit([(1.0, 2.0)]).is_none());
+        
+        let mut predictor = Predictor::new(20);
+        for i in 0..20 {
+            predictor.add_observation(0.2 + i as f32 * 0.01);
+        }
+        let single = predictor.fit_line::<f32>().unwrap();
+        let double = predictor.fit_line::<f64>().unwrap();
+        assert!((single.slope as f64 - double.slope).abs() < 1e-5);
+    }
+    
+    #[test]
+    fn test_holt_trend() {
+        let mut predictor = Predictor::with_model(10, ForecastModel::Holt { alpha: 0.5, beta: 0.3 });
+        for i in 0..20 {
+            predictor.add_observation(0.1 + i as f32 * 0.02);
+        }
+        
+        let prediction = predictor.predict(3).unwrap();
+        assert!((prediction.trend - 0.02).abs() < 1e-4);
+        assert!((prediction.values[2] - 0.54).abs() < 1e-3);
+        assert!(prediction.confidence > 0.9);
+    }
+    
+    #[test]
+    fn test_holt_winters_seasonal() {
+        let period = 12;
+        let series = |t: usize| 0.5 + 0.002 * t as f32 + 0.2 * (t as f32 * std::f32::consts::TAU / period as f32).sin();
+        let model = ForecastModel::HoltWinters { alpha: 0.3, beta: 0.1, gamma: 0.3, period };
+        let mut seasonal = Predictor::with_model(24, model);
+        let mut linear = Predictor::new(24);
+        
+        for t in 0..60 {
+            seasonal.add_observation(series(t));
+            linear.add_observation(series(t));
+        }
+        assert!(Predictor::with_model(24, model).predict(1).is_none());
+        
+        let error = |p: &Prediction| (0..6).map(|h| (p.values[h] - series(60 + h)).abs()).sum::<f32>();
+        let hw = seasonal.predict(6).unwrap();
+        assert!(error(&hw) < 0.1, "error={}", error(&hw));
+        assert!(error(&hw) < error(&linear.predict(6).unwrap()));
+        assert!(hw.confidence > 0.9);
+    }
+    
+    #[test]
+    fn test_arima_autoregressive() {
+        // AR(1) around 0.5 with deterministic pseudo-random shocks
+        let mut seed = 7u32;
+        let mut shock = || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            ((seed >> 8) as f32 / (1u32 << 24) as f32 - 0.5) * 0.05
+        };
+        let mut arima = Predictor::with_model(50, ForecastModel::Arima { p: 1, d: 0, q: 0 });
+        let mut x = 0.9;
+        for _ in 0..500 {
+            x = 0.5 + 0.8 * (x - 0.5) + shock();
+            arima.add_observation(x);
+        }
+        
+        let coefficients = arima.arima_coefficients().unwrap();
+        assert!((coefficients[1] - 0.8).abs() < 0.1, "{:?}", coefficients);
+        assert!((coefficients[0] / (1.0 - coefficients[1]) - 0.5).abs() < 0.05);
+        
+        // Forecasts revert toward the mean
+        let prediction = arima.predict(50).unwrap();
+        assert!((prediction.values[49] - 0.5).abs() < (x - 0.5).abs().max(0.02));
+    }
+    
+    #[test]
+    fn test_arima_differencing() {
+        let mut arima = Predictor::with_model(10, ForecastModel::Arima { p: 1, d: 1, q: 1 });
+        for i in 0..100 {
+            arima.add_observation(0.1 + i as f32 * 0.005);
+        }
+        
+        let prediction = arima.predict(3).unwrap();
+        assert!((prediction.values[0] - 0.6).abs() < 1e-3, "{:?}", prediction.values);
+        assert!((prediction.trend - 0.005).abs() < 1e-3);
+        assert!(prediction.confidence > 0.9);
+    }
+    
+    #[test]
+    fn test_prediction_intervals() {
+        let noisy = |i: usize| 0.5 + 0.01 * i as f32 + if i.is_multiple_of(2) { 0.02 } else { -0.02 };
+        
+        for model in [
+            ForecastModel::LinearRegression,
+            ForecastModel::Holt { alpha: 0.5, beta: 0.1 },
+            ForecastModel::Arima { p: 1, d: 1, q: 0 },
+        ] {
+            let mut predictor = Predictor::with_model(20, model);
+            for i in 0..30 {
+                predictor.add_observation(noisy(i));
+            }
+            let prediction = predictor.predict(5).unwrap();
+            
+            assert_eq!(prediction.lower.len(), 5);
+            for h in 0..5 {
+                assert!(prediction.lower[h] <= prediction.values[h] && prediction.values[h] <= prediction.upper[h]);
+                assert!(prediction.upper[h] - prediction.lower[h] > 0.01, "{:?} {:?}", model, prediction);
+            }
+            // Uncertainty grows with the horizon
+            let width = |h: usize| prediction.upper[h] - prediction.lower[h];
+            assert!(width(4) >= width(0), "{:?}", model);
+        }
+    }
+    
+    #[test]
+    fn test_multivariate() {
+        let mut predictor = MultiPredictor::new(3, 10, ForecastModel::LinearRegression);
+        assert!(predictor.predict(2).is_none());
+        
+        for i in 0..10 {
+            let t = i as f32;
+            predictor.add_observation(&[0.1 + 0.01 * t, 0.5, 0.9 - 0.02 * t]);
+        }
+        
+        let forecasts = predictor.predict(2).unwrap();
+        assert_eq!(forecasts.len(), 3);
+        assert!(forecasts[0].trend > 0.0);
+        assert!((forecasts[1].values[0] - 0.5).abs() < 1e-4);
+        assert!((forecasts[2].values[1] - 0.68).abs() < 1e-3);
+    }
+    
+    #[test]
+    fn test_polynomial_curvature() {
+        // Discharge-like curve: quadratic decay
+        let curve = |t: f32| 0.9 - 0.004 * t * t;
+        let mut quadratic = Predictor::with_model(10, ForecastModel::Polynomial { degree: 2, criterion: None });
+        let mut linear = Predictor::new(10);
+        for i in 0..10 {
+            quadratic.add_observation(curve(i as f32));
+            linear.add_observation(curve(i as f32));
+        }
+        
+        let q = quadratic.predict(3).unwrap();
+        let l = linear.predict(3).unwrap();
+        assert!((q.values[2] - curve(12.0)).abs() < 1e-3, "{:?}", q.values);
+        assert!((q.values[2] - curve(12.0)).abs() < (l.values[2] - curve(12.0)).abs());
+        assert!((q.trend - (-0.008 * 10.0)).abs() < 1e-3);
+    }
+    
+    #[test]
+    fn test_polynomial_degree_selection() {
+        let noisy = |i: usize| if i.is_multiple_of(2) { 0.01 } else { -0.01 };
+        for criterion in [InformationCriterion::Aic, InformationCriterion::Bic] {
+            let model = ForecastModel::Polynomial { degree: 3, criterion: Some(criterion) };
+            
+            // A noisy straight line does not justify extra terms
+            let mut line = Predictor::with_model(20, model);
+            for i in 0..20 {
+                line.add_observation(0.2 + 0.02 * i as f32 + noisy(i));
+            }
+            line.predict(1).unwrap();
+            assert_eq!(line.polynomial_degree(), Some(1), "{:?}", criterion);
+            
+            // Strong curvature does
+            let mut curve = Predictor::with_model(20, model);
+            for i in 0..20 {
+                let t = i as f32 / 19.0;
+                curve.add_observation(0.5 + 0.4 * (t - 0.5).powi(2) + noisy(i) * 0.1);
+            }
+            curve.predict(1).unwrap();
+            assert_eq!(curve.polynomial_degree(), Some(2), "{:?}", criterion);
+        }
+    }
+    
+    #[test]
+    fn test_seasonal_decomposition() {
+        let period = 8;
+        let series = |t: usize| 0.3 + 0.005 * t as f32 + 0.1 * (t as f32 * std::f32::consts::TAU / period as f32).sin();
+        let values: Vec<f32> = (0..48).map(series).collect();
+        
+        let parts = decompose(&values, period).unwrap();
+        assert!(decompose(&values[..15], period).is_none());
+        let max_residual = parts.residual.iter().fold(0.0f32, |m, r| m.max(r.abs()));
+        assert!(max_residual < 0.01, "max residual {}", max_residual);
+        assert!((parts.seasonal[2] - 0.1).abs() < 0.01);
+        assert!((parts.trend[24] - (0.3 + 0.005 * 24.0)).abs() < 0.01);
+        
+        // Forecasts follow the cycle instead of the last window's slope
+        let mut predictor = Predictor::with_model(10, ForecastModel::Stl { period, periods: 6 });
+        for &v in &values {
+            predictor.add_observation(v);
+        }
+        assert_eq!(predictor.decomposition().unwrap().trend.len(), 48);
+        let prediction = predictor.predict(period).unwrap();
+        for (h, v) in prediction.values.iter().enumerate() {
+            assert!((v - series(48 + h)).abs() < 0.01, "h={} {} vs {}", h, v, series(48 + h));
+        }
+        assert!((prediction.trend - 0.005).abs() < 1e-3);
+        assert!(prediction.confidence > 0.95);
+    }
+    
+    #[test]
+    fn test_adaptive_window() {
+        let mut predictor = Predictor::new(10);
+        predictor.set_adaptive_window(Some(AdaptiveWindow { min_size: 4, max_size: 30, sensitivity: 4.0 }));
+        
+        // A steady ramp with small jitter lets the window grow to its bound
+        let jitter = |i: usize| ((i * 37 % 11) as f32 - 5.0) * 0.001;
+        for i in 0..60 {
+            predictor.add_observation(0.2 + i as f32 * 0.002 + jitter(i));
+        }
+        assert_eq!(predictor.window_size(), 30);
+        
+        // A level shift shrinks it so the forecast tracks the new regime
+        predictor.add_observation(0.9);
+        predictor.add_observation(0.9);
+        assert!(predictor.window_size() < 10, "window {}", predictor.window_size());
+        for i in 0..4 {
+            predictor.add_observation(0.9 + jitter(i));
+        }
+        let prediction = predictor.predict(1).unwrap();
+        assert!((prediction.values[0] - 0.9).abs() < 0.05, "{:?}", prediction.values);
+        
+        predictor.set_adaptive_window(None);
+        assert_eq!(predictor.adaptive_window(), None);
+    }
+    
+    #[test]
+    fn test_neural_forecaster() {
+        let series = |t: usize| 0.5 + 0.3 * (t as f32 * std::f32::consts::TAU / 12.0).sin();
+        let mut forecaster = NeuralForecaster::new(6, 8);
+        assert!(forecaster.predict(1).is_none());
+        
+        for t in 0..1500 {
+            forecaster.add_observation(series(t));
+        }
+        assert!(forecaster.rmse().unwrap() < 0.05, "rmse {:?}", forecaster.rmse());
+        
+        let prediction = forecaster.predict(3).unwrap();
+        for (h, v) in prediction.values.iter().enumerate() {
+            assert!((v - series(1500 + h)).abs() < 0.1, "h={} {} vs {}", h, v, series(1500 + h));
+        }
+        assert_eq!(forecaster.prediction_count(), 1);
+        
+        forecaster.clear();
+        assert!(forecaster.rmse().is_none());
+    }
+    
+    #[test]
+    fn test_irregular_timestamps() {
+        // A ramp of 0.01 per second sampled in bursts
+        let times = [0.0, 0.1, 0.2, 2.0, 2.1, 4.0, 4.1, 4.2, 6.0, 6.1];
+        let mut predictor = Predictor::new(10);
+        for &t in &times {
+            predictor.add_observation_at(t, 0.2 + 0.01 * t as f32);
+        }
+        
+        let step = predictor.step_interval().unwrap();
+        assert!((step - 6.1 / 9.0).abs() < 1e-9);
+        let prediction = predictor.predict(3).unwrap();
+        for (i, v) in prediction.values.iter().enumerate() {
+            let t = 6.1 + (i + 1) as f64 * step;
+            assert!((v - (0.2 + 0.01 * t as f32)).abs() < 1e-4, "step {}: {}", i, v);
+        }
+        assert!((prediction.trend - 0.01 * step as f32).abs() < 1e-4);
+        assert!(prediction.confidence > 0.99);
+        
+        let mut polynomial = Predictor::with_model(10, ForecastModel::Polynomial { degree: 2, criterion: None });
+        for &t in &times {
+            polynomial.add_observation_at(t, 0.2 + 0.01 * t as f32);
+        }
+        let values = polynomial.predict(1).unwrap().values;
+        assert!((values[0] - (0.2 + 0.01 * (6.1 + step) as f32)).abs() < 1e-3);
+    }
+    
+    #[test]
+    fn test_save_and_restore() {
+        let models = [
+            ForecastModel::LinearRegression,
+            ForecastModel::Holt { alpha: 0.5, beta: 0.3 },
+            ForecastModel::Arima { p: 2, d: 1, q: 1 },
+            ForecastModel::Stl { period: 4, periods: 3 },
+        ];
+        for model in models {
+            let mut predictor = Predictor::with_model(10, model);
+            predictor.set_adaptive_window(Some(AdaptiveWindow::default()));
+            for i in 0..40 {
+                predictor.add_observation_at(i as f64 * 0.5, 0.3 + 0.1 * ((i % 4) as f32 / 4.0) + i as f32 * 0.002);
+            }
+            
+            let mut buffer = Vec::new();
+            predictor.save_to(&mut buffer).unwrap();
+            let mut restored = Predictor::load_from(buffer.as_slice()).unwrap();
+            assert_eq!(restored.model(), model);
+            assert_eq!(restored.step_interval(), predictor.step_interval());
+            
+            // Both continue identically
+            predictor.add_observation_at(20.0, 0.4);
+            restored.add_observation_at(20.0, 0.4);
+            let (a, b) = (predictor.predict(3).unwrap(), restored.predict(3).unwrap());
+            assert_eq!(a.values, b.values, "{:?}", model);
+            assert_eq!(a.confidence, b.confidence);
+        }
+    }
+    
+    #[test]
+    fn test_ensemble_weights_by_accuracy() {
+        let mut ensemble = EnsembleForecaster::new()
+            .with_member("linear", Box::new(Predictor::new(10)))
+            .with_member("level", Box::new(Predictor::with_model(10, ForecastModel::Exponential { alpha: 0.3 })));
+        assert!(ensemble.predict(1).is_none());
+        
+        // On a ramp the line tracks exactly while the level lags behind
+        for i in 0..50 {
+            ensemble.add_observation_at(i as f64, 0.1 + i as f32 * 0.01);
+        }
+        let forecast = ensemble.predict_all(2).unwrap();
+        assert_eq!(forecast.members.len(), 2);
+        assert!(forecast.members[0].weight > 0.99, "{:?}", forecast.members);
+        assert!((forecast.combined.values[0] - 0.6).abs() < 1e-3);
+        
+        let level = forecast.members[1].prediction.as_ref().unwrap().values[0];
+        assert!(level < 0.6);
+        assert!(forecast.members[1].mse.unwrap() > forecast.members[0].mse.unwrap());
+        assert_eq!(ensemble.last_forecast().unwrap().combined.values, forecast.combined.values);
+        
+        ensemble.clear();
+        assert!(ensemble.members().all(|(_, mse)| mse.is_none()));
+    }
+    
+    #[test]
+    fn test_trend_classification() {
+        assert_eq!(Trend::classify(0.01, 0.01), Trend::Increasing);
+        assert_eq!(Trend::classify(-0.01, 0.01), Trend::Decreasing);
+        assert_eq!(Trend::classify(1e-5, 0.01), Trend::Stable);
+        assert_eq!(Trend::classify(0.05, 0.2), Trend::Volatile);
+        assert_eq!(Trend::classify(0.3, 0.2), Trend::Increasing);
+        assert_eq!(serde_json::to_string(&Trend::Stable).unwrap(), "\"stable\"");
+        
+        // A flat signal is stable, not "decreasing"
+        let mut predictor = Predictor::new(10);
+        for _ in 0..10 {
+            predictor.add_observation(0.5);
+        }
+        assert_eq!(predictor.predict(3).unwrap().direction(), Trend::Stable);
+    }
+    
+    #[test]
+    fn test_confidence_decays_with_horizon() {
+        let mut linear = Predictor::new(10);
+        let mut holt = Predictor::with_model(10, ForecastModel::Holt { alpha: 0.5, beta: 0.2 });
+        for i in 0..30 {
+            let v = 0.3 + i as f32 * 0.005 + ((i * 7 % 5) as f32 - 2.0) * 0.01;
+            linear.add_observation(v);
+            holt.add_observation(v);
+        }
+        for prediction in [linear.predict(20).unwrap(), holt.predict(20).unwrap()] {
+            assert_eq!(prediction.confidences.len(), 20);
+            assert_eq!(prediction.confidence_at(0), prediction.confidence);
+            assert!(prediction.confidences.windows(2).all(|w| w[1] <= w[0]));
+            assert!(prediction.confidence_at(19) < prediction.confidence * 0.8);
+        }
+    }
+    
     #[test]
     fn test_constant_prediction() {
         let mut predictor = Predictor::new(5);
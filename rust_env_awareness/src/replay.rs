@@ -0,0 +1,272 @@
+//! Recorded runs that can be re-run and checked bit for bit
+//!
+//! A `Recorder` runs a seeded system on a `ManualClock` set from each frame's
+//! timestamp, so nothing in a cycle depends on wall time, and logs the
+//! config, every frame and every `CycleResult` as JSON lines. `verify` builds
+//! a fresh system from the logged config, feeds it the same frames at the
+//! same clock times and reports every result that differs from the logged
+//! one, field by field, so a behavior change after a code change stands out.
+//!
+//! Only what `SystemConfig` describes is replayed: custom detectors or
+//! forecasters and changes made while recording are not part of the log.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::ManualClock;
+use crate::config::SystemConfig;
+use crate::error::{GenesisError, Result};
+use crate::sensors::SensorData;
+use crate::{CycleResult, EnvironmentalAwarenessSystem};
+
+/// Version written to and expected in `RecordingHeader::format`
+pub const RECORDING_FORMAT: u32 = 1;
+
+/// Divergences kept in a `ReplayReport`; later ones are only counted
+pub const MAX_DIVERGENCES: usize = 100;
+
+/// First line of a recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingHeader {
+    pub format: u32,
+    /// Config the recorded system was built from, always with a seed
+    pub config: SystemConfig,
+}
+
+/// Every further line of a recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedCycle {
+    /// Clock time of the cycle, from the first frame's timestamp
+    pub at_ns: u64,
+    pub frame: SensorData,
+    pub result: CycleResult,
+}
+
+/// `RecordedCycle` borrowing its parts, for writing without copies
+#[derive(Serialize)]
+struct RecordedCycleRef<'a> {
+    at_ns: u64,
+    frame: &'a SensorData,
+    result: &'a CycleResult,
+}
+
+/// Runs a system deterministically and logs everything needed to replay it
+#[derive(Debug)]
+pub struct Recorder {
+    system: EnvironmentalAwarenessSystem,
+    clock: ManualClock,
+    writer: BufWriter<File>,
+    /// Timestamp of the first frame
+    origin: Option<f64>,
+    last_ns: u64,
+}
+
+impl Recorder {
+    /// Build a system from `config` and start a new log at `path`; a random
+    /// seed is chosen and logged if `config` has none
+    pub fn create(path: impl AsRef<Path>, mut config: SystemConfig) -> Result<Self> {
+        config.seed.get_or_insert_with(rand::random);
+        let mut system = EnvironmentalAwarenessSystem::with_config(config.clone())?;
+        let clock = ManualClock::new();
+        system.set_clock(Box::new(clock.clone()));
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(&mut writer, &RecordingHeader { format: RECORDING_FORMAT, config })?;
+        writer.write_all(b"\n")?;
+        Ok(Self { system, clock, writer, origin: None, last_ns: 0 })
+    }
+
+    /// Process `frame` at its own timestamp and log it with its result
+    ///
+    /// Timestamps running backwards are treated as no time passing.
+    pub fn record(&mut self, frame: &SensorData) -> Result<CycleResult> {
+        let origin = *self.origin.get_or_insert(frame.timestamp);
+        let since_origin = Duration::try_from_secs_f64(frame.timestamp - origin).unwrap_or_default();
+        let at_ns = (since_origin.as_nanos() as u64).max(self.last_ns);
+        self.clock.set(Duration::from_nanos(at_ns));
+        self.last_ns = at_ns;
+
+        let result = self.system.process_sensor_data(frame)?;
+        serde_json::to_writer(&mut self.writer, &RecordedCycleRef { at_ns, frame, result: &result })?;
+        self.writer.write_all(b"\n")?;
+        Ok(result)
+    }
+
+    /// The recorded system, e.g. to read metrics
+    pub fn system(&self) -> &EnvironmentalAwarenessSystem {
+        &self.system
+    }
+
+    /// The recorded system, e.g. to add observers or sinks; changes to its
+    /// behavior are not recorded
+    pub fn system_mut(&mut self) -> &mut EnvironmentalAwarenessSystem {
+        &mut self.system
+    }
+
+    /// Flush the log and hand back the system
+    pub fn finish(mut self) -> Result<EnvironmentalAwarenessSystem> {
+        self.writer.flush()?;
+        Ok(self.system)
+    }
+}
+
+/// One `CycleResult` field whose replayed value differs from the recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldDiff {
+    pub field: String,
+    pub recorded: serde_json::Value,
+    pub replayed: serde_json::Value,
+}
+
+/// A replayed cycle that did not match its recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Divergence {
+    /// Position of the cycle in the recording, from 0
+    pub index: usize,
+    pub cycle: u32,
+    pub fields: Vec<FieldDiff>,
+}
+
+/// Outcome of replaying a recording
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplayReport {
+    pub cycles: usize,
+    /// Cycles whose result differed
+    pub diverged: usize,
+    /// The first `MAX_DIVERGENCES` differing cycles
+    pub divergences: Vec<Divergence>,
+}
+
+impl ReplayReport {
+    /// Whether every replayed result matched its recording exactly
+    pub fn is_identical(&self) -> bool {
+        self.diverged == 0
+    }
+}
+
+/// Re-runs recorded cycles one at a time, comparing as it goes
+#[derive(Debug)]
+pub struct Replayer {
+    system: EnvironmentalAwarenessSystem,
+    clock: ManualClock,
+    report: ReplayReport,
+}
+
+impl Replayer {
+    /// A fresh system built like the recorded one
+    pub fn new(header: &RecordingHeader) -> Result<Self> {
+        if header.format != RECORDING_FORMAT {
+            return Err(GenesisError::Serialization(serde::de::Error::custom(format_args!(
+                "recording format {} is not supported, expected {RECORDING_FORMAT}",
+                header.format
+            ))));
+        }
+        let mut system = EnvironmentalAwarenessSystem::with_config(header.config.clone())?;
+        let clock = ManualClock::new();
+        system.set_clock(Box::new(clock.clone()));
+        Ok(Self { system, clock, report: ReplayReport::default() })
+    }
+
+    /// Replay one cycle; returns whether it matched the recording
+    pub fn check(&mut self, recorded: &RecordedCycle) -> Result<bool> {
+        self.clock.set(Duration::from_nanos(recorded.at_ns));
+        let replayed = self.system.process_sensor_data(&recorded.frame)?;
+        let fields = diff(&recorded.result, &replayed)?;
+        let index = self.report.cycles;
+        self.report.cycles += 1;
+        if fields.is_empty() {
+            return Ok(true);
+        }
+        self.report.diverged += 1;
+        if self.report.divergences.len() < MAX_DIVERGENCES {
+            self.report.divergences.push(Divergence { index, cycle: recorded.result.cycle, fields });
+        }
+        Ok(false)
+    }
+
+    pub fn report(&self) -> &ReplayReport {
+        &self.report
+    }
+
+    pub fn finish(self) -> ReplayReport {
+        self.report
+    }
+}
+
+/// Fields of two results that serialize differently
+fn diff(recorded: &CycleResult, replayed: &CycleResult) -> Result<Vec<FieldDiff>> {
+    let (serde_json::Value::Object(recorded), serde_json::Value::Object(mut replayed)) =
+        (serde_json::to_value(recorded)?, serde_json::to_value(replayed)?)
+    else {
+        return Ok(Vec::new());
+    };
+    Ok(recorded
+        .into_iter()
+        .filter_map(|(field, recorded)| {
+            let replayed = replayed.remove(&field).unwrap_or_default();
+            (recorded != replayed).then_some(FieldDiff { field, recorded, replayed })
+        })
+        .collect())
+}
+
+/// Replay the recording at `path` and compare every cycle with the log
+pub fn verify(path: impl AsRef<Path>) -> Result<ReplayReport> {
+    let mut lines = BufReader::new(File::open(path)?).lines();
+    let header: RecordingHeader = match lines.next() {
+        Some(line) => serde_json::from_str(&line?)?,
+        None => return Ok(ReplayReport::default()),
+    };
+    let mut replayer = Replayer::new(&header)?;
+    for line in lines {
+        let line = line?;
+        if !line.trim().is_empty() {
+            replayer.check(&serde_json::from_str(&line)?)?;
+        }
+    }
+    Ok(replayer.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::fs;
+
+    #[test]
+    fn test_record_and_verify() {
+        let path = std::env::temp_dir().join(format!("genesis_recording_{}.jsonl", std::process::id()));
+        let mut recorder = Recorder::create(&path, SystemConfig::default()).unwrap();
+        let mut rng = StdRng::seed_from_u64(3);
+        for i in 0..60 {
+            let frame = SensorData::generate_with(&mut rng, 1000.0 + i as f64 * 0.01);
+            recorder.record(&frame).unwrap();
+        }
+        assert_eq!(recorder.finish().unwrap().get_metrics().cycles, 60);
+
+        let report = verify(&path).unwrap();
+        assert_eq!((report.cycles, report.diverged), (60, 0));
+        assert!(report.is_identical());
+        // Logged floats must read back exactly, or replays report phantom divergences
+        assert_eq!(serde_json::from_str::<f64>("1.8199999999999998").unwrap(), 1.8199999999999998);
+
+        // Doctor one logged result; exactly that cycle and field stand out
+        let mut lines: Vec<String> = fs::read_to_string(&path).unwrap().lines().map(String::from).collect();
+        let mut entry: RecordedCycle = serde_json::from_str(&lines[10]).unwrap();
+        entry.result.confidence += 0.5;
+        lines[10] = serde_json::to_string(&entry).unwrap();
+        fs::write(&path, lines.join("\n")).unwrap();
+
+        let report = verify(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(report.diverged, 1);
+        let divergence = &report.divergences[0];
+        assert_eq!((divergence.index, divergence.cycle), (9, 10));
+        let fields: Vec<&str> = divergence.fields.iter().map(|f| f.field.as_str()).collect();
+        assert_eq!(fields, ["confidence"]);
+    }
+}
@@ -0,0 +1,305 @@
+//! R-tree index for repeated bounding box queries
+
+use ahash::AHashMap;
+
+use crate::spatial::{Aabb, Coordinates};
+
+/// Per-axis bounds of a tree node
+#[derive(Debug, Clone)]
+struct Bounds {
+    min: Vec<f32>,
+    max: Vec<f32>,
+}
+
+impl Bounds {
+    fn empty(dims: usize) -> Self {
+        Self {
+            min: vec![f32::INFINITY; dims],
+            max: vec![f32::NEG_INFINITY; dims],
+        }
+    }
+
+    fn of_point<P: Coordinates>(p: &P) -> Self {
+        let axes: Vec<f32> = (0..P::DIMENSIONS).map(|i| p.axis(i)).collect();
+        Self { min: axes.clone(), max: axes }
+    }
+
+    fn extend(&mut self, other: &Bounds) {
+        for i in 0..self.min.len() {
+            self.min[i] = self.min[i].min(other.min[i]);
+            self.max[i] = self.max[i].max(other.max[i]);
+        }
+    }
+
+    /// Sum of side lengths; stays meaningful for flat boxes where volume is zero
+    fn margin(&self) -> f32 {
+        self.min.iter().zip(&self.max).map(|(lo, hi)| (hi - lo).max(0.0)).sum()
+    }
+
+    fn enlargement(&self, other: &Bounds) -> f32 {
+        let mut merged = self.clone();
+        merged.extend(other);
+        merged.margin() - self.margin()
+    }
+
+    fn center(&self, axis: usize) -> f32 {
+        (self.min[axis] + self.max[axis]) * 0.5
+    }
+
+    fn intersects<P: Coordinates>(&self, aabb: &Aabb<P>) -> bool {
+        (0..self.min.len()).all(|i| self.min[i] <= aabb.max.axis(i) && self.max[i] >= aabb.min.axis(i))
+    }
+
+    fn contains_point<P: Coordinates>(&self, p: &P) -> bool {
+        (0..self.min.len()).all(|i| {
+            let v = p.axis(i);
+            v >= self.min[i] && v <= self.max[i]
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Entries<P> {
+    Leaf(Vec<(usize, P)>),
+    Inner(Vec<usize>),
+}
+
+#[derive(Debug, Clone)]
+struct TreeNode<P> {
+    bounds: Bounds,
+    entries: Entries<P>,
+}
+
+/// Dynamic R-tree over node positions
+///
+/// Removal does not shrink node bounds, so heavy churn loosens the tree;
+/// `SpatialGraph::compact` rebuilds it.
+#[derive(Debug, Clone)]
+pub struct RTree<P> {
+    nodes: Vec<TreeNode<P>>,
+    root: usize,
+    max_entries: usize,
+    positions: AHashMap<usize, P>,
+}
+
+impl<P: Coordinates> RTree<P> {
+    /// Create an empty tree with at most `max_entries` per node
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            nodes: vec![TreeNode { bounds: Bounds::empty(P::DIMENSIONS), entries: Entries::Leaf(Vec::new()) }],
+            root: 0,
+            max_entries: max_entries.max(4),
+            positions: AHashMap::new(),
+        }
+    }
+
+    /// Maximum entries per node
+    pub fn max_entries(&self) -> usize {
+        self.max_entries
+    }
+
+    /// Number of indexed points
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Check whether the tree is empty
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Estimate heap memory held by the tree in bytes
+    pub fn estimate_memory(&self) -> usize {
+        let per_node: usize = self.nodes.iter()
+            .map(|node| {
+                2 * node.bounds.min.capacity() * std::mem::size_of::<f32>()
+                    + match &node.entries {
+                        Entries::Leaf(e) => e.capacity() * std::mem::size_of::<(usize, P)>(),
+                        Entries::Inner(c) => c.capacity() * std::mem::size_of::<usize>(),
+                    }
+            })
+            .sum();
+
+        self.nodes.capacity() * std::mem::size_of::<TreeNode<P>>()
+            + per_node
+            + self.positions.capacity() * (std::mem::size_of::<(usize, P)>() + 1)
+    }
+
+    /// Insert a point; re-inserting an id moves it
+    pub fn insert(&mut self, id: usize, point: P) {
+        self.remove(id);
+        self.positions.insert(id, point);
+
+        if let Some(sibling) = self.insert_into(self.root, id, point) {
+            let mut bounds = self.nodes[self.root].bounds.clone();
+            bounds.extend(&self.nodes[sibling].bounds);
+            self.nodes.push(TreeNode { bounds, entries: Entries::Inner(vec![self.root, sibling]) });
+            self.root = self.nodes.len() - 1;
+        }
+    }
+
+    /// Remove a point by id
+    pub fn remove(&mut self, id: usize) -> bool {
+        let Some(point) = self.positions.remove(&id) else {
+            return false;
+        };
+
+        let mut stack = vec![self.root];
+        while let Some(index) = stack.pop() {
+            if !self.nodes[index].bounds.contains_point(&point) {
+                continue;
+            }
+            match &mut self.nodes[index].entries {
+                Entries::Leaf(entries) => {
+                    if let Some(at) = entries.iter().position(|&(e, _)| e == id) {
+                        entries.swap_remove(at);
+                        return true;
+                    }
+                }
+                Entries::Inner(children) => stack.extend(children.iter().copied()),
+            }
+        }
+        true
+    }
+
+    /// Ids of all points inside a bounding box
+    pub fn query(&self, aabb: &Aabb<P>) -> Vec<usize> {
+        let mut found = Vec::new();
+        let mut stack = vec![self.root];
+
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !node.bounds.intersects(aabb) {
+                continue;
+            }
+            match &node.entries {
+                Entries::Leaf(entries) => {
+                    found.extend(entries.iter().filter(|(_, p)| aabb.contains(p)).map(|&(id, _)| id));
+                }
+                Entries::Inner(children) => stack.extend(children.iter().copied()),
+            }
+        }
+
+        found.sort_unstable();
+        found
+    }
+
+    /// Insert below `index`, returning a new sibling if the node split
+    fn insert_into(&mut self, index: usize, id: usize, point: P) -> Option<usize> {
+        let point_bounds = Bounds::of_point(&point);
+        self.nodes[index].bounds.extend(&point_bounds);
+
+        let child = match &mut self.nodes[index].entries {
+            Entries::Leaf(entries) => {
+                entries.push((id, point));
+                return (entries.len() > self.max_entries).then(|| self.split(index));
+            }
+            Entries::Inner(children) => {
+                let children = children.clone();
+                // Least enlargement keeps sibling boxes from overlapping
+                children
+                    .into_iter()
+                    .min_by(|&a, &b| {
+                        let ea = self.nodes[a].bounds.enlargement(&point_bounds);
+                        let eb = self.nodes[b].bounds.enlargement(&point_bounds);
+                        ea.total_cmp(&eb)
+                    })
+                    .expect("inner nodes always have children")
+            }
+        };
+
+        let sibling = self.insert_into(child, id, point)?;
+        let Entries::Inner(children) = &mut self.nodes[index].entries else {
+            unreachable!("child was chosen from an inner node");
+        };
+        children.push(sibling);
+        (children.len() > self.max_entries).then(|| self.split(index))
+    }
+
+    /// Split an overfull node in half along its widest axis
+    fn split(&mut self, index: usize) -> usize {
+        let bounds = &self.nodes[index].bounds;
+        let axis = (0..P::DIMENSIONS)
+            .max_by(|&a, &b| (bounds.max[a] - bounds.min[a]).total_cmp(&(bounds.max[b] - bounds.min[b])))
+            .unwrap_or(0);
+
+        let entries = std::mem::replace(&mut self.nodes[index].entries, Entries::Inner(Vec::new()));
+        let (left, right) = match entries {
+            Entries::Leaf(mut entries) => {
+                entries.sort_unstable_by(|a, b| a.1.axis(axis).total_cmp(&b.1.axis(axis)));
+                let right = entries.split_off(entries.len() / 2);
+                (Entries::Leaf(entries), Entries::Leaf(right))
+            }
+            Entries::Inner(mut children) => {
+                children.sort_unstable_by(|&a, &b| {
+                    self.nodes[a].bounds.center(axis).total_cmp(&self.nodes[b].bounds.center(axis))
+                });
+                let right = children.split_off(children.len() / 2);
+                (Entries::Inner(children), Entries::Inner(right))
+            }
+        };
+
+        let left_bounds = self.bounds_of(&left);
+        let right_bounds = self.bounds_of(&right);
+        self.nodes[index] = TreeNode { bounds: left_bounds, entries: left };
+        self.nodes.push(TreeNode { bounds: right_bounds, entries: right });
+        self.nodes.len() - 1
+    }
+
+    fn bounds_of(&self, entries: &Entries<P>) -> Bounds {
+        let mut bounds = Bounds::empty(P::DIMENSIONS);
+        match entries {
+            Entries::Leaf(entries) => entries.iter().for_each(|(_, p)| bounds.extend(&Bounds::of_point(p))),
+            Entries::Inner(children) => children.iter().for_each(|&c| bounds.extend(&self.nodes[c].bounds)),
+        }
+        bounds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spatial::Position;
+
+    fn scan(points: &[Position], aabb: &Aabb) -> Vec<usize> {
+        (0..points.len()).filter(|&i| aabb.contains(&points[i])).collect()
+    }
+
+    #[test]
+    fn test_query_matches_scan() {
+        let points: Vec<Position> = (0..2000)
+            .map(|i| Position { x: (i * 37 % 101) as f32, y: (i * 53 % 97) as f32, z: (i % 7) as f32 })
+            .collect();
+
+        let mut tree = RTree::new(8);
+        for (id, p) in points.iter().enumerate() {
+            tree.insert(id, *p);
+        }
+        assert_eq!(tree.len(), 2000);
+
+        for q in 0..20 {
+            let lo = q as f32 * 4.0;
+            let aabb = Aabb {
+                min: Position { x: lo, y: lo * 0.5, z: 1.0 },
+                max: Position { x: lo + 15.0, y: lo * 0.5 + 30.0, z: 4.0 },
+            };
+            assert_eq!(tree.query(&aabb), scan(&points, &aabb));
+        }
+    }
+
+    #[test]
+    fn test_remove_and_move() {
+        let mut tree = RTree::new(4);
+        for i in 0..50 {
+            tree.insert(i, Position { x: i as f32, y: 0.0, z: 0.0 });
+        }
+
+        let aabb = Aabb { min: Position { x: 9.5, y: -1.0, z: -1.0 }, max: Position { x: 12.5, y: 1.0, z: 1.0 } };
+        assert_eq!(tree.query(&aabb), vec![10, 11, 12]);
+
+        assert!(tree.remove(11));
+        tree.insert(40, Position { x: 10.0, y: 0.5, z: 0.0 });
+        assert_eq!(tree.query(&aabb), vec![10, 12, 40]);
+        assert!(!tree.remove(11));
+    }
+}
@@ -0,0 +1,219 @@
+//! Fixed-rate cycle scheduling
+//!
+//! `Scheduler` paces a loop at a fixed rate. Tick times are computed from
+//! the start of the run rather than by adding the period to the previous
+//! tick, so rounding and late wake-ups never accumulate into drift. Each
+//! wait sleeps until shortly before the tick and spins for the rest, since
+//! sleeps overshoot by up to a scheduler quantum. A cycle that runs past the
+//! next tick is an overrun: the ticks it covered are skipped instead of run
+//! back to back, so the loop stays in phase without bursts.
+//!
+//! `EnvironmentalAwarenessSystem::run_at_hz` drives cycles with it until a
+//! `StopSignal` fires.
+
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{GenesisError, Result};
+use crate::latency::{LatencyHistogram, Summary};
+use crate::StageStats;
+
+/// Final stretch before a tick that is busy-waited instead of slept
+pub const SPIN_MARGIN: Duration = Duration::from_micros(100);
+
+/// Cloneable request for scheduled loops to return
+///
+/// Stopping wakes a loop waiting for its next tick, so it returns without
+/// finishing the period.
+#[derive(Debug, Clone, Default)]
+pub struct StopSignal {
+    state: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl StopSignal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop every loop holding a clone of this signal
+    pub fn stop(&self) {
+        let (stopped, wake) = &*self.state;
+        *stopped.lock().unwrap_or_else(PoisonError::into_inner) = true;
+        wake.notify_all();
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        *self.state.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Block until `deadline` or a stop; returns whether stopped
+    fn wait_until(&self, deadline: Instant) -> bool {
+        let (stopped, wake) = &*self.state;
+        let mut stopped = stopped.lock().unwrap_or_else(PoisonError::into_inner);
+        while !*stopped {
+            let Some(timeout) = deadline.checked_duration_since(Instant::now()) else {
+                break;
+            };
+            stopped = wake.wait_timeout(stopped, timeout).unwrap_or_else(PoisonError::into_inner).0;
+        }
+        *stopped
+    }
+}
+
+/// Tick times of a fixed-rate schedule, anchored at its start
+#[derive(Debug, Clone, Copy)]
+struct Ticks {
+    start: Instant,
+    rate_hz: f64,
+    /// Index of the upcoming tick
+    next: u64,
+}
+
+impl Ticks {
+    fn at(&self, tick: u64) -> Instant {
+        self.start + Duration::from_secs_f64(tick as f64 / self.rate_hz)
+    }
+
+    fn upcoming(&self) -> Instant {
+        self.at(self.next)
+    }
+
+    /// Move on after a cycle that ended at `now`; returns the ticks it ran past
+    fn advance(&mut self, now: Instant) -> u64 {
+        self.next += 1;
+        if now <= self.upcoming() {
+            return 0;
+        }
+        let passed = (now.saturating_duration_since(self.start).as_secs_f64() * self.rate_hz) as u64;
+        let skipped = (passed + 1).saturating_sub(self.next);
+        self.next += skipped;
+        skipped
+    }
+}
+
+/// Timing of a scheduled run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleReport {
+    pub target_hz: f64,
+    /// Cycles per second actually run
+    pub achieved_hz: f64,
+    pub cycles: u64,
+    /// Cycles that were still running at the next tick
+    pub overruns: u64,
+    /// Ticks skipped because of overruns
+    pub skipped_ticks: u64,
+    /// How late cycles started after their tick
+    pub jitter: StageStats,
+    pub runtime_seconds: f64,
+}
+
+/// Paces a loop at a fixed rate and measures how well it kept to it
+#[derive(Debug, Clone)]
+pub struct Scheduler {
+    ticks: Ticks,
+    jitter: LatencyHistogram,
+    cycles: u64,
+    overruns: u64,
+    skipped_ticks: u64,
+}
+
+impl Scheduler {
+    /// Schedule `rate_hz` cycles per second, the first one now
+    pub fn new(rate_hz: f64) -> Result<Self> {
+        if !(rate_hz.is_finite() && rate_hz > 0.0) {
+            return Err(GenesisError::InvalidRate(rate_hz));
+        }
+        Ok(Self {
+            ticks: Ticks { start: Instant::now(), rate_hz, next: 0 },
+            jitter: LatencyHistogram::new(),
+            cycles: 0,
+            overruns: 0,
+            skipped_ticks: 0,
+        })
+    }
+
+    /// Wait for the next tick; false if `stop` fired first
+    pub fn wait(&mut self, stop: &StopSignal) -> bool {
+        let tick = self.ticks.upcoming();
+        if stop.wait_until(tick.checked_sub(SPIN_MARGIN).unwrap_or(tick)) {
+            return false;
+        }
+        while Instant::now() < tick {
+            std::hint::spin_loop();
+        }
+        self.jitter.record(Instant::now().saturating_duration_since(tick).as_nanos() as u64);
+        true
+    }
+
+    /// Record the end of the cycle started after the last `wait`
+    pub fn finish_cycle(&mut self) {
+        self.cycles += 1;
+        let skipped = self.ticks.advance(Instant::now());
+        if skipped > 0 {
+            self.overruns += 1;
+            self.skipped_ticks += skipped;
+        }
+    }
+
+    pub fn report(&self) -> ScheduleReport {
+        let runtime = self.ticks.start.elapsed().as_secs_f64();
+        ScheduleReport {
+            target_hz: self.ticks.rate_hz,
+            achieved_hz: if runtime > 0.0 { self.cycles as f64 / runtime } else { 0.0 },
+            cycles: self.cycles,
+            overruns: self.overruns,
+            skipped_ticks: self.skipped_ticks,
+            jitter: Summary::of_histogram(&self.jitter).into(),
+            runtime_seconds: runtime,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_ticks_skip_after_overrun() {
+        let start = Instant::now();
+        let ms = |n: u64| start + Duration::from_millis(n);
+        let mut ticks = Ticks { start, rate_hz: 100.0, next: 0 };
+
+        assert_eq!(ticks.advance(ms(4)), 0);
+        assert_eq!(ticks.upcoming(), ms(10));
+        // Running from 10 ms to 35 ms covers the ticks at 20 and 30 ms
+        assert_eq!(ticks.advance(ms(35)), 2);
+        assert_eq!(ticks.upcoming(), ms(40));
+        // Ending exactly on the tick is still on time
+        assert_eq!(ticks.advance(ms(50)), 0);
+        assert_eq!(ticks.upcoming(), ms(50));
+    }
+
+    #[test]
+    fn test_stop_wakes_waiting_loop() {
+        assert!(matches!(Scheduler::new(0.0), Err(GenesisError::InvalidRate(_))));
+
+        // One tick a minute, so only the stop can end the wait
+        let mut scheduler = Scheduler::new(1.0 / 60.0).unwrap();
+        let stop = StopSignal::new();
+        assert!(scheduler.wait(&stop));
+        scheduler.finish_cycle();
+
+        let remote = stop.clone();
+        let stopper = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            remote.stop();
+        });
+        let waited = Instant::now();
+        assert!(!scheduler.wait(&stop));
+        assert!(waited.elapsed() < Duration::from_secs(10));
+        stopper.join().unwrap();
+
+        assert!(stop.is_stopped());
+        let report = scheduler.report();
+        assert_eq!((report.cycles, report.overruns), (1, 0));
+    }
+}
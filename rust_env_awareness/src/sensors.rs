@@ -1,10 +1,171 @@
 //! High-performance sensor processing module
 
-use rand::{thread_rng, Rng};
-use std::f32::consts::PI;
+use alloc::vec::Vec;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
+use rand::thread_rng;
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::{GenesisError, Result};
+use crate::float::Float;
+
+/// Names of the default feature channels, in `ProcessedSensorData::features` order
+pub const FEATURE_NAMES: [&str; 4] = ["visual_objects", "lidar_points", "audio_amplitude", "imu_accel_x"];
+
+/// A physical sensor of `SensorData`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Sensor {
+    Visual,
+    Lidar,
+    Audio,
+    Imu,
+}
+
+/// A single reading of `SensorData` that a feature channel can be built from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reading {
+    VisualObjects,
+    VisualBrightness,
+    VisualMotion,
+    LidarPoints,
+    LidarMaxRange,
+    LidarObstacles,
+    AudioAmplitude,
+    AudioFrequency,
+    AudioEventType,
+    ImuAccelX,
+    ImuAccelY,
+    ImuAccelZ,
+    ImuGyro,
+}
+
+impl Reading {
+    /// Channel name, as used in explanations and exports
+    pub fn name(self) -> &'static str {
+        match self {
+            Reading::VisualObjects => "visual_objects",
+            Reading::VisualBrightness => "visual_brightness",
+            Reading::VisualMotion => "visual_motion",
+            Reading::LidarPoints => "lidar_points",
+            Reading::LidarMaxRange => "lidar_max_range",
+            Reading::LidarObstacles => "lidar_obstacles",
+            Reading::AudioAmplitude => "audio_amplitude",
+            Reading::AudioFrequency => "audio_frequency",
+            Reading::AudioEventType => "audio_event_type",
+            Reading::ImuAccelX => "imu_accel_x",
+            Reading::ImuAccelY => "imu_accel_y",
+            Reading::ImuAccelZ => "imu_accel_z",
+            Reading::ImuGyro => "imu_gyro",
+        }
+    }
+    
+    /// Sensor the reading comes from
+    pub fn sensor(self) -> Sensor {
+        match self {
+            Reading::VisualObjects | Reading::VisualBrightness | Reading::VisualMotion => Sensor::Visual,
+            Reading::LidarPoints | Reading::LidarMaxRange | Reading::LidarObstacles => Sensor::Lidar,
+            Reading::AudioAmplitude | Reading::AudioFrequency | Reading::AudioEventType => Sensor::Audio,
+            Reading::ImuAccelX | Reading::ImuAccelY | Reading::ImuAccelZ | Reading::ImuGyro => Sensor::Imu,
+        }
+    }
+    
+    /// Raw value of the reading in `data`
+    #[inline]
+    pub fn of(self, data: &SensorData) -> f32 {
+        match self {
+            Reading::VisualObjects => data.visual.objects as f32,
+            Reading::VisualBrightness => data.visual.brightness,
+            Reading::VisualMotion => data.visual.motion,
+            Reading::LidarPoints => data.lidar.points as f32,
+            Reading::LidarMaxRange => data.lidar.max_range,
+            Reading::LidarObstacles => data.lidar.obstacles as f32,
+            Reading::AudioAmplitude => data.audio.amplitude,
+            Reading::AudioFrequency => data.audio.frequency,
+            Reading::AudioEventType => data.audio.event_type as f32,
+            Reading::ImuAccelX => data.imu.accel_x,
+            Reading::ImuAccelY => data.imu.accel_y,
+            Reading::ImuAccelZ => data.imu.accel_z,
+            Reading::ImuGyro => data.imu.gyro,
+        }
+    }
+}
+
+/// One entry of the processed feature vector and its share of the fused confidence
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FeatureChannel {
+    pub reading: Reading,
+    /// The reading is divided by this to bring it to roughly `[0, 1]`
+    pub scale: f32,
+    /// Use the reading's magnitude, for signed readings
+    #[serde(default)]
+    pub absolute: bool,
+    /// Fusion weight, relative to the other channels' weights
+    pub weight: f32,
+}
+
+impl FeatureChannel {
+    pub fn new(reading: Reading, scale: f32, weight: f32) -> Self {
+        Self { reading, scale, absolute: false, weight }
+    }
+    
+    /// Use the reading's magnitude
+    pub fn absolute(mut self) -> Self {
+        self.absolute = true;
+        self
+    }
+    
+    /// Normalized feature value of this channel in `data`
+    #[inline]
+    pub fn extract(&self, data: &SensorData) -> f32 {
+        self.extract_as(data)
+    }
+    
+    /// `extract` computed at precision `F`
+    #[inline]
+    pub fn extract_as<F: Float>(&self, data: &SensorData) -> F {
+        let value = F::from_f32(self.reading.of(data));
+        let value = if self.absolute { value.abs() } else { value };
+        value / F::from_f32(self.scale)
+    }
+}
+
+/// The channels named by `FEATURE_NAMES`, which `SystemConfig` starts from
+pub fn default_channels() -> Vec<FeatureChannel> {
+    alloc::vec![
+        FeatureChannel::new(Reading::VisualObjects, 10.0, 0.3),
+        FeatureChannel::new(Reading::LidarPoints, 1500.0, 0.3),
+        FeatureChannel::new(Reading::AudioAmplitude, 1.0, 0.2),
+        FeatureChannel::new(Reading::ImuAccelX, 1.0, 0.2).absolute(),
+    ]
+}
+
+/// Why `channels` cannot configure a `SensorProcessor`, if they can't
+pub fn check_channels(channels: &[FeatureChannel]) -> Option<&'static str> {
+    if channels.is_empty() {
+        return Some("needs at least one channel");
+    }
+    if channels.iter().any(|c| !(c.scale.is_finite() && c.scale > 0.0)) {
+        return Some("scales must be positive and finite");
+    }
+    if channels.iter().any(|c| !(c.weight.is_finite() && c.weight >= 0.0)) {
+        return Some("weights must be non-negative and finite");
+    }
+    if channels.iter().map(|c| c.weight).sum::<f32>() <= 0.0 {
+        return Some("at least one weight must be positive");
+    }
+    let duplicate = channels.iter().enumerate().any(|(i, c)| channels[..i].iter().any(|d| d.reading == c.reading));
+    if duplicate {
+        return Some("each reading can be used once");
+    }
+    None
+}
 
 /// Sensor data structure
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SensorData {
     pub visual: VisualData,
     pub lidar: LidarData,
@@ -13,28 +174,28 @@ pub struct SensorData {
     pub timestamp: f64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VisualData {
     pub objects: u8,
     pub brightness: f32,
     pub motion: f32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LidarData {
     pub points: u16,
     pub max_range: f32,
     pub obstacles: u8,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AudioData {
     pub amplitude: f32,
     pub frequency: f32,
     pub event_type: u8,  // 0: quiet, 1: normal, 2: loud
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImuData {
     pub accel_x: f32,
     pub accel_y: f32,
@@ -44,17 +205,22 @@ pub struct ImuData {
 
 impl SensorData {
     /// Generate realistic sensor data
+    #[cfg(feature = "std")]
     pub fn generate() -> Self {
-        let mut rng = thread_rng();
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs_f64();
-        
+        // A clock set before the epoch yields a negative timestamp rather than a panic
+        let timestamp = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_secs_f64(),
+            Err(before) => -before.duration().as_secs_f64(),
+        };
+        Self::generate_with(&mut thread_rng(), timestamp)
+    }
+    
+    /// Generate sensor data from the given RNG, stamped with `timestamp`
+    pub fn generate_with<R: Rng + ?Sized>(rng: &mut R, timestamp: f64) -> Self {
         Self {
             visual: VisualData {
                 objects: rng.gen_range(2..=10),
-                brightness: 0.5 + 0.3 * (timestamp / 5.0).sin() as f32,
+                brightness: 0.5 + 0.3 * libm::sin(timestamp / 5.0) as f32,
                 motion: rng.gen::<f32>(),
             },
             lidar: LidarData {
@@ -76,38 +242,121 @@ impl SensorData {
             timestamp,
         }
     }
+    
+    /// Check that every analog reading is finite
+    pub fn validate(&self) -> Result<()> {
+        let readings = [
+            ("visual.brightness", self.visual.brightness),
+            ("visual.motion", self.visual.motion),
+            ("lidar.max_range", self.lidar.max_range),
+            ("audio.amplitude", self.audio.amplitude),
+            ("audio.frequency", self.audio.frequency),
+            ("imu.accel_x", self.imu.accel_x),
+            ("imu.accel_y", self.imu.accel_y),
+            ("imu.accel_z", self.imu.accel_z),
+            ("imu.gyro", self.imu.gyro),
+        ];
+        match readings.into_iter().find(|(_, value)| !value.is_finite()) {
+            Some((field, value)) => Err(GenesisError::InvalidReading { field, value }),
+            None if !self.timestamp.is_finite() => {
+                Err(GenesisError::InvalidReading { field: "timestamp", value: self.timestamp as f32 })
+            }
+            None => Ok(()),
+        }
+    }
 }
 
 /// Processed sensor data
 #[derive(Debug, Clone)]
-pub struct ProcessedSensorData {
-    pub features: Vec<f32>,
-    pub fused_confidence: f32,
+pub struct ProcessedSensorData<F: Float = f32> {
+    pub features: Vec<F>,
+    pub fused_confidence: F,
+}
+
+/// Support each sensor lends the fused confidence, each in `[0, 1]`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SensorConfidences {
+    pub visual: f32,
+    pub lidar: f32,
+    pub audio: f32,
+    pub imu: f32,
+}
+
+impl SensorConfidences {
+    /// Mean clamped feature of each sensor's channels; sensors without channels count as 0
+    pub fn from_channels(channels: &[FeatureChannel], features: &[f32]) -> Self {
+        let sensor = |sensor: Sensor| {
+            let (sum, count) = channels
+                .iter()
+                .zip(features)
+                .filter(|(c, _)| c.reading.sensor() == sensor)
+                .fold((0.0, 0), |(sum, count), (_, f)| (sum + f.clamp(0.0, 1.0), count + 1));
+            if count == 0 { 0.0 } else { sum / count as f32 }
+        };
+        Self {
+            visual: sensor(Sensor::Visual),
+            lidar: sensor(Sensor::Lidar),
+            audio: sensor(Sensor::Audio),
+            imu: sensor(Sensor::Imu),
+        }
+    }
 }
 
 /// High-performance sensor processor
+#[derive(Debug, Clone)]
 pub struct SensorProcessor {
-    weights: [f32; 4],
+    channels: Vec<FeatureChannel>,
+    /// Channel weights normalized to sum to 1
+    weights: Vec<f32>,
 }
 
 impl SensorProcessor {
-    /// Create a new sensor processor
+    /// Create a sensor processor with the default channels
     pub fn new() -> Self {
-        Self {
-            weights: [0.3, 0.3, 0.2, 0.2],  // Fusion weights
+        Self::with_channels(default_channels()).expect("default channels are valid")
+    }
+    
+    /// Create a processor producing one feature per channel
+    pub fn with_channels(channels: Vec<FeatureChannel>) -> Result<Self> {
+        if let Some(reason) = check_channels(&channels) {
+            return Err(GenesisError::InvalidChannels(reason));
         }
+        let total: f32 = channels.iter().map(|c| c.weight).sum();
+        let weights = channels.iter().map(|c| c.weight / total).collect();
+        Ok(Self { channels, weights })
+    }
+    
+    /// Channels in feature order
+    pub fn channels(&self) -> &[FeatureChannel] {
+        &self.channels
+    }
+    
+    /// Length of every feature vector this processor produces
+    pub fn feature_count(&self) -> usize {
+        self.channels.len()
+    }
+    
+    /// Name of feature `index`
+    pub fn feature_name(&self, index: usize) -> Option<&'static str> {
+        self.channels.get(index).map(|c| c.reading.name())
+    }
+    
+    /// Support each sensor lends the fused confidence
+    pub fn confidences(&self, features: &[f32]) -> SensorConfidences {
+        SensorConfidences::from_channels(&self.channels, features)
     }
     
     /// Process sensor data with SIMD-friendly operations
     #[inline]
     pub fn process(&self, data: &SensorData) -> ProcessedSensorData {
+        self.process_with_buffer(data, Vec::with_capacity(self.channels.len()))
+    }
+    
+    /// Like `process`, writing the features into `features`' allocation
+    pub fn process_with_buffer(&self, data: &SensorData, mut features: Vec<f32>) -> ProcessedSensorData {
         // Extract normalized features
-        let features = vec![
-            data.visual.objects as f32 / 10.0,
-            data.lidar.points as f32 / 1500.0,
-            data.audio.amplitude,
-            data.imu.accel_x.abs(),
-        ];
+        features.clear();
+        features.extend(self.channels.iter().map(|channel| channel.extract(data)));
         
         // Sensor fusion using SIMD-friendly operations
         let fused_confidence = self.fuse_sensors(&features);
@@ -121,19 +370,23 @@ impl SensorProcessor {
     /// Fast sensor fusion
     #[inline(always)]
     fn fuse_sensors(&self, features: &[f32]) -> f32 {
-        // Manual unrolling for known size
-        if features.len() == 4 {
-            features[0] * self.weights[0] +
-            features[1] * self.weights[1] +
-            features[2] * self.weights[2] +
-            features[3] * self.weights[3]
-        } else {
-            // Fallback for different sizes
-            features.iter()
-                .zip(self.weights.iter())
-                .map(|(f, w)| f * w)
-                .sum()
-        }
+        features.iter()
+            .zip(self.weights.iter())
+            .map(|(f, w)| f * w)
+            .sum()
+    }
+    
+    /// `process` computed at precision `F`, with the weights normalized at
+    /// that precision too; `process_as::<f32>` matches `process`
+    pub fn process_as<F: Float>(&self, data: &SensorData) -> ProcessedSensorData<F> {
+        let features: Vec<F> = self.channels.iter().map(|channel| channel.extract_as(data)).collect();
+        let total: F = self.channels.iter().map(|c| F::from_f32(c.weight)).sum();
+        let fused_confidence = features
+            .iter()
+            .zip(&self.channels)
+            .map(|(&f, c)| f * (F::from_f32(c.weight) / total))
+            .sum();
+        ProcessedSensorData { features, fused_confidence }
     }
     
     /// Batch process multiple sensor readings
@@ -144,7 +397,13 @@ impl SensorProcessor {
     }
 }
 
-#[cfg(test)]
+impl Default for SensorProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
     
@@ -163,7 +422,63 @@ mod tests {
         let data = SensorData::generate();
         let processed = processor.process(&data);
         
-        assert_eq!(processed.features.len(), 4);
+        assert_eq!(processed.features.len(), FEATURE_NAMES.len());
         assert!(processed.fused_confidence >= 0.0 && processed.fused_confidence <= 1.0);
+        
+        let confidences = processor.confidences(&processed.features);
+        assert_eq!(confidences.audio, data.audio.amplitude);
+        assert_eq!(processor.confidences(&[2.0, -1.0]).lidar, 0.0);
+        assert_eq!(processor.confidences(&[2.0, -1.0]).visual, 1.0);
+        assert_eq!(processor.confidences(&[2.0, -1.0]).imu, 0.0);
+    }
+    
+    #[test]
+    fn test_configured_channels() {
+        let channels = vec![
+            FeatureChannel::new(Reading::LidarObstacles, 5.0, 3.0),
+            FeatureChannel::new(Reading::ImuAccelZ, 10.0, 1.0),
+            FeatureChannel::new(Reading::ImuGyro, 0.1, 0.0).absolute(),
+        ];
+        let processor = SensorProcessor::with_channels(channels).unwrap();
+        let mut data = SensorData::generate();
+        data.lidar.obstacles = 5;
+        data.imu.accel_z = 9.0;
+        data.imu.gyro = -0.05;
+        
+        let processed = processor.process(&data);
+        assert_eq!(processed.features, [1.0, 0.9, 0.5]);
+        // Weights are normalized, so the gyro channel lends nothing
+        assert!((processed.fused_confidence - (0.75 + 0.25 * 0.9)).abs() < 1e-6);
+        assert_eq!(processor.feature_name(2), Some("imu_gyro"));
+        
+        let confidences = processor.confidences(&processed.features);
+        assert_eq!((confidences.lidar, confidences.imu, confidences.visual), (1.0, 0.7, 0.0));
+        
+        let invalid = [FeatureChannel::new(Reading::AudioFrequency, 0.0, 1.0)];
+        assert!(matches!(SensorProcessor::with_channels(invalid.to_vec()), Err(GenesisError::InvalidChannels(_))));
+        assert_eq!(check_channels(&default_channels()), None);
+    }
+    
+    #[test]
+    fn test_process_as_f64() {
+        let processor = SensorProcessor::new();
+        let data = SensorData::generate();
+        let single = processor.process(&data);
+        assert_eq!(processor.process_as::<f32>(&data).features, single.features);
+        
+        let double = processor.process_as::<f64>(&data);
+        for (a, b) in double.features.iter().zip(&single.features) {
+            assert!((*a as f32 - b).abs() < 1e-6);
+        }
+        assert!((double.fused_confidence as f32 - single.fused_confidence).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn test_validate() {
+        let mut data = SensorData::generate();
+        assert!(data.validate().is_ok());
+        
+        data.imu.gyro = f32::NAN;
+        assert!(matches!(data.validate(), Err(GenesisError::InvalidReading { field: "imu.gyro", .. })));
     }
 }
\ No newline at end of file
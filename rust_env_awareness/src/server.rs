@@ -0,0 +1,312 @@
+//! Embedded REST server exposing a system as JSON
+//!
+//! ```text
+//! GET  /metrics         SystemMetrics
+//! GET  /anomalies       most recent anomalies, oldest first
+//! GET  /graph/summary   spatial graph size, memory and connectivity
+//! GET  /cycles/latest   last CycleResult (null before the first cycle)
+//! GET  /health          HealthReport; 503 when a component is unhealthy
+//! POST /frames          ingest one SensorData, returns its CycleResult
+//! GET  /stream          WebSocket of StreamEvents as JSON text messages
+//! ```
+//!
+//! Handlers lock the system only for the duration of one cycle or metrics
+//! call and never across an `.await`. Stream events are encoded once and
+//! broadcast; a client that falls more than `STREAM_BUFFER` events behind
+//! gets a `lagged` event in place of what it missed, so a slow dashboard
+//! never holds back the cycle or other clients.
+
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use axum::extract::ws::{Message, Utf8Bytes, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Serialize;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::anomaly::Anomaly;
+use crate::error::{GenesisError, Result};
+use crate::health::HealthReport;
+use crate::observer::SystemObserver;
+use crate::sensors::SensorData;
+use crate::{CycleResult, EnvironmentalAwarenessSystem, SystemMetrics};
+
+/// Body of `/graph/summary`
+pub use crate::debug::GraphSummary;
+
+/// Anomalies kept for `/anomalies`
+pub const RECENT_ANOMALIES: usize = 100;
+
+/// Events a `/stream` client may fall behind by before skipping ahead
+pub const STREAM_BUFFER: usize = 256;
+
+/// Message pushed to `/stream` clients, tagged by `type`
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamEvent {
+    Cycle(Box<CycleResult>),
+    Anomaly(Anomaly),
+    /// The client was too slow and `skipped` events were dropped for it
+    Lagged { skipped: u64 },
+}
+
+impl StreamEvent {
+    fn encode(&self) -> Utf8Bytes {
+        serde_json::to_string(self).unwrap_or_default().into()
+    }
+}
+
+/// What the read endpoints report between cycles
+#[derive(Debug, Default)]
+struct Recent {
+    latest: Option<CycleResult>,
+    anomalies: VecDeque<Anomaly>,
+}
+
+/// Observer filling `Recent` and feeding stream clients from inside the cycle
+#[derive(Debug)]
+struct Recorder {
+    recent: Arc<Mutex<Recent>>,
+    events: broadcast::Sender<Utf8Bytes>,
+}
+
+impl Recorder {
+    /// Encode and broadcast `event` only if someone is listening
+    fn publish(&self, event: impl FnOnce() -> StreamEvent) {
+        if self.events.receiver_count() > 0 {
+            let _ = self.events.send(event().encode());
+        }
+    }
+}
+
+impl SystemObserver for Recorder {
+    fn on_cycle(&mut self, result: &CycleResult) {
+        lock(&self.recent).latest = Some(result.clone());
+        self.publish(|| StreamEvent::Cycle(Box::new(result.clone())));
+    }
+
+    fn on_anomaly(&mut self, anomaly: &Anomaly) {
+        let mut recent = lock(&self.recent);
+        if recent.anomalies.len() == RECENT_ANOMALIES {
+            recent.anomalies.pop_front();
+        }
+        recent.anomalies.push_back(anomaly.clone());
+        self.publish(|| StreamEvent::Anomaly(anomaly.clone()));
+    }
+}
+
+/// Lock ignoring poisoning; a panicked handler leaves the data usable
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// A system shared between HTTP handlers
+#[derive(Debug, Clone)]
+pub struct Server {
+    system: Arc<Mutex<EnvironmentalAwarenessSystem>>,
+    recent: Arc<Mutex<Recent>>,
+    events: broadcast::Sender<Utf8Bytes>,
+}
+
+impl Server {
+    pub fn new(mut system: EnvironmentalAwarenessSystem) -> Self {
+        let recent = Arc::new(Mutex::new(Recent::default()));
+        let (events, _) = broadcast::channel(STREAM_BUFFER);
+        system.add_observer(Box::new(Recorder {
+            recent: Arc::clone(&recent),
+            events: events.clone(),
+        }));
+        Self {
+            system: Arc::new(Mutex::new(system)),
+            recent,
+            events,
+        }
+    }
+
+    /// JSON-encoded `StreamEvent`s from now on, as sent to `/stream` clients
+    pub fn subscribe(&self) -> broadcast::Receiver<Utf8Bytes> {
+        self.events.subscribe()
+    }
+
+    /// The served system, e.g. for driving simulated cycles alongside ingestion
+    pub fn system(&self) -> Arc<Mutex<EnvironmentalAwarenessSystem>> {
+        Arc::clone(&self.system)
+    }
+
+    /// Routes for mounting into a larger application
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/metrics", get(metrics))
+            .route("/anomalies", get(anomalies))
+            .route("/graph/summary", get(graph_summary))
+            .route("/cycles/latest", get(latest_cycle))
+            .route("/health", get(health))
+            .route("/frames", post(ingest_frame))
+            .route("/stream", get(stream))
+            .with_state(self.clone())
+    }
+
+    /// Listen on `addr` until the task is cancelled
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        axum::serve(listener, self.router()).await?;
+        Ok(())
+    }
+}
+
+/// `GenesisError` as an HTTP response with a JSON body
+#[derive(Debug)]
+struct ApiError(GenesisError);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match self.0 {
+            GenesisError::InvalidReading { .. } | GenesisError::DimensionMismatch { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, Json(serde_json::json!({ "error": self.0.to_string() }))).into_response()
+    }
+}
+
+async fn metrics(State(server): State<Server>) -> Json<SystemMetrics> {
+    Json(lock(&server.system).get_metrics())
+}
+
+async fn anomalies(State(server): State<Server>) -> Json<Vec<Anomaly>> {
+    Json(lock(&server.recent).anomalies.iter().cloned().collect())
+}
+
+async fn graph_summary(State(server): State<Server>) -> Json<GraphSummary> {
+    Json(lock(&server.system).graph_summary())
+}
+
+async fn latest_cycle(State(server): State<Server>) -> Json<Option<CycleResult>> {
+    Json(lock(&server.recent).latest.clone())
+}
+
+/// Fails with 503 only when a component is unhealthy, so it can back a readiness probe
+async fn health(State(server): State<Server>) -> (StatusCode, Json<HealthReport>) {
+    let report = lock(&server.system).health();
+    let status = if report.is_ready() { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(report))
+}
+
+async fn ingest_frame(State(server): State<Server>, Json(data): Json<SensorData>) -> Result<Json<CycleResult>, ApiError> {
+    lock(&server.system).process_sensor_data(&data).map(Json).map_err(ApiError)
+}
+
+async fn stream(State(server): State<Server>, ws: WebSocketUpgrade) -> Response {
+    let events = server.subscribe();
+    ws.on_upgrade(move |socket| forward_events(socket, events))
+}
+
+/// Relay events to one client until either side goes away
+async fn forward_events(mut socket: WebSocket, mut events: broadcast::Receiver<Utf8Bytes>) {
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                let text = match event {
+                    Ok(text) => text,
+                    Err(RecvError::Lagged(skipped)) => StreamEvent::Lagged { skipped }.encode(),
+                    Err(RecvError::Closed) => break,
+                };
+                if socket.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn call(server: &Server, method: &str, uri: &str, body: Option<String>) -> (StatusCode, serde_json::Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(body.map_or_else(Body::empty, Body::from))
+            .unwrap();
+        let response = server.router().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_endpoints() {
+        let server = Server::new(EnvironmentalAwarenessSystem::new());
+        let (status, latest) = call(&server, "GET", "/cycles/latest", None).await;
+        assert_eq!((status, latest), (StatusCode::OK, serde_json::Value::Null));
+
+        let mut data = SensorData::generate();
+        let (status, result) = call(&server, "POST", "/frames", Some(serde_json::to_string(&data).unwrap())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(result["cycle"], 1);
+
+        server.system().lock().unwrap().run_cycles(20);
+        let (_, latest) = call(&server, "GET", "/cycles/latest", None).await;
+        assert_eq!(latest["cycle"], 21);
+        let (_, metrics) = call(&server, "GET", "/metrics", None).await;
+        assert_eq!(metrics["cycles"], 21);
+        let (_, summary) = call(&server, "GET", "/graph/summary", None).await;
+        assert_eq!(summary["nodes"], 21);
+        let (_, anomalies) = call(&server, "GET", "/anomalies", None).await;
+        assert!(anomalies.as_array().unwrap().len() <= metrics["anomalies_detected"].as_u64().unwrap() as usize);
+        let (status, health) = call(&server, "GET", "/health", None).await;
+        assert_eq!((status, &health["status"]), (StatusCode::OK, &serde_json::json!("healthy")));
+
+        // Past the graph capacity the probe fails
+        server.system().lock().unwrap().run_cycles(1000);
+        let (status, health) = call(&server, "GET", "/health", None).await;
+        assert_eq!((status, &health["status"]), (StatusCode::SERVICE_UNAVAILABLE, &serde_json::json!("unhealthy")));
+
+        // JSON cannot carry NaN, so check the mapping for rejected readings directly
+        data.imu.gyro = f32::NAN;
+        let error = server.system().lock().unwrap().process_sensor_data(&data).unwrap_err();
+        assert_eq!(ApiError(error).into_response().status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    #[tokio::test]
+    async fn test_stream_pushes_cycles() {
+        use tokio_stream::StreamExt;
+        use tokio_tungstenite::tungstenite::Message as ClientMessage;
+
+        let server = Server::new(EnvironmentalAwarenessSystem::new());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let router = server.router();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        let (mut client, _) = tokio_tungstenite::connect_async(format!("ws://{addr}/stream")).await.unwrap();
+        // The handler subscribes before answering the upgrade, so no event is missed
+        let results = server.system().lock().unwrap().run_cycles(5);
+
+        let mut cycles = Vec::new();
+        while cycles.len() < 5 {
+            let Some(Ok(ClientMessage::Text(text))) = client.next().await else {
+                panic!("stream closed early");
+            };
+            let event: serde_json::Value = serde_json::from_str(&text).unwrap();
+            match event["type"].as_str() {
+                Some("cycle") => cycles.push(event["cycle"].as_u64().unwrap()),
+                Some("anomaly") => assert!(results.iter().any(|r| r.anomaly_detected)),
+                other => panic!("unexpected event {other:?}"),
+            }
+        }
+        assert_eq!(cycles, [1, 2, 3, 4, 5]);
+    }
+}
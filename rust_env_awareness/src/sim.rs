@@ -0,0 +1,483 @@
+//! Sensor frames sourced from a physics simulation
+//!
+//! Validating the system in simulation before it meets hardware means
+//! feeding it frames derived from a simulated scene. `SimScene` is the seam
+//! to the simulator (a Genesis scene behind a bridge, or any other engine):
+//! it steps time, reports the state of the body carrying the sensors, casts
+//! rays and optionally summarizes rendered camera frames. `SimSensors` turns
+//! those into `SensorData`: a ray-cast lidar, an IMU measuring the body's
+//! specific force and yaw rate, and visual statistics from rendering.
+//! Simulators render no sound, so the microphone reports silence.
+//!
+//! `PrimitiveScene` is a scene in plain Rust, a body following waypoints
+//! (such as a trajectory exported from a Genesis run) among planes and
+//! spheres, for exercising the adapter without a simulator.
+//!
+//! The IMU's axes are the world's: the body is assumed not to rotate.
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{GenesisError, Result};
+use crate::sensors::{AudioData, ImuData, LidarData, SensorData, VisualData};
+use crate::{CycleResult, EnvironmentalAwarenessSystem};
+
+/// A point or direction in world coordinates, in meters
+pub type Vec3 = [f32; 3];
+
+fn dot(a: Vec3, b: Vec3) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn sub(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn add(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+/// Kinematic state of the body carrying the sensors
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct BodyState {
+    pub position: Vec3,
+    /// Meters per second
+    pub velocity: Vec3,
+    /// Radians per second about the world axes
+    pub angular_velocity: Vec3,
+}
+
+/// Summary of a rendered camera frame
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RenderStats {
+    /// Entities visible in the frame
+    pub objects: u8,
+    /// Mean luminance, in `[0, 1]`
+    pub brightness: f32,
+    /// Mean absolute luminance change since the previous frame, in `[0, 1]`
+    pub motion: f32,
+}
+
+impl RenderStats {
+    /// Statistics of an RGB8 frame, against the previous frame if it has the same size
+    pub fn from_rgb(frame: &[u8], previous: Option<&[u8]>, objects: u8) -> Self {
+        let luminance = |pixel: &[u8]| {
+            (0.2126 * pixel[0] as f32 + 0.7152 * pixel[1] as f32 + 0.0722 * pixel[2] as f32) / 255.0
+        };
+        let pixels = frame.len() / 3;
+        if pixels == 0 {
+            return Self { objects, ..Self::default() };
+        }
+        let brightness = frame.chunks_exact(3).map(luminance).sum::<f32>() / pixels as f32;
+        let motion = match previous {
+            Some(previous) if previous.len() == frame.len() => {
+                frame
+                    .chunks_exact(3)
+                    .zip(previous.chunks_exact(3))
+                    .map(|(now, before)| (luminance(now) - luminance(before)).abs())
+                    .sum::<f32>()
+                    / pixels as f32
+            }
+            _ => 0.0,
+        };
+        Self { objects, brightness, motion }
+    }
+}
+
+/// A simulated world the sensors are placed in
+pub trait SimScene: fmt::Debug + Send {
+    /// Simulated time, in seconds
+    fn time(&self) -> f64;
+
+    /// Advance the simulation by one step
+    fn step(&mut self);
+
+    /// State of the body carrying the sensors
+    fn body(&self) -> BodyState;
+
+    /// Distance from `origin` along the unit vector `direction` to the first
+    /// surface, if there is one within `max_range`
+    fn ray_cast(&self, origin: Vec3, direction: Vec3, max_range: f32) -> Option<f32>;
+
+    /// Statistics of the frame the scene's camera sees now; `None` for
+    /// scenes without a renderer
+    fn render(&mut self) -> Option<RenderStats> {
+        None
+    }
+}
+
+/// Placement and resolution of the simulated sensors
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimSensorConfig {
+    /// Lidar rays per revolution of each ring
+    pub lidar_azimuths: u16,
+    /// Elevation of each lidar ring, in radians above the horizon
+    pub lidar_elevations: Vec<f32>,
+    /// Farthest distance the lidar reports
+    pub lidar_range: f32,
+    /// Hits closer than this make their azimuth part of an obstacle
+    pub obstacle_range: f32,
+    /// Lidar origin relative to the body position
+    pub lidar_offset: Vec3,
+    /// Gravitational acceleration of the scene, which the IMU measures at rest
+    pub gravity: Vec3,
+}
+
+impl Default for SimSensorConfig {
+    fn default() -> Self {
+        Self {
+            lidar_azimuths: 360,
+            lidar_elevations: vec![-0.2, 0.0, 0.2],
+            lidar_range: 100.0,
+            obstacle_range: 2.0,
+            lidar_offset: [0.0; 3],
+            gravity: [0.0, 0.0, -9.81],
+        }
+    }
+}
+
+impl SimSensorConfig {
+    /// Why the config cannot drive `SimSensors`, if it can't
+    pub fn check(&self) -> Option<&'static str> {
+        if self.lidar_azimuths == 0 || self.lidar_elevations.is_empty() {
+            return Some("the lidar needs at least one azimuth and one ring");
+        }
+        if (self.lidar_azimuths as usize) * self.lidar_elevations.len() > u16::MAX as usize {
+            return Some("the lidar casts more rays than a frame can count");
+        }
+        if !(self.lidar_range.is_finite() && self.lidar_range > 0.0) {
+            return Some("the lidar range must be positive and finite");
+        }
+        if !self.obstacle_range.is_finite() || self.lidar_elevations.iter().any(|e| !e.is_finite()) {
+            return Some("lidar geometry must be finite");
+        }
+        if self.lidar_offset.iter().chain(&self.gravity).any(|v| !v.is_finite()) {
+            return Some("offset and gravity must be finite");
+        }
+        None
+    }
+}
+
+/// Simulated lidar, IMU, camera and microphone reading a `SimScene`
+#[derive(Debug)]
+pub struct SimSensors<S: SimScene> {
+    scene: S,
+    config: SimSensorConfig,
+    /// Ray directions, ring by ring, each ring in azimuth order
+    rays: Vec<Vec3>,
+    /// Time and velocity of the previous frame, to differentiate
+    previous: Option<(f64, Vec3)>,
+}
+
+impl<S: SimScene> SimSensors<S> {
+    pub fn new(scene: S, config: SimSensorConfig) -> Result<Self> {
+        if let Some(reason) = config.check() {
+            return Err(GenesisError::InvalidSimulation(reason.to_string()));
+        }
+        let azimuths = config.lidar_azimuths as usize;
+        let rays = config
+            .lidar_elevations
+            .iter()
+            .flat_map(|&elevation| {
+                (0..azimuths).map(move |i| {
+                    let azimuth = std::f32::consts::TAU * i as f32 / azimuths as f32;
+                    let (sin_e, cos_e) = elevation.sin_cos();
+                    [cos_e * azimuth.cos(), cos_e * azimuth.sin(), sin_e]
+                })
+            })
+            .collect();
+        Ok(Self { scene, config, rays, previous: None })
+    }
+
+    pub fn scene(&self) -> &S {
+        &self.scene
+    }
+
+    pub fn scene_mut(&mut self) -> &mut S {
+        &mut self.scene
+    }
+
+    pub fn into_scene(self) -> S {
+        self.scene
+    }
+
+    /// Read every sensor in the scene's current state, stamped with its time
+    pub fn sense(&mut self) -> SensorData {
+        let body = self.scene.body();
+        let timestamp = self.scene.time();
+        let lidar = self.lidar(&body);
+        let imu = self.imu(&body, timestamp);
+        let visual = match self.scene.render() {
+            Some(stats) => VisualData { objects: stats.objects, brightness: stats.brightness, motion: stats.motion },
+            // Without a renderer, what the lidar sees stands in for the camera
+            None => VisualData { objects: lidar.obstacles, brightness: 0.0, motion: 0.0 },
+        };
+        let audio = AudioData { amplitude: 0.0, frequency: 0.0, event_type: 0 };
+        SensorData { visual, lidar, audio, imu, timestamp }
+    }
+
+    /// Step the scene and read the sensors
+    pub fn next_frame(&mut self) -> SensorData {
+        self.scene.step();
+        self.sense()
+    }
+
+    /// Step the scene `cycles` times, running a cycle of `system` on each frame
+    ///
+    /// The system keeps its own clock; set a `ManualClock` from each frame's
+    /// timestamp first if rates should follow simulated time.
+    pub fn run(&mut self, system: &mut EnvironmentalAwarenessSystem, cycles: usize) -> Result<Vec<CycleResult>> {
+        (0..cycles).map(|_| system.process_sensor_data(&self.next_frame())).collect()
+    }
+
+    fn lidar(&self, body: &BodyState) -> LidarData {
+        let origin = add(body.position, self.config.lidar_offset);
+        let range = self.config.lidar_range;
+        let azimuths = self.config.lidar_azimuths as usize;
+        let mut points = 0u16;
+        let mut farthest = 0.0f32;
+        let mut close = vec![false; azimuths];
+        for (i, &direction) in self.rays.iter().enumerate() {
+            if let Some(distance) = self.scene.ray_cast(origin, direction, range) {
+                points += 1;
+                farthest = farthest.max(distance);
+                close[i % azimuths] |= distance < self.config.obstacle_range;
+            }
+        }
+        // An obstacle is a run of adjacent close azimuths, wrapping around
+        let starts = (0..azimuths).filter(|&i| close[i] && !close[(i + azimuths - 1) % azimuths]).count();
+        let obstacles = if starts == 0 && close[0] { 1 } else { starts };
+        LidarData { points, max_range: farthest, obstacles: obstacles.min(u8::MAX as usize) as u8 }
+    }
+
+    fn imu(&mut self, body: &BodyState, timestamp: f64) -> ImuData {
+        let acceleration = match self.previous {
+            Some((then, velocity)) if timestamp > then => {
+                let dt = (timestamp - then) as f32;
+                let dv = sub(body.velocity, velocity);
+                [dv[0] / dt, dv[1] / dt, dv[2] / dt]
+            }
+            _ => [0.0; 3],
+        };
+        self.previous = Some((timestamp, body.velocity));
+        // An accelerometer measures specific force: at rest it reads against gravity
+        let force = sub(acceleration, self.config.gravity);
+        ImuData { accel_x: force[0], accel_y: force[1], accel_z: force[2], gyro: body.angular_velocity[2] }
+    }
+}
+
+impl<S: SimScene> Iterator for SimSensors<S> {
+    type Item = SensorData;
+
+    fn next(&mut self) -> Option<SensorData> {
+        Some(self.next_frame())
+    }
+}
+
+/// Static geometry of a `PrimitiveScene`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Primitive {
+    /// Points `p` with `normal · p = offset`; `normal` has unit length
+    Plane { normal: Vec3, offset: f32 },
+    Sphere { center: Vec3, radius: f32 },
+}
+
+impl Primitive {
+    /// Horizontal ground at height `z`
+    pub fn ground(z: f32) -> Self {
+        Primitive::Plane { normal: [0.0, 0.0, 1.0], offset: z }
+    }
+
+    /// Distance along a unit ray to the surface, if it is hit ahead of `origin`
+    pub fn intersect(&self, origin: Vec3, direction: Vec3) -> Option<f32> {
+        match *self {
+            Primitive::Plane { normal, offset } => {
+                let facing = dot(normal, direction);
+                if facing.abs() < 1e-9 {
+                    return None;
+                }
+                let t = (offset - dot(normal, origin)) / facing;
+                (t >= 0.0).then_some(t)
+            }
+            Primitive::Sphere { center, radius } => {
+                let to_origin = sub(origin, center);
+                let b = dot(to_origin, direction);
+                let c = dot(to_origin, to_origin) - radius * radius;
+                let discriminant = b * b - c;
+                if discriminant < 0.0 {
+                    return None;
+                }
+                let root = discriminant.sqrt();
+                // The far side when the origin is inside the sphere
+                [-b - root, -b + root].into_iter().find(|&t| t >= 0.0)
+            }
+        }
+    }
+}
+
+/// A body moving through waypoints, one per step, among static primitives
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PrimitiveScene {
+    primitives: Vec<Primitive>,
+    waypoints: Vec<Vec3>,
+    /// Seconds per step
+    dt: f64,
+    step: usize,
+}
+
+impl PrimitiveScene {
+    /// An empty scene advancing `dt` seconds per step
+    pub fn new(dt: f64) -> Self {
+        Self { dt, ..Self::default() }
+    }
+
+    pub fn with_primitive(mut self, primitive: Primitive) -> Self {
+        self.primitives.push(primitive);
+        self
+    }
+
+    /// Positions the body takes at steps 0, 1, ...; it rests at the last one
+    pub fn with_waypoints(mut self, waypoints: Vec<Vec3>) -> Self {
+        self.waypoints = waypoints;
+        self
+    }
+
+    /// Load waypoints from a `step,x,y,z` CSV with a header line, as exported
+    /// from Genesis runs (see `data/sphere_trajectory.csv`)
+    pub fn load_trajectory(self, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        let mut waypoints = Vec::new();
+        for (index, line) in text.lines().enumerate().skip(1).filter(|(_, l)| !l.trim().is_empty()) {
+            let values: Vec<f32> = line.split(',').skip(1).map(|v| v.trim().parse()).collect::<Result<_, _>>().map_err(|e| {
+                GenesisError::InvalidSimulation(format!("{} line {}: {e}", path.display(), index + 1))
+            })?;
+            match values[..] {
+                [x, y, z] => waypoints.push([x, y, z]),
+                _ => {
+                    return Err(GenesisError::InvalidSimulation(format!(
+                        "{} line {}: expected step,x,y,z",
+                        path.display(),
+                        index + 1
+                    )))
+                }
+            }
+        }
+        Ok(self.with_waypoints(waypoints))
+    }
+
+    fn waypoint(&self, step: usize) -> Vec3 {
+        match self.waypoints.len() {
+            0 => [0.0; 3],
+            len => self.waypoints[step.min(len - 1)],
+        }
+    }
+}
+
+impl SimScene for PrimitiveScene {
+    fn time(&self) -> f64 {
+        self.step as f64 * self.dt
+    }
+
+    fn step(&mut self) {
+        self.step += 1;
+    }
+
+    fn body(&self) -> BodyState {
+        let position = self.waypoint(self.step);
+        let velocity = match self.step {
+            0 => [0.0; 3],
+            step => {
+                let delta = sub(position, self.waypoint(step - 1));
+                let dt = self.dt as f32;
+                [delta[0] / dt, delta[1] / dt, delta[2] / dt]
+            }
+        };
+        BodyState { position, velocity, angular_velocity: [0.0; 3] }
+    }
+
+    fn ray_cast(&self, origin: Vec3, direction: Vec3, max_range: f32) -> Option<f32> {
+        self.primitives
+            .iter()
+            .filter_map(|primitive| primitive.intersect(origin, direction))
+            .filter(|&t| t <= max_range)
+            .min_by(f32::total_cmp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ray_cast_lidar() {
+        let scene = PrimitiveScene::new(0.1)
+            .with_primitive(Primitive::ground(0.0))
+            .with_primitive(Primitive::Sphere { center: [1.5, 0.0, 1.0], radius: 0.5 })
+            .with_waypoints(vec![[0.0, 0.0, 1.0]]);
+        assert_eq!(scene.ray_cast([0.0, 0.0, 1.0], [1.0, 0.0, 0.0], 10.0), Some(1.0));
+        assert_eq!(scene.ray_cast([0.0, 0.0, 1.0], [0.0, 0.0, -1.0], 10.0), Some(1.0));
+        assert_eq!(scene.ray_cast([0.0, 0.0, 1.0], [0.0, 0.0, 1.0], 10.0), None);
+        assert_eq!(Primitive::Sphere { center: [0.0; 3], radius: 2.0 }.intersect([0.0; 3], [0.0, 1.0, 0.0]), Some(2.0));
+
+        let mut sensors = SimSensors::new(scene, SimSensorConfig::default()).unwrap();
+        let frame = sensors.sense();
+        // Only the downward ring reaches the ground; one ray row of the horizon ring hits the sphere
+        assert!(frame.lidar.points > 360 && frame.lidar.points < 720);
+        assert_eq!(frame.lidar.obstacles, 1);
+        assert_eq!(frame.visual.objects, 1);
+        assert!(frame.validate().is_ok());
+
+        let config = SimSensorConfig { lidar_azimuths: 0, ..SimSensorConfig::default() };
+        assert!(matches!(SimSensors::new(PrimitiveScene::new(0.1), config), Err(GenesisError::InvalidSimulation(_))));
+    }
+
+    #[test]
+    fn test_imu_from_dynamics() {
+        // Falling freely under gravity, then resting on the ground
+        let dt = 0.1f32;
+        let waypoints = (0..5).map(|i| [0.0, 0.0, 2.0 - 0.5 * 9.81 * (i as f32 * dt).powi(2)]).collect();
+        let scene = PrimitiveScene::new(dt as f64).with_primitive(Primitive::ground(0.0)).with_waypoints(waypoints);
+        let mut sensors = SimSensors::new(scene, SimSensorConfig::default()).unwrap();
+
+        let at_rest = sensors.sense();
+        assert_eq!((at_rest.imu.accel_x, at_rest.imu.accel_z), (0.0, 9.81));
+        let frames: Vec<SensorData> = sensors.by_ref().take(6).collect();
+        // An accelerometer in free fall reads nothing
+        assert!(frames[2].imu.accel_z.abs() < 1e-3);
+        assert!((frames[1].timestamp - 0.2).abs() < 1e-6);
+        // Stopping at the last waypoint shows up as a jolt, then gravity again
+        assert!(frames[4].imu.accel_z > 9.81);
+        assert!((frames[5].imu.accel_z - 9.81).abs() < 1e-3);
+        assert_eq!(frames[5].audio.amplitude, 0.0);
+
+        let mut system = EnvironmentalAwarenessSystem::new();
+        let results = sensors.run(&mut system, 10).unwrap();
+        assert_eq!(results.len(), 10);
+        assert_eq!(system.get_metrics().cycles, 10);
+    }
+
+    #[test]
+    fn test_render_stats_and_trajectory() {
+        let dark = [0u8; 12];
+        let light = [255u8; 12];
+        let stats = RenderStats::from_rgb(&light, Some(&dark), 2);
+        assert!((stats.brightness - 1.0).abs() < 1e-6 && (stats.motion - 1.0).abs() < 1e-6);
+        assert_eq!(RenderStats::from_rgb(&light, Some(&dark[..3]), 0).motion, 0.0);
+
+        let path = std::env::temp_dir().join(format!("genesis_trajectory_{}.csv", std::process::id()));
+        fs::write(&path, "step,x,y,z\n  0, 0.0, 0.0, 1.0\n  1, 0.5, 0.0, 1.0\n").unwrap();
+        let mut scene = PrimitiveScene::new(0.5).load_trajectory(&path).unwrap();
+        scene.step();
+        assert_eq!(scene.body().velocity, [1.0, 0.0, 0.0]);
+
+        fs::write(&path, "step,x,y,z\n0, 0.0, ten, 1.0\n").unwrap();
+        assert!(matches!(PrimitiveScene::new(0.5).load_trajectory(&path), Err(GenesisError::InvalidSimulation(_))));
+        fs::remove_file(&path).unwrap();
+    }
+}
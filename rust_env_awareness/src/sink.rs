@@ -0,0 +1,533 @@
+//! Durable logs of every cycle's result
+//!
+//! A `ResultSink` receives each `CycleResult`. `JsonLinesSink` and `CsvSink`
+//! append them to a file and, with a `Rotation`, move on to a fresh file past
+//! a size or age limit; earlier files are kept as `results.1.jsonl`,
+//! `results.2.jsonl` and so on next to the active one. Register a sink with
+//! `EnvironmentalAwarenessSystem::add_result_sink`, or call `write` yourself
+//! to handle write errors.
+//!
+//! `MetricsLog` keeps a timeline of `SystemMetrics` snapshots the same way,
+//! and a `MetricsWriter` appends one from a `MetricsHandle` at a fixed
+//! interval on a background thread. Every record is flushed as it is
+//! written, so a deployment that crashes leaves its history up to the last
+//! interval on disk.
+
+use std::fmt::{self, Write as _};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{GenesisError, Result};
+use crate::observer::SystemObserver;
+use crate::telemetry::MetricsHandle;
+use crate::{CycleResult, SystemMetrics};
+
+/// Columns written by `CsvSink`; lists are `;`-separated, absent values empty
+pub const CSV_COLUMNS: [&str; 23] = [
+    "cycle",
+    "confidence",
+    "node_id",
+    "anomaly_detected",
+    "anomaly_id",
+    "anomaly_score",
+    "severity",
+    "predicted",
+    "prediction_confidence",
+    "trend",
+    "processing_us",
+    "sensor_ns",
+    "neural_ns",
+    "spatial_ns",
+    "anomaly_ns",
+    "prediction_ns",
+    "visual_confidence",
+    "lidar_confidence",
+    "audio_confidence",
+    "imu_confidence",
+    "model_version",
+    "neighbors",
+    "neural_output",
+];
+
+/// Destination for every cycle's result
+pub trait ResultSink: fmt::Debug + Send {
+    /// Record one cycle
+    fn write(&mut self, result: &CycleResult) -> Result<()>;
+
+    /// Push buffered records to storage
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called once when logging ends, e.g. to write a file footer; flushes by default
+    fn finish(&mut self) -> Result<()> {
+        self.flush()
+    }
+}
+
+/// When a file sink starts a new file; never, by default
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rotation {
+    /// Start a new file once the current one holds this many bytes
+    pub max_bytes: Option<u64>,
+    /// Start a new file once the current one has been open this long
+    pub max_age: Option<Duration>,
+}
+
+impl Rotation {
+    pub fn by_size(max_bytes: u64) -> Self {
+        Self { max_bytes: Some(max_bytes), ..Self::default() }
+    }
+
+    pub fn by_age(max_age: Duration) -> Self {
+        Self { max_age: Some(max_age), ..Self::default() }
+    }
+}
+
+/// Append-only file that renames itself aside when a `Rotation` limit is hit
+#[derive(Debug)]
+struct RotatingFile {
+    path: PathBuf,
+    rotation: Rotation,
+    /// Written at the top of every new file
+    header: String,
+    /// `None` after a failed rotation; the next write reopens `path`
+    writer: Option<BufWriter<File>>,
+    bytes: u64,
+    opened: Instant,
+    /// Lowest number that may be free for the next rotated file
+    next_index: u32,
+    rotations: usize,
+}
+
+impl RotatingFile {
+    fn open(path: &Path, rotation: Rotation, header: String) -> Result<Self> {
+        let mut file = Self {
+            path: path.to_path_buf(),
+            rotation,
+            header,
+            writer: None,
+            bytes: 0,
+            opened: Instant::now(),
+            next_index: 1,
+            rotations: 0,
+        };
+        file.reopen()?;
+        Ok(file)
+    }
+
+    /// Open `path` for appending, heading it if it is new or empty
+    fn reopen(&mut self) -> Result<&mut BufWriter<File>> {
+        let file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.bytes = file.metadata()?.len();
+        self.opened = Instant::now();
+        let mut writer = BufWriter::new(file);
+        if self.bytes == 0 {
+            writer.write_all(self.header.as_bytes())?;
+            self.bytes = self.header.len() as u64;
+        }
+        Ok(self.writer.insert(writer))
+    }
+
+    /// Whether the current file is past a limit; one holding only its header never is
+    fn due(&self) -> bool {
+        self.bytes > self.header.len() as u64
+            && (self.rotation.max_bytes.is_some_and(|max| self.bytes >= max)
+                || self.rotation.max_age.is_some_and(|max| self.opened.elapsed() >= max))
+    }
+
+    fn write(&mut self, record: &[u8]) -> Result<()> {
+        if self.due() {
+            self.rotate()?;
+        }
+        let writer = match self.writer.as_mut() {
+            Some(writer) => writer,
+            None => self.reopen()?,
+        };
+        writer.write_all(record)?;
+        self.bytes += record.len() as u64;
+        Ok(())
+    }
+
+    /// Close the current file, move it to the next free numbered name and start afresh
+    fn rotate(&mut self) -> Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush()?;
+        }
+        fs::rename(&self.path, next_free(&self.path, &mut self.next_index))?;
+        self.rotations += 1;
+        self.reopen()?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if let Some(writer) = self.writer.as_mut() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// First unused rotated name for `path` from `*next_index` on, e.g.
+/// `dir/results.3.jsonl` for `dir/results.jsonl`; advances `next_index` past it
+pub(crate) fn next_free(path: &Path, next_index: &mut u32) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    loop {
+        let index = *next_index;
+        *next_index += 1;
+        let name = match path.extension() {
+            Some(extension) => format!("{stem}.{index}.{}", extension.to_string_lossy()),
+            None => format!("{stem}.{index}"),
+        };
+        let candidate = path.with_file_name(name);
+        if !candidate.exists() {
+            return candidate;
+        }
+    }
+}
+
+/// One `CycleResult` per line as JSON
+#[derive(Debug)]
+pub struct JsonLinesSink {
+    file: RotatingFile,
+    line: Vec<u8>,
+}
+
+impl JsonLinesSink {
+    /// Append to `path`, creating it if needed
+    pub fn create(path: impl AsRef<Path>, rotation: Rotation) -> Result<Self> {
+        Ok(Self {
+            file: RotatingFile::open(path.as_ref(), rotation, String::new())?,
+            line: Vec::new(),
+        })
+    }
+
+    /// Files rotated out so far
+    pub fn rotations(&self) -> usize {
+        self.file.rotations
+    }
+}
+
+impl ResultSink for JsonLinesSink {
+    fn write(&mut self, result: &CycleResult) -> Result<()> {
+        self.line.clear();
+        serde_json::to_writer(&mut self.line, result)?;
+        self.line.push(b'\n');
+        self.file.write(&self.line)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.file.flush()
+    }
+}
+
+/// One row of `CSV_COLUMNS` per cycle, under a header line in every file
+#[derive(Debug)]
+pub struct CsvSink {
+    file: RotatingFile,
+    row: String,
+}
+
+impl CsvSink {
+    /// Append to `path`, creating it with a header if needed
+    pub fn create(path: impl AsRef<Path>, rotation: Rotation) -> Result<Self> {
+        let header = format!("{}\n", CSV_COLUMNS.join(","));
+        Ok(Self {
+            file: RotatingFile::open(path.as_ref(), rotation, header)?,
+            row: String::new(),
+        })
+    }
+
+    /// Files rotated out so far
+    pub fn rotations(&self) -> usize {
+        self.file.rotations
+    }
+
+    /// Format `result` into `self.row`
+    fn format(&mut self, result: &CycleResult) -> fmt::Result {
+        let row = &mut self.row;
+        row.clear();
+        write!(
+            row,
+            "{},{},{},{},",
+            result.cycle, result.confidence, result.node_id, result.anomaly_detected
+        )?;
+        if let Some(id) = result.anomaly_id {
+            write!(row, "{id}")?;
+        }
+        write!(row, ",{},", result.anomaly_score)?;
+        if let Some(anomaly) = &result.anomaly {
+            write!(row, "{:?}", anomaly.severity)?;
+        }
+        row.push(',');
+        match result.prediction.as_ref().and_then(|p| p.values.first().map(|&v| (v, p))) {
+            Some((next, prediction)) => write!(row, "{},{},{}", next, prediction.confidence, prediction.trend)?,
+            None => row.push_str(",,"),
+        }
+        let stages = &result.stages;
+        let confidences = &result.sensor_confidences;
+        write!(
+            row,
+            ",{},{},{},{},{},{},{},{},{},{},{},",
+            result.processing_us,
+            stages.sensor_ns,
+            stages.neural_ns,
+            stages.spatial_ns,
+            stages.anomaly_ns,
+            stages.prediction_ns,
+            confidences.visual,
+            confidences.lidar,
+            confidences.audio,
+            confidences.imu,
+            result.model_version
+        )?;
+        join(row, &result.neighbors)?;
+        row.push(',');
+        join(row, &result.neural_output)?;
+        row.push('\n');
+        Ok(())
+    }
+}
+
+/// Append `values` separated by `;`
+fn join<T: fmt::Display>(row: &mut String, values: &[T]) -> fmt::Result {
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            row.push(';');
+        }
+        write!(row, "{value}")?;
+    }
+    Ok(())
+}
+
+impl ResultSink for CsvSink {
+    fn write(&mut self, result: &CycleResult) -> Result<()> {
+        // Writing into a String cannot fail
+        let _ = self.format(result);
+        self.file.write(self.row.as_bytes())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.file.flush()
+    }
+}
+
+/// One line of a `MetricsLog`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsRecord {
+    /// Wall-clock time the snapshot was written, in seconds since the Unix epoch
+    pub unix_time: f64,
+    pub metrics: SystemMetrics,
+}
+
+/// One `MetricsRecord` per line as JSON, flushed as it is written
+#[derive(Debug)]
+pub struct MetricsLog {
+    file: RotatingFile,
+    line: Vec<u8>,
+}
+
+impl MetricsLog {
+    /// Append to `path`, creating it if needed
+    pub fn create(path: impl AsRef<Path>, rotation: Rotation) -> Result<Self> {
+        Ok(Self {
+            file: RotatingFile::open(path.as_ref(), rotation, String::new())?,
+            line: Vec::new(),
+        })
+    }
+
+    /// Files rotated out so far
+    pub fn rotations(&self) -> usize {
+        self.file.rotations
+    }
+
+    /// Append `metrics`, stamped with the current time
+    pub fn write(&mut self, metrics: &SystemMetrics) -> Result<()> {
+        // A clock set before the epoch yields a negative time rather than an error
+        let unix_time = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_secs_f64(),
+            Err(before) => -before.duration().as_secs_f64(),
+        };
+        self.line.clear();
+        serde_json::to_writer(&mut self.line, &MetricsRecord { unix_time, metrics: metrics.clone() })?;
+        self.line.push(b'\n');
+        self.file.write(&self.line)?;
+        self.file.flush()
+    }
+}
+
+/// Writes a `MetricsHandle`'s latest snapshot to a `MetricsLog` every
+/// interval until stopped or dropped
+///
+/// The handle's snapshot is republished every `SystemConfig::metrics_interval`
+/// cycles, so records written faster than that repeat it; a stalled system
+/// shows up as the same snapshot written again and again. A last record is
+/// written on stopping, after the system's `shutdown` if it came first.
+#[derive(Debug)]
+pub struct MetricsWriter {
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<Result<()>>>,
+}
+
+impl MetricsWriter {
+    /// Start writing every `interval`; a write error ends the thread
+    pub fn spawn(handle: MetricsHandle, mut log: MetricsLog, interval: Duration) -> Self {
+        let (stop, stopped) = mpsc::channel();
+        let thread = thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                log.write(&handle.metrics())?;
+            }
+            log.write(&handle.metrics())
+        });
+        Self { stop: Some(stop), thread: Some(thread) }
+    }
+
+    /// Write a last record and stop, returning the error that ended the thread early, if any
+    pub fn stop(mut self) -> Result<()> {
+        self.join()
+    }
+
+    fn join(&mut self) -> Result<()> {
+        drop(self.stop.take());
+        match self.thread.take().map(JoinHandle::join) {
+            Some(Ok(outcome)) => outcome,
+            Some(Err(panic)) => std::panic::resume_unwind(panic),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for MetricsWriter {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(Ok(Err(error))) = self.thread.take().map(JoinHandle::join) {
+            report_failure(self, error);
+        }
+    }
+}
+
+/// Note a sink that is given up on after `error`
+pub(crate) fn report_failure(sink: &dyn fmt::Debug, error: GenesisError) {
+    #[cfg(feature = "tracing")]
+    tracing::error!(%error, ?sink, "sink failed, no longer writing to it");
+    #[cfg(not(feature = "tracing"))]
+    let _ = (sink, error);
+}
+
+/// Feeds a sink from inside the cycle; see `EnvironmentalAwarenessSystem::add_result_sink`
+#[derive(Debug)]
+pub(crate) struct SinkObserver {
+    sink: Box<dyn ResultSink>,
+    failed: bool,
+}
+
+impl SinkObserver {
+    pub(crate) fn new(sink: Box<dyn ResultSink>) -> Self {
+        Self { sink, failed: false }
+    }
+
+    /// Stop writing after the first error rather than leave a log with gaps
+    fn fail(&mut self, error: GenesisError) {
+        report_failure(&self.sink, error);
+        self.failed = true;
+    }
+}
+
+impl SystemObserver for SinkObserver {
+    fn on_cycle(&mut self, result: &CycleResult) {
+        if !self.failed {
+            if let Err(error) = self.sink.write(result) {
+                self.fail(error);
+            }
+        }
+    }
+
+    fn on_shutdown(&mut self, _metrics: &SystemMetrics) {
+        if !self.failed {
+            if let Err(error) = self.sink.finish() {
+                self.fail(error);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use crate::EnvironmentalAwarenessSystem;
+
+    fn lines(path: &Path) -> Vec<String> {
+        BufReader::new(File::open(path).unwrap()).lines().map(|line| line.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_sinks_log_and_rotate() {
+        let dir = std::env::temp_dir().join(format!("genesis_sink_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        // JSON lines through the system, flushed by shutdown
+        let jsonl = dir.join("results.jsonl");
+        let mut system = EnvironmentalAwarenessSystem::new();
+        system.add_result_sink(Box::new(JsonLinesSink::create(&jsonl, Rotation::default()).unwrap()));
+        let results = system.run_cycles(20);
+        system.shutdown();
+        let logged: Vec<CycleResult> = lines(&jsonl).iter().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(logged.len(), 20);
+        assert_eq!((logged[19].cycle, logged[19].node_id), (results[19].cycle, results[19].node_id));
+
+        // CSV rotated by size: every file is headed and no row is lost
+        let csv = dir.join("results.csv");
+        let mut sink = CsvSink::create(&csv, Rotation::by_size(2048)).unwrap();
+        for result in system.run_cycles(50) {
+            sink.write(&result).unwrap();
+        }
+        sink.flush().unwrap();
+        assert!(sink.rotations() > 0);
+        let mut files: Vec<PathBuf> = (1..=sink.rotations()).map(|i| dir.join(format!("results.{i}.csv"))).collect();
+        files.push(csv);
+        let mut cycles = Vec::new();
+        for file in &files {
+            let lines = lines(file);
+            assert_eq!(lines[0], CSV_COLUMNS.join(","));
+            for row in &lines[1..] {
+                assert_eq!(row.split(',').count(), CSV_COLUMNS.len());
+                cycles.push(row.split(',').next().unwrap().parse::<u32>().unwrap());
+            }
+        }
+        assert_eq!(cycles, (21..=70).collect::<Vec<_>>());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_metrics_writer_keeps_a_timeline() {
+        let path = std::env::temp_dir().join(format!("genesis_metrics_log_{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut system = EnvironmentalAwarenessSystem::new();
+        let log = MetricsLog::create(&path, Rotation::default()).unwrap();
+        let writer = MetricsWriter::spawn(system.metrics_handle(), log, Duration::from_millis(5));
+        for _ in 0..4 {
+            system.run_cycles(25);
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let final_metrics = system.shutdown();
+        writer.stop().unwrap();
+
+        // Records are already on disk when the writer stops, the last one after shutdown
+        let records: Vec<MetricsRecord> = lines(&path).iter().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert!(records.len() >= 4);
+        assert!(records.windows(2).all(|pair| pair[0].unix_time <= pair[1].unix_time));
+        assert!(records.windows(2).all(|pair| pair[0].metrics.cycles <= pair[1].metrics.cycles));
+        assert!(records[0].metrics.cycles < final_metrics.cycles);
+        assert_eq!(records.last().unwrap().metrics.cycles, final_metrics.cycles);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,10 +1,26 @@
 //! High-performance spatial graph implementation
 
-use std::collections::HashMap;
-use ahash::AHashMap;  // Faster hash map
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender};
+use ahash::{AHashMap, AHashSet};  // Faster hash map
+use serde::{Serialize, Deserialize};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::frames::FrameRegistry;
+use crate::health::{Health, HealthCheck, HealthContext};
+use crate::hnsw::{HnswConfig, HnswIndex};
+use crate::rtree::RTree;
+
+/// Default radius within which two nodes are connected
+pub const CONNECTION_RADIUS: f32 = 50.0;
+
+/// Default per-node edge budget kept by `compact`
+const COMPACT_MAX_DEGREE: usize = 8;
 
 /// Spatial position in 3D space
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Position {
     pub x: f32,
     pub y: f32,
@@ -31,41 +47,483 @@ impl Position {
     }
 }
 
+/// Planar position for ground-bound deployments
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Position2D {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Point in an N-dimensional feature space, for graphs without physical layout
+pub type FeaturePoint<const N: usize> = [f32; N];
+
+/// Coordinate type a `SpatialGraph` is laid out in
+///
+/// Implemented for `Position` (3D, the default), `Position2D` and
+/// `FeaturePoint<N>` so graphs only store the axes they use.
+pub trait Coordinates: Copy + PartialEq + fmt::Debug + Send + Sync {
+    /// Number of axes
+    const DIMENSIONS: usize;
+    
+    /// Derive coordinates from an observation's feature vector
+    fn from_features(features: &[f32]) -> Self;
+    
+    /// Build coordinates from per-axis values (missing axes are zero)
+    fn from_axes(axes: &[f32]) -> Self;
+    
+    /// Value along an axis
+    fn axis(&self, axis: usize) -> f32;
+    
+    /// Squared Euclidean distance
+    #[inline]
+    fn distance_squared_to(&self, other: &Self) -> f32 {
+        (0..Self::DIMENSIONS)
+            .map(|i| {
+                let d = self.axis(i) - other.axis(i);
+                d * d
+            })
+            .sum()
+    }
+    
+    /// Euclidean distance
+    #[inline]
+    fn distance_to(&self, other: &Self) -> f32 {
+        self.distance_squared_to(other).sqrt()
+    }
+}
+
+impl Coordinates for Position {
+    const DIMENSIONS: usize = 3;
+    
+    #[inline]
+    fn from_features(features: &[f32]) -> Self {
+        Position {
+            x: features.first().copied().unwrap_or(0.0) * 100.0,
+            y: features.get(1).copied().unwrap_or(0.0) * 100.0,
+            z: features.get(2).copied().unwrap_or(0.0) * 10.0,
+        }
+    }
+    
+    #[inline]
+    fn from_axes(axes: &[f32]) -> Self {
+        let axis = |i: usize| axes.get(i).copied().unwrap_or(0.0);
+        Position { x: axis(0), y: axis(1), z: axis(2) }
+    }
+    
+    #[inline(always)]
+    fn axis(&self, axis: usize) -> f32 {
+        match axis {
+            0 => self.x,
+            1 => self.y,
+            _ => self.z,
+        }
+    }
+    
+    #[inline(always)]
+    fn distance_squared_to(&self, other: &Self) -> f32 {
+        Position::distance_squared_to(self, other)
+    }
+}
+
+impl Coordinates for Position2D {
+    const DIMENSIONS: usize = 2;
+    
+    #[inline]
+    fn from_features(features: &[f32]) -> Self {
+        Position2D {
+            x: features.first().copied().unwrap_or(0.0) * 100.0,
+            y: features.get(1).copied().unwrap_or(0.0) * 100.0,
+        }
+    }
+    
+    #[inline]
+    fn from_axes(axes: &[f32]) -> Self {
+        let axis = |i: usize| axes.get(i).copied().unwrap_or(0.0);
+        Position2D { x: axis(0), y: axis(1) }
+    }
+    
+    #[inline(always)]
+    fn axis(&self, axis: usize) -> f32 {
+        if axis == 0 { self.x } else { self.y }
+    }
+    
+    #[inline(always)]
+    fn distance_squared_to(&self, other: &Self) -> f32 {
+        let dx = self.x - other.x;
+        let dy = self.y - other.y;
+        dx * dx + dy * dy
+    }
+}
+
+impl<const N: usize> Coordinates for FeaturePoint<N> {
+    const DIMENSIONS: usize = N;
+    
+    /// Uses the first `N` features as-is, zero-padded
+    #[inline]
+    fn from_features(features: &[f32]) -> Self {
+        let mut point = [0.0; N];
+        for (dst, src) in point.iter_mut().zip(features) {
+            *dst = *src;
+        }
+        point
+    }
+    
+    #[inline]
+    fn from_axes(axes: &[f32]) -> Self {
+        Self::from_features(axes)
+    }
+    
+    #[inline(always)]
+    fn axis(&self, axis: usize) -> f32 {
+        self[axis]
+    }
+}
+
+/// Axis-aligned bounding box
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Aabb<P = Position> {
+    pub min: P,
+    pub max: P,
+}
+
+impl<P: Coordinates> Aabb<P> {
+    /// Check whether a position lies inside the box (inclusive)
+    #[inline(always)]
+    pub fn contains(&self, p: &P) -> bool {
+        (0..P::DIMENSIONS).all(|i| {
+            let v = p.axis(i);
+            v >= self.min.axis(i) && v <= self.max.axis(i)
+        })
+    }
+}
+
+/// Distance metric for feature-space queries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FeatureMetric {
+    #[default]
+    Euclidean,
+    /// `1 - cosine similarity`, in `[0, 2]`
+    Cosine,
+}
+
+impl FeatureMetric {
+    /// Distance between two feature vectors (compared over the shorter length)
+    #[inline]
+    pub fn distance(&self, a: &[f32], b: &[f32]) -> f32 {
+        match self {
+            FeatureMetric::Euclidean => a.iter()
+                .zip(b)
+                .map(|(x, y)| (x - y) * (x - y))
+                .sum::<f32>()
+                .sqrt(),
+            FeatureMetric::Cosine => {
+                let (mut dot, mut norm_a, mut norm_b) = (0.0, 0.0, 0.0);
+                for (x, y) in a.iter().zip(b) {
+                    dot += x * y;
+                    norm_a += x * x;
+                    norm_b += y * y;
+                }
+                let denom = (norm_a * norm_b).sqrt();
+                if denom > f32::EPSILON { 1.0 - dot / denom } else { 1.0 }
+            }
+        }
+    }
+}
+
 /// Spatial graph node
-#[derive(Debug, Clone)]
-pub struct Node {
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Node<P = Position> {
     pub id: usize,
-    pub position: Position,
+    pub position: P,
     pub features: Vec<f32>,
 }
 
+/// Nearest neighbor index backing `SpatialGraph::k_nearest_neighbors`
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum IndexKind {
+    /// Exact linear scan
+    #[default]
+    Exact,
+    /// Approximate HNSW graph for very large maps
+    Hnsw(HnswConfig),
+}
+
+/// Index backing `SpatialGraph::aabb_query`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RegionIndex {
+    /// Linear scan over all nodes
+    #[default]
+    Scan,
+    /// R-tree for rectangle-heavy workloads such as geofencing
+    RTree { max_entries: usize },
+}
+
+/// Serializable contents of a `SpatialGraph` (subscribers are not included)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphSnapshot<P = Position> {
+    pub nodes: Vec<Node<P>>,
+    /// Adjacency lists by node id, in id order
+    pub edges: Vec<(usize, Vec<(usize, f32)>)>,
+    pub next_id: usize,
+    pub connection_radius: f32,
+    pub index: IndexKind,
+    pub region_index: RegionIndex,
+}
+
+/// Structural change to a `SpatialGraph`, streamed to subscribers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GraphEvent<P = Position> {
+    NodeAdded { id: usize, position: P },
+    NodeUpdated { id: usize, position: P },
+    NodeRemoved { id: usize },
+    /// Node `id` was folded into node `into`
+    NodeMerged { id: usize, into: usize },
+    EdgeAdded { from: usize, to: usize, distance: f32 },
+    EdgeRemoved { from: usize, to: usize },
+}
+
+/// Send an event to every live subscriber, dropping disconnected ones
+#[inline]
+fn emit<P: Clone>(subscribers: &mut Vec<Sender<GraphEvent<P>>>, event: impl FnOnce() -> GraphEvent<P>) {
+    if subscribers.is_empty() {
+        return;
+    }
+    let event = event();
+    subscribers.retain(|tx| tx.send(event.clone()).is_ok());
+}
+
+/// Summary of a `SpatialGraph::compact` pass
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompactionReport {
+    pub edges_pruned: usize,
+    pub bytes_before: usize,
+    pub bytes_after: usize,
+}
+
+impl CompactionReport {
+    /// Bytes released by the compaction
+    #[inline]
+    pub fn bytes_reclaimed(&self) -> usize {
+        self.bytes_before.saturating_sub(self.bytes_after)
+    }
+}
+
+/// Per-component memory held by a `SpatialGraph`, in bytes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphMemory {
+    /// Node storage including per-node feature allocations
+    pub nodes: usize,
+    /// Adjacency list allocations
+    pub edges: usize,
+    /// Hash table backing the adjacency index, plus any HNSW or R-tree index
+    pub index: usize,
+}
+
+impl GraphMemory {
+    /// Total bytes across all components
+    #[inline]
+    pub fn total(&self) -> usize {
+        self.nodes + self.edges + self.index
+    }
+}
+
+/// Approximate heap size of a hashbrown table able to hold `capacity` entries
+fn hash_table_bytes<K, V>(capacity: usize) -> usize {
+    if capacity == 0 {
+        return 0;
+    }
+    // Tables keep 1/8 of buckets free, bucket counts are powers of two,
+    // and every bucket carries one control byte plus a trailing group
+    const GROUP_WIDTH: usize = 16;
+    let buckets = (capacity * 8 / 7).next_power_of_two();
+    buckets * (std::mem::size_of::<(K, V)>() + 1) + GROUP_WIDTH
+}
+
 /// High-performance spatial graph
+///
+/// Laid out in 3D `Position`s by default; use `SpatialGraph::<Position2D>::default()`
+/// or a `FeaturePoint<N>` for planar or feature-space-only maps.
 #[derive(Debug)]
-pub struct SpatialGraph {
-    nodes: Vec<Node>,
+pub struct SpatialGraph<P = Position> {
+    nodes: Vec<Node<P>>,
     edges: AHashMap<usize, Vec<(usize, f32)>>,  // Using faster hash map
     next_id: usize,
+    subscribers: Vec<Sender<GraphEvent<P>>>,
+    hnsw: Option<HnswIndex<P>>,
+    rtree: Option<RTree<P>>,
+    /// Squared distance within which new nodes are connected
+    connection_radius_sq: f32,
+}
+
+impl<P: Coordinates> Default for SpatialGraph<P> {
+    fn default() -> Self {
+        Self::with_capacity(1000)  // Pre-allocate for performance
+    }
 }
 
 impl SpatialGraph {
-    /// Create a new spatial graph
+    /// Create a new 3D spatial graph
     pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Add a node observed in a registered frame at `timestamp`.
+    /// Returns `None` if the frame cannot be resolved to world coordinates.
+    pub fn add_node_in_frame(
+        &mut self,
+        frames: &FrameRegistry,
+        frame: &str,
+        timestamp: f64,
+        position: Position,
+        features: &[f32],
+    ) -> Option<usize> {
+        let world = frames.to_world(frame, timestamp, &position)?;
+        Some(self.add_node_at(world, features))
+    }
+}
+
+impl<P: Coordinates> SpatialGraph<P> {
+    /// Create an empty graph with room for `capacity` nodes
+    pub fn with_capacity(capacity: usize) -> Self {
         Self {
-            nodes: Vec::with_capacity(1000),  // Pre-allocate for performance
-            edges: AHashMap::with_capacity(1000),
+            nodes: Vec::with_capacity(capacity),
+            edges: AHashMap::with_capacity(capacity),
             next_id: 0,
+            subscribers: Vec::new(),
+            hnsw: None,
+            rtree: None,
+            connection_radius_sq: CONNECTION_RADIUS * CONNECTION_RADIUS,
         }
     }
     
+    /// Distance within which nodes added from now on are connected
+    pub fn set_connection_radius(&mut self, radius: f32) {
+        self.connection_radius_sq = radius.max(0.0).powi(2);
+    }
+    
+    /// Current connection distance
+    pub fn connection_radius(&self) -> f32 {
+        self.connection_radius_sq.sqrt()
+    }
+    
+    /// Copy nodes, edges and index settings for persistence
+    pub fn snapshot(&self) -> GraphSnapshot<P> {
+        let mut edges: Vec<(usize, Vec<(usize, f32)>)> = self.edges
+            .iter()
+            .map(|(&id, connections)| (id, connections.clone()))
+            .collect();
+        edges.sort_unstable_by_key(|&(id, _)| id);
+        GraphSnapshot {
+            nodes: self.nodes.clone(),
+            edges,
+            next_id: self.next_id,
+            connection_radius: self.connection_radius(),
+            index: self.index_kind(),
+            region_index: self.region_index(),
+        }
+    }
+    
+    /// Rebuild a graph, including its search indexes, from a snapshot
+    pub fn from_snapshot(snapshot: GraphSnapshot<P>) -> Self {
+        let mut graph = Self::with_capacity(snapshot.nodes.len());
+        graph.nodes = snapshot.nodes;
+        graph.edges = snapshot.edges.into_iter().collect();
+        graph.next_id = snapshot.next_id;
+        graph.set_connection_radius(snapshot.connection_radius);
+        graph.set_index(snapshot.index);
+        graph.set_region_index(snapshot.region_index);
+        graph
+    }
+    
+    /// Select the nearest neighbor index, rebuilding it from current nodes
+    pub fn set_index(&mut self, kind: IndexKind) {
+        self.hnsw = match kind {
+            IndexKind::Exact => None,
+            IndexKind::Hnsw(config) => {
+                let mut index = HnswIndex::new(config);
+                for node in &self.nodes {
+                    index.insert(node.id, node.position);
+                }
+                Some(index)
+            }
+        };
+    }
+    
+    /// Currently selected nearest neighbor index
+    pub fn index_kind(&self) -> IndexKind {
+        match &self.hnsw {
+            Some(index) => IndexKind::Hnsw(*index.config()),
+            None => IndexKind::Exact,
+        }
+    }
+    
+    /// Select the bounding box query index, rebuilding it from current nodes
+    pub fn set_region_index(&mut self, kind: RegionIndex) {
+        self.rtree = match kind {
+            RegionIndex::Scan => None,
+            RegionIndex::RTree { max_entries } => {
+                let mut tree = RTree::new(max_entries);
+                for node in &self.nodes {
+                    tree.insert(node.id, node.position);
+                }
+                Some(tree)
+            }
+        };
+    }
+    
+    /// Currently selected bounding box query index
+    pub fn region_index(&self) -> RegionIndex {
+        match &self.rtree {
+            Some(tree) => RegionIndex::RTree { max_entries: tree.max_entries() },
+            None => RegionIndex::Scan,
+        }
+    }
+    
+    /// Keep optional indexes in sync with a node's position
+    fn index_insert(&mut self, id: usize, position: P) {
+        if let Some(index) = &mut self.hnsw {
+            index.insert(id, position);
+        }
+        if let Some(tree) = &mut self.rtree {
+            tree.insert(id, position);
+        }
+    }
+    
+    /// Drop a node from optional indexes
+    fn index_remove(&mut self, id: usize) {
+        if let Some(index) = &mut self.hnsw {
+            index.remove(id);
+        }
+        if let Some(tree) = &mut self.rtree {
+            tree.remove(id);
+        }
+    }
+    
+    /// Rebuild optional indexes from scratch after bulk changes
+    fn rebuild_indexes(&mut self) {
+        self.set_index(self.index_kind());
+        self.set_region_index(self.region_index());
+    }
+    
+    /// Subscribe to structural change events.
+    ///
+    /// Events are only built while at least one receiver is alive; dropping
+    /// the receiver unsubscribes.
+    pub fn subscribe(&mut self) -> Receiver<GraphEvent<P>> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers.push(tx);
+        rx
+    }
+    
     /// Add a node to the graph
     pub fn add_node(&mut self, features: &[f32]) -> usize {
         // Calculate position from features
-        let position = Position {
-            x: features.get(0).copied().unwrap_or(0.0) * 100.0,
-            y: features.get(1).copied().unwrap_or(0.0) * 100.0,
-            z: features.get(2).copied().unwrap_or(0.0) * 10.0,
-        };
-        
+        let position = P::from_features(features);
+        self.add_node_at(position, features)
+    }
+    
+    /// Add a node at an explicit world position
+    pub fn add_node_at(&mut self, position: P, features: &[f32]) -> usize {
         let node = Node {
             id: self.next_id,
             position,
@@ -73,33 +531,266 @@ impl SpatialGraph {
         };
         
         let node_id = node.id;
+        emit(&mut self.subscribers, || GraphEvent::NodeAdded { id: node_id, position });
+        self.connect(node_id, &position);
+        self.index_insert(node_id, position);
+        
+        self.nodes.push(node);
+        self.next_id += 1;
+        
+        node_id
+    }
+    
+    /// Move an existing node and incrementally repair its edge set
+    ///
+    /// Edges that fall out of range are dropped, new in-range neighbors are
+    /// connected and surviving edges get their distances refreshed.
+    /// Returns `false` if no node with `id` exists.
+    pub fn update_node(&mut self, id: usize, new_position: P, new_features: &[f32]) -> bool {
+        let Some(index) = self.node_index(id) else {
+            return false;
+        };
+        
+        self.disconnect(id);
+        
+        let node = &mut self.nodes[index];
+        node.position = new_position;
+        node.features.clear();
+        node.features.extend_from_slice(new_features);
+        
+        emit(&mut self.subscribers, || GraphEvent::NodeUpdated { id, position: new_position });
+        self.connect(id, &new_position);
+        self.index_insert(id, new_position);
+        true
+    }
+    
+    /// Remove a node and all of its edges. Returns the removed node.
+    pub fn remove_node(&mut self, id: usize) -> Option<Node<P>> {
+        let index = self.node_index(id)?;
+        self.disconnect(id);
+        emit(&mut self.subscribers, || GraphEvent::NodeRemoved { id });
+        self.index_remove(id);
+        Some(self.nodes.remove(index))
+    }
+    
+    /// Prune redundant edges down to a k-nearest subgraph and release slack storage
+    pub fn compact(&mut self) -> CompactionReport {
+        self.compact_to_degree(COMPACT_MAX_DEGREE)
+    }
+    
+    /// Compact keeping, for every node, edges to its `max_degree` nearest neighbors.
+    ///
+    /// An edge survives if either endpoint selects it, so the result stays
+    /// undirected and a node may end up with more than `max_degree` edges.
+    pub fn compact_to_degree(&mut self, max_degree: usize) -> CompactionReport {
+        let bytes_before = self.estimate_memory();
+        let edges_before = self.edge_count();
+        
+        let mut keep = AHashSet::with_capacity(self.nodes.len() * max_degree);
+        for (&id, connections) in self.edges.iter_mut() {
+            connections.sort_unstable_by(|a, b| a.1.total_cmp(&b.1));
+            for &(neighbor, _) in connections.iter().take(max_degree) {
+                keep.insert((id.min(neighbor), id.max(neighbor)));
+            }
+        }
+        
+        let subscribers = &mut self.subscribers;
+        self.edges.retain(|&id, connections| {
+            connections.retain(|&(neighbor, _)| {
+                let kept = keep.contains(&(id.min(neighbor), id.max(neighbor)));
+                if !kept && id < neighbor {
+                    emit(subscribers, || GraphEvent::EdgeRemoved { from: id, to: neighbor });
+                }
+                kept
+            });
+            connections.shrink_to_fit();
+            !connections.is_empty()
+        });
+        self.edges.shrink_to_fit();
+        
+        self.nodes.shrink_to_fit();
+        for node in &mut self.nodes {
+            node.features.shrink_to_fit();
+        }
+        
+        // Rebuilding drops HNSW tombstones and loose R-tree bounds left by churn
+        self.rebuild_indexes();
+        
+        CompactionReport {
+            edges_pruned: edges_before - self.edge_count(),
+            bytes_before,
+            bytes_after: self.estimate_memory(),
+        }
+    }
+    
+    /// Collapse all nodes within each `voxel_size` cube into one representative.
+    ///
+    /// The representative keeps the lowest id in its voxel and takes the mean
+    /// position and mean features of its members; edges are rebuilt afterwards.
+    /// Returns the number of nodes removed.
+    pub fn downsample(&mut self, voxel_size: f32) -> usize {
+        if voxel_size.is_nan() || voxel_size <= 0.0 || self.nodes.is_empty() {
+            return 0;
+        }
+        
+        struct Voxel<P> {
+            node: Node<P>,
+            axes: Vec<f32>,
+            count: usize,
+        }
+        
+        let before = self.nodes.len();
+        let mut slots: AHashMap<Vec<i64>, usize> = AHashMap::with_capacity(before);
+        let mut voxels: Vec<Voxel<P>> = Vec::new();
+        
+        // Nodes are stored in id order, so the first member of a voxel has its lowest id
+        for node in self.nodes.drain(..) {
+            let key: Vec<i64> = (0..P::DIMENSIONS)
+                .map(|i| (node.position.axis(i) / voxel_size).floor() as i64)
+                .collect();
+            
+            match slots.get(&key) {
+                Some(&slot) => {
+                    let voxel = &mut voxels[slot];
+                    let into = voxel.node.id;
+                    emit(&mut self.subscribers, || GraphEvent::NodeMerged { id: node.id, into });
+                    for (i, sum) in voxel.axes.iter_mut().enumerate() {
+                        *sum += node.position.axis(i);
+                    }
+                    if voxel.node.features.len() < node.features.len() {
+                        voxel.node.features.resize(node.features.len(), 0.0);
+                    }
+                    for (sum, f) in voxel.node.features.iter_mut().zip(&node.features) {
+                        *sum += f;
+                    }
+                    voxel.count += 1;
+                }
+                None => {
+                    slots.insert(key, voxels.len());
+                    let axes = (0..P::DIMENSIONS).map(|i| node.position.axis(i)).collect();
+                    voxels.push(Voxel { node, axes, count: 1 });
+                }
+            }
+        }
         
-        // Connect to nearby nodes (optimized with squared distance)
-        const THRESHOLD_SQUARED: f32 = 2500.0;  // 50^2
+        if !self.subscribers.is_empty() {
+            for (&from, connections) in &self.edges {
+                for &(to, _) in connections.iter().filter(|&&(to, _)| from < to) {
+                    emit(&mut self.subscribers, || GraphEvent::EdgeRemoved { from, to });
+                }
+            }
+        }
+        self.edges.clear();
         
+        for mut voxel in voxels {
+            let n = voxel.count as f32;
+            voxel.axes.iter_mut().for_each(|a| *a /= n);
+            voxel.node.features.iter_mut().for_each(|f| *f /= n);
+            voxel.node.position = P::from_axes(&voxel.axes);
+            
+            if voxel.count > 1 {
+                let (id, position) = (voxel.node.id, voxel.node.position);
+                emit(&mut self.subscribers, || GraphEvent::NodeUpdated { id, position });
+            }
+            
+            self.connect(voxel.node.id, &voxel.node.position);
+            self.nodes.push(voxel.node);
+        }
+        self.rebuild_indexes();
+        
+        before - self.nodes.len()
+    }
+    
+    /// Estimate memory held by the graph in bytes
+    pub fn estimate_memory(&self) -> usize {
+        std::mem::size_of::<Self>() + self.memory_breakdown().total()
+    }
+    
+    /// Break heap usage down into nodes, edges and index
+    pub fn memory_breakdown(&self) -> GraphMemory {
+        let nodes = self.nodes.capacity() * std::mem::size_of::<Node<P>>()
+            + self.nodes.iter()
+                .map(|node| node.features.capacity() * std::mem::size_of::<f32>())
+                .sum::<usize>();
+        
+        let edges = self.edges.values()
+            .map(|connections| connections.capacity() * std::mem::size_of::<(usize, f32)>())
+            .sum();
+        
+        let index = hash_table_bytes::<usize, Vec<(usize, f32)>>(self.edges.capacity())
+            + self.hnsw.as_ref().map_or(0, HnswIndex::estimate_memory)
+            + self.rtree.as_ref().map_or(0, RTree::estimate_memory);
+        
+        GraphMemory { nodes, edges, index }
+    }
+    
+    /// Connect a node to every other node in range (optimized with squared distance)
+    fn connect(&mut self, node_id: usize, position: &P) {
         let mut connections = Vec::new();
         for existing_node in &self.nodes {
+            if existing_node.id == node_id {
+                continue;
+            }
+            
             let dist_sq = position.distance_squared_to(&existing_node.position);
             
-            if dist_sq < THRESHOLD_SQUARED {
+            if dist_sq < self.connection_radius_sq {
                 let distance = dist_sq.sqrt();
                 connections.push((existing_node.id, distance));
                 
                 // Add reverse edge
                 self.edges.entry(existing_node.id)
-                    .or_insert_with(Vec::new)
+                    .or_default()
                     .push((node_id, distance));
             }
         }
         
+        for &(to, distance) in &connections {
+            emit(&mut self.subscribers, || GraphEvent::EdgeAdded { from: node_id, to, distance });
+        }
+        
         if !connections.is_empty() {
             self.edges.insert(node_id, connections);
         }
+    }
+    
+    /// Remove every edge touching a node
+    fn disconnect(&mut self, node_id: usize) {
+        let Some(connections) = self.edges.remove(&node_id) else {
+            return;
+        };
         
-        self.nodes.push(node);
-        self.next_id += 1;
-        
-        node_id
+        for (neighbor, _) in connections {
+            emit(&mut self.subscribers, || GraphEvent::EdgeRemoved { from: node_id, to: neighbor });
+            if let Some(reverse) = self.edges.get_mut(&neighbor) {
+                reverse.retain(|&(id, _)| id != node_id);
+                if reverse.is_empty() {
+                    self.edges.remove(&neighbor);
+                }
+            }
+        }
+    }
+    
+    /// Get all nodes in insertion order
+    #[inline]
+    pub fn nodes(&self) -> &[Node<P>] {
+        &self.nodes
+    }
+    
+    /// Look up a node by id
+    pub fn node(&self, id: usize) -> Option<&Node<P>> {
+        self.node_index(id).map(|index| &self.nodes[index])
+    }
+    
+    /// Storage slot of a node; ids are assigned in increasing order
+    #[inline]
+    fn node_index(&self, id: usize) -> Option<usize> {
+        self.nodes.binary_search_by_key(&id, |node| node.id).ok()
+    }
+    
+    /// Get the neighbors of a node with their edge distances
+    pub fn neighbors(&self, id: usize) -> &[(usize, f32)] {
+        self.edges.get(&id).map(Vec::as_slice).unwrap_or(&[])
     }
     
     /// Get the number of nodes
@@ -125,29 +816,136 @@ impl SpatialGraph {
     }
     
     /// Find k nearest neighbors (optimized)
-    pub fn k_nearest_neighbors(&self, position: &Position, k: usize) -> Vec<(usize, f32)> {
-        let mut distances: Vec<(usize, f32)> = self.nodes
+    ///
+    /// Exact by default; approximate when an HNSW index is selected.
+    pub fn k_nearest_neighbors(&self, position: &P, k: usize) -> Vec<(usize, f32)> {
+        if let Some(index) = &self.hnsw {
+            return index.search(position, k);
+        }
+        
+        let distances: Vec<(usize, f32)> = self.nodes
             .iter()
             .map(|node| (node.id, position.distance_squared_to(&node.position)))
             .collect();
         
+        let mut nearest = Self::select_k_smallest(distances, k);
+        
+        // Convert squared distances to actual distances
+        nearest.iter_mut()
+            .for_each(|(_, dist)| *dist = dist.sqrt());
+        nearest
+    }
+    
+    /// Find all nodes within `radius` of a position, sorted by distance
+    pub fn radius_search(&self, position: &P, radius: f32) -> Vec<(usize, f32)> {
+        let radius_sq = radius * radius;
+        let mut found: Vec<(usize, f32)> = self.nodes
+            .iter()
+            .filter_map(|node| {
+                let dist_sq = position.distance_squared_to(&node.position);
+                (dist_sq <= radius_sq).then(|| (node.id, dist_sq.sqrt()))
+            })
+            .collect();
+        
+        found.sort_unstable_by(|a, b| a.1.total_cmp(&b.1));
+        found
+    }
+    
+    /// Find the ids of all nodes inside a bounding box
+    pub fn aabb_query(&self, aabb: &Aabb<P>) -> Vec<usize> {
+        if let Some(tree) = &self.rtree {
+            return tree.query(aabb);
+        }
+        
+        self.nodes
+            .iter()
+            .filter(|node| aabb.contains(&node.position))
+            .map(|node| node.id)
+            .collect()
+    }
+    
+    /// Run many kNN queries; uses all cores with the `parallel` feature
+    pub fn k_nearest_neighbors_batch(&self, positions: &[P], k: usize) -> Vec<Vec<(usize, f32)>> {
+        #[cfg(feature = "parallel")]
+        let queries = positions.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let queries = positions.iter();
+        
+        queries.map(|p| self.k_nearest_neighbors(p, k)).collect()
+    }
+    
+    /// Run many radius queries; uses all cores with the `parallel` feature
+    pub fn radius_search_batch(&self, positions: &[P], radius: f32) -> Vec<Vec<(usize, f32)>> {
+        #[cfg(feature = "parallel")]
+        let queries = positions.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let queries = positions.iter();
+        
+        queries.map(|p| self.radius_search(p, radius)).collect()
+    }
+    
+    /// Run many bounding box queries; uses all cores with the `parallel` feature
+    pub fn aabb_query_batch(&self, boxes: &[Aabb<P>]) -> Vec<Vec<usize>> {
+        #[cfg(feature = "parallel")]
+        let queries = boxes.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let queries = boxes.iter();
+        
+        queries.map(|aabb| self.aabb_query(aabb)).collect()
+    }
+    
+    /// Find the k nodes whose features look most like `features` (Euclidean)
+    pub fn k_nearest_by_features(&self, features: &[f32], k: usize) -> Vec<(usize, f32)> {
+        self.k_nearest_by_features_with(features, k, FeatureMetric::Euclidean)
+    }
+    
+    /// Find the k nodes closest to `features` under the given metric
+    pub fn k_nearest_by_features_with(
+        &self,
+        features: &[f32],
+        k: usize,
+        metric: FeatureMetric,
+    ) -> Vec<(usize, f32)> {
+        let distances: Vec<(usize, f32)> = self.nodes
+            .iter()
+            .map(|node| (node.id, metric.distance(features, &node.features)))
+            .collect();
+        
+        Self::select_k_smallest(distances, k)
+    }
+    
+    /// Keep the k smallest distances, sorted ascending
+    fn select_k_smallest(mut distances: Vec<(usize, f32)>, k: usize) -> Vec<(usize, f32)> {
         // Use partial sort for better performance when k << n
         if k < distances.len() {
             distances.select_nth_unstable_by(k, |a, b| {
-                a.1.partial_cmp(&b.1).unwrap()
+                a.1.total_cmp(&b.1)
             });
             distances.truncate(k);
         }
         
-        // Convert squared distances to actual distances
-        distances.iter_mut()
-            .for_each(|(_, dist)| *dist = dist.sqrt());
-        
-        distances.sort_unstable_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        distances.sort_unstable_by(|a, b| a.1.total_cmp(&b.1));
         distances
     }
 }
 
+impl<P: Coordinates> HealthCheck for SpatialGraph<P> {
+    /// Judged by node count against the configured `graph_capacity`
+    fn health(&self, context: &HealthContext<'_>) -> Health {
+        let limit = context.config.graph_capacity;
+        let fill = self.node_count() as f32 / limit.max(1) as f32;
+        let reason = || format!("{} nodes for a capacity of {limit}", self.node_count());
+        let config = &context.config.health;
+        if fill > config.graph_unhealthy {
+            Health::unhealthy(reason())
+        } else if fill >= config.graph_degraded {
+            Health::degraded(reason())
+        } else {
+            Health::healthy()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -188,4 +986,286 @@ mod tests {
         
         assert_eq!(neighbors.len(), 3);
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_update_node_repairs_edges() {
+        let mut graph = SpatialGraph::new();
+        
+        let a = graph.add_node(&[0.0, 0.0, 0.0, 0.0]);
+        let b = graph.add_node(&[0.1, 0.0, 0.0, 0.0]);
+        let c = graph.add_node(&[0.9, 0.9, 0.0, 0.0]);
+        
+        assert_eq!(graph.edge_count(), 1);
+        assert_eq!(graph.neighbors(a), &[(b, 10.0)]);
+        
+        // Move `b` next to `c`: the a-b edge drops, b-c appears
+        let moved = Position { x: 85.0, y: 90.0, z: 0.0 };
+        assert!(graph.update_node(b, moved, &[0.85, 0.9, 0.0, 0.0]));
+        
+        assert_eq!(graph.edge_count(), 1);
+        assert!(graph.neighbors(a).is_empty());
+        assert_eq!(graph.neighbors(b), &[(c, 5.0)]);
+        assert_eq!(graph.neighbors(c), &[(b, 5.0)]);
+        
+        assert!(!graph.update_node(42, moved, &[]));
+    }
+    
+    #[test]
+    fn test_add_node_in_frame() {
+        use crate::frames::{Transform, WORLD_FRAME};
+        
+        let mut frames = FrameRegistry::new();
+        frames.register("lidar", WORLD_FRAME, Transform::from_translation(Position { x: 5.0, y: 0.0, z: 0.0 }));
+        
+        let mut graph = SpatialGraph::new();
+        let local = Position { x: 1.0, y: 2.0, z: 0.0 };
+        let id = graph.add_node_in_frame(&frames, "lidar", 0.0, local, &[0.5]).unwrap();
+        
+        let neighbors = graph.k_nearest_neighbors(&Position { x: 6.0, y: 2.0, z: 0.0 }, 1);
+        assert_eq!(neighbors, vec![(id, 0.0)]);
+        assert!(graph.add_node_in_frame(&frames, "camera", 0.0, local, &[0.5]).is_none());
+    }
+    
+    #[test]
+    fn test_k_nearest_by_features() {
+        let mut graph = SpatialGraph::new();
+        
+        let dark = graph.add_node(&[0.1, 0.1, 0.1, 0.1]);
+        let bright = graph.add_node(&[0.9, 0.9, 0.9, 0.9]);
+        let skewed = graph.add_node(&[0.9, 0.1, 0.1, 0.1]);
+        
+        let nearest = graph.k_nearest_by_features(&[0.8, 0.8, 0.8, 0.8], 2);
+        assert_eq!(nearest.len(), 2);
+        assert_eq!(nearest[0].0, bright);
+        
+        // Cosine ignores magnitude: dark and bright point the same way
+        let nearest = graph.k_nearest_by_features_with(&[0.5, 0.5, 0.5, 0.5], 3, FeatureMetric::Cosine);
+        assert!(nearest[0].1 < 1e-5 && nearest[1].1 < 1e-5);
+        assert_eq!(nearest[2].0, skewed);
+        assert!(nearest.iter().any(|&(id, _)| id == dark));
+    }
+    
+    #[test]
+    fn test_remove_node() {
+        let mut graph = SpatialGraph::new();
+        let a = graph.add_node(&[0.0, 0.0, 0.0]);
+        let b = graph.add_node(&[0.1, 0.0, 0.0]);
+        
+        assert_eq!(graph.remove_node(a).map(|n| n.id), Some(a));
+        assert_eq!(graph.node_count(), 1);
+        assert_eq!(graph.edge_count(), 0);
+        assert!(graph.neighbors(b).is_empty());
+        assert!(graph.remove_node(a).is_none());
+    }
+    
+    #[test]
+    fn test_compact_prunes_to_k_nearest() {
+        let mut graph = SpatialGraph::new();
+        
+        // A dense cluster where every node sees every other node
+        for i in 0..20 {
+            graph.add_node(&[i as f32 * 0.01, 0.0, 0.0]);
+        }
+        assert_eq!(graph.edge_count(), 190);
+        
+        let report = graph.compact_to_degree(2);
+        assert_eq!(report.edges_pruned, 190 - graph.edge_count());
+        assert!(graph.edge_count() < 40);
+        assert!(report.bytes_reclaimed() > 0);
+        
+        // Every node keeps its two closest neighbors
+        for id in 1..19 {
+            let neighbors: Vec<usize> = graph.neighbors(id).iter().map(|&(n, _)| n).collect();
+            assert!(neighbors.contains(&(id - 1)) && neighbors.contains(&(id + 1)));
+        }
+    }
+    
+    #[test]
+    fn test_region_queries() {
+        let mut graph = SpatialGraph::new();
+        for i in 0..10 {
+            graph.add_node(&[i as f32 * 0.1, 0.0, 0.0]);
+        }
+        
+        let origin = Position { x: 0.0, y: 0.0, z: 0.0 };
+        let near = graph.radius_search(&origin, 25.0);
+        assert_eq!(near.iter().map(|&(id, _)| id).collect::<Vec<_>>(), vec![0, 1, 2]);
+        
+        let aabb = Aabb { min: Position { x: 45.0, y: -1.0, z: -1.0 }, max: Position { x: 75.0, y: 1.0, z: 1.0 } };
+        assert_eq!(graph.aabb_query(&aabb), vec![5, 6, 7]);
+        
+        let far = Position { x: 90.0, y: 0.0, z: 0.0 };
+        let batch = graph.k_nearest_neighbors_batch(&[origin, far], 1);
+        assert_eq!(batch[0][0].0, 0);
+        assert_eq!(batch[1][0].0, 9);
+        assert_eq!(graph.radius_search_batch(&[origin, far], 25.0)[1].len(), 3);
+        assert_eq!(graph.aabb_query_batch(&[aabb, aabb]), vec![vec![5, 6, 7]; 2]);
+    }
+    
+    #[test]
+    fn test_memory_breakdown() {
+        let mut graph = SpatialGraph::new();
+        let empty = graph.memory_breakdown();
+        
+        for i in 0..10 {
+            graph.add_node(&[i as f32 * 0.01, 0.0, 0.0, 0.0]);
+        }
+        let memory = graph.memory_breakdown();
+        
+        // Node slots are pre-allocated; features and adjacency lists are not
+        assert_eq!(memory.nodes, empty.nodes + 10 * 4 * std::mem::size_of::<f32>());
+        assert!(memory.edges >= 90 * std::mem::size_of::<(usize, f32)>());
+        assert!(memory.index > 0);
+        assert_eq!(graph.estimate_memory(), std::mem::size_of::<SpatialGraph>() + memory.total());
+    }
+    
+    #[test]
+    fn test_planar_graph() {
+        let mut graph = SpatialGraph::<Position2D>::default();
+        
+        // z is ignored in a planar graph
+        let a = graph.add_node(&[0.0, 0.0, 0.0]);
+        let b = graph.add_node(&[0.0, 0.0, 1.0]);
+        assert_eq!(graph.neighbors(a), &[(b, 0.0)]);
+        
+        let nearest = graph.k_nearest_neighbors(&Position2D { x: 0.0, y: 0.0 }, 1);
+        assert_eq!(nearest[0].1, 0.0);
+    }
+    
+    #[test]
+    fn test_feature_space_graph() {
+        let mut graph = SpatialGraph::<FeaturePoint<4>>::default();
+        
+        graph.add_node(&[0.1, 0.1, 0.1, 0.1]);
+        let b = graph.add_node(&[0.9, 0.9, 0.9, 0.9]);
+        
+        let nearest = graph.k_nearest_neighbors(&[1.0, 1.0, 1.0, 1.0], 1);
+        assert_eq!(nearest[0].0, b);
+        assert!((nearest[0].1 - 0.2).abs() < 1e-6);
+        
+        let aabb = Aabb { min: [0.5; 4], max: [1.0; 4] };
+        assert_eq!(graph.aabb_query(&aabb), vec![b]);
+    }
+    
+    #[test]
+    fn test_downsample() {
+        let mut graph = SpatialGraph::new();
+        
+        // Two tight clusters of three nodes each
+        for i in 0..3 {
+            graph.add_node(&[0.01 + i as f32 * 0.01, 0.01, 0.0, 0.2]);
+            graph.add_node(&[0.61 + i as f32 * 0.01, 0.01, 0.0, 0.8]);
+        }
+        
+        assert_eq!(graph.downsample(10.0), 4);
+        assert_eq!(graph.node_count(), 2);
+        
+        let first = graph.node(0).unwrap();
+        assert!((first.position.x - 2.0).abs() < 1e-4);
+        assert!((first.features[3] - 0.2).abs() < 1e-6);
+        assert!((graph.node(1).unwrap().features[3] - 0.8).abs() < 1e-6);
+        
+        // The clusters are 60 apart, outside the connection radius
+        assert_eq!(graph.edge_count(), 0);
+        assert_eq!(graph.downsample(0.0), 0);
+    }
+    
+    #[test]
+    fn test_change_events() {
+        let mut graph = SpatialGraph::new();
+        let events = graph.subscribe();
+        
+        let a = graph.add_node(&[0.0, 0.0, 0.0]);
+        let b = graph.add_node(&[0.1, 0.0, 0.0]);
+        graph.remove_node(a);
+        
+        let received: Vec<GraphEvent> = events.try_iter().collect();
+        assert_eq!(received, vec![
+            GraphEvent::NodeAdded { id: a, position: Position { x: 0.0, y: 0.0, z: 0.0 } },
+            GraphEvent::NodeAdded { id: b, position: Position { x: 10.0, y: 0.0, z: 0.0 } },
+            GraphEvent::EdgeAdded { from: b, to: a, distance: 10.0 },
+            GraphEvent::EdgeRemoved { from: a, to: b },
+            GraphEvent::NodeRemoved { id: a },
+        ]);
+        
+        // Dropped receivers are pruned on the next event
+        drop(events);
+        graph.add_node(&[0.5, 0.5, 0.5]);
+        assert!(graph.subscribers.is_empty());
+    }
+    
+    #[test]
+    fn test_merge_events() {
+        let mut graph = SpatialGraph::new();
+        graph.add_node(&[0.01, 0.01, 0.0]);
+        graph.add_node(&[0.02, 0.01, 0.0]);
+        
+        let events = graph.subscribe();
+        graph.downsample(10.0);
+        
+        let received: Vec<GraphEvent> = events.try_iter().collect();
+        assert!(received.contains(&GraphEvent::NodeMerged { id: 1, into: 0 }));
+        assert!(received.contains(&GraphEvent::EdgeRemoved { from: 0, to: 1 }));
+    }
+    
+    #[test]
+    fn test_hnsw_index_matches_exact() {
+        let mut graph = SpatialGraph::new();
+        for i in 0..200 {
+            graph.add_node(&[(i % 20) as f32 * 0.05, (i / 20) as f32 * 0.1, 0.0]);
+        }
+        
+        let query = Position { x: 33.0, y: 41.0, z: 0.0 };
+        let exact = graph.k_nearest_neighbors(&query, 5);
+        
+        graph.set_index(IndexKind::Hnsw(HnswConfig::default()));
+        assert!(matches!(graph.index_kind(), IndexKind::Hnsw(_)));
+        assert_eq!(graph.k_nearest_neighbors(&query, 5), exact);
+        
+        // The index follows mutations
+        graph.remove_node(exact[0].0);
+        assert_eq!(graph.k_nearest_neighbors(&query, 1)[0], exact[1]);
+        let id = graph.add_node(&[0.33, 0.41, 0.0]);
+        assert_eq!(graph.k_nearest_neighbors(&query, 1), vec![(id, 0.0)]);
+    }
+    
+    #[test]
+    fn test_rtree_region_index() {
+        let mut graph = SpatialGraph::new();
+        for i in 0..100 {
+            graph.add_node(&[(i % 10) as f32 * 0.1, (i / 10) as f32 * 0.1, 0.0]);
+        }
+        
+        let aabb = Aabb { min: Position { x: 15.0, y: 15.0, z: -1.0 }, max: Position { x: 35.0, y: 25.0, z: 1.0 } };
+        let scanned = graph.aabb_query(&aabb);
+        
+        graph.set_region_index(RegionIndex::RTree { max_entries: 8 });
+        assert_eq!(graph.region_index(), RegionIndex::RTree { max_entries: 8 });
+        assert_eq!(graph.aabb_query(&aabb), scanned);
+        
+        graph.remove_node(scanned[0]);
+        let id = graph.add_node(&[0.25, 0.2, 0.0]);
+        let mut expected = scanned[1..].to_vec();
+        expected.push(id);
+        assert_eq!(graph.aabb_query(&aabb), expected);
+    }
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let mut graph = SpatialGraph::new();
+        for i in 0..50 {
+            graph.add_node(&[(i % 10) as f32 * 0.1, (i / 10) as f32 * 0.1, 0.0]);
+        }
+        graph.remove_node(7);
+        graph.set_index(IndexKind::Hnsw(HnswConfig::default()));
+        
+        let json = serde_json::to_string(&graph.snapshot()).unwrap();
+        let restored: SpatialGraph = SpatialGraph::from_snapshot(serde_json::from_str(&json).unwrap());
+        assert_eq!(restored.node_count(), graph.node_count());
+        assert_eq!(restored.edge_count(), graph.edge_count());
+        assert_eq!(restored.index_kind(), graph.index_kind());
+        
+        let query = Position { x: 42.0, y: 13.0, z: 0.0 };
+        assert_eq!(restored.k_nearest_neighbors(&query, 3), graph.k_nearest_neighbors(&query, 3));
+    }
+}
@@ -0,0 +1,119 @@
+//! User stages inside the processing cycle
+//!
+//! A `Stage` added with `EnvironmentalAwarenessSystem::add_stage` runs at a
+//! `StagePosition` next to one of the built-in stages and works on the
+//! `CycleContext`, which holds what the cycle has produced so far. Stages can
+//! rewrite it: clean up a frame before fusion, adjust features before
+//! detection, or drop an anomaly that a business rule rules out. Their time
+//! counts towards the cycle's `processing_us`, but not towards the built-in
+//! `StageTimings`. Each stage is timed on its own in
+//! `SystemMetrics::custom_stages`.
+//!
+//! In a `pipeline::Pipeline`, fusion and inference run on other threads.
+//! Stages placed around them run when the cycle reaches the system, in
+//! order, with `frame` unset.
+
+use std::borrow::Cow;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::anomaly::Anomaly;
+use crate::latency::{LatencyHistogram, Summary};
+use crate::predictor::Prediction;
+use crate::sensors::SensorData;
+use crate::StageStats;
+
+/// A built-in stage of the cycle, in the order they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BuiltinStage {
+    Sensor,
+    Neural,
+    Spatial,
+    Anomaly,
+    Prediction,
+}
+
+/// Where a user stage runs relative to a built-in one
+///
+/// `After(Anomaly)` runs once the anomaly is detected and explained, but
+/// before it is suppressed, alerted on or counted. A stage there can
+/// reclassify an anomaly or veto it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StagePosition {
+    Before(BuiltinStage),
+    After(BuiltinStage),
+}
+
+/// What the cycle has produced so far, filled in as the built-in stages run
+#[derive(Debug, Clone, Default)]
+pub struct CycleContext<'a> {
+    /// Number of the cycle being run
+    pub cycle: u32,
+    /// Frame being processed; replacing it before `Sensor` changes what is fused
+    pub frame: Option<Cow<'a, SensorData>>,
+    /// Fused features, from `After(Sensor)`
+    pub features: Vec<f32>,
+    pub fused_confidence: f32,
+    /// Network output, from `After(Neural)`
+    pub neural_output: Vec<f32>,
+    /// Node the frame was added as, from `After(Spatial)`; for reference only
+    pub node_id: Option<usize>,
+    /// Anomaly found this cycle, from `After(Anomaly)`
+    pub anomaly: Option<Anomaly>,
+    /// Forecast made this cycle, from `After(Prediction)`
+    pub prediction: Option<Prediction>,
+}
+
+/// Domain logic run inside every cycle
+pub trait Stage: fmt::Debug + Send {
+    /// Name the stage is reported under in metrics
+    fn name(&self) -> &str;
+
+    fn process(&mut self, ctx: &mut CycleContext<'_>);
+}
+
+/// Timing of one user stage over the run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomStageStats {
+    pub name: String,
+    pub position: StagePosition,
+    pub stats: StageStats,
+}
+
+/// A stage installed in a system, with its duration histogram
+#[derive(Debug)]
+pub(crate) struct InstalledStage {
+    pub(crate) position: StagePosition,
+    pub(crate) stage: Box<dyn Stage>,
+    pub(crate) latency: LatencyHistogram,
+}
+
+impl InstalledStage {
+    pub(crate) fn new(position: StagePosition, stage: Box<dyn Stage>) -> Self {
+        Self { position, stage, latency: LatencyHistogram::new() }
+    }
+
+    pub(crate) fn stats(&self) -> CustomStageStats {
+        CustomStageStats {
+            name: self.stage.name().to_string(),
+            position: self.position,
+            stats: Summary::of_histogram(&self.latency).into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_serialization() {
+        let position = StagePosition::After(BuiltinStage::Anomaly);
+        assert_eq!(serde_json::to_value(position).unwrap(), serde_json::json!({ "after": "anomaly" }));
+        let parsed: StagePosition = serde_json::from_str(r#"{"before":"sensor"}"#).unwrap();
+        assert_eq!(parsed, StagePosition::Before(BuiltinStage::Sensor));
+    }
+}
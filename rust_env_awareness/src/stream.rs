@@ -0,0 +1,144 @@
+//! Async pipeline for embedding the system in tokio services
+//!
+//! A cycle is a short CPU-bound step, so the streams run it inline on the
+//! polling task; they only ever wait on a timer or on incoming sensor data and
+//! never block the executor.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::{self, Interval, MissedTickBehavior};
+use tokio_stream::Stream;
+
+use crate::sensors::SensorData;
+use crate::error::Result;
+use crate::{CycleResult, EnvironmentalAwarenessSystem};
+
+/// Async producer handle feeding a `SensorSource`
+#[derive(Debug, Clone)]
+pub struct SensorSink {
+    tx: mpsc::Sender<SensorData>,
+}
+
+impl SensorSink {
+    /// Queue a reading, waiting while the channel is full; false once the source is gone
+    pub async fn send(&self, data: SensorData) -> bool {
+        self.tx.send(data).await.is_ok()
+    }
+
+    /// Queue a reading without waiting; false if the channel is full or closed
+    pub fn try_send(&self, data: SensorData) -> bool {
+        self.tx.try_send(data).is_ok()
+    }
+}
+
+/// Readings sent through `SensorSink`s; ends once every sink is dropped
+#[derive(Debug)]
+pub struct SensorSource {
+    rx: mpsc::Receiver<SensorData>,
+}
+
+impl Stream for SensorSource {
+    type Item = SensorData;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<SensorData>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+/// Bounded channel from async producers to `EnvironmentalAwarenessSystem::process_stream`
+pub fn sensor_channel(capacity: usize) -> (SensorSink, SensorSource) {
+    let (tx, rx) = mpsc::channel(capacity.max(1));
+    (SensorSink { tx }, SensorSource { rx })
+}
+
+/// Cycles on generated sensor data at a fixed period (see `run_stream`)
+#[derive(Debug)]
+pub struct CycleStream<'a> {
+    system: &'a mut EnvironmentalAwarenessSystem,
+    interval: Interval,
+}
+
+impl<'a> CycleStream<'a> {
+    pub(crate) fn new(system: &'a mut EnvironmentalAwarenessSystem, period: Duration) -> Self {
+        let mut interval = time::interval(period.max(Duration::from_micros(1)));
+        // A slow consumer delays later cycles instead of triggering a burst
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        Self { system, interval }
+    }
+}
+
+impl Stream for CycleStream<'_> {
+    type Item = CycleResult;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<CycleResult>> {
+        let this = &mut *self;
+        match this.interval.poll_tick(cx) {
+            Poll::Ready(_) => Poll::Ready(Some(this.system.run_cycle())),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// One cycle per reading of an async sensor stream (see `process_stream`)
+#[derive(Debug)]
+pub struct ProcessStream<'a, S> {
+    system: &'a mut EnvironmentalAwarenessSystem,
+    input: S,
+}
+
+impl<'a, S> ProcessStream<'a, S> {
+    pub(crate) fn new(system: &'a mut EnvironmentalAwarenessSystem, input: S) -> Self {
+        Self { system, input }
+    }
+}
+
+impl<S> Stream for ProcessStream<'_, S>
+where
+    S: Stream<Item = SensorData> + Unpin,
+{
+    type Item = Result<CycleResult>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<CycleResult>>> {
+        let this = &mut *self;
+        match Pin::new(&mut this.input).poll_next(cx) {
+            Poll::Ready(Some(data)) => Poll::Ready(Some(this.system.process_sensor_data(&data))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_stream::StreamExt;
+
+    #[tokio::test]
+    async fn test_run_stream() {
+        let mut system = EnvironmentalAwarenessSystem::new();
+        let results: Vec<CycleResult> = system.run_stream(Duration::from_millis(1)).take(3).collect().await;
+        assert_eq!(results.iter().map(|r| r.cycle).collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_process_channel() {
+        let (sink, source) = sensor_channel(4);
+        let producer = tokio::spawn(async move {
+            for _ in 0..10 {
+                assert!(sink.send(SensorData::generate()).await);
+            }
+        });
+
+        let mut system = EnvironmentalAwarenessSystem::new();
+        let count = system.process_stream(source).fold(0, |n, _| n + 1).await;
+        producer.await.unwrap();
+        assert_eq!(count, 10);
+        assert_eq!(system.get_metrics().cycles, 10);
+    }
+}
@@ -0,0 +1,375 @@
+//! Several systems run side by side, e.g. one per robot in a fleet
+//!
+//! `SwarmCoordinator` advances every member one cycle per tick in parallel
+//! (on rayon with the `parallel` feature, scoped threads otherwise), sums up
+//! their metrics, and flags ticks where anomalies on several members fall
+//! within a few ticks of each other: a shared cause such as a change in the
+//! environment rather than one faulty sensor. `merged_map` combines the
+//! members' spatial graphs into one map.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::SystemConfig;
+use crate::error::{GenesisError, Result};
+use crate::sensors::SensorData;
+use crate::spatial::{Position, SpatialGraph};
+use crate::{CycleResult, EnvironmentalAwarenessSystem, SystemMetrics};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// When anomalies on different members count as one event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CorrelationConfig {
+    /// Ticks an anomaly stays eligible for correlation (1 = same tick only)
+    pub window: u64,
+    /// Distinct members that must have an anomaly within the window
+    pub min_members: usize,
+}
+
+impl Default for CorrelationConfig {
+    fn default() -> Self {
+        Self { window: 5, min_members: 2 }
+    }
+}
+
+/// An anomaly one member reported
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemberAnomaly {
+    /// Index of the member in the swarm
+    pub member: usize,
+    pub tick: u64,
+    /// The member's own cycle number
+    pub cycle: u32,
+    pub anomaly_id: Option<u64>,
+    pub score: f32,
+}
+
+/// Anomalies on several members within `CorrelationConfig::window` ticks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CorrelatedAnomaly {
+    /// Tick whose anomalies completed or extended the group
+    pub tick: u64,
+    /// Every anomaly in the window, oldest first
+    pub anomalies: Vec<MemberAnomaly>,
+}
+
+impl CorrelatedAnomaly {
+    /// Distinct members involved, in ascending order
+    pub fn members(&self) -> Vec<usize> {
+        let mut members: Vec<usize> = self.anomalies.iter().map(|a| a.member).collect();
+        members.sort_unstable();
+        members.dedup();
+        members
+    }
+}
+
+/// One tick of every member
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwarmCycle {
+    pub tick: u64,
+    /// Member results, by member index
+    pub results: Vec<CycleResult>,
+    pub correlated: Option<CorrelatedAnomaly>,
+}
+
+/// Metrics of every member and the swarm as a whole
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwarmMetrics {
+    /// Per-member metrics, by member index
+    pub members: Vec<SystemMetrics>,
+    pub ticks: u64,
+    /// Cycles run by all members together
+    pub cycles: u64,
+    /// Combined cycle rate of all members
+    pub processing_rate_hz: f64,
+    /// Mean over every member's cycles
+    pub avg_processing_us: f64,
+    /// p99 of the slowest member
+    pub max_p99_processing_us: u64,
+    pub anomalies_detected: usize,
+    pub correlated_anomalies: usize,
+    pub spatial_nodes: usize,
+    pub memory_usage_mb: f64,
+}
+
+#[derive(Debug)]
+struct Member {
+    system: EnvironmentalAwarenessSystem,
+    /// Where the member's map sits in the merged map
+    origin: Position,
+}
+
+/// Runs and watches a group of systems
+#[derive(Debug, Default)]
+pub struct SwarmCoordinator {
+    members: Vec<Member>,
+    correlation: CorrelationConfig,
+    tick: u64,
+    /// Anomalies still inside the correlation window, oldest first
+    recent: VecDeque<MemberAnomaly>,
+    correlated: usize,
+}
+
+impl SwarmCoordinator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `count` systems built from `config`; with a seed, member `i` uses
+    /// `seed + i` so members do not all see the same simulated readings
+    pub fn with_members(count: usize, config: &SystemConfig) -> Result<Self> {
+        let mut swarm = Self::new();
+        for i in 0..count {
+            let mut config = config.clone();
+            config.seed = config.seed.map(|seed| seed.wrapping_add(i as u64));
+            swarm.add(EnvironmentalAwarenessSystem::with_config(config)?);
+        }
+        Ok(swarm)
+    }
+
+    /// Add a member whose map starts at the origin; returns its index
+    pub fn add(&mut self, system: EnvironmentalAwarenessSystem) -> usize {
+        self.add_at(system, Position { x: 0.0, y: 0.0, z: 0.0 })
+    }
+
+    /// Add a member whose map is placed at `origin` in `merged_map`
+    pub fn add_at(&mut self, system: EnvironmentalAwarenessSystem, origin: Position) -> usize {
+        self.members.push(Member { system, origin });
+        self.members.len() - 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    pub fn system(&self, member: usize) -> Option<&EnvironmentalAwarenessSystem> {
+        self.members.get(member).map(|m| &m.system)
+    }
+
+    pub fn system_mut(&mut self, member: usize) -> Option<&mut EnvironmentalAwarenessSystem> {
+        self.members.get_mut(member).map(|m| &mut m.system)
+    }
+
+    /// Take the members back, by index
+    pub fn into_systems(self) -> Vec<EnvironmentalAwarenessSystem> {
+        self.members.into_iter().map(|m| m.system).collect()
+    }
+
+    pub fn correlation(&self) -> CorrelationConfig {
+        self.correlation
+    }
+
+    pub fn set_correlation(&mut self, config: CorrelationConfig) {
+        self.correlation = config;
+    }
+
+    /// Run one cycle on simulated readings on every member
+    pub fn run_cycle(&mut self) -> SwarmCycle {
+        let results = self.for_each_member(|_, system| system.run_cycle());
+        self.finish_tick(results)
+    }
+
+    pub fn run_cycles(&mut self, count: usize) -> Vec<SwarmCycle> {
+        (0..count).map(|_| self.run_cycle()).collect()
+    }
+
+    /// Run one cycle per member on `frames`, indexed like the members
+    ///
+    /// Every frame is validated first, so an invalid one leaves all members untouched.
+    pub fn process(&mut self, frames: &[SensorData]) -> Result<SwarmCycle> {
+        if frames.len() != self.members.len() {
+            return Err(GenesisError::DimensionMismatch {
+                what: "swarm frames",
+                expected: self.members.len(),
+                actual: frames.len(),
+            });
+        }
+        for frame in frames {
+            frame.validate()?;
+        }
+        let results = self.for_each_member(|member, system| system.process_validated(&frames[member]));
+        Ok(self.finish_tick(results))
+    }
+
+    /// Per-member and combined metrics
+    pub fn metrics(&self) -> SwarmMetrics {
+        let members: Vec<SystemMetrics> = self.members.iter().map(|m| m.system.get_metrics()).collect();
+        let cycles: u64 = members.iter().map(|m| m.cycles as u64).sum();
+        let busy_us: f64 = members.iter().map(|m| m.avg_processing_us * m.cycles as f64).sum();
+        SwarmMetrics {
+            ticks: self.tick,
+            cycles,
+            processing_rate_hz: members.iter().map(|m| m.processing_rate_hz).filter(|hz| hz.is_finite()).sum(),
+            avg_processing_us: if cycles > 0 { busy_us / cycles as f64 } else { 0.0 },
+            max_p99_processing_us: members.iter().map(|m| m.p99_processing_us).max().unwrap_or(0),
+            anomalies_detected: members.iter().map(|m| m.anomalies_detected).sum(),
+            correlated_anomalies: self.correlated,
+            spatial_nodes: members.iter().map(|m| m.spatial_nodes).sum(),
+            memory_usage_mb: members.iter().map(|m| m.memory_usage_mb).sum(),
+            members,
+        }
+    }
+
+    /// Every member's spatial graph in one, each shifted by its origin
+    ///
+    /// Nodes within `connection_radius` are linked across members too, so
+    /// building the map takes time quadratic in the total node count.
+    pub fn merged_map(&self, connection_radius: f32) -> SpatialGraph {
+        let nodes = self.members.iter().map(|m| m.system.spatial_graph().node_count()).sum();
+        let mut merged = SpatialGraph::with_capacity(nodes);
+        merged.set_connection_radius(connection_radius);
+        for member in &self.members {
+            let origin = member.origin;
+            for node in member.system.spatial_graph().nodes() {
+                let position = Position {
+                    x: node.position.x + origin.x,
+                    y: node.position.y + origin.y,
+                    z: node.position.z + origin.z,
+                };
+                merged.add_node_at(position, &node.features);
+            }
+        }
+        merged
+    }
+
+    /// Apply `f` to every member at once, collecting the results by member index
+    fn for_each_member<R, F>(&mut self, f: F) -> Vec<R>
+    where
+        R: Send,
+        F: Fn(usize, &mut EnvironmentalAwarenessSystem) -> R + Sync,
+    {
+        if self.members.len() <= 1 {
+            return self.members.iter_mut().enumerate().map(|(i, m)| f(i, &mut m.system)).collect();
+        }
+        #[cfg(feature = "parallel")]
+        {
+            self.members.par_iter_mut().enumerate().map(|(i, m)| f(i, &mut m.system)).collect()
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            let f = &f;
+            std::thread::scope(|scope| {
+                let workers: Vec<_> = self
+                    .members
+                    .iter_mut()
+                    .enumerate()
+                    .map(|(i, m)| scope.spawn(move || f(i, &mut m.system)))
+                    .collect();
+                workers
+                    .into_iter()
+                    .map(|worker| worker.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+                    .collect()
+            })
+        }
+    }
+
+    fn finish_tick(&mut self, results: Vec<CycleResult>) -> SwarmCycle {
+        self.tick += 1;
+        let correlated = self.correlate(&results);
+        SwarmCycle { tick: self.tick, results, correlated }
+    }
+
+    /// Record this tick's anomalies and report a group once enough members have one
+    fn correlate(&mut self, results: &[CycleResult]) -> Option<CorrelatedAnomaly> {
+        let tick = self.tick;
+        let window = self.correlation.window.max(1);
+        while self.recent.front().is_some_and(|a| a.tick + window <= tick) {
+            self.recent.pop_front();
+        }
+
+        let before = self.recent.len();
+        self.recent.extend(results.iter().enumerate().filter(|(_, r)| r.anomaly_detected).map(|(member, r)| {
+            MemberAnomaly { member, tick, cycle: r.cycle, anomaly_id: r.anomaly_id, score: r.anomaly_score }
+        }));
+        if self.recent.len() == before {
+            return None;
+        }
+
+        let correlated = CorrelatedAnomaly { tick, anomalies: self.recent.iter().cloned().collect() };
+        if correlated.members().len() < self.correlation.min_members {
+            return None;
+        }
+        self.correlated += 1;
+        #[cfg(feature = "tracing")]
+        tracing::warn!(tick, members = ?correlated.members(), "correlated anomaly across swarm members");
+        Some(correlated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anomaly::{Anomaly, AnomalyDetection, Severity};
+
+    /// Flags loud audio, so the test decides which members see an anomaly
+    #[derive(Debug)]
+    struct Loud;
+
+    impl AnomalyDetection for Loud {
+        fn observe(&mut self, features: &[f32], timestamp: f64) -> Option<Anomaly> {
+            (features[2] > 0.95).then(|| Anomaly {
+                id: 0,
+                timestamp,
+                value: features[2],
+                z_score: 4.0,
+                severity: Severity::High,
+                mean: 0.0,
+                stdev: 1.0,
+                contributions: Vec::new(),
+                explanation: Default::default(),
+                detectors: Vec::new(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_swarm_correlates_and_aggregates() {
+        let config = SystemConfig { seed: Some(7), ..SystemConfig::default() };
+        let mut swarm = SwarmCoordinator::with_members(3, &config).unwrap();
+        for member in 0..3 {
+            swarm.system_mut(member).unwrap().set_detector(Box::new(Loud));
+        }
+        swarm.set_correlation(CorrelationConfig { window: 3, min_members: 2 });
+
+        let frames = |loud: &[usize]| -> Vec<SensorData> {
+            (0..3)
+                .map(|member| {
+                    let mut frame = SensorData::generate();
+                    frame.audio.amplitude = if loud.contains(&member) { 1.0 } else { 0.1 };
+                    frame
+                })
+                .collect()
+        };
+        let mut correlated = Vec::new();
+        for tick in 1..=30 {
+            let loud: &[usize] = match tick {
+                10 => &[0],
+                11 => &[2],
+                20 => &[1],
+                _ => &[],
+            };
+            let cycle = swarm.process(&frames(loud)).unwrap();
+            assert_eq!(cycle.results.len(), 3);
+            correlated.extend(cycle.correlated);
+        }
+        // Members 0 and 2 within the window correlate; member 1 alone does not
+        assert_eq!(correlated.len(), 1);
+        assert_eq!((correlated[0].tick, correlated[0].members()), (11, vec![0, 2]));
+
+        let error = swarm.process(&frames(&[])[..2]).unwrap_err();
+        assert!(matches!(error, GenesisError::DimensionMismatch { expected: 3, actual: 2, .. }));
+
+        swarm.run_cycles(10);
+        let metrics = swarm.metrics();
+        assert_eq!((metrics.ticks, metrics.cycles, metrics.spatial_nodes), (40, 120, 120));
+        assert_eq!(metrics.correlated_anomalies, 1);
+        assert_eq!(swarm.merged_map(50.0).node_count(), 120);
+    }
+}
@@ -0,0 +1,142 @@
+//! Metrics readable from other threads without locking the system
+//!
+//! A running system updates atomic counters every cycle and periodically
+//! publishes a full `SystemMetrics` snapshot. Readers holding a
+//! `MetricsHandle` never contend with `run_cycle`: counters are plain atomic
+//! loads, and the snapshot is an `Arc` swapped in atomically, so publishing
+//! never waits for readers and reading never waits for a publish.
+
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+
+use crate::{CycleResult, SystemMetrics};
+
+/// State shared between a system and its handles
+#[derive(Debug)]
+pub(crate) struct LiveMetrics {
+    cycles: AtomicU32,
+    anomalies: AtomicUsize,
+    predictions: AtomicUsize,
+    last_processing_us: AtomicU64,
+    /// Cycles between snapshots
+    interval: AtomicU32,
+    snapshot: ArcSwap<SystemMetrics>,
+}
+
+impl LiveMetrics {
+    pub(crate) fn new(metrics: SystemMetrics, interval: u32) -> Self {
+        Self {
+            cycles: AtomicU32::new(metrics.cycles),
+            anomalies: AtomicUsize::new(0),
+            predictions: AtomicUsize::new(0),
+            last_processing_us: AtomicU64::new(0),
+            interval: AtomicU32::new(interval.max(1)),
+            snapshot: ArcSwap::from_pointee(metrics),
+        }
+    }
+
+    /// Count one finished cycle, the `cycles`th since metrics were reset
+    pub(crate) fn record(&self, result: &CycleResult, cycles: u32) {
+        self.cycles.store(cycles, Ordering::Release);
+        self.anomalies.fetch_add(result.anomaly_detected as usize, Ordering::Relaxed);
+        self.predictions.fetch_add(result.prediction.is_some() as usize, Ordering::Relaxed);
+        self.last_processing_us.store(result.processing_us, Ordering::Relaxed);
+    }
+
+    /// Whether a snapshot is due after `cycle`
+    pub(crate) fn due(&self, cycle: u32) -> bool {
+        cycle.is_multiple_of(self.interval.load(Ordering::Relaxed))
+    }
+
+    /// Zero the counters and replace the snapshot with `metrics`
+    pub(crate) fn reset(&self, metrics: SystemMetrics) {
+        self.cycles.store(metrics.cycles, Ordering::Release);
+        self.anomalies.store(0, Ordering::Relaxed);
+        self.predictions.store(0, Ordering::Relaxed);
+        self.publish(metrics);
+    }
+
+    /// Publish a snapshot every `interval` cycles
+    pub(crate) fn set_interval(&self, interval: u32) {
+        self.interval.store(interval.max(1), Ordering::Relaxed);
+    }
+
+    /// Swap in a new snapshot
+    pub(crate) fn publish(&self, metrics: SystemMetrics) {
+        self.snapshot.store(Arc::new(metrics));
+    }
+}
+
+/// Cloneable, thread-safe view of a system's progress and latest metrics
+#[derive(Debug, Clone)]
+pub struct MetricsHandle {
+    live: Arc<LiveMetrics>,
+}
+
+impl MetricsHandle {
+    pub(crate) fn new(live: Arc<LiveMetrics>) -> Self {
+        Self { live }
+    }
+
+    /// Cycles completed since metrics were last reset
+    pub fn cycles(&self) -> u32 {
+        self.live.cycles.load(Ordering::Acquire)
+    }
+
+    /// Cycles that detected an anomaly since the handle was created or metrics were reset
+    pub fn anomalous_cycles(&self) -> usize {
+        self.live.anomalies.load(Ordering::Relaxed)
+    }
+
+    /// Cycles that produced a forecast since the handle was created or metrics were reset
+    pub fn predicting_cycles(&self) -> usize {
+        self.live.predictions.load(Ordering::Relaxed)
+    }
+
+    /// Processing time of the most recent cycle
+    pub fn last_processing_us(&self) -> u64 {
+        self.live.last_processing_us.load(Ordering::Relaxed)
+    }
+
+    /// Most recently published full metrics
+    pub fn metrics(&self) -> Arc<SystemMetrics> {
+        self.live.snapshot.load_full()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use crate::EnvironmentalAwarenessSystem;
+
+    #[test]
+    fn test_handle_reads_from_another_thread() {
+        let mut system = EnvironmentalAwarenessSystem::new();
+        let handle = system.metrics_handle();
+        system.set_metrics_interval(10);
+        assert_eq!(handle.metrics().cycles, 0);
+
+        let reader = {
+            let handle = handle.clone();
+            thread::spawn(move || {
+                let mut seen = 0;
+                while handle.cycles() < 50 {
+                    seen = seen.max(handle.metrics().cycles);
+                }
+                seen
+            })
+        };
+        let mut results = system.run_cycles(50);
+        assert!(reader.join().unwrap() <= 50);
+        assert_eq!(handle.metrics().cycles, 50);
+
+        // Every due snapshot lands, whether or not readers were active
+        results.extend(system.run_cycles(10));
+        assert_eq!(handle.cycles(), 60);
+        assert_eq!(handle.metrics().cycles, 60);
+        assert_eq!(handle.anomalous_cycles(), results.iter().filter(|r| r.anomaly_detected).count());
+        assert_eq!(handle.predicting_cycles(), results.iter().filter(|r| r.prediction.is_some()).count());
+    }
+}
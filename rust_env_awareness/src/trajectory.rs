@@ -0,0 +1,153 @@
+//! Trajectory extraction and smoothing over the spatial graph
+
+use serde::{Serialize, Deserialize};
+
+use crate::spatial::{Position, SpatialGraph};
+
+/// A single pose along the system's own path
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TrajectoryPoint {
+    pub node_id: usize,
+    pub position: Position,
+}
+
+/// Ordered node sequence traced by the system over time
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Trajectory {
+    pub points: Vec<TrajectoryPoint>,
+}
+
+impl Trajectory {
+    /// Extract the path from the graph; node ids are assigned in insertion order
+    pub fn from_graph(graph: &SpatialGraph) -> Self {
+        Self::from_graph_since(graph, 0)
+    }
+
+    /// Extract the path starting at node `since_id`
+    pub fn from_graph_since(graph: &SpatialGraph, since_id: usize) -> Self {
+        let points = graph
+            .nodes()
+            .iter()
+            .filter(|node| node.id >= since_id)
+            .map(|node| TrajectoryPoint { node_id: node.id, position: node.position })
+            .collect();
+        Self { points }
+    }
+
+    /// Number of points in the path
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Check whether the path is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Total path length
+    pub fn length(&self) -> f32 {
+        self.points
+            .windows(2)
+            .map(|w| w[0].position.distance_to(&w[1].position))
+            .sum()
+    }
+
+    /// Centered moving average over `window` points (shrinks at the ends)
+    pub fn moving_average(&self, window: usize) -> Vec<Position> {
+        let half = window.max(1) / 2;
+        let n = self.points.len();
+
+        (0..n)
+            .map(|i| {
+                let lo = i.saturating_sub(half);
+                let hi = (i + half + 1).min(n);
+                let count = (hi - lo) as f32;
+                let (x, y, z) = self.points[lo..hi].iter().fold((0.0, 0.0, 0.0), |acc, p| {
+                    (acc.0 + p.position.x, acc.1 + p.position.y, acc.2 + p.position.z)
+                });
+                Position { x: x / count, y: y / count, z: z / count }
+            })
+            .collect()
+    }
+
+    /// Catmull-Rom spline through the path, `samples` points per segment
+    pub fn spline(&self, samples: usize) -> Vec<Position> {
+        let n = self.points.len();
+        if n < 2 || samples == 0 {
+            return self.points.iter().map(|p| p.position).collect();
+        }
+
+        let at = |i: isize| self.points[i.clamp(0, n as isize - 1) as usize].position;
+        let mut out = Vec::with_capacity((n - 1) * samples + 1);
+
+        for seg in 0..n as isize - 1 {
+            let (p0, p1, p2, p3) = (at(seg - 1), at(seg), at(seg + 1), at(seg + 2));
+            for s in 0..samples {
+                let t = s as f32 / samples as f32;
+                out.push(catmull_rom(&p0, &p1, &p2, &p3, t));
+            }
+        }
+
+        out.push(self.points[n - 1].position);
+        out
+    }
+}
+
+/// Evaluate a uniform Catmull-Rom segment between `p1` and `p2`
+#[inline]
+fn catmull_rom(p0: &Position, p1: &Position, p2: &Position, p3: &Position, t: f32) -> Position {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    let axis = |a: f32, b: f32, c: f32, d: f32| {
+        0.5 * (2.0 * b + (c - a) * t + (2.0 * a - 5.0 * b + 4.0 * c - d) * t2 + (3.0 * b - a - 3.0 * c + d) * t3)
+    };
+    Position {
+        x: axis(p0.x, p1.x, p2.x, p3.x),
+        y: axis(p0.y, p1.y, p2.y, p3.y),
+        z: axis(p0.z, p1.z, p2.z, p3.z),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zigzag() -> SpatialGraph {
+        let mut graph = SpatialGraph::new();
+        for i in 0..10 {
+            let y = if i % 2 == 0 { 0.0 } else { 0.1 };
+            graph.add_node(&[i as f32 * 0.1, y, 0.0]);
+        }
+        graph
+    }
+
+    #[test]
+    fn test_extraction_order() {
+        let graph = zigzag();
+        let trajectory = Trajectory::from_graph_since(&graph, 4);
+        assert_eq!(trajectory.len(), 6);
+        assert_eq!(trajectory.points[0].node_id, 4);
+        assert!(Trajectory::from_graph(&graph).length() > 90.0);
+    }
+
+    #[test]
+    fn test_moving_average_smooths() {
+        let smoothed = Trajectory::from_graph(&zigzag()).moving_average(3);
+        assert_eq!(smoothed.len(), 10);
+        for p in &smoothed[1..9] {
+            assert!(p.y > 2.0 && p.y < 8.0, "zigzag should be flattened: {:?}", p);
+        }
+    }
+
+    #[test]
+    fn test_spline_interpolates_endpoints() {
+        let trajectory = Trajectory::from_graph(&zigzag());
+        let spline = trajectory.spline(4);
+        assert_eq!(spline.len(), 9 * 4 + 1);
+        assert_eq!(spline[0], trajectory.points[0].position);
+        assert_eq!(spline[4], trajectory.points[1].position);
+        assert_eq!(*spline.last().unwrap(), trajectory.points[9].position);
+    }
+}
@@ -0,0 +1,108 @@
+//! Live 3D visualization through the rerun viewer (`visualization` feature)
+
+use std::path::Path;
+
+use rerun::{Color, LineStrips3D, Points3D, RecordingStream, RecordingStreamBuilder, RecordingStreamResult, Scalar};
+
+use crate::spatial::SpatialGraph;
+use crate::{CycleResult, EnvironmentalAwarenessSystem};
+
+const APPLICATION_ID: &str = "genesis_env_awareness";
+
+/// Streams nodes, edges, anomalies and predictions to a rerun viewer per cycle
+pub struct RerunLogger {
+    stream: RecordingStream,
+    /// Log the full node/edge set every this many cycles
+    graph_interval: u32,
+    anomalies: Vec<[f32; 3]>,
+}
+
+impl RerunLogger {
+    /// Spawn a local viewer and stream to it
+    pub fn spawn() -> RecordingStreamResult<Self> {
+        Ok(Self::from_stream(RecordingStreamBuilder::new(APPLICATION_ID).spawn()?))
+    }
+
+    /// Record to an `.rrd` file for later viewing
+    pub fn save<P: AsRef<Path>>(path: P) -> RecordingStreamResult<Self> {
+        Ok(Self::from_stream(RecordingStreamBuilder::new(APPLICATION_ID).save(path)?))
+    }
+
+    /// Wrap an existing recording stream
+    pub fn from_stream(stream: RecordingStream) -> Self {
+        Self {
+            stream,
+            graph_interval: 10,
+            anomalies: Vec::new(),
+        }
+    }
+
+    /// Set how often the whole graph is re-logged (1 = every cycle)
+    pub fn with_graph_interval(mut self, cycles: u32) -> Self {
+        self.graph_interval = cycles.max(1);
+        self
+    }
+
+    /// Log everything the system perceived in one cycle
+    pub fn log_cycle(&mut self, system: &EnvironmentalAwarenessSystem, result: &CycleResult) -> RecordingStreamResult<()> {
+        let graph = system.spatial_graph();
+        self.stream.set_time_sequence("cycle", result.cycle as i64);
+
+        if let Some(node) = graph.node(result.node_id) {
+            let p = node.position;
+            self.stream.log(
+                "world/current",
+                &Points3D::new([[p.x, p.y, p.z]]).with_radii([1.5]).with_colors([Color::from_rgb(80, 160, 255)]),
+            )?;
+
+            if result.anomaly_detected {
+                self.anomalies.push([p.x, p.y, p.z]);
+                self.stream.log(
+                    "world/anomalies",
+                    &Points3D::new(self.anomalies.iter().copied())
+                        .with_radii([2.0])
+                        .with_colors([Color::from_rgb(255, 60, 60)]),
+                )?;
+            }
+        }
+
+        if result.cycle % self.graph_interval == 0 {
+            self.log_graph(graph)?;
+        }
+
+        self.stream.log("signals/confidence", &Scalar::new(result.confidence as f64))?;
+        if let Some(prediction) = &result.prediction {
+            if let Some(next) = prediction.values.first() {
+                self.stream.log("signals/prediction", &Scalar::new(*next as f64))?;
+            }
+            self.stream.log("signals/prediction_confidence", &Scalar::new(prediction.confidence as f64))?;
+        }
+
+        Ok(())
+    }
+
+    /// Log all nodes and edges of the graph
+    pub fn log_graph(&self, graph: &SpatialGraph) -> RecordingStreamResult<()> {
+        let points = graph.nodes().iter().map(|n| [n.position.x, n.position.y, n.position.z]);
+        self.stream.log("world/nodes", &Points3D::new(points).with_radii([0.5]))?;
+
+        let edges = graph.nodes().iter().flat_map(|node| {
+            graph
+                .neighbors(node.id)
+                .iter()
+                .filter(move |&&(to, _)| node.id < to)
+                .filter_map(move |&(to, _)| {
+                    let (a, b) = (node.position, graph.node(to)?.position);
+                    Some(vec![[a.x, a.y, a.z], [b.x, b.y, b.z]])
+                })
+        });
+        self.stream.log("world/edges", &LineStrips3D::new(edges))?;
+
+        Ok(())
+    }
+
+    /// Access the underlying recording stream
+    pub fn stream(&self) -> &RecordingStream {
+        &self.stream
+    }
+}